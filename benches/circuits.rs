@@ -0,0 +1,122 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_std::test_rng;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use r1cs_float::{
+    groth16::{prepare_verifying_key, CircuitSpecificSetupSNARK, Groth16, SNARK},
+    r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisMode},
+    regression::{optimality_gap, InferenceCircuit, LinearModel, TrainingCircuit},
+};
+use rand::{RngCore, SeedableRng};
+
+/// The numbers of samples to benchmark, each with `NUM_FEATURES` features.
+const NUM_SAMPLES: [usize; 3] = [4, 16, 64];
+const NUM_FEATURES: usize = 2;
+
+fn dataset(n: usize) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let x = (0..n)
+        .map(|i| {
+            (0..NUM_FEATURES)
+                .map(|j| ((i * 7 + j * 3) % 11) as f64 * 0.5 - 2.)
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    let y = x
+        .iter()
+        .enumerate()
+        .map(|(i, x)| 2. * x[0] - 3. * x[1] + 1. + (i % 3) as f64 * 0.01)
+        .collect();
+    (x, y)
+}
+
+fn model() -> LinearModel {
+    LinearModel {
+        weights: vec![0.1; NUM_FEATURES],
+        bias: 0.7,
+    }
+}
+
+fn training(n: usize) -> TrainingCircuit {
+    let (x, y) = dataset(n);
+    let model = LinearModel::fit(&x, &y).unwrap();
+    let tolerance = optimality_gap(&model, &x, &y);
+    TrainingCircuit::new(x, y, model, tolerance)
+}
+
+fn synthesize(circuit: impl ConstraintSynthesizer<Fr>, mode: SynthesisMode) {
+    let cs = ConstraintSystem::new_ref();
+    cs.set_mode(mode);
+    circuit.generate_constraints(cs).unwrap();
+}
+
+fn synthesis(c: &mut Criterion) {
+    let mut group = c.benchmark_group("synthesis");
+    group.sample_size(10);
+    for n in NUM_SAMPLES {
+        for (mode_name, mode) in [
+            ("constraints", SynthesisMode::Setup),
+            (
+                "witnesses",
+                SynthesisMode::Prove {
+                    construct_matrices: false,
+                },
+            ),
+        ] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("inference/{mode_name}"), n),
+                &n,
+                |b, &n| b.iter(|| synthesize(InferenceCircuit::new(model(), dataset(n).0), mode)),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("training/{mode_name}"), n),
+                &n,
+                |b, &n| b.iter(|| synthesize(training(n), mode)),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn groth16(c: &mut Criterion) {
+    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let mut group = c.benchmark_group("groth16");
+    group.sample_size(10);
+    for n in NUM_SAMPLES {
+        let (x, _) = dataset(n);
+        let (pk, vk) =
+            Groth16::<Bls12_381>::setup(InferenceCircuit::shape(n, NUM_FEATURES), rng).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let circuit = InferenceCircuit::new(model(), x.clone());
+        let inputs = InferenceCircuit::public_inputs(&x, &circuit.y);
+        let proof = Groth16::<Bls12_381>::prove(&pk, circuit, rng).unwrap();
+        group.bench_with_input(BenchmarkId::new("inference/prove", n), &n, |b, _| {
+            b.iter(|| {
+                Groth16::<Bls12_381>::prove(&pk, InferenceCircuit::new(model(), x.clone()), rng)
+                    .unwrap()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("inference/verify", n), &n, |b, _| {
+            b.iter(|| {
+                Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &inputs, &proof).unwrap()
+            })
+        });
+
+        let (pk, vk) =
+            Groth16::<Bls12_381>::setup(TrainingCircuit::shape(n, NUM_FEATURES), rng).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let circuit = training(n);
+        let inputs = TrainingCircuit::public_inputs(&circuit.model, circuit.tolerance);
+        let proof = Groth16::<Bls12_381>::prove(&pk, circuit, rng).unwrap();
+        group.bench_with_input(BenchmarkId::new("training/prove", n), &n, |b, &n| {
+            b.iter(|| Groth16::<Bls12_381>::prove(&pk, training(n), rng).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("training/verify", n), &n, |b, _| {
+            b.iter(|| {
+                Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &inputs, &proof).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, synthesis, groth16);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+use ark_bls12_381::Fr;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use r1cs_float::{
+    f64::F64Var,
+    r1cs::{ConstraintSystem, ConstraintSystemRef, SynthesisMode},
+    r1cs_std::prelude::AllocVar,
+};
+
+/// The modes to benchmark: constraint generation without witnesses, and witness generation
+/// without constraint matrices.
+const MODES: [(&str, SynthesisMode); 2] = [
+    ("constraints", SynthesisMode::Setup),
+    (
+        "witnesses",
+        SynthesisMode::Prove {
+            construct_matrices: false,
+        },
+    ),
+];
+
+fn new_cs(mode: SynthesisMode) -> ConstraintSystemRef<Fr> {
+    let cs = ConstraintSystem::new_ref();
+    cs.set_mode(mode);
+    cs
+}
+
+fn binary_ops(c: &mut Criterion) {
+    let mut group = c.benchmark_group("f64");
+    for (mode_name, mode) in MODES {
+        for (op_name, op) in [
+            (
+                "add",
+                (|x, y| x + y) as fn(&F64Var<Fr>, &F64Var<Fr>) -> F64Var<Fr>,
+            ),
+            ("mul", |x, y| x * y),
+        ] {
+            group.bench_function(BenchmarkId::new(mode_name, op_name), |b| {
+                // Only the operation is measured, excluding the allocation of its operands.
+                b.iter_batched(
+                    || {
+                        let cs = new_cs(mode);
+                        (
+                            F64Var::new_witness(cs.clone(), || Ok(black_box(0.1f64))).unwrap(),
+                            F64Var::new_witness(cs.clone(), || Ok(black_box(0.2f64))).unwrap(),
+                        )
+                    },
+                    |(x, y)| op(&x, &y),
+                    BatchSize::SmallInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+fn dot_product(c: &mut Criterion) {
+    let mut group = c.benchmark_group("f64/dot");
+    group.sample_size(10);
+    for (mode_name, mode) in MODES {
+        for n in [16, 64, 256] {
+            let x = (0..n).map(|i| i as f64 * 0.1).collect::<Vec<_>>();
+            let y = (0..n).map(|i| 1. / (i + 1) as f64).collect::<Vec<_>>();
+            group.bench_with_input(BenchmarkId::new(mode_name, n), &n, |b, _| {
+                b.iter(|| {
+                    let cs = new_cs(mode);
+                    let x = F64Var::<Fr>::new_witness_vec(cs.clone(), &x).unwrap();
+                    let y = F64Var::<Fr>::new_witness_vec(cs.clone(), &y).unwrap();
+                    let mut acc = &x[0] * &y[0];
+                    for (x, y) in x.iter().zip(&y).skip(1) {
+                        acc += x * y;
+                    }
+                    acc
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, binary_ops, dot_product);
+criterion_main!(benches);
@@ -0,0 +1,121 @@
+use crate::{float::FloatVar, unnormalized::UnnormalizedFloatVar};
+
+pub type BF16Var<F> = FloatVar<F, 8, 7>;
+pub type UnnormalizedBF16Var<F> = UnnormalizedFloatVar<F, 8, 7>;
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use half::{bf16, f16};
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        f16::F16Var,
+        r1cs::{ConstraintSystem, ConstraintSystemRef},
+        r1cs_std::prelude::AllocVar,
+    };
+    use ark_bls12_381::Fr;
+    use rayon::prelude::*;
+
+    /// Special values followed by random ones, where the random bits cover all formats of numbers
+    /// (normal, subnormal, infinite, and NaN).
+    fn values(count: usize) -> Vec<bf16> {
+        let rng = &mut rand::rngs::StdRng::seed_from_u64(16);
+        [
+            0.,
+            -0.,
+            1.,
+            -1.,
+            0.5,
+            1.1,
+            1e-40,
+            f32::INFINITY,
+            f32::NAN,
+        ]
+        .into_iter()
+        .map(bf16::from_f32)
+        .chain([
+            bf16::MIN_POSITIVE_SUBNORMAL,
+            bf16::MAX,
+            bf16::MIN,
+            bf16::EPSILON,
+        ])
+        .chain((0..count).map(|_| bf16::from_bits(rng.gen())))
+        .collect()
+    }
+
+    fn is_same(a: bf16, b: bf16) -> bool {
+        (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+    }
+
+    fn test_binary_op(
+        op: fn(BF16Var<Fr>, BF16Var<Fr>) -> BF16Var<Fr>,
+        expected: fn(bf16, bf16) -> bf16,
+    ) -> Result<(), Box<dyn Error>> {
+        let values = values(40);
+        let failures = values
+            .par_iter()
+            .flat_map(|&a| values.par_iter().map(move |&b| (a, b)))
+            .filter(|&(a, b)| {
+                let cs = ConstraintSystem::<Fr>::new_ref();
+                let x = BF16Var::new_witness(cs.clone(), || Ok(a)).unwrap();
+                let y = BF16Var::new_witness(cs.clone(), || Ok(b)).unwrap();
+                let r = op(x, y).to_bf16().unwrap();
+                !(cs.is_satisfied().unwrap() && is_same(r, expected(a, b)))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(failures.len(), 0, "{failures:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_add() -> Result<(), Box<dyn Error>> {
+        test_binary_op(std::ops::Add::add, |a, b| a + b)
+    }
+
+    #[test]
+    fn test_sub() -> Result<(), Box<dyn Error>> {
+        test_binary_op(std::ops::Sub::sub, |a, b| a - b)
+    }
+
+    #[test]
+    fn test_mul() -> Result<(), Box<dyn Error>> {
+        test_binary_op(std::ops::Mul::mul, |a, b| a * b)
+    }
+
+    #[test]
+    fn test_div() -> Result<(), Box<dyn Error>> {
+        test_binary_op(std::ops::Div::div, |a, b| a / b)
+    }
+
+    #[test]
+    fn dot_product() -> Result<(), Box<dyn Error>> {
+        // A prediction computed in `bfloat16` step by step, as by a framework that evaluates the
+        // model in `bfloat16`, is reproduced exactly.
+        let weights = [0.7f32, -1.25, 3e-3, 12.5].map(bf16::from_f32);
+        let features = [1.5f32, 0.1, -7.75, 2e-2].map(bf16::from_f32);
+        let expected = weights
+            .iter()
+            .zip(&features)
+            .fold(bf16::ZERO, |acc, (&w, &x)| acc + w * x);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let weights = BF16Var::new_input_vec(cs.clone(), &weights)?;
+        let features = BF16Var::new_witness_vec(cs.clone(), &features)?;
+        let prediction = weights.iter().zip(&features).fold(
+            BF16Var::new_constant(cs.clone(), bf16::ZERO)?,
+            |acc, (w, x)| acc + w * x,
+        );
+        assert!(cs.is_satisfied()?);
+        assert_eq!(prediction.to_bf16()?, expected);
+
+        let x = BF16Var::<Fr>::new_constant(ConstraintSystemRef::None, bf16::from_f32(1.5))?;
+        assert_eq!(x.to_bf16()?, bf16::from_f32(1.5));
+        assert!(x.to_f16().is_err());
+        let x = F16Var::<Fr>::new_constant(ConstraintSystemRef::None, f16::from_f32(1.5))?;
+        assert!(x.to_bf16().is_err());
+        Ok(())
+    }
+}
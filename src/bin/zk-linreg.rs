@@ -0,0 +1,348 @@
+use std::{collections::HashMap, env, error::Error, fs, path::PathBuf, process::ExitCode};
+
+use rand::Rng;
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_serialize::Compress;
+use r1cs_float::{
+    groth16::{
+        io, seeded_rng, CircuitSpecificSetupSNARK, Groth16, Proof, ProvingKey, VerifyingKey, SNARK,
+    },
+    import,
+    regression::{optimality_gap, InferenceCircuit, LinearModel, TrainingCircuit},
+    shaped::Shape,
+};
+
+const USAGE: &str = "\
+Usage:
+    zk-linreg setup  --circuit <training|inference> --data <csv> --dir <dir>
+    zk-linreg prove  --circuit training  --data <csv> --dir <dir> [--tolerance <value>]
+    zk-linreg prove  --circuit inference --data <csv> --dir <dir> --model <file>
+    zk-linreg verify --circuit training  --dir <dir>
+    zk-linreg verify --circuit inference --data <csv> --dir <dir>
+    zk-linreg report --circuit <training|inference> --rows <n> --features <d>
+
+All commands accept `--curve <bls12-381|bn254>` (default: bls12-381), which should be the same
+for the setup, proving, and verification of a circuit. Proofs over BN254 can be verified on EVM
+chains, see `r1cs_float::groth16::solidity`, and proofs over both curves on Polkadot-based chains,
+see `r1cs_float::groth16::ink`.
+
+`setup` and `prove` also accept `--seed <string>`, which derives their randomness from the string
+instead of the system RNG, so that the same seed and inputs always give byte-identical keys and
+proofs, e.g., for CI. Anyone who knows the seed of `setup` can forge proofs, and the seed of
+`prove` should not be reused for different data.
+
+`setup` and `prove` also accept `--compress <yes|no>` (default: yes). Uncompressed keys and
+proofs are about twice as large but faster to load, and are detected automatically when loading.
+
+For the training circuit, the last column of the CSV file is the target, and the other columns
+are the features. For the inference circuit, all columns are features.
+
+The keys and the proof are stored in <dir> as `pk`, `vk`, and `proof`. The training circuit
+additionally writes the fitted model to `model` and the tolerance to `tolerance`, and the
+inference circuit writes the predictions to `predictions`, which are read back by `verify`.
+A model file contains the bias followed by the weights, one number per line. The model of the
+inference circuit may also be an ONNX file (`.onnx`) with a single `LinearRegressor` node, or,
+with the `serde` feature, a scikit-learn JSON export (`.json`), see `r1cs_float::import`.
+
+`report` prints the size of the circuit for a dataset of <n> rows and <d> features, without
+generating any keys.";
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Circuit {
+    Training,
+    Inference,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Curve {
+    Bls12_381,
+    Bn254,
+}
+
+struct Args {
+    command: String,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = env::args().skip(1);
+        let command = args.next().ok_or("Missing command")?;
+        let mut options = HashMap::new();
+        while let Some(key) = args.next() {
+            let key = key
+                .strip_prefix("--")
+                .ok_or_else(|| format!("Unexpected argument `{key}`"))?;
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for `--{key}`"))?;
+            options.insert(key.to_string(), value);
+        }
+        Ok(Self { command, options })
+    }
+
+    fn get(&self, key: &str) -> Result<&str> {
+        self.options
+            .get(key)
+            .map(String::as_str)
+            .ok_or_else(|| format!("Missing option `--{key}`").into())
+    }
+
+    fn circuit(&self) -> Result<Circuit> {
+        match self.get("circuit")? {
+            "training" => Ok(Circuit::Training),
+            "inference" => Ok(Circuit::Inference),
+            c => Err(format!("Unknown circuit `{c}`").into()),
+        }
+    }
+
+    fn curve(&self) -> Result<Curve> {
+        match self.options.get("curve").map(String::as_str) {
+            None | Some("bls12-381") => Ok(Curve::Bls12_381),
+            Some("bn254") => Ok(Curve::Bn254),
+            Some(c) => Err(format!("Unknown curve `{c}`").into()),
+        }
+    }
+
+    fn compress(&self) -> Result<Compress> {
+        match self.options.get("compress").map(String::as_str) {
+            None | Some("yes") => Ok(Compress::Yes),
+            Some("no") => Ok(Compress::No),
+            Some(c) => Err(format!("Unknown compression `{c}`").into()),
+        }
+    }
+
+    fn dir(&self) -> Result<PathBuf> {
+        Ok(PathBuf::from(self.get("dir")?))
+    }
+
+    /// The RNG of `setup` and `prove`, which is seeded by the SHA-256 hash of `--seed` if given,
+    /// and by the system RNG otherwise.
+    fn rng(&self) -> ChaCha20Rng {
+        seeded_rng(match self.options.get("seed") {
+            Some(seed) => Sha256::digest(seed.as_bytes()).into(),
+            None => rand::thread_rng().gen(),
+        })
+    }
+}
+
+/// Read the numeric rows of a CSV file, skipping the header line (if any) and empty lines.
+fn read_csv(path: &str) -> Result<Vec<Vec<f64>>> {
+    let mut rows = vec![];
+    for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match line
+            .split(',')
+            .map(|v| v.trim().parse::<f64>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+        {
+            Ok(row) => rows.push(row),
+            Err(_) if i == 0 => continue,
+            Err(e) => return Err(format!("{path}:{}: {e}", i + 1).into()),
+        }
+    }
+    if rows.is_empty() {
+        return Err(format!("{path}: No data").into());
+    }
+    if rows.iter().any(|row| row.len() != rows[0].len()) {
+        return Err(format!("{path}: Rows have different numbers of columns").into());
+    }
+    Ok(rows)
+}
+
+/// Split the rows into features and targets, where the target is the last column.
+fn split_targets(rows: Vec<Vec<f64>>) -> Result<(Vec<Vec<f64>>, Vec<f64>)> {
+    if rows[0].len() < 2 {
+        return Err("The training data should have at least one feature and the target".into());
+    }
+    Ok(rows
+        .into_iter()
+        .map(|mut row| {
+            let y = row.pop().unwrap();
+            (row, y)
+        })
+        .unzip())
+}
+
+fn read_numbers(path: PathBuf) -> Result<Vec<f64>> {
+    fs::read_to_string(&path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.trim()
+                .parse::<f64>()
+                .map_err(|e| format!("{}: {e}", path.display()).into())
+        })
+        .collect()
+}
+
+fn write_numbers(path: PathBuf, numbers: &[f64]) -> Result<()> {
+    Ok(fs::write(
+        path,
+        numbers
+            .iter()
+            .map(|v| format!("{v:?}\n"))
+            .collect::<String>(),
+    )?)
+}
+
+fn read_model(path: PathBuf) -> Result<LinearModel> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("onnx") => return Ok(import::from_onnx(&fs::read(&path)?)?),
+        #[cfg(feature = "serde")]
+        Some("json") => return Ok(import::from_sklearn_json(&fs::read_to_string(&path)?)?),
+        _ => {}
+    }
+    let coefficients = read_numbers(path)?;
+    let (&bias, weights) = coefficients.split_first().ok_or("Empty model")?;
+    Ok(LinearModel {
+        weights: weights.to_vec(),
+        bias,
+    })
+}
+
+fn setup<E: Pairing>(args: &Args) -> Result<()> {
+    let rows = read_csv(args.get("data")?)?;
+    let dir = args.dir()?;
+    fs::create_dir_all(&dir)?;
+
+    let rng = &mut args.rng();
+    let (pk, vk) = match args.circuit()? {
+        Circuit::Training => {
+            let (x, _) = split_targets(rows)?;
+            Groth16::<E>::setup(TrainingCircuit::shape(x.len(), x[0].len()), rng)?
+        }
+        Circuit::Inference => {
+            Groth16::<E>::setup(InferenceCircuit::shape(rows.len(), rows[0].len()), rng)?
+        }
+    };
+
+    let compress = args.compress()?;
+    io::save_with(dir.join("pk"), &pk, compress)?;
+    io::save_with(dir.join("vk"), &vk, compress)?;
+    println!("Keys are written to {}", dir.display());
+    Ok(())
+}
+
+fn prove<E: Pairing>(args: &Args) -> Result<()> {
+    let rows = read_csv(args.get("data")?)?;
+    let dir = args.dir()?;
+    let pk: ProvingKey<E> = io::load(dir.join("pk"))?;
+
+    let rng = &mut args.rng();
+    let proof = match args.circuit()? {
+        Circuit::Training => {
+            let (x, y) = split_targets(rows)?;
+            let model = LinearModel::fit(&x, &y).ok_or("The normal equations are singular")?;
+            let gap = optimality_gap(&model, &x, &y);
+            let tolerance = match args.options.get("tolerance") {
+                Some(t) => t.parse::<f64>()?,
+                None => gap,
+            };
+            if gap > tolerance {
+                return Err(format!(
+                    "The optimality gap {gap} of the fitted model exceeds the tolerance {tolerance}"
+                )
+                .into());
+            }
+            write_numbers(dir.join("model"), &model.coefficients())?;
+            write_numbers(dir.join("tolerance"), &[tolerance])?;
+            Groth16::<E>::prove(&pk, TrainingCircuit::new(x, y, model, tolerance), rng)?
+        }
+        Circuit::Inference => {
+            let model = read_model(PathBuf::from(args.get("model")?))?;
+            if model.weights.len() != rows[0].len() {
+                return Err("The model and the data have different numbers of features".into());
+            }
+            let circuit = InferenceCircuit::new(model, rows);
+            write_numbers(dir.join("predictions"), &circuit.y)?;
+            Groth16::<E>::prove(&pk, circuit, rng)?
+        }
+    };
+
+    io::save_with(dir.join("proof"), &proof, args.compress()?)?;
+    println!("Proof is written to {}", dir.join("proof").display());
+    Ok(())
+}
+
+fn verify<E: Pairing>(args: &Args) -> Result<bool> {
+    let dir = args.dir()?;
+    let vk: VerifyingKey<E> = io::load(dir.join("vk"))?;
+    let proof: Proof<E> = io::load(dir.join("proof"))?;
+
+    let inputs: Vec<E::ScalarField> = match args.circuit()? {
+        Circuit::Training => {
+            let model = read_model(dir.join("model"))?;
+            let tolerance = *read_numbers(dir.join("tolerance"))?
+                .first()
+                .ok_or("Empty tolerance")?;
+            println!("Model: {model:?}, tolerance: {tolerance}");
+            TrainingCircuit::public_inputs(&model, tolerance)
+        }
+        Circuit::Inference => {
+            let x = read_csv(args.get("data")?)?;
+            let y = read_numbers(dir.join("predictions"))?;
+            if x.len() != y.len() {
+                return Err("The data and the predictions have different numbers of rows".into());
+            }
+            InferenceCircuit::public_inputs(&x, &y)
+        }
+    };
+
+    Ok(Groth16::<E>::verify(&vk, &inputs, &proof)?)
+}
+
+fn report<E: Pairing>(args: &Args) -> Result<()> {
+    let rows = args.get("rows")?.parse::<usize>()?;
+    let features = args.get("features")?.parse::<usize>()?;
+    let report = match args.circuit()? {
+        Circuit::Training => TrainingCircuit::constraint_report::<E::ScalarField>(rows, features)?,
+        Circuit::Inference => {
+            InferenceCircuit::constraint_report::<E::ScalarField>(rows, features)?
+        }
+    };
+    println!("{report}");
+    Ok(())
+}
+
+fn run<E: Pairing>(args: &Args) -> Result<bool> {
+    match args.command.as_str() {
+        "setup" => setup::<E>(args).map(|_| true),
+        "prove" => prove::<E>(args).map(|_| true),
+        "report" => report::<E>(args).map(|_| true),
+        "verify" => verify::<E>(args).inspect(|&valid| {
+            println!("Proof is {}", if valid { "valid" } else { "invalid" });
+        }),
+        "help" => {
+            println!("{USAGE}");
+            Ok(true)
+        }
+        c => Err(format!("Unknown command `{c}`").into()),
+    }
+}
+
+fn main() -> ExitCode {
+    let result = Args::parse().and_then(|args| match args.curve()? {
+        Curve::Bls12_381 => run::<Bls12_381>(&args),
+        Curve::Bn254 => run::<Bn254>(&args),
+    });
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Error: {e}\n\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,665 @@
+//! Loading of datasets from CSV files into the features and targets expected by the regression
+//! circuits, e.g., `TrainingCircuit::new(x, y, model, tolerance)` and `InferenceCircuit::new(model, x)`.
+//!
+//! The first line of a CSV file is the header, which names the columns, and each of the other
+//! non-empty lines is a sample. Fields are separated by commas and trimmed, and quoting is not
+//! supported. Only the selected columns are parsed, so the others (e.g., identifiers) may contain
+//! arbitrary text, and every selected value should be a finite number, since NaNs and infinities
+//! would make the circuits unsatisfiable or the fits meaningless.
+//!
+//! Datasets can also be loaded from NumPy arrays (`.npy` files, or `.npz` archives with the `npz`
+//! feature) of `float64` or `float32`, and with the `arrow` feature, from Arrow record batches
+//! and Parquet files, which are selected by column names in the same way as CSV files.
+
+use std::{fs, path::Path};
+
+use crate::Error;
+
+/// The features `x` and the targets `y` of a dataset, where `y` is empty if the dataset has no
+/// target column (e.g., for inference).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dataset {
+    pub x: Vec<Vec<f64>>,
+    pub y: Vec<f64>,
+}
+
+/// Find the indices of the feature columns and the target column among the columns `names`,
+/// where the features are all columns other than the target if `features` is `None`.
+fn select_columns(
+    names: &[&str],
+    features: Option<&[&str]>,
+    target: Option<&str>,
+) -> Result<(Vec<usize>, Option<usize>), String> {
+    if let Some(name) = names
+        .iter()
+        .enumerate()
+        .find_map(|(i, name)| names[..i].contains(name).then_some(name))
+    {
+        return Err(format!("duplicate column `{name}`"));
+    }
+    let column = |name: &str| {
+        names
+            .iter()
+            .position(|n| *n == name)
+            .ok_or_else(|| format!("missing column `{name}`"))
+    };
+    let target = target.map(column).transpose()?;
+    let features = match features {
+        Some(features) => features
+            .iter()
+            .map(|&name| column(name))
+            .collect::<Result<_, _>>()?,
+        None => (0..names.len())
+            .filter(|&i| Some(i) != target)
+            .collect::<Vec<_>>(),
+    };
+    if features.is_empty() {
+        return Err("no feature columns".into());
+    }
+    Ok((features, target))
+}
+
+impl Dataset {
+    /// Parse the CSV `text`, whose features are the columns named by `features` in that order,
+    /// or all columns other than `target` if `features` is `None`, and whose targets are the
+    /// column named by `target`, if any.
+    pub fn from_csv(
+        text: &str,
+        features: Option<&[&str]>,
+        target: Option<&str>,
+    ) -> Result<Self, Error> {
+        let invalid = |line: usize, message: String| Error::Data(format!("line {line}: {message}"));
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let (header_line, header) = lines
+            .next()
+            .ok_or_else(|| Error::Data("missing header".into()))?;
+        let names = header.split(',').map(str::trim).collect::<Vec<_>>();
+        let (features, target) =
+            select_columns(&names, features, target).map_err(|e| invalid(header_line, e))?;
+
+        let mut dataset = Self {
+            x: vec![],
+            y: vec![],
+        };
+        for (line, row) in lines {
+            let fields = row.split(',').map(str::trim).collect::<Vec<_>>();
+            if fields.len() != names.len() {
+                return Err(invalid(
+                    line,
+                    format!("expected {} columns, found {}", names.len(), fields.len()),
+                ));
+            }
+            let value = |i: usize| match fields[i].parse::<f64>() {
+                Ok(v) if v.is_finite() => Ok(v),
+                _ => Err(invalid(
+                    line,
+                    format!(
+                        "`{}` of column `{}` is not a finite number",
+                        fields[i], names[i]
+                    ),
+                )),
+            };
+            dataset.x.push(
+                features
+                    .iter()
+                    .map(|&i| value(i))
+                    .collect::<Result<_, _>>()?,
+            );
+            if let Some(i) = target {
+                dataset.y.push(value(i)?);
+            }
+        }
+        if dataset.x.is_empty() {
+            return Err(Error::Data("no samples".into()));
+        }
+        Ok(dataset)
+    }
+
+    /// Read the CSV file at `path`, see [`Dataset::from_csv`].
+    pub fn read_csv(
+        path: impl AsRef<Path>,
+        features: Option<&[&str]>,
+        target: Option<&str>,
+    ) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|e| Error::Data(format!("{}: {e}", path.display())))?;
+        Self::from_csv(&text, features, target).map_err(|e| match e {
+            Error::Data(e) => Error::Data(format!("{}: {e}", path.display())),
+            e => e,
+        })
+    }
+
+    /// Get the number of samples.
+    pub fn num_samples(&self) -> usize {
+        self.x.len()
+    }
+
+    /// Get the number of features of each sample, which is 0 if there are no samples.
+    pub fn num_features(&self) -> usize {
+        self.x.first().map_or(0, Vec::len)
+    }
+}
+
+/// Parse the NumPy array `bytes` in the `.npy` format of `float64` or `float32` numbers, and
+/// return its shape and its numbers in row-major order.
+fn parse_npy(bytes: &[u8]) -> Result<(Vec<usize>, Vec<f64>), Error> {
+    let invalid = |message: &str| Error::Data(format!("invalid .npy array: {message}"));
+    let (version, rest) = match bytes {
+        [0x93, b'N', b'U', b'M', b'P', b'Y', major, _, rest @ ..] => (*major, rest),
+        _ => return Err(invalid("wrong magic string")),
+    };
+    let (length, rest) = match (version, rest) {
+        (1, [a, b, rest @ ..]) => (u16::from_le_bytes([*a, *b]) as usize, rest),
+        (2 | 3, [a, b, c, d, rest @ ..]) => (u32::from_le_bytes([*a, *b, *c, *d]) as usize, rest),
+        _ => return Err(invalid("unsupported version")),
+    };
+    if rest.len() < length {
+        return Err(invalid("truncated header"));
+    }
+    let (header, data) = rest.split_at(length);
+    let header = std::str::from_utf8(header).map_err(|_| invalid("header is not UTF-8"))?;
+
+    // The header is a Python dictionary literal, e.g.,
+    // `{'descr': '<f8', 'fortran_order': False, 'shape': (3, 2), }`.
+    let value = |key: &str| {
+        let start = header.find(&format!("'{key}':")).map(|i| i + key.len() + 3);
+        start.map(|i| header[i..].trim_start())
+    };
+    let descr = value("descr")
+        .and_then(|v| v.strip_prefix('\''))
+        .and_then(|v| v.split('\'').next())
+        .ok_or_else(|| invalid("missing `descr`"))?;
+    let fortran_order = match value("fortran_order") {
+        Some(v) if v.starts_with("False") => false,
+        Some(v) if v.starts_with("True") => true,
+        _ => return Err(invalid("missing `fortran_order`")),
+    };
+    let shape = value("shape")
+        .and_then(|v| v.strip_prefix('('))
+        .and_then(|v| v.split(')').next())
+        .ok_or_else(|| invalid("missing `shape`"))?
+        .split(',')
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(|n| n.parse::<usize>().map_err(|_| invalid("invalid `shape`")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The shape comes from the untrusted header, so the number of bytes may overflow.
+    let count = shape
+        .iter()
+        .try_fold(1usize, |count, &n| count.checked_mul(n))
+        .filter(|count| count.checked_mul(8).is_some())
+        .ok_or_else(|| invalid("`shape` is too large"))?;
+    let values = match descr {
+        "<f8" | ">f8" if data.len() == count * 8 => (data.chunks(8))
+            .map(|v| {
+                let v = v.try_into().unwrap();
+                if descr == "<f8" {
+                    f64::from_le_bytes(v)
+                } else {
+                    f64::from_be_bytes(v)
+                }
+            })
+            .collect::<Vec<_>>(),
+        "<f4" | ">f4" if data.len() == count * 4 => (data.chunks(4))
+            .map(|v| {
+                let v = v.try_into().unwrap();
+                f64::from(if descr == "<f4" {
+                    f32::from_le_bytes(v)
+                } else {
+                    f32::from_be_bytes(v)
+                })
+            })
+            .collect(),
+        "<f8" | ">f8" | "<f4" | ">f4" => return Err(invalid("wrong length of data")),
+        _ => return Err(invalid(&format!("unsupported type `{descr}`"))),
+    };
+    if !values.iter().all(|v| v.is_finite()) {
+        return Err(invalid("contains non-finite numbers"));
+    }
+    let values = match (&shape[..], fortran_order) {
+        ([rows, columns], true) => (0..rows * columns)
+            .map(|i| values[i % columns * rows + i / columns])
+            .collect(),
+        _ => values,
+    };
+    Ok((shape, values))
+}
+
+impl Dataset {
+    /// Parse the NumPy arrays `x` of shape `(m, n)` and `y` of shape `(m,)` (or `(m, 1)`), if any,
+    /// in the `.npy` format of `float64` or `float32` numbers, e.g., written by `np.save`.
+    pub fn from_npy(x: &[u8], y: Option<&[u8]>) -> Result<Self, Error> {
+        let (shape, values) = parse_npy(x)?;
+        let [rows, columns] = shape[..] else {
+            return Err(Error::Data("the features should be a 2-D array".into()));
+        };
+        if rows == 0 || columns == 0 {
+            return Err(Error::Data("the features should be non-empty".into()));
+        }
+        let y = match y.map(parse_npy).transpose()? {
+            None => vec![],
+            Some((shape, values)) if shape == [rows] || shape == [rows, 1] => values,
+            Some(_) => {
+                return Err(Error::Data(
+                    "the targets should be a 1-D array of the same length as the features".into(),
+                ))
+            }
+        };
+        Ok(Self {
+            x: values.chunks(columns).map(<[_]>::to_vec).collect(),
+            y,
+        })
+    }
+
+    /// Read the `.npy` files at `x` and `y`, if any, see [`Dataset::from_npy`].
+    pub fn read_npy(x: impl AsRef<Path>, y: Option<impl AsRef<Path>>) -> Result<Self, Error> {
+        let read = |path: &Path| {
+            fs::read(path).map_err(|e| Error::Data(format!("{}: {e}", path.display())))
+        };
+        let x = read(x.as_ref())?;
+        let y = y.map(|y| read(y.as_ref())).transpose()?;
+        Self::from_npy(&x, y.as_deref())
+    }
+
+    /// Read the arrays named `x` and `y`, if any, of the `.npz` archive at `path`, e.g.,
+    /// written by `np.savez(path, x=x, y=y)` or `np.savez_compressed`, see
+    /// [`Dataset::from_npy`].
+    #[cfg(feature = "npz")]
+    pub fn read_npz(path: impl AsRef<Path>, x: &str, y: Option<&str>) -> Result<Self, Error> {
+        use std::io::Read;
+
+        let path = path.as_ref();
+        let invalid = |e: &dyn std::fmt::Display| Error::Data(format!("{}: {e}", path.display()));
+        let file = fs::File::open(path).map_err(|e| invalid(&e))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| invalid(&e))?;
+        let mut read = |name: &str| {
+            let mut bytes = vec![];
+            archive
+                .by_name(&format!("{name}.npy"))
+                .map_err(|e| invalid(&format!("array `{name}`: {e}")))?
+                .read_to_end(&mut bytes)
+                .map_err(|e| invalid(&e))?;
+            Ok::<_, Error>(bytes)
+        };
+        let x = read(x)?;
+        let y = y.map(&mut read).transpose()?;
+        Self::from_npy(&x, y.as_deref())
+    }
+}
+
+#[cfg(feature = "arrow")]
+mod arrow {
+    use std::{fs::File, path::Path};
+
+    use arrow_array::{
+        cast::AsArray,
+        types::{Float32Type, Float64Type, Int32Type, Int64Type},
+        Array, RecordBatch,
+    };
+    use arrow_schema::{ArrowError, DataType};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::{select_columns, Dataset};
+    use crate::Error;
+
+    /// Convert the values of the column `name` to `f64`, which should be floating-point numbers
+    /// or integers that are exactly representable, without nulls.
+    fn column_values(array: &dyn Array, name: &str) -> Result<Vec<f64>, Error> {
+        let invalid = |message: &str| Error::Data(format!("column `{name}` {message}"));
+        if array.null_count() > 0 {
+            return Err(invalid("contains nulls"));
+        }
+        let exact = |v: i64| {
+            let f = v as f64;
+            (f as i64 == v && f.abs() < 2f64.powi(63)).then_some(f)
+        };
+        let values = match array.data_type() {
+            DataType::Float64 => array.as_primitive::<Float64Type>().values().to_vec(),
+            DataType::Float32 => (array.as_primitive::<Float32Type>().values().iter())
+                .map(|&v| f64::from(v))
+                .collect(),
+            DataType::Int32 => (array.as_primitive::<Int32Type>().values().iter())
+                .map(|&v| f64::from(v))
+                .collect(),
+            DataType::Int64 => (array.as_primitive::<Int64Type>().values().iter())
+                .map(|&v| exact(v))
+                .collect::<Option<_>>()
+                .ok_or_else(|| invalid("contains integers that are not exactly representable"))?,
+            t => return Err(invalid(&format!("has the unsupported type {t}"))),
+        };
+        if !values.iter().all(|v: &f64| v.is_finite()) {
+            return Err(invalid("contains non-finite numbers"));
+        }
+        Ok(values)
+    }
+
+    impl Dataset {
+        /// Convert the Arrow record batch `batch`, whose columns are selected in the same way as
+        /// [`Dataset::from_csv`], and should be `Float64`, `Float32`, `Int32`, or `Int64`
+        /// without nulls.
+        pub fn from_record_batch(
+            batch: &RecordBatch,
+            features: Option<&[&str]>,
+            target: Option<&str>,
+        ) -> Result<Self, Error> {
+            let schema = batch.schema();
+            let names = schema
+                .fields()
+                .iter()
+                .map(|f| f.name().as_str())
+                .collect::<Vec<_>>();
+            let (features, target) =
+                select_columns(&names, features, target).map_err(Error::Data)?;
+            let column = |i: usize| column_values(batch.column(i), names[i]);
+
+            let columns = features
+                .iter()
+                .map(|&i| column(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Self {
+                x: (0..batch.num_rows())
+                    .map(|j| columns.iter().map(|c| c[j]).collect())
+                    .collect(),
+                y: target.map(column).transpose()?.unwrap_or_default(),
+            })
+        }
+
+        /// Read the whole Parquet file at `path`, see [`Dataset::from_record_batch`].
+        pub fn read_parquet(
+            path: impl AsRef<Path>,
+            features: Option<&[&str]>,
+            target: Option<&str>,
+        ) -> Result<Self, Error> {
+            let mut dataset = Self {
+                x: vec![],
+                y: vec![],
+            };
+            for chunk in parquet_chunks(path, features, target, usize::MAX)? {
+                let chunk = chunk?;
+                dataset.x.extend(chunk.x);
+                dataset.y.extend(chunk.y);
+            }
+            if dataset.x.is_empty() {
+                return Err(Error::Data("no samples".into()));
+            }
+            Ok(dataset)
+        }
+    }
+
+    /// Split the samples of the record batches `batches` into chunks of `chunk_size` samples,
+    /// except the last chunk, which may be smaller, regardless of the sizes of the batches.
+    /// Each chunk can be proven by a `StatisticsUpdateCircuit` of `chunk_size` samples (the last
+    /// chunk by one of its size), so that the dataset never has to be loaded at once.
+    pub fn record_batch_chunks<'a>(
+        batches: impl IntoIterator<Item = Result<RecordBatch, ArrowError>> + 'a,
+        features: Option<&'a [&'a str]>,
+        target: Option<&'a str>,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Result<Dataset, Error>> + 'a {
+        assert!(chunk_size > 0);
+        let mut batches = batches.into_iter();
+        let mut buffer = Dataset {
+            x: vec![],
+            y: vec![],
+        };
+        std::iter::from_fn(move || {
+            while buffer.x.len() < chunk_size {
+                match batches.next() {
+                    Some(batch) => {
+                        let batch = batch.map_err(|e| Error::Data(e.to_string()));
+                        match batch.and_then(|b| Dataset::from_record_batch(&b, features, target)) {
+                            Ok(b) => {
+                                buffer.x.extend(b.x);
+                                buffer.y.extend(b.y);
+                            }
+                            Err(e) => return Some(Err(e)),
+                        }
+                    }
+                    None => break,
+                }
+            }
+            if buffer.x.is_empty() {
+                return None;
+            }
+            let n = chunk_size.min(buffer.x.len());
+            Some(Ok(Dataset {
+                x: buffer.x.drain(..n).collect(),
+                y: buffer.y.drain(..n.min(buffer.y.len())).collect(),
+            }))
+        })
+    }
+
+    /// Read the Parquet file at `path` in chunks of `chunk_size` samples, see
+    /// [`record_batch_chunks`].
+    pub fn parquet_chunks<'a>(
+        path: impl AsRef<Path>,
+        features: Option<&'a [&'a str]>,
+        target: Option<&'a str>,
+        chunk_size: usize,
+    ) -> Result<impl Iterator<Item = Result<Dataset, Error>> + 'a, Error> {
+        let path = path.as_ref();
+        let invalid = |e: &dyn std::fmt::Display| Error::Data(format!("{}: {e}", path.display()));
+        let file = File::open(path).map_err(|e| invalid(&e))?;
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .map_err(|e| invalid(&e))?
+            .with_batch_size(chunk_size.clamp(1, 1 << 16))
+            .build()
+            .map_err(|e| invalid(&e))?;
+        Ok(record_batch_chunks(reader, features, target, chunk_size))
+    }
+}
+
+#[cfg(feature = "arrow")]
+pub use self::arrow::{parquet_chunks, record_batch_chunks};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "\
+id, age, income, score
+alice, 31, 5.5e4, 0.7
+
+bob, 45, 72000, -1.25
+";
+
+    #[test]
+    fn csv() -> Result<(), Error> {
+        let dataset = Dataset::from_csv(CSV, Some(&["income", "age"]), Some("score"))?;
+        assert_eq!(
+            dataset,
+            Dataset {
+                x: vec![vec![55000., 31.], vec![72000., 45.]],
+                y: vec![0.7, -1.25],
+            }
+        );
+        assert_eq!((dataset.num_samples(), dataset.num_features()), (2, 2));
+        let empty = Dataset {
+            x: vec![],
+            y: vec![],
+        };
+        assert_eq!((empty.num_samples(), empty.num_features()), (0, 0));
+
+        let all = Dataset::from_csv("a,b\n1,2\n3,4", None, Some("a"))?;
+        assert_eq!((all.x, all.y), (vec![vec![2.], vec![4.]], vec![1., 3.]));
+        let inference = Dataset::from_csv("a,b\n1,2", None, None)?;
+        assert_eq!((inference.x, inference.y), (vec![vec![1., 2.]], vec![]));
+
+        // The identifiers are not numbers.
+        assert!(Dataset::from_csv(CSV, None, Some("score")).is_err());
+        assert!(Dataset::from_csv(CSV, Some(&["height"]), None).is_err());
+        assert!(Dataset::from_csv("a,b\n1,NaN", None, None).is_err());
+        assert!(Dataset::from_csv("a,b\n1,inf", None, None).is_err());
+        assert!(Dataset::from_csv("a,b\n1,2,3", None, None).is_err());
+        assert!(Dataset::from_csv("a,a\n1,2", None, None).is_err());
+        assert!(Dataset::from_csv("a,b\n", None, None).is_err());
+        assert!(Dataset::from_csv("a\n1", None, Some("a")).is_err());
+        Ok(())
+    }
+
+    /// Encode `values` of `shape` as a `.npy` array of `float64` numbers in row-major order.
+    fn npy(shape: &[usize], values: &[f64]) -> Vec<u8> {
+        let shape = shape.iter().map(|n| format!("{n},")).collect::<String>();
+        let header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': ({shape}), }}\n");
+        let mut bytes = [
+            b"\x93NUMPY\x01\x00".as_slice(),
+            &(header.len() as u16).to_le_bytes(),
+        ]
+        .concat();
+        bytes.extend(header.as_bytes());
+        bytes.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn numpy() -> Result<(), Error> {
+        let x = npy(&[3, 2], &[1., 2., 3., 4., 5., 6.]);
+        let y = npy(&[3], &[0.5, -1., 2.]);
+        let expected = Dataset {
+            x: vec![vec![1., 2.], vec![3., 4.], vec![5., 6.]],
+            y: vec![0.5, -1., 2.],
+        };
+        assert_eq!(Dataset::from_npy(&x, Some(&y))?, expected);
+        assert!(Dataset::from_npy(&x, None)?.y.is_empty());
+
+        // The same features in `float32` and in column-major order.
+        let header = "{'descr': '<f4', 'fortran_order': True, 'shape': (3, 2), }";
+        let mut fortran = [
+            b"\x93NUMPY\x02\x00".as_slice(),
+            &(header.len() as u32).to_le_bytes(),
+        ]
+        .concat();
+        fortran.extend(header.as_bytes());
+        fortran.extend(
+            [1f32, 3., 5., 2., 4., 6.]
+                .iter()
+                .flat_map(|v| v.to_le_bytes()),
+        );
+        assert_eq!(Dataset::from_npy(&fortran, Some(&y))?, expected);
+
+        assert!(Dataset::from_npy(&y, None).is_err());
+        assert!(Dataset::from_npy(&x, Some(&npy(&[2], &[0.5, -1.]))).is_err());
+        assert!(Dataset::from_npy(&npy(&[1, 2], &[1., f64::NAN]), None).is_err());
+        assert!(Dataset::from_npy(&x[..x.len() - 1], None).is_err());
+        assert!(Dataset::from_npy(&npy(&[1 << 62, 2], &[]), None).is_err());
+        assert!(Dataset::from_npy(&npy(&[usize::MAX, 2], &[]), None).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "npz")]
+    #[test]
+    fn npz() -> Result<(), Box<dyn std::error::Error>> {
+        use std::io::Write;
+
+        use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+        let path = std::env::temp_dir().join(format!("r1cs_float_{}.npz", std::process::id()));
+        let mut writer = ZipWriter::new(fs::File::create(&path)?);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        writer.start_file("features.npy", options)?;
+        writer.write_all(&npy(&[2, 1], &[1., 2.]))?;
+        writer.start_file("targets.npy", options)?;
+        writer.write_all(&npy(&[2], &[3., 4.]))?;
+        writer.finish()?;
+
+        assert_eq!(
+            Dataset::read_npz(&path, "features", Some("targets"))?,
+            Dataset {
+                x: vec![vec![1.], vec![2.]],
+                y: vec![3., 4.],
+            }
+        );
+        assert!(Dataset::read_npz(&path, "x", None).is_err());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn parquet() -> Result<(), Box<dyn std::error::Error>> {
+        use std::{fs::File, sync::Arc};
+
+        use arrow_array::{ArrayRef, Float32Array, Float64Array, Int64Array, RecordBatch};
+        use parquet::arrow::ArrowWriter;
+
+        let batch = |offset: usize, n: usize| -> Result<RecordBatch, arrow_schema::ArrowError> {
+            let range = offset..offset + n;
+            RecordBatch::try_from_iter([
+                (
+                    "id",
+                    Arc::new(Int64Array::from_iter_values(
+                        range.clone().map(|i| i as i64),
+                    )) as ArrayRef,
+                ),
+                (
+                    "a",
+                    Arc::new(Float32Array::from_iter_values(
+                        range.clone().map(|i| i as f32 / 4.),
+                    )),
+                ),
+                (
+                    "y",
+                    Arc::new(Float64Array::from_iter_values(range.map(|i| i as f64 * 2.))),
+                ),
+            ])
+        };
+        let expected = Dataset {
+            x: (0..7).map(|i| vec![i as f64 / 4., i as f64]).collect(),
+            y: (0..7).map(|i| i as f64 * 2.).collect(),
+        };
+        let features: &[&str] = &["a", "id"];
+        assert_eq!(
+            Dataset::from_record_batch(&batch(0, 7)?, Some(features), Some("y"))?,
+            expected
+        );
+
+        // The chunks do not depend on the sizes of the batches.
+        let chunks = record_batch_chunks(
+            [batch(0, 2), batch(2, 4), batch(6, 1)],
+            Some(features),
+            Some("y"),
+            3,
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            chunks.iter().map(|c| c.num_samples()).collect::<Vec<_>>(),
+            [3, 3, 1]
+        );
+        assert_eq!(
+            chunks.iter().flat_map(|c| c.x.clone()).collect::<Vec<_>>(),
+            expected.x
+        );
+
+        let path = std::env::temp_dir().join(format!("r1cs_float_{}.parquet", std::process::id()));
+        let mut writer = ArrowWriter::try_new(File::create(&path)?, batch(0, 1)?.schema(), None)?;
+        writer.write(&batch(0, 4)?)?;
+        writer.flush()?;
+        writer.write(&batch(4, 3)?)?;
+        writer.close()?;
+        assert_eq!(
+            Dataset::read_parquet(&path, Some(features), Some("y"))?,
+            expected
+        );
+        let chunks = parquet_chunks(&path, None, Some("y"), 5)?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            chunks.iter().map(|c| c.num_samples()).collect::<Vec<_>>(),
+            [5, 2]
+        );
+        assert_eq!(chunks[1].x, [[5., 1.25], [6., 1.5]]);
+        std::fs::remove_file(&path)?;
+
+        let nulls = RecordBatch::try_from_iter([(
+            "a",
+            Arc::new(Float64Array::from(vec![Some(1.), None])) as ArrayRef,
+        )])?;
+        assert!(Dataset::from_record_batch(&nulls, None, None).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,379 @@
+//! Fixed-precision decimal numbers, i.e., integers scaled by `10^P`, which represent decimal
+//! amounts (e.g., cents with `P = 2`) exactly, unlike binary floating-point numbers, where `0.1`
+//! is already rounded when it is allocated.
+
+use std::fmt::{Debug, Formatter};
+
+use ark_ff::PrimeField;
+use num::{BigInt, BigRational, ToPrimitive};
+
+use crate::{
+    error::Error,
+    float::FloatVar,
+    powers::pow2,
+    r1cs::{ConstraintSystemRef, Namespace, SynthesisError},
+    r1cs_std::{
+        alloc::{AllocVar, AllocationMode},
+        boolean::Boolean,
+        fields::{fp::FpVar, FieldVar},
+        prelude::EqGadget,
+        R1CSVar,
+    },
+    rational::{parse_decimal, Rounding},
+    traits::BitDecompose,
+    utils::{field_to_signed, signed_to_field},
+};
+
+/// `DecimalVar` represents the decimal number `n / 10^P` in the constraint system by the integer
+/// `n`, which is in the range of `i64`, i.e., `[-2^63, 2^63)`. A negative integer `-n` is
+/// represented by the field element `-n`.
+///
+/// All operations are exact, except for the ones that drop digits (`Self::mul` and
+/// `Self::rescale` to a lower precision), which round the result by an explicit `Rounding`.
+/// The circuit is unsatisfiable if a result is out of the range.
+#[derive(Clone)]
+pub struct DecimalVar<F: PrimeField, const P: u32> {
+    scaled: FpVar<F>,
+}
+
+impl<F: PrimeField, const P: u32> Debug for DecimalVar<F, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.value() {
+            Ok(v) => write!(f, "{}", Self::format(v)),
+            Err(_) => write!(f, "<unassigned>"),
+        }
+    }
+}
+
+impl<F: PrimeField, const P: u32> DecimalVar<F, P> {
+    /// The bit length of the magnitude of the scaled integers.
+    pub const BITS: usize = 63;
+
+    /// `10^P`, which is checked to be an `i64` when it is used.
+    const SCALE: i64 = 10i64.pow(P);
+
+    /// Parse a decimal string with an optional sign, fraction, and exponent, e.g., `-12.34` or
+    /// `1.5e3`, into the integer `n` such that the value is `n / 10^P`.
+    /// Return an error if the string is invalid, has more than `P` significant fractional digits,
+    /// or is out of the range, since the value cannot be represented exactly.
+    pub fn parse(value: &str) -> Result<i64, Error> {
+        let (sign, magnitude) = parse_decimal(value, 40 + P as i64)?;
+        let scaled = magnitude * BigInt::from(Self::SCALE);
+        let n = scaled
+            .is_integer()
+            .then(|| scaled.to_integer())
+            .and_then(|n| if sign { -n } else { n }.to_i64())
+            .ok_or_else(|| {
+                Error::Conversion(format!(
+                    "cannot represent {value:?} exactly with {P}-digit precision"
+                ))
+            })?;
+
+        Ok(n)
+    }
+
+    /// Format the number `n / 10^P` with exactly `P` fractional digits, e.g., `-12.30` for `-1230`
+    /// with `P = 2`.
+    pub fn format(n: i64) -> String {
+        let (sign, n) = (if n < 0 { "-" } else { "" }, n.unsigned_abs());
+        let scale = Self::SCALE as u64;
+        match P {
+            0 => format!("{sign}{n}"),
+            _ => format!("{sign}{}.{:0p$}", n / scale, n % scale, p = P as usize),
+        }
+    }
+
+    /// Allocate a variable from the scaled integer `n` returned by `f`, i.e., the number
+    /// `n / 10^P`.
+    pub fn new_variable(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<i64, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let scaled = FpVar::new_variable(cs, || f().map(|n| signed_to_field::<F>(n.into())), mode)?;
+        Self::from_scaled(scaled)
+    }
+
+    /// Allocate a variable from a decimal string, see `Self::parse`.
+    pub fn new_decimal(
+        cs: impl Into<Namespace<F>>,
+        value: &str,
+        mode: AllocationMode,
+    ) -> Result<Self, Error> {
+        let n = Self::parse(value)?;
+        Ok(Self::new_variable(cs, || Ok(n), mode)?)
+    }
+
+    /// Wrap the scaled integer `n` (e.g., computed by other gadgets), and enforce that it is in
+    /// the range.
+    pub fn from_scaled(n: FpVar<F>) -> Result<Self, SynthesisError> {
+        // `n` is in `[-2^BITS, 2^BITS)` if and only if `n + 2^BITS` has `BITS + 1` bits.
+        (&n + pow2::<F>(Self::BITS)).enforce_bit_length(Self::BITS + 1)?;
+        Ok(Self { scaled: n })
+    }
+
+    /// The scaled integer `n` of the number `n / 10^P`.
+    pub fn scaled(&self) -> &FpVar<F> {
+        &self.scaled
+    }
+
+    /// Compute the public input for a number `n / 10^P` allocated in the input mode, which is the
+    /// same as `PublicInputs::add_integer(n)`.
+    pub fn verifier_input(n: i64) -> F {
+        signed_to_field(n.into())
+    }
+
+    /// Compute the public input for a number allocated by `Self::new_decimal` in the input mode,
+    /// or return an error if the string cannot be represented exactly.
+    pub fn verifier_input_decimal(value: &str) -> Result<F, Error> {
+        Ok(Self::verifier_input(Self::parse(value)?))
+    }
+
+    /// The scaled integer `n` of the number `n / 10^P`.
+    pub fn value(&self) -> Result<i64, Error> {
+        field_to_signed(self.scaled.value()?)
+            .and_then(|n| n.to_i64())
+            .ok_or_else(|| Error::Conversion("the scaled integer is out of the range".into()))
+    }
+
+    /// The value of the number as a decimal string with `P` fractional digits.
+    pub fn to_decimal(&self) -> Result<String, Error> {
+        Ok(Self::format(self.value()?))
+    }
+
+    pub fn negate(&self) -> Result<Self, SynthesisError> {
+        Self::from_scaled(self.scaled.negate()?)
+    }
+
+    pub fn add(x: &Self, y: &Self) -> Result<Self, SynthesisError> {
+        Self::from_scaled(&x.scaled + &y.scaled)
+    }
+
+    pub fn sub(x: &Self, y: &Self) -> Result<Self, SynthesisError> {
+        Self::from_scaled(&x.scaled - &y.scaled)
+    }
+
+    /// Multiply two numbers, whose exact product has `2P` fractional digits, and round it to `P`
+    /// digits by `rounding`.
+    pub fn mul(x: &Self, y: &Self, rounding: Rounding) -> Result<Self, SynthesisError> {
+        Self::divide(&(&x.scaled * &y.scaled), Self::SCALE, rounding)
+    }
+
+    /// Convert the number to precision `Q`, which is exact if `Q >= P`, and rounded by `rounding`
+    /// otherwise.
+    pub fn rescale<const Q: u32>(
+        &self,
+        rounding: Rounding,
+    ) -> Result<DecimalVar<F, Q>, SynthesisError> {
+        if Q >= P {
+            DecimalVar::from_scaled(&self.scaled * F::from(10u64.pow(Q - P)))
+        } else {
+            DecimalVar::divide(&self.scaled, 10i64.pow(P - Q), rounding)
+        }
+    }
+
+    /// Compute `n / divisor` rounded to an integer by `rounding`, where `n` is in the range of
+    /// `i128`, and `divisor` is positive.
+    fn divide(n: &FpVar<F>, divisor: i64, rounding: Rounding) -> Result<Self, SynthesisError> {
+        let cs = n.cs();
+        // `n = q * divisor + r`, where `q = floor(n / divisor)` and `0 <= r < divisor`.
+        let (q, r) = {
+            let division = n.value().and_then(|n| {
+                let n = field_to_signed(n).ok_or(SynthesisError::Unsatisfiable)?;
+                Ok((n.div_euclid(divisor as i128), n.rem_euclid(divisor as i128)))
+            });
+            (
+                FpVar::new_hint(cs.clone(), || {
+                    division.map(|(q, _)| signed_to_field::<F>(q))
+                })?,
+                FpVar::new_hint(cs.clone(), || {
+                    division.map(|(_, r)| signed_to_field::<F>(r))
+                })?,
+            )
+        };
+        let divisor_bits = 64 - divisor.leading_zeros() as usize;
+        r.enforce_bit_length(divisor_bits)?;
+        (FpVar::constant(F::from(divisor as u64 - 1)) - &r).enforce_bit_length(divisor_bits)?;
+        (&q * F::from(divisor as u64) + &r).enforce_equal(n)?;
+
+        let is_exact = r.is_zero()?;
+        let round_up = match rounding {
+            // The range of `q` is enforced by that of the result.
+            Rounding::TowardNegative => Boolean::FALSE,
+            Rounding::TowardPositive => is_exact.not(),
+            // The quotient is rounded up only if it is negative, i.e., `n` is negative.
+            Rounding::TowardZero => q.is_positive(Self::BITS)?.or(&is_exact)?.not(),
+            Rounding::NearestEven => {
+                // `q` is decomposed into `2 * q_half + q_is_odd`, so that ties can be rounded to
+                // even, which also enforces the range of `q`.
+                let q_is_odd = Boolean::new_hint(cs.clone(), || {
+                    q.value()
+                        .map(|q| field_to_signed(q).is_some_and(|q| q & 1 == 1))
+                })?;
+                let q_half = (&q - FpVar::from(q_is_odd.clone())) * F::from(2u8).inverse().unwrap();
+                (q_half + pow2::<F>(Self::BITS - 1)).enforce_bit_length(Self::BITS)?;
+                let twice_r = r.double()?;
+                let above_half =
+                    (&twice_r - F::from(divisor as u64 + 1)).is_positive(divisor_bits + 1)?;
+                let is_tie = twice_r.is_eq(&FpVar::constant(F::from(divisor as u64)))?;
+                above_half.or(&is_tie.and(&q_is_odd)?)?
+            }
+        };
+
+        Self::from_scaled(q + FpVar::from(round_up))
+    }
+
+    pub fn is_lt(x: &Self, y: &Self) -> Result<Boolean<F>, SynthesisError> {
+        Ok(Self::is_le(y, x)?.not())
+    }
+
+    pub fn is_le(x: &Self, y: &Self) -> Result<Boolean<F>, SynthesisError> {
+        (&y.scaled - &x.scaled).is_positive(Self::BITS + 1)
+    }
+
+    pub fn is_gt(x: &Self, y: &Self) -> Result<Boolean<F>, SynthesisError> {
+        Self::is_lt(y, x)
+    }
+
+    pub fn is_ge(x: &Self, y: &Self) -> Result<Boolean<F>, SynthesisError> {
+        Self::is_le(y, x)
+    }
+
+    /// `10^P` as a floating-point constant.
+    fn float_scale<const E: usize, const M: usize>() -> Result<FloatVar<F, E, M>, SynthesisError> {
+        FloatVar::new_rational(
+            ConstraintSystemRef::None,
+            &BigRational::from_integer(Self::SCALE.into()),
+            Rounding::NearestEven,
+            AllocationMode::Constant,
+        )
+    }
+
+    /// Convert the number to a floating-point number, i.e., `n / 10^P` computed by a division of
+    /// `n` by `10^P`. The result is correctly rounded if `|n| <= 2^(M + 1)` and `10^P` is exactly
+    /// representable (e.g., `P <= 22` for `binary64`), in which case the division is the only
+    /// rounding. Otherwise, the conversion of `n` itself may also be rounded.
+    pub fn to_float<const E: usize, const M: usize>(
+        &self,
+    ) -> Result<FloatVar<F, E, M>, SynthesisError> {
+        Ok(FloatVar::from_fixed(&self.scaled, 0, Self::BITS + 1)? / Self::float_scale()?)
+    }
+
+    /// Convert a floating-point number to `P` fractional digits, i.e., `x * 10^P` rounded to the
+    /// nearest integer with ties to even, where the product itself is rounded to the format of
+    /// `x` first. The circuit is unsatisfiable if the number is NaN, infinity, or out of the range.
+    pub fn from_float<const E: usize, const M: usize>(
+        x: &FloatVar<F, E, M>,
+    ) -> Result<Self, SynthesisError> {
+        let (_, n) = FloatVar::round(&(x * Self::float_scale()?), Self::BITS)?;
+        Ok(Self { scaled: n })
+    }
+}
+
+impl<F: PrimeField, const P: u32> EqGadget<F> for DecimalVar<F, P> {
+    fn is_eq(&self, other: &Self) -> Result<Boolean<F>, SynthesisError> {
+        self.scaled.is_eq(&other.scaled)
+    }
+
+    fn enforce_equal(&self, other: &Self) -> Result<(), SynthesisError> {
+        self.scaled.enforce_equal(&other.scaled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{f64::F64Var, r1cs::ConstraintSystem};
+    use ark_bls12_381::Fr;
+
+    type Cents = DecimalVar<Fr, 2>;
+
+    #[test]
+    fn parse_and_format() -> Result<(), Error> {
+        for (value, expected) in [
+            ("0", 0),
+            ("12.34", 1234),
+            ("-0.05", -5),
+            ("+1.5e3", 150000),
+            ("1200e-4", 12),
+            ("1.230000", 123),
+            ("92233720368547758.07", i64::MAX),
+        ] {
+            assert_eq!(Cents::parse(value)?, expected, "{value}");
+        }
+        for value in [
+            "0.001",
+            "1e-9999",
+            "92233720368547758.08",
+            "1e9999",
+            "abc",
+            "",
+        ] {
+            assert!(Cents::parse(value).is_err(), "{value}");
+        }
+        assert_eq!(Cents::format(-1230), "-12.30");
+        assert_eq!(Cents::format(5), "0.05");
+        assert_eq!(DecimalVar::<Fr, 0>::format(-7), "-7");
+        Ok(())
+    }
+
+    #[test]
+    fn arithmetic() -> Result<(), Error> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let new = |v: &str| Cents::new_decimal(cs.clone(), v, AllocationMode::Witness);
+        let (x, y) = (new("0.10")?, new("0.20")?);
+        // Unlike binary floating-point numbers, `0.1 + 0.2` is exactly `0.3`.
+        Cents::add(&x, &y)?.enforce_equal(&new("0.3")?)?;
+        assert_eq!(Cents::sub(&x, &y)?.to_decimal()?, "-0.10");
+        assert_eq!(x.negate()?.value()?, -10);
+
+        // `1.25 * 0.5 = 0.625` and `-1.25 * 0.5 = -0.625` are ties.
+        let (a, b, c) = (new("1.25")?, new("0.5")?, new("-1.25")?);
+        for (rounding, positive, negative) in [
+            (Rounding::NearestEven, 62, -62),
+            (Rounding::TowardZero, 62, -62),
+            (Rounding::TowardPositive, 63, -62),
+            (Rounding::TowardNegative, 62, -63),
+        ] {
+            assert_eq!(Cents::mul(&a, &b, rounding)?.value()?, positive);
+            assert_eq!(Cents::mul(&c, &b, rounding)?.value()?, negative);
+        }
+        // `0.75 * 0.5 = 0.375` is a tie rounded up to the even `0.38`.
+        let d = Cents::mul(&new("0.75")?, &b, Rounding::NearestEven)?;
+        assert_eq!(d.value()?, 38);
+        assert_eq!(Cents::mul(&a, &a, Rounding::NearestEven)?.value()?, 156);
+
+        let dollars = c.rescale::<0>(Rounding::NearestEven)?;
+        assert_eq!(dollars.value()?, -1);
+        assert_eq!(dollars.rescale::<3>(Rounding::NearestEven)?.value()?, -1000);
+
+        assert!(Cents::is_lt(&c, &x)?.value()?);
+        assert!(Cents::is_le(&x, &x)?.value()?);
+        assert!(!Cents::is_gt(&x, &y)?.value()?);
+        assert!(Cents::is_ge(&y, &x)?.value()?);
+        assert!(cs.is_satisfied()?);
+
+        // The product of the largest numbers is out of the range.
+        let max = Cents::new_variable(cs.clone(), || Ok(i64::MAX), AllocationMode::Witness)?;
+        Cents::mul(&max, &max, Rounding::NearestEven)?;
+        assert!(!cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn conversions() -> Result<(), Error> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = Cents::new_decimal(cs.clone(), "-1234.56", AllocationMode::Input)?;
+        assert_eq!(x.to_float::<11, 52>()?.to_f64()?, -1234.56);
+        let y = F64Var::new_witness(cs.clone(), || Ok(0.1))?;
+        assert_eq!(Cents::from_float(&y)?.value()?, 10);
+        let z = F64Var::new_witness(cs.clone(), || Ok(-0.125))?;
+        assert_eq!(Cents::from_float(&z)?.value()?, -12);
+        assert!(cs.is_satisfied()?);
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            [Cents::verifier_input_decimal("-1234.56")?]
+        );
+        Ok(())
+    }
+}
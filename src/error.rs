@@ -0,0 +1,78 @@
+use core::fmt;
+
+use ark_serialize::SerializationError;
+
+use crate::{
+    groth16::{aggregation::AggregationError, mpc::CeremonyError},
+    r1cs::SynthesisError,
+};
+
+/// The errors that may occur when using this crate, which wrap the errors of the underlying
+/// components so that a single error type can be propagated by `?`.
+#[derive(Debug)]
+pub enum Error {
+    /// An error when synthesizing a circuit, or when proving or verifying a proof.
+    Synthesis(SynthesisError),
+    /// An error when serializing or deserializing keys, proofs, or other artifacts.
+    Serialization(SerializationError),
+    /// An error when aggregating proofs or verifying aggregate proofs.
+    Aggregation(AggregationError),
+    /// An error when verifying or finalizing a phase-2 ceremony.
+    Ceremony(CeremonyError),
+    /// A value cannot be converted to the requested type, e.g., a `binary64` number to `f32`.
+    Conversion(String),
+    /// A model or a key cannot be imported from another framework, e.g., a malformed ONNX file.
+    Import(String),
+    /// A dataset cannot be loaded, e.g., a CSV file with non-finite values.
+    Data(String),
+}
+
+impl ark_std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn ark_std::error::Error + 'static)> {
+        match self {
+            Error::Synthesis(e) => Some(e),
+            Error::Serialization(e) => Some(e),
+            Error::Aggregation(e) => Some(e),
+            Error::Ceremony(e) => Some(e),
+            Error::Conversion(_) | Error::Import(_) | Error::Data(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Error::Synthesis(e) => write!(f, "synthesis error: {e}"),
+            Error::Serialization(e) => write!(f, "serialization error: {e}"),
+            Error::Aggregation(e) => write!(f, "aggregation error: {e}"),
+            Error::Ceremony(e) => write!(f, "ceremony error: {e}"),
+            Error::Conversion(e) => write!(f, "conversion error: {e}"),
+            Error::Import(e) => write!(f, "import error: {e}"),
+            Error::Data(e) => write!(f, "data error: {e}"),
+        }
+    }
+}
+
+impl From<SynthesisError> for Error {
+    fn from(e: SynthesisError) -> Self {
+        Error::Synthesis(e)
+    }
+}
+
+impl From<SerializationError> for Error {
+    fn from(e: SerializationError) -> Self {
+        Error::Serialization(e)
+    }
+}
+
+impl From<AggregationError> for Error {
+    fn from(e: AggregationError) -> Self {
+        Error::Aggregation(e)
+    }
+}
+
+impl From<CeremonyError> for Error {
+    fn from(e: CeremonyError) -> Self {
+        Error::Ceremony(e)
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{float::FloatVar, unnormalized::UnnormalizedFloatVar};
+
+pub type F16Var<F> = FloatVar<F, 5, 10>;
+pub type UnnormalizedF16Var<F> = UnnormalizedFloatVar<F, 5, 10>;
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use half::f16;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        f32::F32Var,
+        r1cs::{ConstraintSystem, ConstraintSystemRef},
+        r1cs_std::{prelude::AllocVar, R1CSVar},
+        report::ConstraintReport,
+    };
+    use ark_bls12_381::Fr;
+    use rayon::prelude::*;
+
+    /// Special values followed by random ones, where the random bits cover all formats of numbers
+    /// (normal, subnormal, infinite, and NaN).
+    fn values(count: usize) -> Vec<f16> {
+        let rng = &mut rand::rngs::StdRng::seed_from_u64(16);
+        [
+            0.,
+            -0.,
+            1.,
+            -1.,
+            0.5,
+            65504.,
+            -65504.,
+            6.1e-5,
+            6e-8,
+            f32::INFINITY,
+            f32::NAN,
+        ]
+        .into_iter()
+        .map(f16::from_f32)
+        .chain([f16::MIN_POSITIVE_SUBNORMAL, f16::MAX, f16::EPSILON])
+        .chain((0..count).map(|_| f16::from_bits(rng.gen())))
+        .collect()
+    }
+
+    fn is_same(a: f16, b: f16) -> bool {
+        (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+    }
+
+    fn test_binary_op(
+        op: fn(F16Var<Fr>, F16Var<Fr>) -> F16Var<Fr>,
+        expected: fn(f16, f16) -> f16,
+    ) -> Result<(), Box<dyn Error>> {
+        let values = values(40);
+        let failures = values
+            .par_iter()
+            .flat_map(|&a| values.par_iter().map(move |&b| (a, b)))
+            .filter(|&(a, b)| {
+                let cs = ConstraintSystem::<Fr>::new_ref();
+                let x = F16Var::new_witness(cs.clone(), || Ok(a)).unwrap();
+                let y = F16Var::new_witness(cs.clone(), || Ok(b)).unwrap();
+                let r = op(x, y).to_f16().unwrap();
+                !(cs.is_satisfied().unwrap() && is_same(r, expected(a, b)))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(failures.len(), 0, "{failures:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_add() -> Result<(), Box<dyn Error>> {
+        test_binary_op(std::ops::Add::add, |a, b| a + b)
+    }
+
+    #[test]
+    fn test_sub() -> Result<(), Box<dyn Error>> {
+        test_binary_op(std::ops::Sub::sub, |a, b| a - b)
+    }
+
+    #[test]
+    fn test_mul() -> Result<(), Box<dyn Error>> {
+        test_binary_op(std::ops::Mul::mul, |a, b| a * b)
+    }
+
+    #[test]
+    fn test_div() -> Result<(), Box<dyn Error>> {
+        test_binary_op(std::ops::Div::div, |a, b| a / b)
+    }
+
+    #[test]
+    fn test_sqrt_and_comparisons() -> Result<(), Box<dyn Error>> {
+        let values = values(200);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let vars = F16Var::new_witness_vec(cs.clone(), &values)?;
+        for (&a, x) in values.iter().zip(&vars) {
+            let expected = f16::from_f32(a.to_f32().sqrt());
+            assert!(is_same(F16Var::sqrt(x)?.to_f16()?, expected), "sqrt({a})");
+        }
+        for (&a, x) in values.iter().zip(&vars).take(20) {
+            for (&b, y) in values.iter().zip(&vars) {
+                assert_eq!(F16Var::is_lt(x, y)?.value()?, a < b, "{a} < {b}");
+                assert_eq!(F16Var::is_le(x, y)?.value()?, a <= b, "{a} <= {b}");
+            }
+        }
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn constraints() -> Result<(), Box<dyn Error>> {
+        // Half precision takes fewer lookups than single precision for the same operations.
+        let report = |half: bool| -> Result<ConstraintReport, Box<dyn Error>> {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            if half {
+                let x = F16Var::new_witness(cs.clone(), || Ok(f16::from_f32(0.1)))?;
+                let y = F16Var::new_witness(cs.clone(), || Ok(f16::from_f32(0.2)))?;
+                F16Var::sqrt(&(&(&x + &y) * &(&x / &y)))?;
+            } else {
+                let x = F32Var::new_witness(cs.clone(), || Ok(0.1f32))?;
+                let y = F32Var::new_witness(cs.clone(), || Ok(0.2f32))?;
+                F32Var::sqrt(&(&(&x + &y) * &(&x / &y)))?;
+            }
+            assert!(cs.is_satisfied()?);
+            Ok(ConstraintReport::of(&cs))
+        };
+        let (half, single) = (report(true)?, report(false)?);
+        assert!(half.num_lookup_bits() < single.num_lookup_bits());
+
+        let x = F16Var::<Fr>::new_constant(ConstraintSystemRef::None, f16::from_f32(1.5))?;
+        assert_eq!(x.to_f16()?, f16::from_f32(1.5));
+        assert!(x.to_f32().is_err());
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
-use crate::float::FloatVar;
+use crate::{float::FloatVar, unnormalized::UnnormalizedFloatVar};
 
 pub type F32Var<F> = FloatVar<F, 8, 23>;
+pub type UnnormalizedF32Var<F> = UnnormalizedFloatVar<F, 8, 23>;
 
 #[cfg(test)]
 mod tests {
@@ -13,13 +14,15 @@ mod tests {
 
     use super::*;
     use crate::{
-        r1cs::{ConstraintSystem, ConstraintSystemRef},
+        groth16::LOOKUP_TABLE_BITS,
+        r1cs::{ConstraintSystem, ConstraintSystemRef, SynthesisError},
         r1cs_std::{
             prelude::{AllocVar, Boolean},
             R1CSVar,
         },
     };
     use ark_bls12_381::Fr;
+    use ark_ff::PrimeField;
     use num::ToPrimitive;
     use rayon::prelude::*;
 
@@ -36,7 +39,8 @@ mod tests {
 
         println!(
             "{}",
-            num_constraints(&cs, || F32Var::new_witness(cs.clone(), || Ok(0.1f32)).unwrap())
+            num_constraints(&cs, || F32Var::new_witness(cs.clone(), || Ok(0.1f32))
+                .unwrap())
         );
 
         assert!(cs.is_satisfied()?);
@@ -270,9 +274,9 @@ mod tests {
         Ok(())
     }
 
-    fn test_unary_op(
+    fn test_unary_op<F: PrimeField>(
         test_data: File,
-        op: fn(F32Var<Fr>) -> F32Var<Fr>,
+        op: fn(F32Var<F>) -> F32Var<F>,
     ) -> Result<(), Box<dyn Error>> {
         test_op(
             test_data,
@@ -285,7 +289,7 @@ mod tests {
             },
             |_| true,
             |v| {
-                let cs = ConstraintSystem::<Fr>::new_ref();
+                let cs = ConstraintSystem::<F>::new_ref();
                 let a = F32Var::new_witness(cs.clone(), || Ok(v[0])).unwrap();
 
                 let r = op(a).value().unwrap().to_u32().unwrap();
@@ -296,9 +300,9 @@ mod tests {
         )
     }
 
-    fn test_binary_op(
+    fn test_binary_op<F: PrimeField>(
         test_data: File,
-        op: fn(F32Var<Fr>, F32Var<Fr>) -> F32Var<Fr>,
+        op: fn(F32Var<F>, F32Var<F>) -> F32Var<F>,
     ) -> Result<(), Box<dyn Error>> {
         test_op(
             test_data,
@@ -311,7 +315,7 @@ mod tests {
             },
             |_| true,
             |v| {
-                let cs = ConstraintSystem::<Fr>::new_ref();
+                let cs = ConstraintSystem::<F>::new_ref();
                 let a = F32Var::new_witness(cs.clone(), || Ok(v[0])).unwrap();
                 let b = F32Var::new_witness(cs.clone(), || Ok(v[1])).unwrap();
 
@@ -323,9 +327,9 @@ mod tests {
         )
     }
 
-    fn test_comparison_op(
+    fn test_comparison_op<F: PrimeField>(
         test_data: File,
-        op: fn(F32Var<Fr>, F32Var<Fr>) -> Boolean<Fr>,
+        op: fn(F32Var<F>, F32Var<F>) -> Boolean<F>,
     ) -> Result<(), Box<dyn Error>> {
         test_op(
             test_data,
@@ -340,7 +344,7 @@ mod tests {
             },
             |_| true,
             |(a, b, c)| {
-                let cs = ConstraintSystem::<Fr>::new_ref();
+                let cs = ConstraintSystem::<F>::new_ref();
                 let a = F32Var::new_witness(cs.clone(), || Ok(*a)).unwrap();
                 let b = F32Var::new_witness(cs.clone(), || Ok(*b)).unwrap();
 
@@ -353,73 +357,212 @@ mod tests {
 
     #[test]
     fn test_add() -> Result<(), Box<dyn Error>> {
-        test_binary_op(File::open("data/f32/add")?, std::ops::Add::add)
+        test_binary_op::<Fr>(File::open("data/f32/add")?, std::ops::Add::add)
     }
 
     #[test]
     fn test_sub() -> Result<(), Box<dyn Error>> {
-        test_binary_op(File::open("data/f32/sub")?, std::ops::Sub::sub)
+        test_binary_op::<Fr>(File::open("data/f32/sub")?, std::ops::Sub::sub)
     }
 
     #[test]
     fn test_mul() -> Result<(), Box<dyn Error>> {
-        test_binary_op(File::open("data/f32/mul")?, std::ops::Mul::mul)
+        test_binary_op::<Fr>(File::open("data/f32/mul")?, std::ops::Mul::mul)
     }
 
     #[test]
     fn test_div() -> Result<(), Box<dyn Error>> {
-        test_binary_op(File::open("data/f32/div")?, std::ops::Div::div)
+        test_binary_op::<Fr>(File::open("data/f32/div")?, std::ops::Div::div)
     }
 
     #[test]
     fn test_sqrt() -> Result<(), Box<dyn Error>> {
-        test_unary_op(File::open("data/f32/sqrt")?, |x| F32Var::sqrt(&x).unwrap())
+        test_unary_op::<Fr>(File::open("data/f32/sqrt")?, |x| F32Var::sqrt(&x).unwrap())
+    }
+
+    /// Check `op` against `expected` on `inputs`, where finite results should be within 2 ulps
+    /// and special values should be exact.
+    fn test_transcendental_op(
+        inputs: &[f32],
+        op: fn(&F32Var<Fr>) -> Result<F32Var<Fr>, SynthesisError>,
+        expected: fn(f32) -> f32,
+    ) -> Result<(), Box<dyn Error>> {
+        for &v in inputs {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a = F32Var::new_witness(cs.clone(), || Ok(v))?;
+            let r = f32::from_bits(op(&a)?.value()?.to_u32().unwrap());
+            assert!(cs.is_satisfied()?, "{v}");
+            let e = expected(v);
+            let close = r.is_finite()
+                && e.is_finite()
+                && r.is_sign_negative() == e.is_sign_negative()
+                && r.to_bits().abs_diff(e.to_bits()) <= 2;
+            assert!(
+                close || r.to_bits() == e.to_bits() || (r.is_nan() && e.is_nan()),
+                "{v}: {r} != {e}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp2() -> Result<(), Box<dyn Error>> {
+        let specials = [f32::INFINITY, f32::NEG_INFINITY, f32::NAN, 0., -0.];
+        let inputs = [
+            1., -1., 0.5, -0.5, 10.3, -10.3, 3.7, -0.999, 20., 127.9, -149., -149.5, -150., -160.,
+        ];
+        test_transcendental_op(&inputs, F32Var::exp2, f32::exp2)?;
+        test_transcendental_op(&specials, F32Var::exp2, f32::exp2)?;
+        // Integers are exact.
+        test_transcendental_op(&[-3., 7., 100.], F32Var::exp2, f32::exp2)
+    }
+
+    #[test]
+    fn test_tanh() -> Result<(), Box<dyn Error>> {
+        let specials = [
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NAN,
+            0.,
+            -0.,
+            1e-30,
+            30.,
+        ];
+        let inputs = [0.1, -0.2, 0.2499, 0.25, -0.3, 0.5, 1., -2.5, 5.];
+        test_transcendental_op(&inputs, F32Var::tanh, f32::tanh)?;
+        test_transcendental_op(&specials, F32Var::tanh, f32::tanh)
+    }
+
+    #[test]
+    fn test_log2() -> Result<(), Box<dyn Error>> {
+        let specials = [
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NAN,
+            0.,
+            -0.,
+            -1.,
+            1.,
+            0.25,
+            1024.,
+        ];
+        let inputs = [
+            3.,
+            10.,
+            0.1,
+            0.7,
+            1.9,
+            1.0001,
+            0.9999,
+            123456.79,
+            3.7e-30,
+            1e-45,
+            f32::MAX,
+        ];
+        test_transcendental_op(&inputs, F32Var::log2, f32::log2)?;
+        test_transcendental_op(&specials, F32Var::log2, f32::log2)
     }
 
     #[test]
     fn test_lt() -> Result<(), Box<dyn Error>> {
-        test_comparison_op(File::open("data/f32/lt")?, |x, y| {
+        test_comparison_op::<Fr>(File::open("data/f32/lt")?, |x, y| {
             F32Var::is_lt(&x, &y).unwrap()
         })
     }
 
     #[test]
     fn test_le() -> Result<(), Box<dyn Error>> {
-        test_comparison_op(File::open("data/f32/le")?, |x, y| {
+        test_comparison_op::<Fr>(File::open("data/f32/le")?, |x, y| {
             F32Var::is_le(&x, &y).unwrap()
         })
     }
 
     #[test]
     fn test_gt() -> Result<(), Box<dyn Error>> {
-        test_comparison_op(File::open("data/f32/lt")?, |x, y| {
+        test_comparison_op::<Fr>(File::open("data/f32/lt")?, |x, y| {
             F32Var::is_gt(&y, &x).unwrap()
         })
     }
 
     #[test]
     fn test_ge() -> Result<(), Box<dyn Error>> {
-        test_comparison_op(File::open("data/f32/le")?, |x, y| {
+        test_comparison_op::<Fr>(File::open("data/f32/le")?, |x, y| {
             F32Var::is_ge(&y, &x).unwrap()
         })
     }
 
     #[test]
     fn test_trunc() -> Result<(), Box<dyn Error>> {
-        test_unary_op(File::open("data/f32/trunc")?, |x| {
+        test_unary_op::<Fr>(File::open("data/f32/trunc")?, |x| {
             F32Var::trunc(&x).unwrap()
         })
     }
 
     #[test]
     fn test_floor() -> Result<(), Box<dyn Error>> {
-        test_unary_op(File::open("data/f32/floor")?, |x| {
+        test_unary_op::<Fr>(File::open("data/f32/floor")?, |x| {
             F32Var::floor(&x).unwrap()
         })
     }
 
     #[test]
     fn test_ceil() -> Result<(), Box<dyn Error>> {
-        test_unary_op(File::open("data/f32/ceil")?, |x| F32Var::ceil(&x).unwrap())
+        test_unary_op::<Fr>(File::open("data/f32/ceil")?, |x| F32Var::ceil(&x).unwrap())
+    }
+
+    #[test]
+    fn test_round() -> Result<(), Box<dyn Error>> {
+        let bits = 60;
+        for v in [
+            0f32, -0., 0.3, -0.3, 0.5, -0.5, 1.5, 2.5, -2.5, 0.49999997, 3.7, -1234.5, 1e-30,
+            16777215.,
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let x = F32Var::new_witness(cs.clone(), || Ok(v))?;
+            let (y, n) = F32Var::round(&x, bits)?;
+            assert!(cs.is_satisfied()?, "{v}");
+
+            let expected = v.round_ties_even();
+            assert_eq!(y.to_f32()?.to_bits(), expected.to_bits(), "{v}");
+            assert_eq!(n.value()?, Fr::from(expected as i64), "{v}");
+        }
+
+        // Numbers out of the range and abnormal numbers are rejected.
+        for v in [2f32.powi(40), -2f32.powi(40), f32::INFINITY, f32::NAN] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let x = F32Var::new_witness(cs.clone(), || Ok(v))?;
+            let _ = F32Var::round(&x, 32)?;
+            let in_table = cs
+                .borrow()
+                .unwrap()
+                .committed_assignment
+                .iter()
+                .all(|&c| c < Fr::from(1u64 << LOOKUP_TABLE_BITS));
+            assert!(!(cs.is_satisfied()? && in_table), "{v}");
+        }
+        Ok(())
+    }
+
+    fn test_field<F: PrimeField>() -> Result<(), Box<dyn Error>> {
+        test_binary_op::<F>(File::open("data/f32/add")?, std::ops::Add::add)?;
+        test_binary_op::<F>(File::open("data/f32/mul")?, std::ops::Mul::mul)?;
+        test_binary_op::<F>(File::open("data/f32/div")?, std::ops::Div::div)?;
+        test_unary_op::<F>(File::open("data/f32/sqrt")?, |x| F32Var::sqrt(&x).unwrap())?;
+        test_unary_op::<F>(File::open("data/f32/floor")?, |x| {
+            F32Var::floor(&x).unwrap()
+        })?;
+        test_comparison_op::<F>(File::open("data/f32/lt")?, |x, y| {
+            F32Var::is_lt(&x, &y).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_pallas() -> Result<(), Box<dyn Error>> {
+        test_field::<ark_pallas::Fr>()
+    }
+
+    #[test]
+    fn test_vesta() -> Result<(), Box<dyn Error>> {
+        test_field::<ark_vesta::Fr>()
     }
 }
@@ -1,6 +1,7 @@
-use crate::float::FloatVar;
+use crate::{float::FloatVar, unnormalized::UnnormalizedFloatVar};
 
 pub type F64Var<F> = FloatVar<F, 11, 52>;
+pub type UnnormalizedF64Var<F> = UnnormalizedFloatVar<F, 11, 52>;
 
 #[cfg(test)]
 mod tests {
@@ -13,13 +14,17 @@ mod tests {
 
     use super::*;
     use crate::{
-        r1cs::{ConstraintSystem, ConstraintSystemRef},
+        groth16::LOOKUP_TABLE_BITS,
+        r1cs::{ConstraintSystem, ConstraintSystemRef, SynthesisError, SynthesisMode},
         r1cs_std::{
+            alloc::AllocationMode,
+            fields::fp::FpVar,
             prelude::{AllocVar, Boolean},
-            R1CSVar,
+            R1CSVar, ToConstraintFieldGadget,
         },
     };
     use ark_bls12_381::Fr;
+    use ark_ff::PrimeField;
     use num::ToPrimitive;
     use rayon::prelude::*;
 
@@ -36,7 +41,8 @@ mod tests {
 
         println!(
             "{}",
-            num_constraints(&cs, || F64Var::new_witness(cs.clone(), || Ok(0.1f64)).unwrap())
+            num_constraints(&cs, || F64Var::new_witness(cs.clone(), || Ok(0.1f64))
+                .unwrap())
         );
 
         assert!(cs.is_satisfied()?);
@@ -270,9 +276,9 @@ mod tests {
         Ok(())
     }
 
-    fn test_unary_op(
+    fn test_unary_op<F: PrimeField>(
         test_data: File,
-        op: fn(F64Var<Fr>) -> F64Var<Fr>,
+        op: fn(F64Var<F>) -> F64Var<F>,
     ) -> Result<(), Box<dyn Error>> {
         test_op(
             test_data,
@@ -285,7 +291,7 @@ mod tests {
             },
             |_| true,
             |v| {
-                let cs = ConstraintSystem::<Fr>::new_ref();
+                let cs = ConstraintSystem::<F>::new_ref();
                 let a = F64Var::new_witness(cs.clone(), || Ok(v[0])).unwrap();
 
                 let r = op(a).value().unwrap().to_u64().unwrap();
@@ -296,9 +302,9 @@ mod tests {
         )
     }
 
-    fn test_binary_op(
+    fn test_binary_op<F: PrimeField>(
         test_data: File,
-        op: fn(F64Var<Fr>, F64Var<Fr>) -> F64Var<Fr>,
+        op: fn(F64Var<F>, F64Var<F>) -> F64Var<F>,
     ) -> Result<(), Box<dyn Error>> {
         test_op(
             test_data,
@@ -311,7 +317,7 @@ mod tests {
             },
             |_| true,
             |v| {
-                let cs = ConstraintSystem::<Fr>::new_ref();
+                let cs = ConstraintSystem::<F>::new_ref();
                 let a = F64Var::new_witness(cs.clone(), || Ok(v[0])).unwrap();
                 let b = F64Var::new_witness(cs.clone(), || Ok(v[1])).unwrap();
 
@@ -323,9 +329,9 @@ mod tests {
         )
     }
 
-    fn test_comparison_op(
+    fn test_comparison_op<F: PrimeField>(
         test_data: File,
-        op: fn(F64Var<Fr>, F64Var<Fr>) -> Boolean<Fr>,
+        op: fn(F64Var<F>, F64Var<F>) -> Boolean<F>,
     ) -> Result<(), Box<dyn Error>> {
         test_op(
             test_data,
@@ -340,7 +346,7 @@ mod tests {
             },
             |_| true,
             |(a, b, c)| {
-                let cs = ConstraintSystem::<Fr>::new_ref();
+                let cs = ConstraintSystem::<F>::new_ref();
                 let a = F64Var::new_witness(cs.clone(), || Ok(*a)).unwrap();
                 let b = F64Var::new_witness(cs.clone(), || Ok(*b)).unwrap();
 
@@ -353,73 +359,639 @@ mod tests {
 
     #[test]
     fn test_add() -> Result<(), Box<dyn Error>> {
-        test_binary_op(File::open("data/f64/add")?, std::ops::Add::add)
+        test_binary_op::<Fr>(File::open("data/f64/add")?, std::ops::Add::add)
     }
 
     #[test]
     fn test_sub() -> Result<(), Box<dyn Error>> {
-        test_binary_op(File::open("data/f64/sub")?, std::ops::Sub::sub)
+        test_binary_op::<Fr>(File::open("data/f64/sub")?, std::ops::Sub::sub)
     }
 
     #[test]
     fn test_mul() -> Result<(), Box<dyn Error>> {
-        test_binary_op(File::open("data/f64/mul")?, std::ops::Mul::mul)
+        test_binary_op::<Fr>(File::open("data/f64/mul")?, std::ops::Mul::mul)
     }
 
     #[test]
     fn test_div() -> Result<(), Box<dyn Error>> {
-        test_binary_op(File::open("data/f64/div")?, std::ops::Div::div)
+        test_binary_op::<Fr>(File::open("data/f64/div")?, std::ops::Div::div)
     }
 
     #[test]
     fn test_sqrt() -> Result<(), Box<dyn Error>> {
-        test_unary_op(File::open("data/f64/sqrt")?, |x| F64Var::sqrt(&x).unwrap())
+        test_unary_op::<Fr>(File::open("data/f64/sqrt")?, |x| F64Var::sqrt(&x).unwrap())
+    }
+
+    /// Check `op` against `expected` on `inputs`, where finite results should be within 2 ulps
+    /// and special values should be exact.
+    fn test_transcendental_op(
+        inputs: &[f64],
+        op: fn(&F64Var<Fr>) -> Result<F64Var<Fr>, SynthesisError>,
+        expected: fn(f64) -> f64,
+    ) -> Result<(), Box<dyn Error>> {
+        for &v in inputs {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a = F64Var::new_witness(cs.clone(), || Ok(v))?;
+            let r = f64::from_bits(op(&a)?.value()?.to_u64().unwrap());
+            assert!(cs.is_satisfied()?, "{v}");
+            let e = expected(v);
+            let close = r.is_finite()
+                && e.is_finite()
+                && r.is_sign_negative() == e.is_sign_negative()
+                && r.to_bits().abs_diff(e.to_bits()) <= 2;
+            assert!(
+                close || r.to_bits() == e.to_bits() || (r.is_nan() && e.is_nan()),
+                "{v}: {r} != {e}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp2() -> Result<(), Box<dyn Error>> {
+        let specials = [f64::INFINITY, f64::NEG_INFINITY, f64::NAN, 0., -0.];
+        let inputs = [
+            1., -1., 0.5, -0.5, 10.3, -10.3, 3.7, -0.999, 20., 1023.9, -1074., -1074.5, -1075.,
+            -1080.,
+        ];
+        test_transcendental_op(&inputs, F64Var::exp2, f64::exp2)?;
+        test_transcendental_op(&specials, F64Var::exp2, f64::exp2)?;
+        // Integers are exact.
+        test_transcendental_op(&[-3., 7., 100.], F64Var::exp2, f64::exp2)
+    }
+
+    #[test]
+    fn test_tanh() -> Result<(), Box<dyn Error>> {
+        let specials = [
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+            0.,
+            -0.,
+            1e-30,
+            30.,
+        ];
+        let inputs = [0.1, -0.2, 0.2499, 0.25, -0.3, 0.5, 1., -2.5, 5.];
+        test_transcendental_op(&inputs, F64Var::tanh, f64::tanh)?;
+        test_transcendental_op(&specials, F64Var::tanh, f64::tanh)
+    }
+
+    #[test]
+    fn test_log2() -> Result<(), Box<dyn Error>> {
+        let specials = [
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+            0.,
+            -0.,
+            -1.,
+            1.,
+            0.25,
+            1024.,
+        ];
+        let inputs = [
+            3.,
+            10.,
+            0.1,
+            0.7,
+            1.9,
+            1.0001,
+            0.9999,
+            123456.79,
+            3.7e-300,
+            5e-324,
+            f64::MAX,
+        ];
+        test_transcendental_op(&inputs, F64Var::log2, f64::log2)?;
+        test_transcendental_op(&specials, F64Var::log2, f64::log2)
+    }
+
+    #[test]
+    fn test_sort() -> Result<(), Box<dyn Error>> {
+        for n in 1..=10 {
+            // Scrambled numbers with duplicates and infinities.
+            let mut values = (0..n)
+                .map(|i| ((i * 7 + 3) % 5) as f64 * 1.5 - 2.)
+                .collect::<Vec<_>>();
+            values[n / 2] = if n % 2 == 0 { f64::NEG_INFINITY } else { 1e300 };
+            values[n - 1] = f64::INFINITY.min(values[n - 1] * 3.);
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let xs = F64Var::new_witness_vec(cs.clone(), &values)?;
+            let sorted = F64Var::sort(&xs)?
+                .iter()
+                .map(F64Var::to_f64)
+                .collect::<Result<Vec<_>, _>>()?;
+            let median = F64Var::median(&xs)?.to_f64()?;
+            assert!(cs.is_satisfied()?);
+
+            values.sort_by(f64::total_cmp);
+            assert_eq!(sorted, values);
+            let expected = if n % 2 == 1 {
+                values[n / 2]
+            } else {
+                (values[n / 2 - 1] + values[n / 2]) / 2.
+            };
+            assert_eq!(median.to_bits(), expected.to_bits(), "{values:?}");
+        }
+        // The median of an empty slice is undefined.
+        assert!(F64Var::<Fr>::median(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_argmin() -> Result<(), Box<dyn Error>> {
+        for (values, min, max) in [
+            (vec![5.], 0, 0),
+            (vec![3., 1., 2., 1., 3.], 1, 0),
+            (vec![f64::NAN, 2., -1.], 2, 1),
+            (vec![2., f64::NAN, -1.], 2, 0),
+            (vec![f64::NAN, f64::NAN], 0, 0),
+            (vec![-0., 0.], 0, 0),
+            (vec![1e-300, f64::INFINITY, f64::NEG_INFINITY], 2, 1),
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let xs = F64Var::new_witness_vec(cs.clone(), &values)?;
+            for (op, expected) in [(F64Var::argmin as fn(&_) -> _, min), (F64Var::argmax, max)] {
+                let (index, one_hot) = op(&xs)?;
+                assert_eq!(index.value()?, Fr::from(expected as u64), "{values:?}");
+                let one_hot = one_hot.value()?;
+                assert!(one_hot
+                    .iter()
+                    .enumerate()
+                    .all(|(i, &b)| b == (i == expected)));
+            }
+            assert!(cs.is_satisfied()?);
+        }
+        // The minimum of an empty slice is undefined.
+        assert!(F64Var::<Fr>::argmin(&[]).is_err());
+        assert!(F64Var::<Fr>::argmax(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hypot() -> Result<(), Box<dyn Error>> {
+        for (a, b) in [
+            (3., 4.),
+            (-0.3, 1e-3),
+            (123.456, -7.25),
+            (0., -0.),
+            (f64::INFINITY, 1.),
+            (f64::NAN, 1.),
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let x = F64Var::new_witness(cs.clone(), || Ok(a))?;
+            let y = F64Var::new_witness(cs.clone(), || Ok(b))?;
+            let r = F64Var::hypot(&x, &y)?.to_f64()?;
+            assert!(cs.is_satisfied()?);
+            let e = a.hypot(b);
+            assert!(
+                r.to_bits().abs_diff(e.to_bits()) <= 1 || (r.is_nan() && e.is_nan()),
+                "{a}, {b}: {r} != {e}"
+            );
+        }
+
+        let values = [0.5, -1.25, 3., 1e-8, -2.75, 10.];
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let xs = F64Var::new_witness_vec(cs.clone(), &values)?;
+        let r = F64Var::l2_norm(&xs)?.to_f64()?;
+        assert!(cs.is_satisfied()?);
+        let e = values.iter().map(|v| v * v).sum::<f64>().sqrt();
+        assert!(r.to_bits().abs_diff(e.to_bits()) <= 1, "{r} != {e}");
+        assert_eq!(F64Var::<Fr>::l2_norm(&[])?.to_f64()?.to_bits(), 0);
+        Ok(())
     }
 
     #[test]
     fn test_lt() -> Result<(), Box<dyn Error>> {
-        test_comparison_op(File::open("data/f64/lt")?, |x, y| {
+        test_comparison_op::<Fr>(File::open("data/f64/lt")?, |x, y| {
             F64Var::is_lt(&x, &y).unwrap()
         })
     }
 
+    #[test]
+    fn test_enforce_cmp() -> Result<(), Box<dyn Error>> {
+        type Enforce = fn(&F64Var<Fr>, &F64Var<Fr>) -> Result<(), SynthesisError>;
+        for (a, b) in [
+            (1., 2.),
+            (2., 1.),
+            (1., 1.),
+            (-0., 0.),
+            (-1., -2.),
+            (-3., 1e-300),
+            (f64::MAX, f64::INFINITY),
+            (f64::NEG_INFINITY, -f64::MAX),
+            (f64::from_bits(1), 0.),
+            (f64::NAN, 1.),
+        ] {
+            for (enforce, expected) in [
+                (F64Var::enforce_lt as Enforce, a < b),
+                (F64Var::enforce_le, a <= b),
+                (F64Var::enforce_gt, a > b),
+                (F64Var::enforce_ge, a >= b),
+            ] {
+                let cs = ConstraintSystem::<Fr>::new_ref();
+                let x = F64Var::new_witness(cs.clone(), || Ok(a))?;
+                let y = F64Var::new_witness(cs.clone(), || Ok(b))?;
+                enforce(&x, &y)?;
+                assert_eq!(cs.is_satisfied()?, expected, "{a} {b}");
+            }
+        }
+
+        let x = F64Var::<Fr>::new_constant(ConstraintSystemRef::None, 1.)?;
+        assert!(F64Var::enforce_cmp(&x, &x, std::cmp::Ordering::Equal, true).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_le() -> Result<(), Box<dyn Error>> {
-        test_comparison_op(File::open("data/f64/le")?, |x, y| {
+        test_comparison_op::<Fr>(File::open("data/f64/le")?, |x, y| {
             F64Var::is_le(&x, &y).unwrap()
         })
     }
 
     #[test]
     fn test_gt() -> Result<(), Box<dyn Error>> {
-        test_comparison_op(File::open("data/f64/lt")?, |x, y| {
+        test_comparison_op::<Fr>(File::open("data/f64/lt")?, |x, y| {
             F64Var::is_gt(&y, &x).unwrap()
         })
     }
 
     #[test]
     fn test_ge() -> Result<(), Box<dyn Error>> {
-        test_comparison_op(File::open("data/f64/le")?, |x, y| {
+        test_comparison_op::<Fr>(File::open("data/f64/le")?, |x, y| {
             F64Var::is_ge(&y, &x).unwrap()
         })
     }
 
     #[test]
     fn test_trunc() -> Result<(), Box<dyn Error>> {
-        test_unary_op(File::open("data/f64/trunc")?, |x| {
+        test_unary_op::<Fr>(File::open("data/f64/trunc")?, |x| {
             F64Var::trunc(&x).unwrap()
         })
     }
 
     #[test]
     fn test_floor() -> Result<(), Box<dyn Error>> {
-        test_unary_op(File::open("data/f64/floor")?, |x| {
+        test_unary_op::<Fr>(File::open("data/f64/floor")?, |x| {
             F64Var::floor(&x).unwrap()
         })
     }
 
     #[test]
     fn test_ceil() -> Result<(), Box<dyn Error>> {
-        test_unary_op(File::open("data/f64/ceil")?, |x| F64Var::ceil(&x).unwrap())
+        test_unary_op::<Fr>(File::open("data/f64/ceil")?, |x| F64Var::ceil(&x).unwrap())
+    }
+
+    #[test]
+    fn test_round() -> Result<(), Box<dyn Error>> {
+        let bits = 60;
+        for v in [
+            0f64,
+            -0.,
+            0.3,
+            -0.3,
+            0.5,
+            -0.5,
+            1.5,
+            2.5,
+            -2.5,
+            0.49999997,
+            3.7,
+            -1234.5,
+            1e-30,
+            4503599627370497.,
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let x = F64Var::new_witness(cs.clone(), || Ok(v))?;
+            let (y, n) = F64Var::round(&x, bits)?;
+            assert!(cs.is_satisfied()?, "{v}");
+
+            let expected = v.round_ties_even();
+            assert_eq!(y.to_f64()?.to_bits(), expected.to_bits(), "{v}");
+            assert_eq!(n.value()?, Fr::from(expected as i64), "{v}");
+        }
+
+        // Numbers out of the range and abnormal numbers are rejected.
+        for v in [2f64.powi(40), -2f64.powi(40), f64::INFINITY, f64::NAN] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let x = F64Var::new_witness(cs.clone(), || Ok(v))?;
+            let _ = F64Var::round(&x, 32)?;
+            let in_table = cs
+                .borrow()
+                .unwrap()
+                .committed_assignment
+                .iter()
+                .all(|&c| c < Fr::from(1u64 << LOOKUP_TABLE_BITS));
+            assert!(!(cs.is_satisfied()? && in_table), "{v}");
+        }
+        Ok(())
+    }
+
+    fn test_field<F: PrimeField>() -> Result<(), Box<dyn Error>> {
+        test_binary_op::<F>(File::open("data/f64/add")?, std::ops::Add::add)?;
+        test_binary_op::<F>(File::open("data/f64/mul")?, std::ops::Mul::mul)?;
+        test_binary_op::<F>(File::open("data/f64/div")?, std::ops::Div::div)?;
+        test_unary_op::<F>(File::open("data/f64/sqrt")?, |x| F64Var::sqrt(&x).unwrap())?;
+        test_unary_op::<F>(File::open("data/f64/floor")?, |x| {
+            F64Var::floor(&x).unwrap()
+        })?;
+        test_comparison_op::<F>(File::open("data/f64/lt")?, |x, y| {
+            F64Var::is_lt(&x, &y).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_pallas() -> Result<(), Box<dyn Error>> {
+        test_field::<ark_pallas::Fr>()
+    }
+
+    #[test]
+    fn test_vesta() -> Result<(), Box<dyn Error>> {
+        test_field::<ark_vesta::Fr>()
+    }
+
+    #[test]
+    fn conversions() -> Result<(), Box<dyn Error>> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = F64Var::new_witness(cs.clone(), || Ok(-0.1f64))?;
+        assert_eq!(x.to_f64()?, -0.1);
+        assert_eq!(x.to_string(), "-0.1");
+        assert!(matches!(x.to_f32(), Err(crate::Error::Conversion(_))));
+
+        // Numbers of another format are rejected instead of being encoded incorrectly.
+        assert!(F64Var::new_witness(cs.clone(), || Ok(0.1f32)).is_err());
+        assert!(F64Var::<Fr>::try_verifier_input(0.1f32).is_err());
+        assert!(cs.is_satisfied()?);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        let x = F64Var::new_witness(cs, || Ok(0.1f64))?;
+        assert!(matches!(
+            x.to_f64(),
+            Err(crate::Error::Synthesis(SynthesisError::AssignmentMissing))
+        ));
+        assert_eq!(x.to_string(), "<unassigned>");
+
+        Ok(())
+    }
+    #[test]
+    fn vectors() -> Result<(), Box<dyn Error>> {
+        let values = [0.1, -2.5, f64::INFINITY];
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = F64Var::new_witness_vec(cs.clone(), &values)?;
+        let y = F64Var::new_input_vec(cs.clone(), &values)?;
+        let z = <Vec<F64Var<_>> as AllocVar<Vec<f64>, _>>::new_witness(cs.clone(), || {
+            Ok(values.to_vec())
+        })?;
+        assert!(cs.is_satisfied()?);
+        // The public inputs of `new_input_vec` are exactly those computed by `verifier_inputs`.
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            F64Var::<Fr>::verifier_inputs(&values)
+        );
+        assert!(F64Var::<Fr>::try_verifier_inputs(&[0.1f32]).is_err());
+
+        for v in [x, y, z] {
+            let v = v
+                .iter()
+                .map(|v| v.to_f64())
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(v, values);
+        }
+        Ok(())
+    }
+    #[test]
+    fn fixed() -> Result<(), Box<dyn Error>> {
+        let (scale_bits, bits) = (16, 48);
+        for v in [
+            0.,
+            -0.,
+            1.5,
+            -2.75,
+            0.1,
+            -1e-3,
+            1e-6,
+            123456.789,
+            -2f64.powi(31),
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let x = F64Var::new_witness(cs.clone(), || Ok(v))?;
+            let n = x.to_fixed(scale_bits, bits)?;
+            let y = F64Var::from_fixed(&n, scale_bits, bits)?;
+            assert!(cs.is_satisfied()?);
+
+            let expected = (v * 2f64.powi(scale_bits as i32)).trunc();
+            assert_eq!(n.value()?, Fr::from(expected as i64), "{v}");
+            assert_eq!(y.to_f64()?, expected / 2f64.powi(scale_bits as i32), "{v}");
+        }
+
+        // Integers with more bits than the mantissa are rounded to the nearest number.
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let n = FpVar::new_witness(cs.clone(), || Ok(-Fr::from((1u64 << 60) + 1)))?;
+        assert_eq!(F64Var::from_fixed(&n, 4, 61)?.to_f64()?, -2f64.powi(56));
+        assert!(cs.is_satisfied()?);
+
+        // Numbers out of the range and abnormal numbers are rejected.
+        for v in [2f64.powi(32), -2f64.powi(32), f64::INFINITY, f64::NAN] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let x = F64Var::new_witness(cs.clone(), || Ok(v))?;
+            let _ = x.to_fixed(scale_bits, bits)?;
+            // The range checks are only enforced by the lookup argument when proving, so we check
+            // the committed chunks against the table here.
+            let in_table = cs
+                .borrow()
+                .unwrap()
+                .committed_assignment
+                .iter()
+                .all(|&c| c < Fr::from(1u64 << LOOKUP_TABLE_BITS));
+            assert!(!(cs.is_satisfied()? && in_table), "{v}");
+        }
+
+        // A malicious prover cannot use another power of two for the exponent: doubling `2^e` and
+        // the values derived from it would convert 3 to 6.
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = F64Var::new_witness(cs.clone(), || Ok(3.))?;
+        let start = (cs.num_witness_variables(), cs.num_committed_variables());
+        let n = x.to_fixed(0, 8)?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(n.value()?, Fr::from(3u8));
+        let forged = [Fr::from(3u8), Fr::from(4u8), Fr::from(3u64 << 53)];
+        forge(&cs, start, |v| if forged.contains(&v) { v + v } else { v });
+        assert_eq!(n.value()?, Fr::from(6u8));
+        assert!(!cs.is_satisfied()?);
+        Ok(())
+    }
+
+    /// Apply `f` to the values of the witness and committed variables allocated after the first
+    /// `start` ones, which simulates a prover that replaces a hint and recomputes what depends on it.
+    fn forge(cs: &ConstraintSystemRef<Fr>, start: (usize, usize), f: impl Fn(Fr) -> Fr) {
+        let mut cs = cs.borrow_mut().unwrap();
+        for v in &mut cs.witness_assignment[start.0..] {
+            *v = f(*v);
+        }
+        for v in &mut cs.committed_assignment[start.1..] {
+            *v = f(*v);
+        }
+    }
+
+    #[test]
+    fn display() -> Result<(), Box<dyn Error>> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = F64Var::new_witness(cs.clone(), || Ok(-1.5))?;
+        assert_eq!(format!("{x}"), "-1.5");
+        assert_eq!(format!("{x:#}"), "bff8000000000000");
+        assert_eq!(
+            format!("{x:?}"),
+            "FloatVar { value: Some(-1.5), sign: Some(true), exponent: Some(0), \
+             mantissa: Some(6755399441055744), is_abnormal: Some(false) }"
+        );
+        assert_eq!(x.approximate_value(), Some(-1.5));
+
+        // Formats without native types are printed in `f64`.
+        // The smallest subnormal number of `bfloat16`.
+        let y = FloatVar::<Fr, 8, 7>::new_variable_from_components(
+            cs.clone(),
+            || Ok((false, 0, 1)),
+            AllocationMode::Witness,
+        )?;
+        assert_eq!(format!("{y}"), format!("{}", 2f64.powi(-133)));
+
+        // Inconsistent components are shown as they are instead of failing.
+        let mut z = x.clone();
+        z.mantissa = FpVar::Constant(Fr::from(3u64));
+        assert_eq!(
+            format!("{z}"),
+            format!("<invalid: {}>", -3. * 2f64.powi(-52))
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        let x = F64Var::new_witness(cs.clone(), || Ok(1.))?;
+        assert_eq!(format!("{x}"), "<unassigned>");
+        assert!(format!("{x:?}").contains("value: None"));
+        Ok(())
+    }
+
+    #[test]
+    fn components() -> Result<(), Box<dyn Error>> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        for v in [1., -0.1, f64::from_bits(1), f64::INFINITY] {
+            let bits = v.to_bits();
+            let components = (
+                bits >> 63 == 1,
+                (bits >> 52) as u128 & 0x7ff,
+                bits as u128 & ((1 << 52) - 1),
+            );
+            let x = F64Var::new_variable_from_components(
+                cs.clone(),
+                || Ok(components),
+                AllocationMode::Input,
+            )?;
+            assert_eq!(x.to_f64()?, v);
+            assert_eq!(
+                F64Var::<Fr>::verifier_input(v),
+                [
+                    Fr::from(components.0),
+                    Fr::from(components.1),
+                    Fr::from(components.2)
+                ]
+            );
+        }
+        assert!(cs.is_satisfied()?);
+
+        // Components out of the range cannot be assigned.
+        for components in [(false, 1 << 11, 0), (true, 0, 1 << 52)] {
+            assert!(F64Var::new_variable_from_components(
+                cs.clone(),
+                || Ok(components),
+                AllocationMode::Witness
+            )
+            .is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn to_constraint_field() -> Result<(), Box<dyn Error>> {
+        let values = [
+            1.,
+            -0.1,
+            0.,
+            -0.,
+            f64::from_bits(1),
+            -f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+        ];
+        let packed = |v: &F64Var<Fr>| -> Result<Fr, SynthesisError> {
+            let fields = v.to_constraint_field()?;
+            assert_eq!(fields.len(), 1);
+            fields[0].value()
+        };
+        let constants = values
+            .iter()
+            .map(|&v| packed(&F64Var::new_constant(ConstraintSystemRef::None, v)?))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Distinct numbers have distinct elements.
+        for (i, a) in constants.iter().enumerate() {
+            assert!(constants[i + 1..].iter().all(|b| a != b));
+        }
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        for (&v, c) in values.iter().zip(&constants) {
+            let x = F64Var::new_witness(cs.clone(), || Ok(v))?;
+            assert_eq!(packed(&x)?, *c, "{v}");
+        }
+        // The results of operations are packed in the same way.
+        let x = F64Var::new_witness(cs.clone(), || Ok(1.5))?;
+        let zero = F64Var::new_constant(cs.clone(), 0.)?;
+        assert_eq!(packed(&(&x - &x))?, constants[2]);
+        assert_eq!(packed(&(-(&x / &zero) * &zero))?, constants[9]);
+        let nan = F64Var::new_witness(cs.clone(), || Ok(-f64::NAN))?;
+        assert_eq!(packed(&nan)?, constants[9]);
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn decode_verifier_inputs() -> Result<(), Box<dyn Error>> {
+        let values = [
+            1.,
+            -0.1,
+            0.,
+            -0.,
+            f64::from_bits(1),
+            -f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+        ];
+        let inputs = F64Var::<Fr>::verifier_inputs(&values);
+        let decoded = F64Var::<Fr>::from_verifier_inputs(&inputs)?;
+        for (v, d) in values.iter().zip(&decoded) {
+            assert!(
+                v.to_bits() == d.to_bits() || (v.is_nan() && d.is_nan()),
+                "{v}"
+            );
+        }
+        for v in [0.1f32, -f32::from_bits(3), f32::MAX] {
+            let input = FloatVar::<Fr, 8, 23>::verifier_input(v);
+            assert_eq!(
+                FloatVar::<Fr, 8, 23>::from_verifier_input(&input)?,
+                v as f64
+            );
+        }
+
+        // Malformed public inputs are rejected.
+        assert!(F64Var::<Fr>::from_verifier_inputs(&inputs[..4]).is_err());
+        let out_of_range = [Fr::from(2u64), Fr::from(0u64), Fr::from(0u64)];
+        assert!(F64Var::<Fr>::from_verifier_input(&out_of_range).is_err());
+        let out_of_range = [Fr::from(0u64), Fr::from(0u64), -Fr::from(1u64)];
+        assert!(F64Var::<Fr>::from_verifier_input(&out_of_range).is_err());
+        Ok(())
     }
 }
@@ -1,24 +1,33 @@
 use std::{
     borrow::Borrow,
-    fmt::{Display, Formatter},
+    cmp::Ordering,
+    fmt::{Debug, Display, Formatter},
     ops::Neg,
 };
 
 use crate::{
+    error::Error,
     impl_ops,
-    r1cs::{ConstraintSystemRef, Namespace, SynthesisError},
+    powers::{pow2, pow2_of},
+    r1cs::{ConstraintSystemRef, Namespace, SynthesisError, Variable},
     r1cs_std::{
         alloc::{AllocVar, AllocationMode},
-        boolean::Boolean,
-        fields::{fp::FpVar, FieldVar},
+        boolean::{AllocatedBool, Boolean},
+        fields::{
+            fp::{AllocatedFp, FpVar},
+            FieldVar,
+        },
         prelude::EqGadget,
         select::CondSelectGadget,
-        R1CSVar,
+        R1CSVar, ToConstraintFieldGadget,
     },
-    traits::BitDecompose,
+    rational::Rounding,
+    traits::{BitDecompose, ToBigUint},
+    utils::field_to_signed,
 };
 use ark_ff::{BigInteger, One, PrimeField};
 use ark_std::Zero;
+use half::{bf16, f16};
 use num::{traits::float::FloatCore, BigUint, Integer, ToPrimitive};
 
 #[derive(Clone)]
@@ -48,21 +57,80 @@ pub struct FloatVar<F: PrimeField, const E: usize, const M: usize> {
     is_abnormal: Boolean<F>,
 }
 
+/// A component of a `DetachedFloatVar`.
+#[derive(Clone, Debug)]
+enum DetachedComponent<F: PrimeField> {
+    Constant(F),
+    Variable(Variable, Option<F>),
+    /// The negation of a boolean variable, whose value is that of the variable itself.
+    NegatedVariable(Variable, Option<F>),
+}
+
+/// A `FloatVar` detached from its constraint system, which only records the variables and values
+/// of its components, so that it can be sent across threads and be rebuilt in another
+/// constraint system by `FloatVar::attach` or `FloatVar::restore`.
+#[derive(Clone, Debug)]
+pub struct DetachedFloatVar<F: PrimeField> {
+    /// The sign, the exponent, the mantissa, and the flag `is_abnormal`, in this order.
+    components: [DetachedComponent<F>; 4],
+}
+
+impl<F: PrimeField> DetachedFloatVar<F> {
+    /// The variables in the components, in the same order as `FloatVar::attach` allocates them.
+    pub fn variables(&self) -> impl Iterator<Item = Variable> + '_ {
+        self.components.iter().filter_map(|c| match c {
+            DetachedComponent::Constant(_) => None,
+            DetachedComponent::Variable(v, _) | DetachedComponent::NegatedVariable(v, _) => {
+                Some(*v)
+            }
+        })
+    }
+}
+
 impl<F: PrimeField, const E: usize, const M: usize> Display for FloatVar<F, E, M> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let v = self.value().unwrap();
+        let Ok(v) = self.value() else {
+            // The components are either unassigned or inconsistent, where the latter happens when
+            // the assignment is not satisfying, so we show what the components represent instead.
+            return match self.approximate_value() {
+                Some(v) => write!(f, "<invalid: {v}>"),
+                None => write!(f, "<unassigned>"),
+            };
+        };
         if f.alternate() {
             write!(f, "{:0len$x}", v, len = (1 + E + M) / 8)
+        } else if let Ok(v) = self.to_f64() {
+            write!(f, "{v}")
+        } else if let Ok(v) = self.to_f32() {
+            write!(f, "{v}")
         } else {
-            match 1 + E + M {
-                64 => write!(f, "{}", f64::from_bits(v.to_u64().unwrap())),
-                32 => write!(f, "{}", f32::from_bits(v.to_u32().unwrap())),
-                _ => panic!("Unsupported float length"),
-            }
+            // Other formats have no native type, so we print their values in `f64`, which are
+            // exact for formats that are narrower than `binary64`.
+            write!(f, "{}", self.approximate_value().unwrap_or(f64::NAN))
         }
     }
 }
 
+/// Print the value of the number together with its raw components, where the exponent is
+/// printed as a signed integer, and unavailable values are printed as `None`.
+impl<F: PrimeField, const E: usize, const M: usize> Debug for FloatVar<F, E, M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FloatVar")
+            .field("value", &self.approximate_value())
+            .field("sign", &self.sign.value().ok())
+            .field(
+                "exponent",
+                &self.exponent.value().ok().and_then(field_to_signed),
+            )
+            .field(
+                "mantissa",
+                &self.mantissa.value().ok().map(Into::<BigUint>::into),
+            )
+            .field("is_abnormal", &self.is_abnormal.value().ok())
+            .finish()
+    }
+}
+
 impl<F: PrimeField, U: FloatCore, const E: usize, const M: usize> AllocVar<U, F>
     for FloatVar<F, E, M>
 {
@@ -74,25 +142,73 @@ impl<F: PrimeField, U: FloatCore, const E: usize, const M: usize> AllocVar<U, F>
         f: impl FnOnce() -> Result<T, SynthesisError>,
         mode: AllocationMode,
     ) -> Result<Self, SynthesisError> {
+        // The value cannot be assigned if it is of another format.
+        Self::new_variable_from_components(
+            cs,
+            || {
+                f().and_then(|v| {
+                    Self::decode(*v.borrow()).map_err(|_| SynthesisError::AssignmentMissing)
+                })
+            },
+            mode,
+        )
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
+    /// Allocate a variable from the components of its IEEE-754 encoding, i.e., the sign, the biased
+    /// exponent of `E` bits, and the mantissa of `M` bits without the leading 1, and enforce they
+    /// are well-formed. This allows allocating numbers that are already decomposed (e.g., by an
+    /// external pipeline) without converting them to `f64` or `f32` first.
+    /// In the input mode, the public inputs are the components themselves, in the same order.
+    ///
+    /// If `f` returns components out of the range, the value cannot be assigned, which is reported
+    /// as `SynthesisError::AssignmentMissing`.
+    pub fn new_variable_from_components(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<(bool, u128, u128), SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self::new_variable_with_components(cs, f, mode)?.0)
+    }
+
+    /// Allocate a variable like `Self::new_variable_from_components`, and also return the
+    /// allocated components, i.e., the sign (as 0 or 1), the biased exponent, and the mantissa
+    /// without the leading 1. Unlike the components of `Self`, they are the canonical encoding of
+    /// the number, and hence can be hashed to commit to the number, whose native counterpart is
+    /// `Self::verifier_input`.
+    pub fn new_variable_with_components(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<(bool, u128, u128), SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<(Self, [FpVar<F>; 3]), SynthesisError> {
         let cs = cs.into().cs();
 
-        // Extract sign, exponent, and mantissa from the value
         let (sign, exponent, mantissa) = {
-            let (m, e, s) = f()?.borrow().integer_decode();
-
-            let s = s == -1;
-            let e = (e + ((1 << (E - 1)) - 1 + M) as i16) as u128;
-            let m = if e == 0 { m >> 1 } else { m - (1 << M) } as u128;
+            // Errors of `f` are only reported when the value is actually needed, i.e., not in
+            // setup mode.
+            let components = f().and_then(|(s, e, m)| {
+                if e < 1 << E && m < 1 << M {
+                    Ok((s, e, m))
+                } else {
+                    Err(SynthesisError::AssignmentMissing)
+                }
+            });
 
             (
-                Boolean::new_variable(cs.clone(), || Ok(s), mode)?,
-                FpVar::new_variable(cs.clone(), || Ok(F::from(e)), mode)?,
-                FpVar::new_variable(cs.clone(), || Ok(F::from(m)), mode)?,
+                Boolean::new_variable(cs.clone(), || components.map(|(s, _, _)| s), mode)?,
+                FpVar::new_variable(cs.clone(), || components.map(|(_, e, _)| F::from(e)), mode)?,
+                FpVar::new_variable(cs.clone(), || components.map(|(_, _, m)| F::from(m)), mode)?,
             )
         };
         // Enforce the bit length of exponent and mantissa
         exponent.enforce_bit_length(E)?;
         mantissa.enforce_bit_length(M)?;
+        let components = [
+            FpVar::from(sign.clone()),
+            exponent.clone(),
+            mantissa.clone(),
+        ];
 
         let exponent_min = -F::from(((1 << (E - 1)) - 1) as u128);
         let exponent_max = F::from((1 << (E - 1)) as u128);
@@ -107,19 +223,17 @@ impl<F: PrimeField, U: FloatCore, const E: usize, const M: usize> AllocVar<U, F>
         // Find how many bits to shift the mantissa to the left to have the `(M - 1)`-th bit equal to 1
         // and prodive it as a hint to the circuit
         let (l, two_to_l) = {
-            let l = mantissa
-                .value()
-                .unwrap_or_default()
-                .into_bigint()
-                .to_bits_le()[..M]
-                .iter()
-                .rev()
-                .position(|&i| i)
-                .unwrap_or(M);
+            let l = mantissa.value().map(|m| {
+                m.into_bigint().to_bits_le()[..M]
+                    .iter()
+                    .rev()
+                    .position(|&i| i)
+                    .unwrap_or(M)
+            });
 
             (
-                FpVar::new_hint(cs.clone(), || Ok(F::from(l as u128)))?,
-                FpVar::new_hint(cs.clone(), || Ok(F::from(1u128 << l)))?,
+                FpVar::new_hint(cs.clone(), || Ok(F::from(l? as u128)))?,
+                FpVar::new_hint(cs.clone(), || Ok(pow2::<F>(l?)))?,
             )
         };
         // TODO: enforce `(l, two_to_l)` is in lookup table `[0, M]`
@@ -139,7 +253,7 @@ impl<F: PrimeField, U: FloatCore, const E: usize, const M: usize> AllocVar<U, F>
         // 2. `shifted_mantissa` is less than `2^M`, since otherwise,
         // `shifted_mantissa - F::from(1u128 << (M - 1))` will be greater than `2^(M - 1) - 1`, which
         // takes at least `M` bits to represent.
-        (&shifted_mantissa - FpVar::from(mantissa_is_zero.not()) * F::from(1u128 << (M - 1)))
+        (&shifted_mantissa - FpVar::from(mantissa_is_zero.not()) * pow2::<F>(M - 1))
             .enforce_bit_length(M - 1)?;
 
         let exponent = exponent_is_min.select(
@@ -149,17 +263,35 @@ impl<F: PrimeField, U: FloatCore, const E: usize, const M: usize> AllocVar<U, F>
         let mantissa = exponent_is_min.select(
             &shifted_mantissa.double()?, // If subnormal, shift the mantissa to the left by 1 to make its `M`-th bit 1
             &exponent_is_max.and(&mantissa_is_zero.not())?.select(
-                &FpVar::zero(),                     // If NaN, set the mantissa to 0
-                &(&mantissa + F::from(1u128 << M)), // Otherwise, add `2^M` to the mantissa to make its `M`-th bit 1
+                &FpVar::zero(),              // If NaN, set the mantissa to 0
+                &(&mantissa + pow2::<F>(M)), // Otherwise, add `2^M` to the mantissa to make its `M`-th bit 1
             )?,
         )?;
 
-        Ok(Self {
-            sign,
-            exponent,
-            mantissa,
-            is_abnormal: exponent_is_max,
-        })
+        Ok((
+            Self {
+                sign,
+                exponent,
+                mantissa,
+                is_abnormal: exponent_is_max,
+            },
+            components,
+        ))
+    }
+}
+
+impl<F: PrimeField, U: FloatCore, const E: usize, const M: usize> AllocVar<Vec<U>, F>
+    for Vec<FloatVar<F, E, M>>
+{
+    /// Allocate a variable for each value in the vector, e.g., for circuits that are generic over
+    /// the allocated values. Note that `Vec<FloatVar>` can also be allocated from a slice, so
+    /// `FloatVar::new_witness_vec` and `FloatVar::new_input_vec` are more convenient otherwise.
+    fn new_variable<T: Borrow<Vec<U>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        FloatVar::new_variable_vec(cs, f()?.borrow(), mode)
     }
 }
 
@@ -180,20 +312,25 @@ impl<F: PrimeField, const E: usize, const M: usize> R1CSVar<F> for FloatVar<F, E
         let m: BigUint = self.mantissa.value()?.into();
         let is_abnormal = self.is_abnormal.value()?;
 
+        // The components are inconsistent only if the assignment is not satisfying.
         if e <= BigUint::from(M) {
-            assert!(!is_abnormal);
-            assert_eq!(e.is_zero(), m.is_zero());
-            let delta = M + 1 - e.to_usize().unwrap();
-            assert_eq!((&m >> delta) << delta, m);
-            return Ok((s << (M + E)) + (m >> delta));
+            let delta = M + 1 - e.to_usize().ok_or(SynthesisError::Unsatisfiable)?;
+            if is_abnormal || e.is_zero() != m.is_zero() || (&m >> delta) << delta != m {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            Ok((s << (M + E)) + (m >> delta))
         } else {
             let e = e - BigUint::from(M);
-            assert_eq!(e == BigUint::from((1u128 << E) - 1), is_abnormal);
+            if (e == BigUint::from((1u128 << E) - 1)) != is_abnormal {
+                return Err(SynthesisError::Unsatisfiable);
+            }
 
             let m = if is_abnormal && m.is_zero() {
                 BigUint::one()
-            } else {
+            } else if m >= BigUint::from(1u128 << M) {
                 m - BigUint::from(1u128 << M)
+            } else {
+                return Err(SynthesisError::Unsatisfiable);
             };
             Ok((s << (M + E)) + (e << M) + m)
         }
@@ -291,10 +428,380 @@ impl<F: PrimeField, const E: usize, const M: usize> EqGadget<F> for FloatVar<F,
     }
 }
 
+/// Pack the number into a single field element `s + 2 m + 2^(M + 2) e`, where `s` is the sign,
+/// `m` is the mantissa, and `e` is the exponent shifted by `2^(E - 1) - 1 + M` to be non-negative,
+/// so that the number can be hashed (e.g., by `hash::hash_gadget`) or absorbed into a transcript
+/// without handling its components.
+///
+/// The element is unique for each number: the exponent of zeros (which is otherwise unbounded)
+/// is replaced by 0, and NaNs are all packed as the positive NaN, so the prover cannot choose
+/// among several elements of the same number. The element of a constant `v` can be computed
+/// natively by `FloatVar::new_constant(ConstraintSystemRef::None, v)?.to_constraint_field()?`.
+impl<F: PrimeField, const E: usize, const M: usize> ToConstraintFieldGadget<F>
+    for FloatVar<F, E, M>
+{
+    fn to_constraint_field(&self) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        assert!(
+            E + M + 3 < F::MODULUS_BIT_SIZE as usize,
+            "cannot pack a number of {} bits in a field of {} bits",
+            1 + E + M,
+            F::MODULUS_BIT_SIZE
+        );
+        // Only zeros and NaNs have a mantissa of 0, which are told apart by `is_abnormal`.
+        let mantissa_is_zero = self.mantissa.is_zero()?;
+        let is_zero = mantissa_is_zero.and(&self.is_abnormal.not())?;
+        let is_nan = mantissa_is_zero.and(&self.is_abnormal)?;
+
+        let sign = FpVar::from(self.sign.and(&is_nan.not())?);
+        let exponent = is_zero.select(
+            &FpVar::zero(),
+            &(&self.exponent + F::from(((1 << (E - 1)) - 1 + M) as u128)),
+        )?;
+        Ok(vec![
+            sign + self.mantissa.double()? + exponent * pow2::<F>(M + 2),
+        ])
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
+    /// Detach `self` from its constraint system.
+    pub fn detach(&self) -> DetachedFloatVar<F> {
+        let boolean = |b: &Boolean<F>| match b {
+            Boolean::Constant(b) => DetachedComponent::Constant(F::from(*b)),
+            Boolean::Is(b) => {
+                DetachedComponent::Variable(b.variable(), b.value().ok().map(F::from))
+            }
+            Boolean::Not(b) => {
+                DetachedComponent::NegatedVariable(b.variable(), b.value().ok().map(F::from))
+            }
+        };
+        let field = |f: &FpVar<F>| match f {
+            FpVar::Constant(f) => DetachedComponent::Constant(*f),
+            FpVar::Var(f) => DetachedComponent::Variable(f.variable, f.value),
+        };
+
+        DetachedFloatVar {
+            components: [
+                boolean(&self.sign),
+                field(&self.exponent),
+                field(&self.mantissa),
+                boolean(&self.is_abnormal),
+            ],
+        }
+    }
+
+    /// Rebuild `detached` in `cs` by allocating a new witness for each of its variables, in the
+    /// order of `detached.variables()`.
+    /// No constraint is enforced on the new witnesses, so this should only be used when they are
+    /// later replaced by the original variables, e.g., in a `Fragment`.
+    pub fn attach(
+        cs: ConstraintSystemRef<F>,
+        detached: &DetachedFloatVar<F>,
+    ) -> Result<Self, SynthesisError> {
+        let boolean = |c: &DetachedComponent<F>| {
+            Ok(match c {
+                DetachedComponent::Constant(b) => Boolean::Constant(b.is_one()),
+                DetachedComponent::Variable(_, b) => Boolean::Is(
+                    AllocatedBool::new_witness_without_booleanity_check(cs.clone(), || {
+                        b.map(|b| b.is_one())
+                            .ok_or(SynthesisError::AssignmentMissing)
+                    })?,
+                ),
+                DetachedComponent::NegatedVariable(_, b) => Boolean::Not(
+                    AllocatedBool::new_witness_without_booleanity_check(cs.clone(), || {
+                        b.map(|b| b.is_one())
+                            .ok_or(SynthesisError::AssignmentMissing)
+                    })?,
+                ),
+            })
+        };
+        let field = |c: &DetachedComponent<F>| {
+            Ok(match c {
+                DetachedComponent::Constant(f) => FpVar::Constant(*f),
+                DetachedComponent::Variable(_, f) => FpVar::Var(AllocatedFp::new(
+                    *f,
+                    cs.new_witness_variable(|| f.ok_or(SynthesisError::AssignmentMissing))?,
+                    cs.clone(),
+                )),
+                DetachedComponent::NegatedVariable(..) => unreachable!(),
+            })
+        };
+
+        let [sign, exponent, mantissa, is_abnormal] = &detached.components;
+        Ok(Self {
+            sign: boolean(sign)?,
+            exponent: field(exponent)?,
+            mantissa: field(mantissa)?,
+            is_abnormal: boolean(is_abnormal)?,
+        })
+    }
+
+    /// Rebuild `detached` in `cs` over the existing variables `map(v)` for each variable `v` of
+    /// `detached`, e.g., after the `Fragment` containing `detached` is appended to `cs`.
+    pub fn restore(
+        cs: ConstraintSystemRef<F>,
+        detached: &DetachedFloatVar<F>,
+        map: impl Fn(Variable) -> Variable,
+    ) -> Self {
+        let boolean = |c: &DetachedComponent<F>| match c {
+            DetachedComponent::Constant(b) => Boolean::Constant(b.is_one()),
+            DetachedComponent::Variable(v, _) => {
+                Boolean::Is(AllocatedBool::from_variable(map(*v), cs.clone()))
+            }
+            DetachedComponent::NegatedVariable(v, _) => {
+                Boolean::Not(AllocatedBool::from_variable(map(*v), cs.clone()))
+            }
+        };
+        let field = |c: &DetachedComponent<F>| match c {
+            DetachedComponent::Constant(f) => FpVar::Constant(*f),
+            DetachedComponent::Variable(v, f) => {
+                FpVar::Var(AllocatedFp::new(*f, map(*v), cs.clone()))
+            }
+            DetachedComponent::NegatedVariable(..) => unreachable!(),
+        };
+
+        let [sign, exponent, mantissa, is_abnormal] = &detached.components;
+        Self {
+            sign: boolean(sign),
+            exponent: field(exponent),
+            mantissa: field(mantissa),
+            is_abnormal: boolean(is_abnormal),
+        }
+    }
+}
+
 impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
     const E_MAX: u128 = 1 << (E - 1);
     const NEG_E_NORMAL_MIN: u128 = Self::E_MAX - 2;
     const NEG_E_MIN: u128 = Self::E_MAX - 1 + M as u128;
+    /// The bit length of the sums and differences of two exponents (e.g., in `Self::mul` and
+    /// `Self::div`), which is `E + 1` unless the subnormal numbers extend the exponents below
+    /// `-2^(E - 1)` by too much, i.e., unless the mantissa is long relative to the range of the
+    /// exponent (e.g., for `binary16`, whose subnormal numbers have exponents down to -24).
+    const E_SUM_BITS: usize = if M < 1 << (E - 2) { E + 1 } else { E + 2 };
+
+    /// Decode the value into the sign, the biased exponent, and the mantissa without the
+    /// leading 1, i.e., the components of its IEEE-754 encoding.
+    /// Return an error if `U` is not of the same format as `Self`.
+    pub(crate) fn decode<U: FloatCore>(value: U) -> Result<(bool, u128, u128), Error> {
+        let bits = std::mem::size_of::<U>() * 8;
+        if bits != 1 + E + M {
+            return Err(Error::Conversion(format!(
+                "cannot encode a {bits}-bit number as a {}-bit number",
+                1 + E + M
+            )));
+        }
+        // `integer_decode` may be computed in a wider format (e.g., for `half::f16` and
+        // `half::bf16`, which are decoded as `f32`), so the mantissa is renormalized to `M` bits
+        // rather than assumed to be in the format of `Self`.
+        let (m, e, s) = value.integer_decode();
+        let (m, e, s) = (m as u128, e as i32, s == -1);
+        let shift = |m: u128, k: i32| if k >= 0 { m << k } else { m >> -k };
+
+        if value.is_nan() || value.is_infinite() {
+            // The leading 1 of the mantissa is explicit, and the payload of a NaN is in the
+            // highest bits below it.
+            let width = 127 - m.leading_zeros() as i32;
+            return Ok((s, (1 << E) - 1, shift(m - (1 << width), M as i32 - width)));
+        }
+        if m == 0 {
+            return Ok((s, 0, 0));
+        }
+        let bias = (1 << (E - 1)) - 1;
+        let length = 128 - m.leading_zeros() as i32;
+        let e_biased = e + length - 1 + bias;
+        Ok(if e_biased > 0 {
+            (
+                s,
+                e_biased as u128,
+                shift(m, M as i32 + 1 - length) - (1 << M),
+            )
+        } else {
+            // Subnormal numbers have the same scale as the smallest normal numbers.
+            (s, 0, shift(m, e + bias - 1 + M as i32))
+        })
+    }
+
+    fn new_variable_vec<U: FloatCore>(
+        cs: impl Into<Namespace<F>>,
+        values: &[U],
+        mode: AllocationMode,
+    ) -> Result<Vec<Self>, SynthesisError> {
+        let cs = cs.into().cs();
+        values
+            .iter()
+            .map(|&v| Self::new_variable(cs.clone(), || Ok(v), mode))
+            .collect()
+    }
+
+    /// Allocate a witness for each value in `values`, e.g., the features of a row.
+    pub fn new_witness_vec<U: FloatCore>(
+        cs: impl Into<Namespace<F>>,
+        values: &[U],
+    ) -> Result<Vec<Self>, SynthesisError> {
+        Self::new_variable_vec(cs, values, AllocationMode::Witness)
+    }
+
+    /// Allocate a public input for each value in `values`, in this order. The public inputs that
+    /// the verifier should provide are exactly `Self::verifier_inputs(values)`, i.e., the 3
+    /// components of each value concatenated in the same order.
+    pub fn new_input_vec<U: FloatCore>(
+        cs: impl Into<Namespace<F>>,
+        values: &[U],
+    ) -> Result<Vec<Self>, SynthesisError> {
+        Self::new_variable_vec(cs, values, AllocationMode::Input)
+    }
+
+    /// Compute the public inputs that the verifier should provide for a number allocated by
+    /// `FloatVar::new_input`, which are the sign, the biased exponent, and the mantissa without
+    /// the leading 1, in the same order as they are allocated.
+    ///
+    /// Panics if `U` is not of the same format as `Self`, see `Self::try_verifier_input`.
+    pub fn verifier_input<U: FloatCore>(value: U) -> [F; 3] {
+        Self::try_verifier_input(value).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Compute the public inputs like `Self::verifier_input`, or return an error if `U` is not of
+    /// the same format as `Self`.
+    pub fn try_verifier_input<U: FloatCore>(value: U) -> Result<[F; 3], Error> {
+        let (s, e, m) = Self::decode(value)?;
+
+        Ok([F::from(s), F::from(e), F::from(m)])
+    }
+
+    /// Compute the public inputs for numbers allocated by `Self::new_input_vec` (or by
+    /// `FloatVar::new_input` one by one in the same order), which are the public inputs of each
+    /// number concatenated in order.
+    ///
+    /// Panics if `U` is not of the same format as `Self`, see `Self::try_verifier_inputs`.
+    pub fn verifier_inputs<U: FloatCore>(values: &[U]) -> Vec<F> {
+        Self::try_verifier_inputs(values).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Compute the public inputs like `Self::verifier_inputs`, or return an error if `U` is not of
+    /// the same format as `Self`.
+    pub fn try_verifier_inputs<U: FloatCore>(values: &[U]) -> Result<Vec<F>, Error> {
+        let mut inputs = Vec::with_capacity(3 * values.len());
+        for &v in values {
+            inputs.extend(Self::try_verifier_input(v)?);
+        }
+        Ok(inputs)
+    }
+
+    /// Reconstruct the number from its public inputs computed by `Self::verifier_input`, so that
+    /// the verifier can display and sanity-check the statement. The result is exact for `binary64`
+    /// and narrower formats, and is rounded to the nearest `f64` for wider ones.
+    /// Return an error if the components are out of the range, in which case no proof would be
+    /// accepted with these public inputs.
+    pub fn from_verifier_input(input: &[F; 3]) -> Result<f64, Error> {
+        let component = |v: F, bits: usize, name: &str| {
+            let v: BigUint = v.into();
+            v.to_u128().filter(|&v| v >> bits == 0).ok_or_else(|| {
+                Error::Conversion(format!("the {name} {v} does not fit in {bits} bits"))
+            })
+        };
+        let s = component(input[0], 1, "sign")?;
+        let e = component(input[1], E, "biased exponent")?;
+        let m = component(input[2], M, "mantissa")?
+            .to_f64()
+            .unwrap_or(f64::INFINITY);
+
+        let sign = if s == 1 { -1. } else { 1. };
+        let (m, e) = match e {
+            e if e == (1 << E) - 1 => {
+                return Ok(if m == 0. {
+                    sign * f64::INFINITY
+                } else {
+                    f64::NAN
+                })
+            }
+            // Subnormal numbers have the same scale as the smallest normal numbers.
+            0 => (m, 1),
+            e => (m + 2f64.powi(M as i32), e),
+        };
+        // As in `Self::approximate_value`, the scale is applied in two steps to avoid overflow.
+        let shift = (e as i128 - ((1 << (E - 1)) - 1 + M) as i128).clamp(-4096, 4096) as i32;
+        Ok(sign * m * 2f64.powi(shift / 2) * 2f64.powi(shift - shift / 2))
+    }
+
+    /// Reconstruct the numbers from their public inputs computed by `Self::verifier_inputs`, e.g.,
+    /// the segment of the public inputs of a circuit for numbers allocated by `Self::new_input_vec`.
+    /// Return an error if the length is not a multiple of 3 or if any number is malformed.
+    pub fn from_verifier_inputs(inputs: &[F]) -> Result<Vec<f64>, Error> {
+        if !inputs.len().is_multiple_of(3) {
+            return Err(Error::Conversion(format!(
+                "{} public inputs do not form triples of components",
+                inputs.len()
+            )));
+        }
+        inputs
+            .chunks(3)
+            .map(|c| Self::from_verifier_input(&[c[0], c[1], c[2]]))
+            .collect()
+    }
+
+    /// Convert the value of a `binary64` number to `f64`.
+    pub fn to_f64(&self) -> Result<f64, Error> {
+        match (1 + E + M, self.value()?.to_u64()) {
+            (64, Some(v)) => Ok(f64::from_bits(v)),
+            (bits, _) => Err(Error::Conversion(format!(
+                "cannot convert a {bits}-bit number to f64"
+            ))),
+        }
+    }
+
+    /// Reconstruct the value of the number from its components as an `f64`, which is exact for
+    /// `binary64` and narrower formats, and is rounded (possibly twice) to the nearest `f64` for
+    /// wider ones. Unlike `R1CSVar::value`, this does not check that the components are
+    /// consistent, so it also helps debug unsatisfying assignments.
+    /// Return `None` if the values of the components are unavailable.
+    pub fn approximate_value(&self) -> Option<f64> {
+        let sign = if self.sign.value().ok()? { -1. } else { 1. };
+        let exponent = field_to_signed(self.exponent.value().ok()?)?;
+        let mantissa: BigUint = self.mantissa.value().ok()?.into();
+        let mantissa = mantissa.to_f64()?;
+        if self.is_abnormal.value().ok()? {
+            return Some(if mantissa == 0. {
+                f64::NAN
+            } else {
+                sign * f64::INFINITY
+            });
+        }
+        // The scale is applied in two steps, as `2^shift` alone may overflow or underflow even if
+        // the value is in the range.
+        let shift = (exponent - M as i128).clamp(-4096, 4096) as i32;
+        Some(sign * mantissa * 2f64.powi(shift / 2) * 2f64.powi(shift - shift / 2))
+    }
+
+    /// Convert the value of a `binary32` number to `f32`.
+    pub fn to_f32(&self) -> Result<f32, Error> {
+        match (1 + E + M, self.value()?.to_u32()) {
+            (32, Some(v)) => Ok(f32::from_bits(v)),
+            (bits, _) => Err(Error::Conversion(format!(
+                "cannot convert a {bits}-bit number to f32"
+            ))),
+        }
+    }
+
+    /// Convert the value of a `binary16` number to `f16`.
+    pub fn to_f16(&self) -> Result<f16, Error> {
+        match ((E, M), self.value()?.to_u16()) {
+            ((5, 10), Some(v)) => Ok(f16::from_bits(v)),
+            _ => Err(Error::Conversion(format!(
+                "cannot convert a number with {E} exponent bits and {M} mantissa bits to f16"
+            ))),
+        }
+    }
+
+    /// Convert the value of a `bfloat16` number to `bf16`.
+    pub fn to_bf16(&self) -> Result<bf16, Error> {
+        match ((E, M), self.value()?.to_u16()) {
+            ((8, 7), Some(v)) => Ok(bf16::from_bits(v)),
+            _ => Err(Error::Conversion(format!(
+                "cannot convert a number with {E} exponent bits and {M} mantissa bits to bf16"
+            ))),
+        }
+    }
 
     /// Negate the number by flipping the sign.
     fn neg(&self) -> Self {
@@ -316,6 +823,46 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         }
     }
 
+    /// Check whether the number is NaN.
+    pub fn is_nan(&self) -> Result<Boolean<F>, SynthesisError> {
+        self.is_abnormal.and(&self.mantissa.is_zero()?)
+    }
+
+    /// Step the number outwards by one ulp, i.e., towards positive infinity if `upward` and towards
+    /// negative infinity otherwise, where the ulp is that of the binade of the number (or the
+    /// smallest subnormal number for 0 and subnormal numbers).
+    /// For a correctly rounded result `x` of an exact value `v`, the stepped number is a bound of `v`
+    /// in the direction, since `v` is within half an ulp of `x`. An infinity on the other side is
+    /// stepped to the largest finite number, as it may be the result of an overflow, while NaN is
+    /// kept as is.
+    pub(crate) fn widen(&self, upward: bool) -> Result<Self, SynthesisError> {
+        let e_normal_min = FpVar::constant(-F::from(Self::NEG_E_NORMAL_MIN));
+        let ulp = Self {
+            sign: Boolean::constant(!upward),
+            exponent: self.exponent.max(&e_normal_min, Self::E_SUM_BITS)? - F::from(M as u128),
+            mantissa: FpVar::constant(pow2::<F>(M)),
+            is_abnormal: Boolean::FALSE,
+        };
+        // `x + ulp` is exactly representable unless it overflows, so the addition is exact.
+        let stepped = Self::add(self, &ulp)?;
+
+        let is_opposite_inf =
+            self.is_abnormal
+                .and(&self.mantissa.is_zero()?.not())?
+                .and(&if upward {
+                    self.sign.clone()
+                } else {
+                    self.sign.not()
+                })?;
+        let largest = Self {
+            sign: self.sign.clone(),
+            exponent: FpVar::constant(F::from(Self::E_MAX - 1)),
+            mantissa: FpVar::constant(pow2::<F>(M + 1) - F::one()),
+            is_abnormal: Boolean::FALSE,
+        };
+        is_opposite_inf.select(&largest, &stepped)
+    }
+
     /// Round the mantissa.
     /// Note that the precision for subnormal numbers should be smaller than normal numbers, but in
     /// our representation, the mantissa of subnormal numbers also has `M + 1` bits, and we have to set
@@ -328,16 +875,18 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
     /// reduce the number of constraints.
     /// `half_flag` is a flag that indicates whether we should determine the rounding direction according
     /// to the equality between the remainder and 1/2.
-    fn round(
+    /// If `msb` is provided, we additionally enforce that it is the MSB of the shifted mantissa, which
+    /// allows the caller to reuse the decomposition here for checking the normalization of the mantissa
+    /// instead of decomposing it again.
+    fn round_mantissa(
         mantissa: &FpVar<F>,
         mantissa_bit_length: usize,
         shift: &FpVar<F>,
         shift_max: usize,
         half_flag: &Boolean<F>,
+        msb: Option<&Boolean<F>>,
     ) -> Result<FpVar<F>, SynthesisError> {
-        let two_to_shift = FpVar::new_hint(shift.cs(), || {
-            shift.value().map(|v| F::from(2u8).pow(v.into_bigint()))
-        })?;
+        let two_to_shift = FpVar::new_hint(shift.cs(), || shift.value().map(pow2_of))?;
         // TODO: enforce `(u, two_to_u)` is in lookup table `[0, u_max]`
 
         let r_idx = shift_max + mantissa_bit_length - M - 2;
@@ -361,42 +910,58 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         let (p, q, r, s) = {
             let cs = mantissa.cs().or(two_to_shift.cs());
 
-            let v = mantissa.value().unwrap_or_default() * F::from(1u128 << shift_max)
-                / two_to_shift.value().unwrap_or_default();
-            let bits = v.into_bigint().to_bits_le();
-
-            let p = F::from(F::BigInt::from_bits_le(&bits[p_idx..]));
-            let s = F::from(F::BigInt::from_bits_le(&bits[..r_idx]));
+            let bits = mantissa.value().and_then(|m| {
+                // `2^shift` is never 0, so the inverse always exists.
+                let v = m * pow2::<F>(shift_max) * two_to_shift.value()?.inverse().unwrap();
+                Ok(v.into_bigint().to_bits_le())
+            });
+            let bits = bits.as_deref().map_err(|&e| e);
 
             (
-                FpVar::new_hint(cs.clone(), || Ok(p))?,
-                Boolean::new_hint(cs.clone(), || Ok(bits[q_idx]))?,
-                Boolean::new_hint(cs.clone(), || Ok(bits[r_idx]))?,
-                FpVar::new_hint(cs.clone(), || Ok(s))?,
+                FpVar::new_hint(cs.clone(), || {
+                    Ok(F::from(F::BigInt::from_bits_le(&bits?[p_idx..])))
+                })?,
+                Boolean::new_hint(cs.clone(), || Ok(bits?[q_idx]))?,
+                Boolean::new_hint(cs.clone(), || Ok(bits?[r_idx]))?,
+                FpVar::new_hint(cs.clone(), || {
+                    Ok(F::from(F::BigInt::from_bits_le(&bits?[..r_idx])))
+                })?,
             )
         };
-        // Enforce the bit length of `p` and `s`
-        p.enforce_bit_length(p_len)?;
+        // Enforce the bit length of `p` and `s`.
+        // If `msb` is provided, `p - msb << (M - 1)` should have `M - 1` bits, which also guarantees that
+        // `p` has `M` bits and that its MSB is `msb`.
+        match msb {
+            Some(msb) => (&p - FpVar::from(msb.clone()) * pow2::<F>(p_len - 1))
+                .enforce_bit_length(p_len - 1)?,
+            None => p.enforce_bit_length(p_len)?,
+        }
         s.enforce_bit_length(s_len)?;
 
         // Concatenate `p || q`, `r || s`, and `p || q || r || s`.
         // `p || q` is what we want, i.e., the final mantissa, and `r || s` will be thrown away.
         let pq = p.double()? + FpVar::from(q.clone());
-        let rs = FpVar::from(r.clone()) * F::from(1u128 << r_idx) + &s;
-        let pqrs = &pq * F::from(1u128 << q_idx) + &rs;
+        let rs = FpVar::from(r.clone()) * pow2::<F>(r_idx) + &s;
+        let pqrs = &pq * pow2::<F>(q_idx) + &rs;
 
         // Enforce that `(p || q || r || s) << shift` is equal to `mantissa << shift_max`
         // Multiplication here is safe because `p || q || r || s` has `shift_max + mantissa_bit_length` bits,
         // and `2^shift` has at most `shift_max` bits, hence the product has `2 * shift_max + mantissa_bit_length`
-        // bits, which (at least for f32 and f64) is less than `F::MODULUS_BIT_SIZE` and will not overflow.
+        // bits, which should be less than `F::MODULUS_BIT_SIZE` to avoid overflow.
+        // For f32 and f64, this holds for all fields of at least 215 bits, e.g., the scalar fields of BN254,
+        // BLS12-381, and the Pasta curves.
         // This constraint guarantees that `p || q || r || s` is indeed `mantissa << (shift_max - shift)`.
-        pqrs.mul_equals(&two_to_shift, &(mantissa * F::from(1u128 << shift_max)))?;
+        assert!(
+            2 * shift_max + mantissa_bit_length < F::MODULUS_BIT_SIZE as usize,
+            "the field is too small for rounding {mantissa_bit_length}-bit mantissas"
+        );
+        pqrs.mul_equals(&two_to_shift, &(mantissa * pow2::<F>(shift_max)))?;
 
         // Determine whether `r == 1` and `s == 0`. If so, we need to round the mantissa according to `q`,
         // and otherwise, we need to round the mantissa according to `r`.
         // Also, we use `half_flag` to allow the caller to specify the rounding direction.
         let is_half = rs
-            .is_eq(&FpVar::constant(F::from(1u128 << r_idx)))?
+            .is_eq(&FpVar::constant(pow2::<F>(r_idx)))?
             .and(half_flag)?;
         let carry = FpVar::from(is_half.select(&q, &r)?);
 
@@ -418,13 +983,13 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         // Since the mantissa without carry is always smaller than `2^(M + 1)`, overflow only happens
         // when the original mantissa is `2^(M + 1) - 1` and the carry is 1. Therefore, the only possible
         // value of mantissa in this case is `2^(M + 1)`.
-        let mantissa_overflow = mantissa.is_eq(&FpVar::Constant(F::from(1u128 << (M + 1))))?;
+        let mantissa_overflow = mantissa.is_eq(&FpVar::Constant(pow2::<F>(M + 1)))?;
         // If mantissa overflows, we need to increment the exponent
         let exponent = exponent + FpVar::from(mantissa_overflow.clone());
         // Check if exponent overflows. If so, the result is abnormal.
         // Also, if the input is already abnormal, the result is of course abnormal.
         let is_abnormal = (&exponent - FpVar::constant(exponent_max))
-            .is_positive(E + 1)?
+            .is_positive(Self::E_SUM_BITS)?
             .or(&input_is_abnormal)?;
 
         Ok((
@@ -434,7 +999,7 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
             // We will adjust the mantissa latter if the result is NaN.
             mantissa_overflow
                 .or(&is_abnormal)?
-                .select(&FpVar::constant(F::from(1u128 << M)), &mantissa)?,
+                .select(&FpVar::constant(pow2::<F>(M)), &mantissa)?,
             is_abnormal.select(
                 // If the result is abnormal, we set the exponent to infinity/NaN's exponent.
                 &FpVar::constant(exponent_max),
@@ -449,30 +1014,147 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         ))
     }
 
+    /// Align a mantissa to a larger exponent, where `d` is the difference between the larger exponent and the
+    /// exponent of the mantissa, which should be non-negative.
+    /// If `d >= window`, the mantissa will be completely shifted out of the window, and hence the effect of shifting
+    /// it to the right by `d` bits is the same as shifting by `window` bits.
+    /// As discussed in `Self::round_mantissa`, we can shift left by `delta = window - min(d, window)` bits instead of shifting
+    /// right by `min(d, window)` bits in order to save constraints, where `delta` is `window - d` if `d < window`
+    /// and 0 otherwise.
+    /// Return `2^delta` and whether `d >= window`.
+    /// This also enforces that `d` is non-negative and has at most `Self::E_SUM_BITS` bits.
+    fn align(d: &FpVar<F>, window: usize) -> Result<(FpVar<F>, Boolean<F>), SynthesisError> {
+        let is_far = Boolean::new_hint(d.cs(), || {
+            d.value().map(|d| {
+                d.into_bigint() < F::MODULUS_MINUS_ONE_DIV_TWO && d >= F::from(window as u128)
+            })
+        })?;
+        let delta = FpVar::from(is_far.not()) * (d.negate()? + F::from(window as u128));
+        // Enforce that `is_far` is correct and `d` is non-negative by checking that
+        // * `d - is_far * window` is non-negative, i.e., it has `Self::E_SUM_BITS` bits. This implies that `d` is non-negative,
+        // and that `d >= window` if `is_far`.
+        // * `delta` is non-negative. Since `delta <= window` by definition, it suffices to check that `delta` fits
+        // in the bit length of `window`, which is a much smaller window than that of the exponent difference.
+        // This implies that `d <= window` if `!is_far`.
+        (d - FpVar::from(is_far.clone()) * F::from(window as u128))
+            .enforce_bit_length(Self::E_SUM_BITS)?;
+        let delta_bit_length = window.ilog2() as usize + 1;
+        // With `shift-tables`, the check is done by `pow2` when computing `2^delta`.
+        #[cfg(not(feature = "shift-tables"))]
+        let two_to_delta = {
+            delta.enforce_bit_length(delta_bit_length)?;
+            // TODO: enforce `(delta, two_to_delta)` is in lookup table `[0, window]`
+            FpVar::new_hint(delta.cs(), || delta.value().map(pow2_of))?
+        };
+        #[cfg(feature = "shift-tables")]
+        let two_to_delta = delta.pow2(delta_bit_length)?;
+
+        Ok((two_to_delta, is_far))
+    }
+
+    /// Normalize and round the signed mantissa `s` of `mantissa_bit_length` bits, whose MSB has exponent `exponent`.
+    /// Return the rounded absolute value of the mantissa, the exponent of the result, whether `s` is non-negative,
+    /// and whether `s` is zero. The caller should handle the overflow by `Self::fix_overflow`.
+    /// If the result is subnormal, we need to clear the lowest `E_NORMAL_MIN - exponent` bits of the rounded
+    /// mantissa, where `exponent` is the exponent of the result. Instead of doing so, we require that `s` has at
+    /// least `E_NORMAL_MIN - exponent + mantissa_bit_length - M - 1` trailing 0s in this case, where `exponent`
+    /// is the given one.
+    #[allow(clippy::type_complexity)]
+    fn normalize(
+        s: &FpVar<F>,
+        mantissa_bit_length: usize,
+        exponent: FpVar<F>,
+    ) -> Result<(FpVar<F>, FpVar<F>, Boolean<F>, Boolean<F>), SynthesisError> {
+        // Get the sign of the mantissa and find how many bits to shift the mantissa to the left to have the
+        // `mantissa_bit_length - 1`-th bit equal to 1.
+        // Prodive these values as hints to the circuit.
+        let (mantissa_ge_0, shift) = {
+            let cs = s.cs();
+            let hints = s.value().map(|mantissa| {
+                let mantissa_ge_0 = mantissa.into_bigint() < F::MODULUS_MINUS_ONE_DIV_TWO;
+
+                let bits = if mantissa_ge_0 { mantissa } else { -mantissa }
+                    .into_bigint()
+                    .to_bits_le();
+
+                let shift = bits[..mantissa_bit_length]
+                    .iter()
+                    .rev()
+                    .position(|&i| i)
+                    .unwrap_or(mantissa_bit_length);
+
+                (mantissa_ge_0, shift)
+            });
+
+            (
+                Boolean::new_hint(cs.clone(), || Ok(hints?.0))?,
+                FpVar::new_hint(cs.clone(), || Ok(F::from(hints?.1 as u128)))?,
+            )
+        };
+        #[cfg(not(feature = "shift-tables"))]
+        let two_to_shift = {
+            // TODO: enforce range of shift `[0, mantissa_bit_length]`
+            // TODO: enforce `(shift, two_to_shift)` is in lookup table
+            FpVar::new_hint(shift.cs(), || shift.value().map(pow2_of))?
+        };
+        // With `shift-tables`, `shift` is only enforced to be in `[0, 2^l)`, where `l` is the bit length of
+        // `mantissa_bit_length`, instead of `[0, mantissa_bit_length]`. This is fine, because if the mantissa
+        // is non-zero, a larger `shift` makes the shifted mantissa exceed `mantissa_bit_length` bits, which is
+        // rejected by `Self::round_mantissa`, and otherwise the exponent is reset by `Self::fix_overflow`.
+        #[cfg(feature = "shift-tables")]
+        let two_to_shift = shift.pow2(mantissa_bit_length.ilog2() as usize + 1)?;
+
+        // Compute the shifted absolute value of mantissa
+        let mantissa = mantissa_ge_0.select(s, &s.negate()?)? * two_to_shift;
+        let mantissa_is_zero = mantissa.is_zero()?;
+        // We need to enforce that the shifted absolute value of mantissa is non-negative, has at most
+        // `mantissa_bit_length` bits, and has MSB 1 unless the mantissa is zero.
+        // Instead of decomposing `mantissa` here, we let `Self::round_mantissa` enforce that the MSB is
+        // `!mantissa_is_zero` while decomposing `mantissa` into `p || q || r || s`.
+        // Soundness holds because the decomposition constrains `mantissa` to be the concatenation of
+        // `p`, `q`, `r`, and `s`, each of which is range-checked, so that `mantissa` is non-negative and
+        // has at most `mantissa_bit_length` bits, and `mantissa`'s MSB is the MSB of `p`.
+        let mantissa = Self::round_mantissa(
+            &mantissa,
+            mantissa_bit_length,
+            // If the result is subnormal, we need to clear the lowest `E_NORMAL_MIN - exponent` bits of rounded
+            // mantissa. However, as we are going to show below, the required bits are already 0.
+            // Note that `mantissa` is the product of `s` and `2^shift`, where `2^shift` has `shift` trailing 0s,
+            // and `s` has `E_NORMAL_MIN - exponent + mantissa_bit_length - M - 1` trailing 0s by assumption.
+            // After rounding, we choose the `M + 1` MSBs as the rounded mantissa, which should contain at least
+            // `E_NORMAL_MIN - exponent + shift` trailing 0s.
+            // Since the exponent of the result is `exponent - shift`, the lowest `E_NORMAL_MIN - (exponent - shift)`
+            // bits of the rounded mantissa should be 0.
+            &FpVar::zero(),
+            0,
+            &Boolean::TRUE,
+            Some(&mantissa_is_zero.not()),
+        )?;
+
+        // Decrement the exponent by `shift`.
+        Ok((mantissa, exponent - shift, mantissa_ge_0, mantissa_is_zero))
+    }
+
     /// Add two numbers.
+    #[cfg_attr(
+        feature = "profile",
+        tracing::instrument(target = "r1cs_float", skip_all)
+    )]
     fn add(x: &Self, y: &Self) -> Result<Self, SynthesisError> {
-        // Compute `y.exponent - x.exponent`'s absolute value and sign.
-        // Since `delta` is the absolute value, `delta >= 0`.
-        let (delta, ex_le_ey) = (&y.exponent - &x.exponent).abs(E + 1)?;
+        // Compute the sign and the absolute value of `y.exponent - x.exponent`.
+        // Provide the sign as a hint to the circuit, which is enforced by `Self::align`.
+        let ex_le_ey = Boolean::new_hint(x.exponent.cs().or(y.exponent.cs()), || {
+            Ok((y.exponent.value()? - x.exponent.value()?).into_bigint()
+                < F::MODULUS_MINUS_ONE_DIV_TWO)
+        })?;
+        let abs = ex_le_ey.select(&(&y.exponent - &x.exponent), &(&x.exponent - &y.exponent))?;
+        // Then we are going to align the mantissas of `x` and `y` by shifting the mantissa of the number with
+        // smaller exponent to the right by `min(abs, M + 3)` bits.
+        // `Self::align` also guarantees that `abs` is indeed the absolute value, as otherwise `abs` is negative.
+        let (two_to_delta, _) = Self::align(&abs, M + 3)?;
 
         // The exponent of the result is at most `max(x.exponent, y.exponent) + 1`, where 1 is the possible carry.
         let exponent = ex_le_ey.select(&y.exponent, &x.exponent)? + FpVar::one();
-        // Then we are going to use `delta` to align the mantissas of `x` and `y`.
-        // If `delta` is 0, we don't need to shift the mantissas.
-        // Otherwise, we need to shift the mantissa of the number with smaller exponent to the right by `delta` bits.
-        // Now we check if `delta >= M + 3`, i.e., if the difference is too large.
-        // If so, the mantissa of the number with smaller exponent will be completely shifted out, and hence the
-        // effect of shifting by `delta` bits is the same as shifting by `M + 3` bits.
-        // Therefore, the actual right shift count is `min(delta, M + 3)`.
-        // As discussed in `Self::round`, we can shift left by `M + 3 - min(delta, M + 3) = max(M + 3 - delta, 0)`
-        // bits instead of shifting right by `min(delta, M + 3)` bits in order to save constraints.
-        let delta = (delta.negate()? + F::from((M + 3) as u128)).max(&FpVar::zero(), E)?;
-        let two_to_delta = FpVar::new_hint(delta.cs(), || {
-            delta
-                .value()
-                .map(|delta| F::from(2u8).pow(delta.into_bigint()))
-        })?;
-        // TODO: enforce `(delta, two_to_delta)` is in lookup table `[0, >=M + 3]`
 
         // Compute the signed mantissas
         let xx = x.sign.select(&x.mantissa.negate()?, &x.mantissa)?;
@@ -482,64 +1164,23 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         let ww = &xx + &yy - &zz;
 
         // Align `zz` and `ww`.
-        // Naively, we can shift `zz` to the right by `delta` bits and keep `ww` unchanged.
-        // However, as mentioned above, we left shift `zz` by `M + 3 - min(delta, M + 3)` bits and `ww` by `M + 3`
+        // Naively, we can shift `zz` to the right by `min(abs, M + 3)` bits and keep `ww` unchanged.
+        // However, as mentioned above, we left shift `zz` by `M + 3 - min(abs, M + 3)` bits and `ww` by `M + 3`
         // bits instead for circuit efficiency.
         // Also, note that if `exponent` is subnormal and w.l.o.g. `x.exponent < y.exponent`, then `zz` has
         // `E_NORMAL_MIN - x.exponent` trailing 0s, and `ww` has `E_NORMAL_MIN - y.exponent` trailing 0s.
         // Hence, `zz * 2^delta` has `E_NORMAL_MIN - x.exponent + M + 3 - y.exponent + x.exponent` trailing 0s,
         // and `ww << (M + 3)` has `E_NORMAL_MIN - y.exponent + M + 3` trailing 0s.
         // This implies that `s` also has `E_NORMAL_MIN - y.exponent + M + 3` trailing 0s.
-        // Generally, `s` should have `max(E_NORMAL_MIN - max(x.exponent, y.exponent), 0) + M + 3` trailing 0s.
-        let s = zz * two_to_delta + ww * F::from(1u128 << (M + 3));
+        // Generally, `s` should have `max(E_NORMAL_MIN - max(x.exponent, y.exponent), 0) + M + 3` trailing 0s,
+        // which is required by `Self::normalize`.
+        let s = zz * two_to_delta + ww * pow2::<F>(M + 3);
         // The shift count is at most `M + 3`, and both `zz` and `ww` have `M + 1` bits, hence the result has at most
         // `(M + 3) + (M + 1) + 1` bits, where 1 is the possible carry.
         let mantissa_bit_length = (M + 3) + (M + 1) + 1;
 
-        // Get the sign of the mantissa and find how many bits to shift the mantissa to the left to have the
-        // `mantissa_bit_length - 1`-th bit equal to 1.
-        // Prodive these values as hints to the circuit.
-        let (mantissa_ge_0, shift, two_to_shift) = {
-            let cs = s.cs();
-            let mantissa = s.value().unwrap_or_default();
-            let mantissa_ge_0 = mantissa.into_bigint() < F::MODULUS_MINUS_ONE_DIV_TWO;
-
-            let bits = if mantissa_ge_0 { mantissa } else { -mantissa }
-                .into_bigint()
-                .to_bits_le();
-
-            let shift = bits[..mantissa_bit_length]
-                .iter()
-                .rev()
-                .position(|&i| i)
-                .unwrap_or(mantissa_bit_length);
-
-            (
-                Boolean::new_hint(cs.clone(), || Ok(mantissa_ge_0))?,
-                FpVar::new_hint(cs.clone(), || Ok(F::from(shift as u128)))?,
-                FpVar::new_hint(cs.clone(), || Ok(F::from(1u128 << shift)))?,
-            )
-        };
-        // TODO: enforce range of shift `[0, M + 4]`
-        // TODO: enforce `(shift, two_to_shift)` is in lookup table
-
-        // Compute the shifted absolute value of mantissa
-        let mantissa = mantissa_ge_0.select(&s, &s.negate()?)? * two_to_shift;
-        let mantissa_is_zero = mantissa.is_zero()?;
-        // Enforce that the MSB of the shifted absolute value of mantissa is 1 unless the mantissa is zero.
-        // Soundness holds because
-        // * `mantissa` is non-negative. Otherwise, `mantissa - !mantissa_is_zero << (mantissa_bit_length - 1)`
-        // will be negative and cannot fit in `mantissa_bit_length - 1` bits.
-        // * `mantissa` has at most `mantissa_bit_length` bits. Otherwise,
-        // `mantissa - !mantissa_is_zero << (mantissa_bit_length - 1)` will be greater than or equal to
-        // `2^(mantissa_bit_length - 1)` and cannot fit in `mantissa_bit_length - 1` bits.
-        // * `mantissa`'s MSB is 1 unless `mantissa_is_zero`. Otherwise, `mantissa - 1 << (mantissa_bit_length - 1)`
-        // will be negative and cannot fit in `mantissa_bit_length - 1` bits.
-        (&mantissa
-            - FpVar::from(mantissa_is_zero.not()) * F::from(1u128 << (mantissa_bit_length - 1)))
-        .enforce_bit_length(mantissa_bit_length - 1)?;
-        // Decrement the exponent by `shift`.
-        let exponent = exponent - shift;
+        let (mantissa, exponent, mantissa_ge_0, mantissa_is_zero) =
+            Self::normalize(&s, mantissa_bit_length, exponent)?;
 
         // `mantissa_ge_0` can be directly used to determine the sign of the result, except for the case
         // `-0 + -0`. Therefore, we first check whether the signs of `x` and `y` are the same. If so,
@@ -549,24 +1190,6 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
             .is_eq(&y.sign)?
             .select(&x.sign, &mantissa_ge_0.not())?;
 
-        let mantissa = Self::round(
-            &mantissa,
-            mantissa_bit_length,
-            // If the result is subnormal, we need to clear the lowest `E_NORMAL_MIN - exponent` bits of rounded
-            // mantissa. However, as we are going to show below, the required bits are already 0.
-            // Note that `mantissa` is the product of `s` and `2^shift`, where `2^shift` has `shift` trailing 0s,
-            // `s` has `E_NORMAL_MIN - max(x.exponent, y.exponent) + M + 3` trailing 0s.
-            // In summary, `mantissa` has `E_NORMAL_MIN - max(x.exponent, y.exponent) + M + 3 + shift` trailing 0s
-            // and `(M + 3) + (M + 1) + 1` bits in total.
-            // After rounding, we choose the `M + 1` MSBs as the rounded mantissa, which should contain at least
-            // `E_NORMAL_MIN - max(x.exponent, y.exponent) + shift - 1` trailing 0s.
-            // Since `exponent == max(x.exponent, y.exponent) + 1 - shift`, the lowest `E_NORMAL_MIN - exponent`
-            // bits of the rounded mantissa should be 0.
-            &FpVar::zero(),
-            0,
-            &Boolean::TRUE,
-        )?;
-
         let (mantissa, exponent, is_abnormal) = Self::fix_overflow(
             &mantissa,
             &mantissa_is_zero,
@@ -605,7 +1228,123 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         })
     }
 
+    /// The window of `Self::sum`, i.e., how many bits below the largest term are kept during summation.
+    pub(crate) const SUM_WINDOW: usize = 2 * M + 3;
+
+    /// Compute the sum of `terms` with a single normalization and rounding.
+    /// The terms within `Self::SUM_WINDOW` bits below the largest (finite) term are summed exactly, and the
+    /// exact sum is rounded to the nearest representable number. Smaller terms are ignored, and their effect
+    /// on the sum is at most `terms.len() * 2^-SUM_WINDOW` relative to the largest term.
+    #[cfg_attr(
+        feature = "profile",
+        tracing::instrument(target = "r1cs_float", skip_all)
+    )]
+    pub(crate) fn sum(terms: &[Self]) -> Result<Self, SynthesisError> {
+        let exponent_min = FpVar::constant(-F::from(Self::NEG_E_MIN));
+        if terms.is_empty() {
+            return Ok(Self {
+                sign: Boolean::FALSE,
+                exponent: exponent_min,
+                mantissa: FpVar::zero(),
+                is_abnormal: Boolean::FALSE,
+            });
+        }
+
+        // Abnormal terms are excluded from the summation by treating them as 0, whose exponent is the minimum.
+        let exponents = terms
+            .iter()
+            .map(|x| x.is_abnormal.select(&exponent_min, &x.exponent))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Find the maximum exponent and provide it as a hint to the circuit.
+        let exponent_max = FpVar::new_hint(
+            exponents
+                .iter()
+                .fold(ConstraintSystemRef::None, |cs, e| cs.or(e.cs())),
+            || {
+                let offset = F::from(Self::NEG_E_MIN);
+                let exponents = exponents
+                    .iter()
+                    .map(|e| Ok(e.value()? + offset))
+                    .collect::<Result<Vec<_>, SynthesisError>>()?;
+                Ok(exponents.into_iter().max().unwrap() - offset)
+            },
+        )?;
+
+        let mut s = FpVar::zero();
+        let mut product = FpVar::one();
+        for (x, exponent) in terms.iter().zip(&exponents) {
+            let d = &exponent_max - exponent;
+            // Align the mantissa of `x` to `exponent_max`, which also enforces `exponent_max >= x.exponent`.
+            let (two_to_delta, is_far) = Self::align(&d, Self::SUM_WINDOW)?;
+            // If `x` is out of the window or abnormal, it contributes nothing to the sum.
+            let mantissa = is_far
+                .or(&x.is_abnormal)?
+                .select(&FpVar::zero(), &x.mantissa)?;
+            s += x.sign.select(&mantissa.negate()?, &mantissa)? * two_to_delta;
+            product *= d;
+        }
+        // `exponent_max` is indeed the maximum if it is also equal to one of the exponents.
+        product.enforce_equal(&FpVar::zero())?;
+
+        // Each aligned mantissa has at most `SUM_WINDOW + M + 1` bits, and the sum of `terms.len()` of them
+        // requires `terms.len().ilog2() + 1` more bits for the carry.
+        let carry_bit_length = terms.len().ilog2() as usize + 1;
+        let mantissa_bit_length = Self::SUM_WINDOW + (M + 1) + carry_bit_length;
+        // If the result is subnormal, each aligned mantissa, and hence `s`, has at least
+        // `E_NORMAL_MIN - exponent_max + SUM_WINDOW` trailing 0s, which is required by `Self::normalize`.
+        // See `Self::add` for a detailed analysis of the case of two terms.
+        let (mantissa, exponent, mantissa_ge_0, mantissa_is_zero) = Self::normalize(
+            &s,
+            mantissa_bit_length,
+            exponent_max + F::from(carry_bit_length as u128),
+        )?;
+
+        // Count the abnormal terms, where the mantissa of an infinity is `2^M` and that of NaN is 0.
+        let inv_two_to_m = pow2::<F>(M).inverse().unwrap();
+        let mut num_abnormal = FpVar::zero();
+        let mut num_inf = FpVar::zero();
+        let mut num_neg_inf = FpVar::zero();
+        let mut num_neg = FpVar::zero();
+        for x in terms {
+            let is_inf = FpVar::from(x.is_abnormal.clone()) * &x.mantissa * inv_two_to_m;
+            num_neg_inf += &is_inf * FpVar::from(x.sign.clone());
+            num_inf += is_inf;
+            num_abnormal += FpVar::from(x.is_abnormal.clone());
+            num_neg += FpVar::from(x.sign.clone());
+        }
+        let has_abnormal = num_abnormal.is_zero()?.not();
+        let has_neg_inf = num_neg_inf.is_zero()?.not();
+        // The result is NaN if any term is NaN, or if there are both +Inf and -Inf.
+        let is_nan = num_abnormal
+            .is_eq(&num_inf)?
+            .not()
+            .or(&has_neg_inf.and(&num_inf.is_eq(&num_neg_inf)?.not())?)?;
+
+        // The sign is determined by `mantissa_ge_0` unless the sum is 0, in which case the result is -0 if and
+        // only if all terms are -0.
+        let sign = mantissa_is_zero.select(
+            &num_neg.is_eq(&FpVar::constant(F::from(terms.len() as u128)))?,
+            &mantissa_ge_0.not(),
+        )?;
+        let (mantissa, exponent, is_abnormal) =
+            Self::fix_overflow(&mantissa, &mantissa_is_zero, &exponent, &has_abnormal)?;
+
+        Ok(Self {
+            // If there are infinities (and no NaN), the result is -Inf if and only if there is -Inf.
+            sign: has_abnormal.select(&has_neg_inf, &sign)?,
+            exponent,
+            // `Self::fix_overflow` sets the mantissa to infinity's mantissa if the result is abnormal, so we only
+            // need to handle NaN here.
+            mantissa: is_nan.select(&FpVar::zero(), &mantissa)?,
+            is_abnormal,
+        })
+    }
+
     /// Multiply two numbers.
+    #[cfg_attr(
+        feature = "profile",
+        tracing::instrument(target = "r1cs_float", skip_all)
+    )]
     fn mul(x: &Self, y: &Self) -> Result<Self, SynthesisError> {
         // The result is negative if and only if the signs of x and y are different.
         let sign = x.sign.xor(&y.sign)?;
@@ -627,9 +1366,8 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         // mantissa_bit_length bits.
         // * If `mantissa_msb == 1` but the actual MSB is 0, then the subtraction will underflow to a negative
         // value.
-        (&mantissa
-            - FpVar::from(mantissa_msb.clone()) * F::from(1u128 << (mantissa_bit_length - 1)))
-        .enforce_bit_length(mantissa_bit_length - 1)?;
+        (&mantissa - FpVar::from(mantissa_msb.clone()) * pow2::<F>(mantissa_bit_length - 1))
+            .enforce_bit_length(mantissa_bit_length - 1)?;
         // Shift the mantissa to the left to make the MSB 1.
         // Since `mantissa` is in the range `[2^(2M), 2^(2M + 2))`, either the MSB is 1 or the second MSB is 1.
         // Therefore, we can simply double the mantissa if the MSB is 0.
@@ -639,16 +1377,20 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         let exponent = &x.exponent + &y.exponent + FpVar::from(mantissa_msb);
 
         let shift_max = M + 2;
-        let mantissa = Self::round(
+        let mantissa = Self::round_mantissa(
             &mantissa,
             mantissa_bit_length,
             // If `exponent >= E_NORMAL_MIN`, i.e., the result is normal, we don't need to clear the lower bits.
             // Otherwise, we need to clear `min(E_NORMAL_MIN - exponent, shift_max)` bits of the rounded mantissa.
             &(exponent.negate()? - F::from(Self::NEG_E_NORMAL_MIN))
-                .min(&FpVar::constant(F::from(shift_max as u128)), E + 1)?
-                .max(&FpVar::zero(), E + 1)?,
+                .min(
+                    &FpVar::constant(F::from(shift_max as u128)),
+                    Self::E_SUM_BITS,
+                )?
+                .max(&FpVar::zero(), Self::E_SUM_BITS)?,
             shift_max,
             &Boolean::TRUE,
+            None,
         )?;
 
         let mantissa_is_zero = mantissa.is_zero()?;
@@ -671,6 +1413,10 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
     }
 
     /// Divide two numbers.
+    #[cfg_attr(
+        feature = "profile",
+        tracing::instrument(target = "r1cs_float", skip_all)
+    )]
     fn div(x: &Self, y: &Self) -> Result<Self, SynthesisError> {
         // The result is negative if and only if the signs of `x` and `y` are different.
         let sign = x.sign.xor(&y.sign)?;
@@ -678,7 +1424,7 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         let y_is_zero = y.mantissa.is_zero()?;
 
         // If the divisor is 0, we increase it to `2^M`, because we cannot represent an infinite value in circuit.
-        let y_mantissa = y_is_zero.select(&FpVar::constant(F::from(1u128 << M)), &y.mantissa)?;
+        let y_mantissa = y_is_zero.select(&FpVar::constant(pow2::<F>(M)), &y.mantissa)?;
 
         // The result's mantissa is the quotient of `x.mantissa << (M + 2)` and `y_mantissa`.
         // Since both `x.mantissa` and `y_mantissa` are in the range `[2^M, 2^(M + 1))`, the quotient is in the range
@@ -688,16 +1434,19 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         // Provide the quotient and the MSB as hints to the circuit.
         let (mantissa, mantissa_msb) = {
             let cs = x.mantissa.cs().or(y_mantissa.cs());
-            let x: BigUint = x.mantissa.value().unwrap_or_default().into();
-            let y: BigUint = y_mantissa.value().unwrap_or_default().into();
-            let mantissa = (x << (M + 2)) / y;
+            // `y_mantissa` is never 0, as it is replaced by `2^M` if `y` is 0.
+            let mantissa = x
+                .mantissa
+                .to_biguint()
+                .and_then(|x| Ok((x << (M + 2)) / y_mantissa.to_biguint()?));
+            let mantissa = mantissa.as_ref().map_err(|&e| e);
             (
-                FpVar::new_hint(cs.clone(), || Ok(F::from(mantissa.clone())))?,
-                Boolean::new_hint(cs, || Ok(mantissa.bit(mantissa_bit_length as u64 - 1)))?,
+                FpVar::new_hint(cs.clone(), || Ok(F::from(mantissa?.clone())))?,
+                Boolean::new_hint(cs, || Ok(mantissa?.bit(mantissa_bit_length as u64 - 1)))?,
             )
         };
         // Compute the remainder `(x.mantissa << (M + 2)) % y_mantissa`.
-        let remainder = &x.mantissa * F::from(1u128 << (M + 2)) - &mantissa * &y_mantissa;
+        let remainder = &x.mantissa * pow2::<F>(M + 2) - &mantissa * &y_mantissa;
         // Enforce that `0 <= remainder < y_mantissa`.
         remainder.enforce_bit_length(M + 1)?;
         (&y_mantissa - &remainder - FpVar::one()).enforce_bit_length(M + 1)?;
@@ -707,9 +1456,8 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         // mantissa_bit_length bits.
         // * If `mantissa_msb == 1` but the actual MSB is 0, then the subtraction will underflow to a negative
         // value.
-        (&mantissa
-            - FpVar::from(mantissa_msb.clone()) * F::from(1u128 << (mantissa_bit_length - 1)))
-        .enforce_bit_length(mantissa_bit_length - 1)?;
+        (&mantissa - FpVar::from(mantissa_msb.clone()) * pow2::<F>(mantissa_bit_length - 1))
+            .enforce_bit_length(mantissa_bit_length - 1)?;
 
         // Since `mantissa` is in the range `[2^(2M), 2^(2M + 2))`, either the MSB is 1 or the second MSB is 1.
         // Therefore, we can simply double the mantissa if the MSB is 0.
@@ -719,16 +1467,20 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         let exponent = &x.exponent - &y.exponent - FpVar::from(mantissa_msb.not());
 
         let shift_max = M + 2;
-        let mantissa = Self::round(
+        let mantissa = Self::round_mantissa(
             &mantissa,
             mantissa_bit_length,
             // If `exponent >= E_NORMAL_MIN`, i.e., the result is normal, we don't need to clear the lower bits.
             // Otherwise, we need to clear `min(E_NORMAL_MIN - exponent, shift_max)` bits of the rounded mantissa.
             &(exponent.negate()? - F::from(Self::NEG_E_NORMAL_MIN))
-                .min(&FpVar::constant(F::from(shift_max as u128)), E + 1)?
-                .max(&FpVar::zero(), E + 1)?,
+                .min(
+                    &FpVar::constant(F::from(shift_max as u128)),
+                    Self::E_SUM_BITS,
+                )?
+                .max(&FpVar::zero(), Self::E_SUM_BITS)?,
             shift_max,
             &remainder.is_zero()?,
+            None,
         )?;
 
         // If `y` is infinity, the result is zero.
@@ -754,11 +1506,15 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         })
     }
 
+    #[cfg_attr(
+        feature = "profile",
+        tracing::instrument(target = "r1cs_float", skip_all)
+    )]
     pub fn sqrt(x: &Self) -> Result<Self, SynthesisError> {
         // Get the LSB of the exponent and provide it as a hint to the circuit.
         let e_lsb = Boolean::new_hint(x.exponent.cs(), || {
             x.exponent.value().map(|v| {
-                (v + F::from(Self::NEG_E_MIN.next_multiple_of(&2)))
+                (v + F::from(Self::NEG_E_MIN.next_multiple_of(2)))
                     .into_bigint()
                     .is_odd()
             })
@@ -776,7 +1532,7 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         // We are going to find `n` such that `n^2 <= m < (n + 1)^2`, and `r = m - n^2` decides the rounding direction.
         // To this end, we shift `x.mantissa` to the left to allow a more accurate `r`.
         // TODO: `mantissa_bit_length * 2 - (M + 2)` is obtained by empirical analysis. We need to find why it works.
-        let m = &x.mantissa * F::from(1u128 << (mantissa_bit_length * 2 - (M + 2)));
+        let m = &x.mantissa * pow2::<F>(mantissa_bit_length * 2 - (M + 2));
         // `sqrt(2^e * m) == sqrt(2^(e - 1) * 2m)`
         // If `e` is even, then the result is `sqrt(2^e * m) = 2^(e >> 1) * sqrt(m)`.
         // If `e` is odd, then the result is `sqrt(2^(e - 1) * 2m) = 2^(e >> 1) * sqrt(2m)`.
@@ -786,19 +1542,21 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         // Prodive these values as hints to the circuit.
         let (n, shift, two_to_shift) = {
             let cs = m.cs();
-            let m: BigUint = m.value().unwrap_or_default().into();
-            let n = F::from(m.sqrt());
-            let bits = n.into_bigint().to_bits_le();
-
-            let shift = bits[..mantissa_bit_length]
-                .iter()
-                .rev()
-                .position(|&i| i)
-                .unwrap_or(mantissa_bit_length);
+            let hints = m.to_biguint().map(|m| {
+                let n = F::from(m.sqrt());
+                let bits = n.into_bigint().to_bits_le();
+
+                let shift = bits[..mantissa_bit_length]
+                    .iter()
+                    .rev()
+                    .position(|&i| i)
+                    .unwrap_or(mantissa_bit_length);
+                (n, shift)
+            });
             (
-                FpVar::new_hint(cs.clone(), || Ok(n))?,
-                FpVar::new_hint(cs.clone(), || Ok(F::from(shift as u128)))?,
-                FpVar::new_hint(cs.clone(), || Ok(F::from(1u128 << shift)))?,
+                FpVar::new_hint(cs.clone(), || Ok(hints?.0))?,
+                FpVar::new_hint(cs.clone(), || Ok(F::from(hints?.1 as u128)))?,
+                FpVar::new_hint(cs.clone(), || Ok(pow2::<F>(hints?.1)))?,
             )
         };
         // TODO: enforce range of shift `[0, mantissa_bit_length]`
@@ -820,13 +1578,13 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         // `2^(mantissa_bit_length - 1)` and cannot fit in `mantissa_bit_length - 1` bits.
         // * `n`'s MSB is 1 unless `n_is_zero`. Otherwise, `n - 1 << (mantissa_bit_length - 1)`
         // will be negative and cannot fit in `mantissa_bit_length - 1` bits.
-        (&n - FpVar::from(n_is_zero.not()) * F::from(1u128 << (mantissa_bit_length - 1)))
+        (&n - FpVar::from(n_is_zero.not()) * pow2::<F>(mantissa_bit_length - 1))
             .enforce_bit_length(mantissa_bit_length - 1)?;
 
         // Decrement the exponent by `shift`.
         let exponent = exponent - shift;
 
-        let mantissa = Self::round(
+        let mantissa = Self::round_mantissa(
             &n,
             mantissa_bit_length,
             // The result is always normal or 0, as `exponent = (x.exponent >> 1) - shift > E_NORMAL_MIN`.
@@ -834,6 +1592,7 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
             &FpVar::zero(),
             0,
             &r.is_zero()?,
+            None,
         )?;
 
         // If `x` is negative and `x` is not `-0`, the result is NaN.
@@ -863,8 +1622,29 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         })
     }
 
+    /// Compute `sqrt(x^2 + y^2)`. See `Self::l2_norm`.
+    pub fn hypot(x: &Self, y: &Self) -> Result<Self, SynthesisError> {
+        Self::l2_norm(&[x.clone(), y.clone()])
+    }
+
+    /// Compute the Euclidean norm `sqrt(x_1^2 + ... + x_n^2)` of `xs`, where the squares are summed
+    /// by `Self::sum` with a single normalization, and the norm of an empty slice is `+0`.
+    /// Unlike `f64::hypot`, the squares are not scaled, so they may overflow to infinity (or underflow
+    /// to 0) although the norm is in the range, and the norm is NaN if any number is NaN.
+    #[cfg_attr(
+        feature = "profile",
+        tracing::instrument(target = "r1cs_float", skip_all)
+    )]
+    pub fn l2_norm(xs: &[Self]) -> Result<Self, SynthesisError> {
+        let squares = xs
+            .iter()
+            .map(|x| Self::mul(x, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::sqrt(&Self::sum(&squares)?)
+    }
+
     fn less(x: &Self, y: &Self, allow_eq: bool) -> Result<Boolean<F>, SynthesisError> {
-        let xe_ge_ye = (&x.exponent - &y.exponent).is_positive(E + 1)?;
+        let xe_ge_ye = (&x.exponent - &y.exponent).is_positive(Self::E_SUM_BITS)?;
         let xm_ge_ym = (&x.mantissa - &y.mantissa).is_positive(M + 1)?;
 
         x.is_abnormal
@@ -934,22 +1714,129 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         Self::less(y, x, true)
     }
 
+    /// Enforce the ordering between `x` and `y`, i.e., `x < y` if `ordering` is `Ordering::Less`
+    /// and `x > y` if it is `Ordering::Greater`, where the equality is also accepted if
+    /// `should_also_check_equality` is `true`. This is the same as `FpVar::enforce_cmp`, but follows
+    /// the semantics of floating-point comparisons as `Self::is_lt` and its friends, e.g.,
+    /// `-0 < +0` is rejected, and the circuit is unsatisfiable if either number is NaN.
+    /// Return an error if `ordering` is `Ordering::Equal`.
+    pub fn enforce_cmp(
+        x: &Self,
+        y: &Self,
+        ordering: Ordering,
+        should_also_check_equality: bool,
+    ) -> Result<(), SynthesisError> {
+        let (left, right) = match ordering {
+            Ordering::Less => (x, y),
+            Ordering::Greater => (y, x),
+            Ordering::Equal => return Err(SynthesisError::Unsatisfiable),
+        };
+        Self::less(left, right, should_also_check_equality)?.enforce_equal(&Boolean::TRUE)
+    }
+
+    pub fn enforce_lt(x: &Self, y: &Self) -> Result<(), SynthesisError> {
+        Self::enforce_cmp(x, y, Ordering::Less, false)
+    }
+
+    pub fn enforce_le(x: &Self, y: &Self) -> Result<(), SynthesisError> {
+        Self::enforce_cmp(x, y, Ordering::Less, true)
+    }
+
+    pub fn enforce_gt(x: &Self, y: &Self) -> Result<(), SynthesisError> {
+        Self::enforce_cmp(x, y, Ordering::Greater, false)
+    }
+
+    pub fn enforce_ge(x: &Self, y: &Self) -> Result<(), SynthesisError> {
+        Self::enforce_cmp(x, y, Ordering::Greater, true)
+    }
+
+    /// Find the index of the first number in `xs` that is better than all the previous ones
+    /// according to `is_better`, where NaNs lose to all the other numbers.
+    fn arg_best(
+        xs: &[Self],
+        is_better: fn(&Self, &Self) -> Result<Boolean<F>, SynthesisError>,
+    ) -> Result<(FpVar<F>, Vec<Boolean<F>>), SynthesisError> {
+        if xs.is_empty() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let (mut best, mut best_is_nan, mut index) =
+            (xs[0].clone(), xs[0].is_nan()?, FpVar::zero());
+        for (i, x) in xs.iter().enumerate().skip(1) {
+            let x_is_nan = x.is_nan()?;
+            let better = is_better(x, &best)?.or(&best_is_nan.and(&x_is_nan.not())?)?;
+            best = better.select(x, &best)?;
+            best_is_nan = better.select(&x_is_nan, &best_is_nan)?;
+            index = better.select(&FpVar::constant(F::from(i as u64)), &index)?;
+        }
+        let one_hot = (0..xs.len())
+            .map(|i| index.is_eq(&FpVar::constant(F::from(i as u64))))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((index, one_hot))
+    }
+
+    /// Find the index of the minimum of `xs`, returned both as an `FpVar` and as one-hot
+    /// `Boolean`s, e.g., for proving that a candidate achieves the lowest validation error.
+    /// Same as `numpy.argmin`, the first index is returned if there are ties, but NaNs are ignored
+    /// unless all the numbers are NaN, and `-0` and `+0` are considered equal.
+    /// The circuit is unsatisfiable if `xs` is empty.
+    pub fn argmin(xs: &[Self]) -> Result<(FpVar<F>, Vec<Boolean<F>>), SynthesisError> {
+        Self::arg_best(xs, Self::is_lt)
+    }
+
+    /// Find the index of the maximum of `xs`. See `Self::argmin`.
+    pub fn argmax(xs: &[Self]) -> Result<(FpVar<F>, Vec<Boolean<F>>), SynthesisError> {
+        Self::arg_best(xs, Self::is_gt)
+    }
+
+    /// Sort `xs` in ascending order by a sorting network of `O(n log^2(n))` comparators, each of
+    /// which costs a comparison and two selections.
+    /// Since the network only depends on `xs.len()`, the slice should have a fixed size for the
+    /// circuit to be the same across different inputs.
+    /// `-0` and `+0` are considered equal, so their relative order is unspecified, and so is the
+    /// order of the whole result if there is any NaN.
+    #[cfg_attr(
+        feature = "profile",
+        tracing::instrument(target = "r1cs_float", skip_all)
+    )]
+    pub fn sort(xs: &[Self]) -> Result<Vec<Self>, SynthesisError> {
+        let mut xs = xs.to_vec();
+        for (i, j) in sorting_network(xs.len()) {
+            let swap = Self::is_lt(&xs[j], &xs[i])?;
+            let (min, max) = (swap.select(&xs[j], &xs[i])?, swap.select(&xs[i], &xs[j])?);
+            (xs[i], xs[j]) = (min, max);
+        }
+        Ok(xs)
+    }
+
+    /// Compute the median of `xs` by `Self::sort`, which is the mean of the two middle numbers if
+    /// `xs.len()` is even (the same as `numpy.median`).
+    /// The circuit is unsatisfiable if `xs` is empty.
+    pub fn median(xs: &[Self]) -> Result<Self, SynthesisError> {
+        if xs.is_empty() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let sorted = Self::sort(xs)?;
+        let n = sorted.len();
+        if n % 2 == 1 {
+            return Ok(sorted[n / 2].clone());
+        }
+        let half = Self::new_constant(ConstraintSystemRef::None, 0.5)?;
+        Self::mul(&Self::add(&sorted[n / 2 - 1], &sorted[n / 2])?, &half)
+    }
+
     pub fn trunc(x: &Self) -> Result<Self, SynthesisError> {
         let e_ge_0 = x.exponent.is_positive(E)?;
         let e = e_ge_0.select(&x.exponent, &FpVar::one().negate()?)?;
         let f = (e.negate()? + F::from(M as u128)).max(&FpVar::zero(), E)?;
-        let two_to_f = FpVar::new_hint(f.cs(), || {
-            f.value().map(|f| F::from(2u8).pow(f.into_bigint()))
-        })?;
+        let two_to_f = FpVar::new_hint(f.cs(), || f.value().map(pow2_of))?;
         // TODO: enforce (f, two_to_f) is in lookup table [0, >=M + 1]
-        let m = (&x.mantissa * F::from(1u128 << (M + 1))).mul_by_inverse_unchecked(&two_to_f)?;
+        let m = (&x.mantissa * pow2::<F>(M + 1)).mul_by_inverse_unchecked(&two_to_f)?;
         let q = {
             let cs = m.cs();
-            let m: BigUint = m.value().unwrap_or_default().into();
-            FpVar::new_hint(cs.clone(), || Ok(F::from(m >> (M + 1))))?
+            FpVar::new_hint(cs.clone(), || Ok(F::from(m.to_biguint()? >> (M + 1))))?
         };
         q.enforce_bit_length(M + 1)?;
-        (m - &q * F::from(1u128 << (M + 1))).enforce_bit_length(M + 1)?;
+        (m - &q * pow2::<F>(M + 1)).enforce_bit_length(M + 1)?;
 
         Ok(Self {
             sign: x.sign.clone(),
@@ -963,17 +1850,14 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         let e_ge_0 = x.exponent.is_positive(E)?;
         let e = e_ge_0.select(&x.exponent, &FpVar::one().negate()?)?;
         let f = (e.negate()? + F::from(M as u128)).max(&FpVar::zero(), E)?;
-        let two_to_f = FpVar::new_hint(f.cs(), || {
-            f.value().map(|f| F::from(2u8).pow(f.into_bigint()))
-        })?;
+        let two_to_f = FpVar::new_hint(f.cs(), || f.value().map(pow2_of))?;
         // TODO: enforce (f, two_to_f) is in lookup table [0, >=M + 1]
-        let m = (&x.mantissa * F::from(1u128 << (M + 1))).mul_by_inverse_unchecked(&two_to_f)?;
+        let m = (&x.mantissa * pow2::<F>(M + 1)).mul_by_inverse_unchecked(&two_to_f)?;
         let q = {
             let cs = m.cs().or(x.sign.cs());
-            let m: BigUint = m.value().unwrap_or_default().into();
-            let s = x.sign.value().unwrap();
             FpVar::new_hint(cs.clone(), || {
-                Ok(F::from(if s {
+                let m = m.to_biguint()?;
+                Ok(F::from(if x.sign.value()? {
                     m.div_ceil(&(BigUint::one() << (M + 1)))
                 } else {
                     m >> (M + 1)
@@ -982,18 +1866,15 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
         };
         q.enforce_bit_length(M + 1)?;
         x.sign
-            .select(
-                &(&q * F::from(1u128 << (M + 1)) - &m),
-                &(&m - &q * F::from(1u128 << (M + 1))),
-            )?
+            .select(&(&q * pow2::<F>(M + 1) - &m), &(&m - &q * pow2::<F>(M + 1)))?
             .enforce_bit_length(M + 1)?;
 
         let n = q * &two_to_f;
         let (n, e) = {
-            let mantissa_overflow = n.is_eq(&FpVar::Constant(F::from(1u128 << (M + 1))))?;
+            let mantissa_overflow = n.is_eq(&FpVar::Constant(pow2::<F>(M + 1)))?;
 
             Ok((
-                mantissa_overflow.select(&FpVar::constant(F::from(1u128 << M)), &n)?,
+                mantissa_overflow.select(&FpVar::constant(pow2::<F>(M)), &n)?,
                 e + FpVar::from(mantissa_overflow),
             ))
         }?;
@@ -1012,4 +1893,398 @@ impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
     pub fn ceil(x: &Self) -> Result<Self, SynthesisError> {
         Ok(Self::floor(&x.neg())?.neg())
     }
+
+    /// Round the number to the nearest integer, with ties rounded to the even integer, i.e., the
+    /// same as `f64::round_ties_even`.
+    /// Return the result both as a floating-point number and as an integer in the range
+    /// `(-2^bits, 2^bits)` (in the same representation as `Self::to_fixed`).
+    /// The circuit is unsatisfiable if the number is NaN, infinity, or out of the range.
+    pub fn round(x: &Self, bits: usize) -> Result<(Self, FpVar<F>), SynthesisError> {
+        // Similar to `Self::trunc`, but numbers below 1/2 (rather than 1) are rounded to 0, so we
+        // treat the exponent as -1 if it is less than -1, and reset the result to 0 in that case.
+        let e_ge_neg_1 = (&x.exponent + F::one()).is_positive(E)?;
+        let e = e_ge_neg_1.select(&x.exponent, &FpVar::one().negate()?)?;
+        let f = (e.negate()? + F::from(M as u128)).max(&FpVar::zero(), E)?;
+        // `f` is at most `M + 1`, so `2^f` is computed from its bits.
+        let two_to_f = f.pow2((M + 1).ilog2() as usize + 1)?;
+        let m = (&x.mantissa * pow2::<F>(M + 1)).mul_by_inverse_unchecked(&two_to_f)?;
+        // The integer part `q` of `m / 2^(M + 1)` is decomposed into `2 * q_half + q_is_odd`, so
+        // that ties can be rounded to even.
+        let (q_half, q_is_odd) = {
+            let cs = m.cs();
+            let q = m.to_biguint().map(|m| m >> (M + 1));
+            let q = q.as_ref().map_err(|&e| e);
+            (
+                FpVar::new_hint(cs.clone(), || Ok(F::from(q? >> 1u8)))?,
+                Boolean::new_hint(cs.clone(), || Ok(q?.bit(0)))?,
+            )
+        };
+        q_half.enforce_bit_length(M)?;
+        let q = &q_half.double()? + FpVar::from(q_is_odd.clone());
+        let r = &m - &q * pow2::<F>(M + 1);
+        r.enforce_bit_length(M + 1)?;
+
+        // Round up if the remainder is greater than 1/2, or equal to 1/2 and `q` is odd.
+        let r_gt_half = (&r - pow2::<F>(M) - F::one()).is_positive(M + 1)?;
+        let r_is_half = r.is_eq(&FpVar::constant(pow2::<F>(M)))?;
+        let round_up = r_gt_half.or(&r_is_half.and(&q_is_odd)?)?;
+        let q = e_ge_neg_1.select(&(q + FpVar::from(round_up)), &FpVar::zero())?;
+
+        let n = q * &two_to_f;
+        let (n, e) = {
+            let mantissa_overflow = n.is_eq(&FpVar::Constant(pow2::<F>(M + 1)))?;
+
+            Ok((
+                mantissa_overflow.select(&FpVar::constant(pow2::<F>(M)), &n)?,
+                e + FpVar::from(mantissa_overflow),
+            ))
+        }?;
+
+        let y = Self {
+            sign: x.sign.clone(),
+            exponent: n
+                .is_zero()?
+                .and(&x.is_abnormal.not())?
+                .select(&FpVar::constant(-F::from(Self::NEG_E_MIN)), &e)?,
+            mantissa: n,
+            is_abnormal: x.is_abnormal.clone(),
+        };
+        // The result is an integer, so the conversion is exact.
+        let n = y.to_fixed(0, bits)?;
+
+        Ok((y, n))
+    }
+
+    /// Convert the number to a fixed-point number with `scale_bits` fractional bits, i.e., the
+    /// integer `trunc(x * 2^scale_bits)`, where a negative integer `-n` is represented by the field
+    /// element `-n`.
+    /// The integer is enforced to be in the range `(-2^bits, 2^bits)`, so that it can be fed into
+    /// integer-based gadgets that require a bounded input, e.g., range proofs and comparators.
+    /// The circuit is unsatisfiable if the number is NaN, infinity, or out of the range.
+    pub fn to_fixed(&self, scale_bits: usize, bits: usize) -> Result<FpVar<F>, SynthesisError> {
+        assert!(
+            scale_bits as u128 <= Self::NEG_E_NORMAL_MIN,
+            "the scale is too large for {}-bit numbers",
+            1 + E + M
+        );
+        assert!(
+            bits + M + 1 < F::MODULUS_BIT_SIZE as usize,
+            "the field is too small for {bits}-bit fixed-point numbers"
+        );
+        self.is_abnormal.enforce_equal(&Boolean::FALSE)?;
+
+        // After scaling, the number is `mantissa * 2^(e - M)`, where `e = exponent + scale_bits`.
+        // Similar to `Self::trunc`, the integer part is 0 if `e < 0`, so we can treat `e` as -1.
+        let e = &self.exponent + F::from(scale_bits as u128);
+        let e_ge_0 = e.is_positive(E)?;
+        let e = e_ge_0.select(&e, &FpVar::one().negate()?)? + F::one();
+        // `e` is at most `bits` for numbers in the range, which is enforced before computing `2^e`
+        // from its bits, so that the power is exact.
+        let e_bit_length = (usize::BITS - bits.leading_zeros()) as usize;
+        (FpVar::constant(F::from(bits as u128)) - &e).enforce_bit_length(e_bit_length)?;
+        let two_to_e = e.pow2(e_bit_length)?;
+        let m = &self.mantissa * &two_to_e;
+        let q = {
+            let cs = m.cs();
+            FpVar::new_hint(cs.clone(), || Ok(F::from(m.to_biguint()? >> (M + 1))))?
+        };
+        // `q` is the integer part of `m / 2^(M + 1)`. Checking its bit length also enforces that the
+        // number is in the range, as `q >= 2^(e - 1)` unless the mantissa is 0.
+        q.enforce_bit_length(bits)?;
+        (m - &q * pow2::<F>(M + 1)).enforce_bit_length(M + 1)?;
+
+        self.sign.select(&q.negate()?, &q)
+    }
+
+    /// Convert the fixed-point number `n * 2^-scale_bits` to a floating-point number, where `n` is
+    /// an integer in the range `(-2^bits, 2^bits)` (e.g., the output of `Self::to_fixed`), which is
+    /// enforced by the circuit. If `n` has more than `M + 1` bits, the result is rounded to the
+    /// nearest number.
+    pub fn from_fixed(
+        n: &FpVar<F>,
+        scale_bits: usize,
+        bits: usize,
+    ) -> Result<Self, SynthesisError> {
+        assert!(
+            scale_bits as u128 <= Self::NEG_E_NORMAL_MIN,
+            "the scale is too large for {}-bit numbers",
+            1 + E + M
+        );
+        assert!(bits > 0, "fixed-point numbers should have at least 1 bit");
+
+        // `Self::normalize` expects at least `M + 3` bits for rounding, i.e., `M + 1` bits for the
+        // result, and at least 2 bits for the remainder, so we append 0s to `n` if it is shorter.
+        let mantissa_bit_length = bits.max(M + 3);
+        let s = n * pow2::<F>(mantissa_bit_length - bits);
+        // The MSB of `s` has exponent `bits - 1 - scale_bits`. Since `scale_bits` is at most
+        // `-E_NORMAL_MIN`, the result is never subnormal, and hence `Self::normalize` requires no
+        // trailing 0s of `s`.
+        let (mantissa, exponent, mantissa_ge_0, mantissa_is_zero) = Self::normalize(
+            &s,
+            mantissa_bit_length,
+            FpVar::constant(F::from((bits - 1) as u128) - F::from(scale_bits as u128)),
+        )?;
+        let (mantissa, exponent, is_abnormal) =
+            Self::fix_overflow(&mantissa, &mantissa_is_zero, &exponent, &Boolean::FALSE)?;
+
+        Ok(Self {
+            sign: mantissa_ge_0.not(),
+            exponent,
+            mantissa,
+            is_abnormal,
+        })
+    }
+}
+
+/// The comparators `(i, j)` of a bitonic sorting network for `n` numbers, after each of which the
+/// smaller number is at `i` and the larger one is at `j`.
+/// The network is generalized to arbitrary `n` by splitting the sequences unevenly and comparing
+/// across the largest power of two below the length when merging.
+fn sorting_network(n: usize) -> Vec<(usize, usize)> {
+    fn sort(comparators: &mut Vec<(usize, usize)>, lo: usize, n: usize, ascending: bool) {
+        if n > 1 {
+            let m = n / 2;
+            sort(comparators, lo, m, !ascending);
+            sort(comparators, lo + m, n - m, ascending);
+            merge(comparators, lo, n, ascending);
+        }
+    }
+
+    fn merge(comparators: &mut Vec<(usize, usize)>, lo: usize, n: usize, ascending: bool) {
+        if n > 1 {
+            // The largest power of two below `n`.
+            let m = 1 << (n - 1).ilog2();
+            for i in lo..lo + n - m {
+                comparators.push(if ascending { (i, i + m) } else { (i + m, i) });
+            }
+            merge(comparators, lo, m, ascending);
+            merge(comparators, lo + m, n - m, ascending);
+        }
+    }
+
+    let mut comparators = vec![];
+    sort(&mut comparators, 0, n, true);
+    comparators
+}
+
+/// The coefficients `ln(2)^k / k!` of the Taylor series of `2^f = e^(f ln(2))`.
+const EXP2_COEFFICIENTS: [&str; 21] = [
+    "1",
+    "6.9314718055994530941723212145817656807550e-1",
+    "2.4022650695910071233355126316333248586528e-1",
+    "5.5504108664821579953142263768621757359354e-2",
+    "9.6181291076284771619790715736588654799298e-3",
+    "1.3333558146428443423412221987996174730782e-3",
+    "1.5403530393381609954437097332742347961531e-4",
+    "1.5252733804059840280025439012009638169615e-5",
+    "1.3215486790144309488403758228288360755748e-6",
+    "1.0178086009239699727490007597744629254947e-7",
+    "7.0549116208011233298753921815507382589380e-9",
+    "4.4455382718708114975964085588881120529970e-10",
+    "2.5678435993488205141994802391830894505359e-11",
+    "1.3691488853904128880891953995335070586058e-12",
+    "6.7787263548225456334491043139846463613689e-14",
+    "3.1324367070884286216349444409730763399909e-15",
+    "1.3570247948755147193112966226343482998233e-16",
+    "5.5330465324582420434855460952858402960373e-18",
+    "2.1306753354891179960203984778933649908143e-19",
+    "7.7730084288573564190889971590100940363010e-21",
+    "2.6939194384655834169728611518451115181221e-22",
+];
+
+/// The coefficients `2 / (ln(2) (2k + 1))` of the series `log2(m) = Σ c_k t^(2k + 1)`, where
+/// `t = (m - 1) / (m + 1)`.
+const LOG2_COEFFICIENTS: [&str; 16] = [
+    "2.8853900817779268147198493620037842748533",
+    "9.6179669392597560490661645400126142495110e-1",
+    "5.7707801635558536294396987240075685497066e-1",
+    "4.1219858311113240210283562314339775355047e-1",
+    "3.2059889797532520163553881800042047498370e-1",
+    "2.6230818925253880133816812381852584316848e-1",
+    "2.2195308321368667805537302784644494421948e-1",
+    "1.9235933878519512098132329080025228499022e-1",
+    "1.6972882833987804792469702129434025146196e-1",
+    "1.5186263588304877972209733484230443551859e-1",
+    "1.3739952770371080070094520771446591785016e-1",
+    "1.2545174268599681803129779834799062064580e-1",
+    "1.1541560327111707258879397448015137099413e-1",
+    "1.0686629932510840054517960600014015832790e-1",
+    "9.9496209716480234990339633172544285339769e-2",
+    "9.3077099412191187571608043935605944350106e-2",
+];
+
+/// The coefficients of the Taylor series `tanh(x) = Σ c_k x^(2k + 1)`.
+const TANH_COEFFICIENTS: [&str; 15] = [
+    "1",
+    "-3.3333333333333333333333333333333333333333e-1",
+    "1.3333333333333333333333333333333333333333e-1",
+    "-5.3968253968253968253968253968253968253968e-2",
+    "2.1869488536155202821869488536155202821869e-2",
+    "-8.8632355299021965688632355299021965688632e-3",
+    "3.5921280365724810169254613699058143502588e-3",
+    "-1.4558343870513182682494851807021119190431e-3",
+    "5.9002744094558598137807599370002108875436e-4",
+    "-2.3912911424355248148573145888511280932057e-4",
+    "9.6915379569294503255958750003888093778588e-5",
+    "-3.9278323883316834053370808093123335095207e-5",
+    "1.5918905069328964740744279816570043055315e-5",
+    "-6.4516892156554307631908423153025580446318e-6",
+    "2.6147711512907545542635942564097410973704e-6",
+];
+
+impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
+    /// The constant rounded from the decimal string `value`.
+    fn decimal_constant(value: &str) -> Self {
+        Self::new_decimal(
+            ConstraintSystemRef::None,
+            value,
+            Rounding::NearestEven,
+            AllocationMode::Constant,
+        )
+        .expect("the constant should be a valid decimal number")
+    }
+
+    /// The constant `+0`, `+infinity`, or NaN.
+    fn special_constant(mantissa: u128, is_abnormal: bool) -> Self {
+        Self {
+            sign: Boolean::FALSE,
+            exponent: FpVar::constant(if is_abnormal {
+                F::from(Self::E_MAX)
+            } else {
+                -F::from(Self::NEG_E_MIN)
+            }),
+            mantissa: FpVar::constant(F::from(mantissa)),
+            is_abnormal: Boolean::constant(is_abnormal),
+        }
+    }
+
+    /// The number `mantissa * 2^(exponent - M)` for a non-zero `mantissa` in `[2^M, 2^(M + 1))` and an
+    /// `exponent` in the range of finite non-zero numbers, without any check.
+    fn from_parts(exponent: FpVar<F>, mantissa: FpVar<F>) -> Self {
+        Self {
+            sign: Boolean::FALSE,
+            exponent,
+            mantissa,
+            is_abnormal: Boolean::FALSE,
+        }
+    }
+
+    /// Evaluate the polynomial `Σ coefficients[k] x^k` by Horner's method, where the fewest
+    /// coefficients are used such that the next term `|coefficients[k] bound^k|` is less than
+    /// `2^-(M + 3)` times the constant term.
+    fn horner(x: &Self, coefficients: &[&str], bound: f64) -> Self {
+        let values = coefficients
+            .iter()
+            .map(|c| c.parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        let terms = (1..values.len())
+            .find(|&k| {
+                (values[k] * bound.powi(k as i32)).abs()
+                    < values[0].abs() * 2f64.powi(-(M as i32) - 3)
+            })
+            .unwrap_or_else(|| panic!("the series is too short for {}-bit numbers", 1 + E + M));
+        coefficients[..terms]
+            .iter()
+            .rev()
+            .map(|c| Self::decimal_constant(c))
+            .reduce(|p, c| p * x + c)
+            .unwrap()
+    }
+
+    /// Compute `2^x`, whose error is within a few ulps, and which is exact if `x` is an integer.
+    ///
+    /// `x` is split into the integer `n = trunc(x)`, which becomes the exponent of the result, and
+    /// the fraction `f = x - n` in `(-1, 1)`, whose power `2^f` is computed by a truncated Taylor
+    /// series of `e^(f ln(2))`, so that the result is `2^f * 2^n` rounded only once, including when
+    /// it is subnormal or overflows.
+    pub fn exp2(x: &Self) -> Result<Self, SynthesisError> {
+        let mantissa_is_zero = x.mantissa.is_zero()?;
+        let is_nan = x.is_abnormal.and(&mantissa_is_zero)?;
+        // `2^x` overflows if `x >= E_MAX`, and is rounded to 0 if `x <= E_MIN - 1`.
+        let overflow = Self::is_ge(x, &Self::decimal_constant(&Self::E_MAX.to_string()))?;
+        let underflow = Self::is_le(x, &Self::decimal_constant(&format!("-{}", Self::NEG_E_MIN)))?;
+        let in_range = is_nan.or(&overflow)?.or(&underflow)?.not();
+        // Otherwise, `|x| < 2^E`, and `n = trunc(x)` is in the range of exponents of finite non-zero
+        // numbers. We replace `x` by 0 when it is out of the range to keep `to_fixed` satisfiable.
+        let x = in_range.select(x, &Self::special_constant(0, false))?;
+        let n = x.to_fixed(0, E)?;
+        let f = &x - Self::from_fixed(&n, 0, E)?;
+
+        let result = Self::horner(&f, &EXP2_COEFFICIENTS, 1.)
+            * Self::from_parts(n, FpVar::constant(pow2::<F>(M)));
+
+        is_nan.select(
+            &Self::special_constant(0, true),
+            &overflow.select(
+                &Self::special_constant(1 << M, true),
+                &underflow.select(&Self::special_constant(0, false), &result)?,
+            )?,
+        )
+    }
+
+    /// Compute `tanh(x)`, whose error is within a few ulps.
+    ///
+    /// The approximation is piecewise in `a = |x|`: for `a < 1/4`, `tanh(a)` is computed by a
+    /// truncated Taylor series, and otherwise by `(1 - z) / (1 + z)` with `z = 2^(-2a log2(e))`
+    /// (see `Self::exp2`), where `z <= e^(-1/2)` avoids the cancellation in `1 - z`. The sign of
+    /// the result is that of `x`, so `tanh(±0) = ±0` and `tanh(±infinity) = ±1`.
+    pub fn tanh(x: &Self) -> Result<Self, SynthesisError> {
+        let a = x.abs();
+        let is_small = Self::is_lt(&a, &Self::decimal_constant("0.25"))?;
+
+        let small = &a * Self::horner(&(&a * &a), &TANH_COEFFICIENTS, 1. / 16.);
+        let one = Self::decimal_constant("1");
+        let z = Self::exp2(&-(&a * Self::decimal_constant(LOG2_COEFFICIENTS[0])))?;
+        let large = (&one - &z) / (&one + &z);
+
+        let result = is_small.select(&small, &large)?;
+        Ok(Self {
+            sign: x.sign.clone(),
+            ..result
+        })
+    }
+
+    /// Compute `log2(x)`, whose error is within a few ulps, and which is exact if `x` is a power of
+    /// two. As required by IEEE 754, `log2(±0)` is negative infinity, and `log2(x)` is NaN for
+    /// `x < 0`.
+    ///
+    /// `x` is split into its exponent `e` and its mantissa `m` in `[1, 2)`, which act on the
+    /// components directly, where `m` is halved and `e` is incremented if `m > sqrt(2)`, so that
+    /// `log2(m)` in `(-1/2, 1/2]` is computed by a fast converging series of
+    /// `t = (m - 1) / (m + 1)`, and the result is `e + log2(m)`.
+    pub fn log2(x: &Self) -> Result<Self, SynthesisError> {
+        let mantissa_is_zero = x.mantissa.is_zero()?;
+        let is_nan = x.is_abnormal.and(&mantissa_is_zero)?;
+        let is_zero = x.is_abnormal.not().and(&mantissa_is_zero)?;
+        let is_infinity = x.is_abnormal.and(&mantissa_is_zero.not())?;
+        let is_invalid = is_nan.or(&x.sign.and(&is_zero.not())?)?;
+
+        // For 0, infinity, and NaN, we compute `log2(1)` instead, whose result is discarded.
+        let is_special = x.is_abnormal.or(&mantissa_is_zero)?;
+        let e = is_special.select(&FpVar::zero(), &x.exponent)?;
+        let m = Self::from_parts(
+            FpVar::zero(),
+            mantissa_is_zero.select(&FpVar::constant(pow2::<F>(M)), &x.mantissa)?,
+        );
+        let is_large = Self::is_gt(
+            &m,
+            &Self::decimal_constant("1.4142135623730950488016887242096980785697"),
+        )?;
+        let m = Self::from_parts(FpVar::zero() - FpVar::from(is_large.clone()), m.mantissa);
+        let e = e + FpVar::from(is_large);
+
+        let one = Self::decimal_constant("1");
+        let t = (&m - &one) / (&m + &one);
+        // `|t| <= 3 - 2 sqrt(2)`, so `t^2 < 0.0295`.
+        let result =
+            &t * Self::horner(&(&t * &t), &LOG2_COEFFICIENTS, 0.0295) + Self::from_fixed(&e, 0, E)?;
+
+        is_invalid.select(
+            &Self::special_constant(0, true),
+            &is_zero.select(
+                &-Self::special_constant(1 << M, true),
+                &is_infinity.select(&Self::special_constant(1 << M, true), &result)?,
+            )?,
+        )
+    }
 }
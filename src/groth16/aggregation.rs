@@ -0,0 +1,744 @@
+//! The aggregation follows [[SnarkPack]](https://eprint.iacr.org/2021/529.pdf): the prover commits to the
+//! vectors `A`, `B`, `C` of `n` proofs with pairing-based commitments, and proves with a GIPA argument
+//! (TIPP for `∏ e(A_i, B_i)^{r^i}`, MIPP for `Σ r^i C_i`) that the aggregated values are consistent with
+//! the commitments, which allows the verifier to check all Groth16 equations at once with `O(log n)`
+//! pairings.
+//! The commitment keys are structured, and their final folded values are proven correct by KZG openings.
+//!
+//! Different from the original SnarkPack, our proofs contain the commitments `cm` and the proofs of
+//! knowledge `pok` for the lookup argument.
+//! Since the challenge of each proof is derived from its `cm`, the aggregate proof has to include all `cm`s,
+//! while the `pok`s are aggregated by another MIPP instance.
+
+use ark_ec::{
+    pairing::{Pairing, PairingOutput},
+    scalar_mul::fixed_base::FixedBase,
+    AffineRepr, CurveGroup, Group, VariableBaseMSM,
+};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_serialize::*;
+use ark_std::{cfg_iter, end_timer, fmt, rand::Rng, start_timer};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+use super::{verifier::commitment_challenge, PreparedVerifyingKey, Proof};
+
+/// The errors that may occur when aggregating proofs or verifying aggregate proofs.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AggregationError {
+    /// The number of proofs is not a power of two greater than 1, or exceeds the size of the
+    /// aggregation key.
+    InvalidNumberOfProofs,
+    /// The proofs are inconsistent with each other or with the verifying key, e.g., only some of
+    /// them contain a commitment.
+    MalformedProof,
+    /// The number of public inputs does not match the verifying key.
+    MalformedVerifyingKey,
+}
+
+impl ark_std::error::Error for AggregationError {}
+
+impl fmt::Display for AggregationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            AggregationError::InvalidNumberOfProofs => {
+                write!(f, "the number of proofs is not supported")
+            }
+            AggregationError::MalformedProof => write!(f, "malformed proof"),
+            AggregationError::MalformedVerifyingKey => write!(f, "malformed verifying key"),
+        }
+    }
+}
+
+/// The key for aggregating at most `n` proofs, which consists of the powers of two secrets `a` and `b`
+/// in both groups.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregationKey<E: Pairing> {
+    /// The elements `a^i * G` in `E::G1` for `i` in `[0, 2n)`.
+    pub g_a: Vec<E::G1Affine>,
+    /// The elements `b^i * G` in `E::G1` for `i` in `[0, 2n)`.
+    pub g_b: Vec<E::G1Affine>,
+    /// The elements `a^i * H` in `E::G2` for `i` in `[0, n)`.
+    pub h_a: Vec<E::G2Affine>,
+    /// The elements `b^i * H` in `E::G2` for `i` in `[0, n)`.
+    pub h_b: Vec<E::G2Affine>,
+}
+
+/// The key for verifying aggregate proofs.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregationVerifyingKey<E: Pairing> {
+    /// The generator `G` of `E::G1`.
+    pub g: E::G1Affine,
+    /// The generator `H` of `E::G2`.
+    pub h: E::G2Affine,
+    /// The element `a * G` in `E::G1`.
+    pub g_a: E::G1Affine,
+    /// The element `b * G` in `E::G1`.
+    pub g_b: E::G1Affine,
+    /// The element `a * H` in `E::G2`.
+    pub h_a: E::G2Affine,
+    /// The element `b * H` in `E::G2`.
+    pub h_b: E::G2Affine,
+}
+
+/// A commitment in `E::TargetField` to vectors with respect to the keys derived from `a` and `b`.
+pub type Commitment<E> = (PairingOutput<E>, PairingOutput<E>);
+
+/// The messages of the prover in one round of the GIPA argument, where each pair contains the
+/// cross terms for the left and the right halves.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GipaRound<E: Pairing> {
+    /// The cross terms of the inner pairing product of `A` and `B`.
+    pub ip_ab: (PairingOutput<E>, PairingOutput<E>),
+    /// The cross terms of the commitment to `A` and `B`.
+    pub com_ab: (Commitment<E>, Commitment<E>),
+    /// The cross terms of the multi-exponentiations of `C` (and `pok`).
+    pub agg_c: Vec<(E::G1Affine, E::G1Affine)>,
+    /// The cross terms of the commitments to `C` (and `pok`).
+    pub com_c: Vec<(Commitment<E>, Commitment<E>)>,
+}
+
+/// An aggregate proof of `n` Groth16 proofs over the same circuit.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregateProof<E: Pairing> {
+    /// The commitments `cm` of the proofs, which are empty if the circuit uses no lookups.
+    pub cm: Vec<E::G1Affine>,
+    /// The commitment to `A` and `B`.
+    pub com_ab: Commitment<E>,
+    /// The commitments to `C` (and `pok`).
+    pub com_c: Vec<Commitment<E>>,
+    /// The inner pairing product `∏ e(A_i, B_i)^{r^i}`.
+    pub ip_ab: PairingOutput<E>,
+    /// The multi-exponentiations `Σ r^i C_i` (and `Σ r^i pok_i`).
+    pub agg_c: Vec<E::G1Affine>,
+    /// The messages of the GIPA argument.
+    pub rounds: Vec<GipaRound<E>>,
+    /// The folded `A`.
+    pub final_a: E::G1Affine,
+    /// The folded `B`.
+    pub final_b: E::G2Affine,
+    /// The folded `C` (and `pok`).
+    pub final_c: Vec<E::G1Affine>,
+    /// The folded commitment keys for `A` and `C`.
+    pub final_v: (E::G2Affine, E::G2Affine),
+    /// The folded commitment keys for `B`.
+    pub final_w: (E::G1Affine, E::G1Affine),
+    /// The KZG openings of `final_v`.
+    pub opening_v: (E::G2Affine, E::G2Affine),
+    /// The KZG openings of `final_w`.
+    pub opening_w: (E::G1Affine, E::G1Affine),
+}
+
+/// The Fiat-Shamir transcript of the aggregation.
+struct Transcript(Sha256);
+
+impl Transcript {
+    fn new() -> Self {
+        Self(Sha256::new_with_prefix(b"r1cs_float/groth16/aggregation"))
+    }
+
+    fn append(&mut self, v: &impl CanonicalSerialize) {
+        let mut bytes = vec![];
+        v.serialize_compressed(&mut bytes).unwrap();
+        self.0.update(&bytes);
+    }
+
+    fn challenge<F: PrimeField>(&mut self) -> F {
+        let hash = self.0.clone().finalize();
+        self.0.update(hash);
+        F::from_le_bytes_mod_order(&hash)
+    }
+
+    /// Absorb the statement, i.e., the number of proofs, the public inputs, and the commitments.
+    fn append_statement<E: Pairing>(
+        &mut self,
+        public_inputs: &[&[E::ScalarField]],
+        cm: &[E::G1Affine],
+        com_ab: &Commitment<E>,
+        com_c: &[Commitment<E>],
+    ) {
+        self.append(&(public_inputs.len() as u64));
+        for inputs in public_inputs {
+            self.append(&inputs.to_vec());
+        }
+        self.append(&cm.to_vec());
+        self.append(com_ab);
+        self.append(&com_c.to_vec());
+    }
+}
+
+/// Generate a random key for aggregating at most `max_proofs` proofs, which should be a power of two.
+/// The secrets `a` and `b` must be discarded after the generation.
+pub fn setup_aggregation<E: Pairing>(
+    max_proofs: usize,
+    rng: &mut impl Rng,
+) -> (AggregationKey<E>, AggregationVerifyingKey<E>) {
+    let setup_time = start_timer!(|| "Aggregation::Setup");
+
+    let a = E::ScalarField::rand(rng);
+    let b = E::ScalarField::rand(rng);
+    let g = E::G1::generator();
+    let h = E::G2::generator();
+
+    let scalar_bits = E::ScalarField::MODULUS_BIT_SIZE as usize;
+    let g1_window = FixedBase::get_mul_window_size(4 * max_proofs);
+    let g1_table = FixedBase::get_window_table::<E::G1>(scalar_bits, g1_window, g);
+    let g2_window = FixedBase::get_mul_window_size(2 * max_proofs);
+    let g2_table = FixedBase::get_window_table::<E::G2>(scalar_bits, g2_window, h);
+
+    let g_a = FixedBase::msm::<E::G1>(
+        scalar_bits,
+        g1_window,
+        &g1_table,
+        &powers(a, 2 * max_proofs),
+    );
+    let g_b = FixedBase::msm::<E::G1>(
+        scalar_bits,
+        g1_window,
+        &g1_table,
+        &powers(b, 2 * max_proofs),
+    );
+    let h_a = FixedBase::msm::<E::G2>(scalar_bits, g2_window, &g2_table, &powers(a, max_proofs));
+    let h_b = FixedBase::msm::<E::G2>(scalar_bits, g2_window, &g2_table, &powers(b, max_proofs));
+
+    let ak = AggregationKey {
+        g_a: E::G1::normalize_batch(&g_a),
+        g_b: E::G1::normalize_batch(&g_b),
+        h_a: E::G2::normalize_batch(&h_a),
+        h_b: E::G2::normalize_batch(&h_b),
+    };
+    let avk = AggregationVerifyingKey {
+        g: g.into_affine(),
+        h: h.into_affine(),
+        g_a: ak.g_a[1],
+        g_b: ak.g_b[1],
+        h_a: ak.h_a[1],
+        h_b: ak.h_b[1],
+    };
+
+    end_timer!(setup_time);
+
+    (ak, avk)
+}
+
+/// Commit to `a` and `b` with keys `(v_1, w_1)` and `(v_2, w_2)`.
+fn commit_pair<E: Pairing>(
+    v: (&[E::G2Affine], &[E::G2Affine]),
+    w: (&[E::G1Affine], &[E::G1Affine]),
+    a: &[E::G1Affine],
+    b: &[E::G2Affine],
+) -> Commitment<E> {
+    (
+        E::multi_pairing(a.iter().chain(w.0), v.0.iter().chain(b)),
+        E::multi_pairing(a.iter().chain(w.1), v.1.iter().chain(b)),
+    )
+}
+
+/// Commit to `c` with keys `v_1` and `v_2`.
+fn commit_single<E: Pairing>(
+    v: (&[E::G2Affine], &[E::G2Affine]),
+    c: &[E::G1Affine],
+) -> Commitment<E> {
+    (E::multi_pairing(c, v.0), E::multi_pairing(c, v.1))
+}
+
+/// Fold `v` into `v_L + x * v_R`.
+fn fold<G: CurveGroup>(v: &[G::Affine], x: G::ScalarField) -> Vec<G::Affine> {
+    let (l, r) = v.split_at(v.len() / 2);
+    G::normalize_batch(
+        &cfg_iter!(l)
+            .zip(r)
+            .map(|(l, r)| *r * x + l)
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Fold `v` into `v_L + x * v_R`.
+fn fold_scalars<F: Field>(v: &[F], x: F) -> Vec<F> {
+    let (l, r) = v.split_at(v.len() / 2);
+    l.iter().zip(r).map(|(&l, &r)| l + r * x).collect()
+}
+
+/// Scale the `i`-th element of `v` by `s_i`.
+fn scale<G: CurveGroup>(v: &[G::Affine], s: &[G::ScalarField]) -> Vec<G::Affine> {
+    G::normalize_batch(&cfg_iter!(v).zip(s).map(|(v, s)| *v * s).collect::<Vec<_>>())
+}
+
+fn powers<F: Field>(x: F, n: usize) -> Vec<F> {
+    let mut powers = vec![F::one(); n];
+    for i in 1..n {
+        powers[i] = powers[i - 1] * x;
+    }
+    powers
+}
+
+/// Compute the coefficients of `∏_j (1 + c_j X^{2^(k - 1 - j)})`, where `k` is the length of `c`.
+fn product_coefficients<F: Field>(c: &[F]) -> Vec<F> {
+    let mut coefficients = vec![F::one()];
+    for &c in c.iter().rev() {
+        let high = coefficients.iter().map(|&v| v * c).collect::<Vec<_>>();
+        coefficients.extend(high);
+    }
+    coefficients
+}
+
+/// Evaluate `∏_j (1 + c_j X^{2^(k - 1 - j)})` at `z`, where `k` is the length of `c`.
+fn product_evaluation<F: Field>(c: &[F], z: F) -> F {
+    let mut result = F::one();
+    let mut power = z;
+    for &c in c.iter().rev() {
+        result *= F::one() + c * power;
+        power.square_in_place();
+    }
+    result
+}
+
+/// Compute the coefficients of `(f(X) - f(z)) / (X - z)`.
+fn kzg_quotient<F: Field>(f: &[F], z: F) -> Vec<F> {
+    let mut quotient = vec![F::zero(); f.len() - 1];
+    let mut acc = F::zero();
+    for i in (1..f.len()).rev() {
+        acc = f[i] + acc * z;
+        quotient[i - 1] = acc;
+    }
+    quotient
+}
+
+/// Get the coefficients `c_j` of `f_v` and `f_w`, where the folded keys are `f_v(a) * H` and
+/// `f_w(a) * G` (resp. `b`), and `f_w(X) = X^n * ∏_j (1 + c_j X^{2^(k - 1 - j)})`.
+fn key_coefficients<F: Field>(challenges: &[F], r_inv: F, n: usize) -> (Vec<F>, Vec<F>) {
+    let v = challenges.iter().map(|x| x.inverse().unwrap()).collect();
+    let w = challenges
+        .iter()
+        .enumerate()
+        .map(|(j, &x)| x * r_inv.pow([(n >> (j + 1)) as u64]))
+        .collect();
+    (v, w)
+}
+
+/// Aggregate Groth16 proofs over the same circuit, each with its instance public inputs, into a
+/// single proof.
+/// The number of proofs must be a power of two greater than 1 and at most the size of `ak`.
+pub fn aggregate_proofs<E: Pairing>(
+    ak: &AggregationKey<E>,
+    proofs: &[(&Proof<E>, &[E::ScalarField])],
+) -> Result<AggregateProof<E>, AggregationError> {
+    let aggregation_time = start_timer!(|| "Aggregation::Prover");
+
+    let n = proofs.len();
+    if n < 2 || !n.is_power_of_two() || n > ak.h_a.len() {
+        return Err(AggregationError::InvalidNumberOfProofs);
+    }
+    let has_commitment = proofs[0].0.cm.is_some();
+    if proofs
+        .iter()
+        .any(|(p, _)| p.cm.is_some() != has_commitment || p.pok.is_some() != has_commitment)
+    {
+        return Err(AggregationError::MalformedProof);
+    }
+
+    let mut a = proofs.iter().map(|(p, _)| p.a).collect::<Vec<_>>();
+    let b = proofs.iter().map(|(p, _)| p.b).collect::<Vec<_>>();
+    let mut c = vec![proofs.iter().map(|(p, _)| p.c).collect::<Vec<_>>()];
+    let cm = proofs.iter().filter_map(|(p, _)| p.cm).collect::<Vec<_>>();
+    if has_commitment {
+        c.push(proofs.iter().map(|(p, _)| p.pok.unwrap()).collect());
+    }
+
+    let mut v = (ak.h_a[..n].to_vec(), ak.h_b[..n].to_vec());
+    let w = (&ak.g_a[n..2 * n], &ak.g_b[n..2 * n]);
+
+    let commit_time = start_timer!(|| "Commit");
+    let com_ab = commit_pair::<E>((&v.0, &v.1), w, &a, &b);
+    let com_c = c
+        .iter()
+        .map(|c| commit_single::<E>((&v.0, &v.1), c))
+        .collect::<Vec<_>>();
+    end_timer!(commit_time);
+
+    let mut transcript = Transcript::new();
+    let public_inputs = proofs.iter().map(|(_, x)| *x).collect::<Vec<_>>();
+    transcript.append_statement(&public_inputs, &cm, &com_ab, &com_c);
+    let r = transcript.challenge::<E::ScalarField>();
+    let r_inv = r.inverse().ok_or(AggregationError::MalformedProof)?;
+
+    // Rescale `B` by `r^i` and the keys for `B` by `r^{-i}`, so that the commitment is unchanged, and
+    // the inner pairing product of `A` and the rescaled `B` is `∏ e(A_i, B_i)^{r^i}`.
+    let mut s = powers(r, n);
+    let mut b = scale::<E::G2>(&b, &s);
+    let r_inv_powers = powers(r_inv, n);
+    let mut w = (
+        scale::<E::G1>(w.0, &r_inv_powers),
+        scale::<E::G1>(w.1, &r_inv_powers),
+    );
+
+    let ip_ab = E::multi_pairing(&a, &b);
+    let agg_c = c
+        .iter()
+        .map(|c| E::G1::msm_unchecked(c, &s).into_affine())
+        .collect::<Vec<_>>();
+    transcript.append(&ip_ab);
+    transcript.append(&agg_c);
+
+    let gipa_time = start_timer!(|| "GIPA");
+    let mut rounds = vec![];
+    let mut challenges = vec![];
+    while a.len() > 1 {
+        let m = a.len() / 2;
+        let (a_l, a_r) = a.split_at(m);
+        let (b_l, b_r) = b.split_at(m);
+        let (v1_l, v1_r) = v.0.split_at(m);
+        let (v2_l, v2_r) = v.1.split_at(m);
+        let (w1_l, w1_r) = w.0.split_at(m);
+        let (w2_l, w2_r) = w.1.split_at(m);
+        let (s_l, s_r) = s.split_at(m);
+
+        let round = GipaRound {
+            ip_ab: (E::multi_pairing(a_r, b_l), E::multi_pairing(a_l, b_r)),
+            com_ab: (
+                commit_pair::<E>((v1_l, v2_l), (w1_r, w2_r), a_r, b_l),
+                commit_pair::<E>((v1_r, v2_r), (w1_l, w2_l), a_l, b_r),
+            ),
+            agg_c: c
+                .iter()
+                .map(|c| {
+                    let (c_l, c_r) = c.split_at(m);
+                    (
+                        E::G1::msm_unchecked(c_r, s_l).into_affine(),
+                        E::G1::msm_unchecked(c_l, s_r).into_affine(),
+                    )
+                })
+                .collect(),
+            com_c: c
+                .iter()
+                .map(|c| {
+                    let (c_l, c_r) = c.split_at(m);
+                    (
+                        commit_single::<E>((v1_l, v2_l), c_r),
+                        commit_single::<E>((v1_r, v2_r), c_l),
+                    )
+                })
+                .collect(),
+        };
+
+        transcript.append(&round);
+        let x = transcript.challenge::<E::ScalarField>();
+        let x_inv = x.inverse().ok_or(AggregationError::MalformedProof)?;
+
+        a = fold::<E::G1>(&a, x);
+        b = fold::<E::G2>(&b, x_inv);
+        c = c.iter().map(|c| fold::<E::G1>(c, x)).collect();
+        v = (fold::<E::G2>(&v.0, x_inv), fold::<E::G2>(&v.1, x_inv));
+        w = (fold::<E::G1>(&w.0, x), fold::<E::G1>(&w.1, x));
+        s = fold_scalars(&s, x_inv);
+
+        rounds.push(round);
+        challenges.push(x);
+    }
+    end_timer!(gipa_time);
+
+    let final_a = a[0];
+    let final_b = b[0];
+    let final_c = c.iter().map(|c| c[0]).collect::<Vec<_>>();
+    let final_v = (v.0[0], v.1[0]);
+    let final_w = (w.0[0], w.1[0]);
+    transcript.append(&((final_a, final_b), final_c.clone(), (final_v, final_w)));
+    let z = transcript.challenge::<E::ScalarField>();
+
+    let kzg_time = start_timer!(|| "KZG openings");
+    let (v_coefficients, w_coefficients) = key_coefficients(&challenges, r_inv, n);
+    let f_v = product_coefficients(&v_coefficients);
+    let f_w = [
+        vec![E::ScalarField::zero(); n],
+        product_coefficients(&w_coefficients),
+    ]
+    .concat();
+    let q_v = kzg_quotient(&f_v, z);
+    let q_w = kzg_quotient(&f_w, z);
+    let opening_v = (
+        E::G2::msm_unchecked(&ak.h_a[..q_v.len()], &q_v).into_affine(),
+        E::G2::msm_unchecked(&ak.h_b[..q_v.len()], &q_v).into_affine(),
+    );
+    let opening_w = (
+        E::G1::msm_unchecked(&ak.g_a[..q_w.len()], &q_w).into_affine(),
+        E::G1::msm_unchecked(&ak.g_b[..q_w.len()], &q_w).into_affine(),
+    );
+    end_timer!(kzg_time);
+
+    end_timer!(aggregation_time);
+
+    Ok(AggregateProof {
+        cm,
+        com_ab,
+        com_c,
+        ip_ab,
+        agg_c,
+        rounds,
+        final_a,
+        final_b,
+        final_c,
+        final_v,
+        final_w,
+        opening_v,
+        opening_w,
+    })
+}
+
+/// Verify an aggregate proof `proof` against the aggregation verifying key `avk` and the prepared
+/// verifying key `pvk` of the circuit, with respect to the instance public inputs of all proofs
+/// in the same order as they are aggregated.
+pub fn verify_aggregate_proof<E: Pairing>(
+    avk: &AggregationVerifyingKey<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    public_inputs: &[&[E::ScalarField]],
+    proof: &AggregateProof<E>,
+) -> Result<bool, AggregationError> {
+    let verifier_time = start_timer!(|| "Aggregation::Verifier");
+
+    let n = public_inputs.len();
+    if n < 2 || !n.is_power_of_two() {
+        return Err(AggregationError::InvalidNumberOfProofs);
+    }
+    let has_commitment = !proof.cm.is_empty();
+    let num_mipp = 1 + has_commitment as usize;
+    if (has_commitment && proof.cm.len() != n)
+        || proof.rounds.len() != n.trailing_zeros() as usize
+        || proof.com_c.len() != num_mipp
+        || proof.agg_c.len() != num_mipp
+        || proof.final_c.len() != num_mipp
+        || proof
+            .rounds
+            .iter()
+            .any(|round| round.agg_c.len() != num_mipp || round.com_c.len() != num_mipp)
+    {
+        return Err(AggregationError::MalformedProof);
+    }
+    let num_inputs = pvk
+        .vk
+        .gamma_abc_g1
+        .len()
+        .checked_sub(1 + has_commitment as usize)
+        .ok_or(AggregationError::MalformedVerifyingKey)?;
+    if public_inputs.iter().any(|x| x.len() != num_inputs) {
+        return Err(AggregationError::MalformedVerifyingKey);
+    }
+
+    let mut transcript = Transcript::new();
+    transcript.append_statement(public_inputs, &proof.cm, &proof.com_ab, &proof.com_c);
+    let r = transcript.challenge::<E::ScalarField>();
+    let r_inv = r.inverse().ok_or(AggregationError::MalformedProof)?;
+    transcript.append(&proof.ip_ab);
+    transcript.append(&proof.agg_c);
+
+    // Replay the GIPA argument to obtain the final values of the inner products and commitments.
+    let mut ip_ab = proof.ip_ab;
+    let mut com_ab = proof.com_ab;
+    let mut agg_c = proof
+        .agg_c
+        .iter()
+        .map(|c| c.into_group())
+        .collect::<Vec<_>>();
+    let mut com_c = proof.com_c.clone();
+    let mut challenges = vec![];
+    for round in &proof.rounds {
+        transcript.append(round);
+        let x = transcript.challenge::<E::ScalarField>();
+        let x_inv = x.inverse().ok_or(AggregationError::MalformedProof)?;
+
+        ip_ab += round.ip_ab.0 * x + round.ip_ab.1 * x_inv;
+        com_ab.0 += round.com_ab.0 .0 * x + round.com_ab.1 .0 * x_inv;
+        com_ab.1 += round.com_ab.0 .1 * x + round.com_ab.1 .1 * x_inv;
+        for (agg_c, (l, r)) in agg_c.iter_mut().zip(&round.agg_c) {
+            *agg_c += *l * x + *r * x_inv;
+        }
+        for (com_c, (l, r)) in com_c.iter_mut().zip(&round.com_c) {
+            com_c.0 += l.0 * x + r.0 * x_inv;
+            com_c.1 += l.1 * x + r.1 * x_inv;
+        }
+        challenges.push(x);
+    }
+
+    transcript.append(&(
+        (proof.final_a, proof.final_b),
+        proof.final_c.clone(),
+        (proof.final_v, proof.final_w),
+    ));
+    let z = transcript.challenge::<E::ScalarField>();
+
+    // Check the final values of the GIPA argument.
+    let (v_coefficients, w_coefficients) = key_coefficients(&challenges, r_inv, n);
+    let s = product_evaluation(&v_coefficients, r);
+    let (v1, v2) = proof.final_v;
+    let (w1, w2) = proof.final_w;
+    let a = proof.final_a;
+    let b = proof.final_b;
+    if ip_ab != E::pairing(a, b)
+        || com_ab.0 != E::multi_pairing([a, w1], [v1, b])
+        || com_ab.1 != E::multi_pairing([a, w2], [v2, b])
+    {
+        return Ok(false);
+    }
+    for ((agg_c, com_c), &c) in agg_c.iter().zip(&com_c).zip(&proof.final_c) {
+        if *agg_c != c * s || com_c.0 != E::pairing(c, v1) || com_c.1 != E::pairing(c, v2) {
+            return Ok(false);
+        }
+    }
+
+    // Check the KZG openings of the final commitment keys.
+    let f_v_z = product_evaluation(&v_coefficients, z);
+    let f_w_z = z.pow([n as u64]) * product_evaluation(&w_coefficients, z);
+    let g_z = avk.g * z;
+    let h_z = avk.h * z;
+    for (v, g_x, opening) in [
+        (v1, avk.g_a, proof.opening_v.0),
+        (v2, avk.g_b, proof.opening_v.1),
+    ] {
+        if !E::multi_pairing(
+            [avk.g.into_group(), g_z - g_x],
+            [v.into_group() - avk.h * f_v_z, opening.into_group()],
+        )
+        .is_zero()
+        {
+            return Ok(false);
+        }
+    }
+    for (w, h_x, opening) in [
+        (w1, avk.h_a, proof.opening_w.0),
+        (w2, avk.h_b, proof.opening_w.1),
+    ] {
+        if !E::multi_pairing(
+            [w.into_group() - avk.g * f_w_z, opening.into_group()],
+            [avk.h.into_group(), h_z - h_x],
+        )
+        .is_zero()
+        {
+            return Ok(false);
+        }
+    }
+
+    // Check the Groth16 equations with the random linear combination, i.e.,
+    // `∏ e(A_i, B_i)^{r^i} = e(alpha, beta)^{Σ r^i} e(Σ r^i X_i, gamma) e(Σ r^i C_i, delta)`,
+    // where `X_i` is the prepared inputs of the `i`-th proof.
+    let r_powers = powers(r, n);
+    let mut input_scalars = vec![E::ScalarField::zero(); pvk.vk.gamma_abc_g1.len()];
+    for (r_i, x) in r_powers.iter().zip(public_inputs) {
+        input_scalars[0] += r_i;
+        for (s, x) in input_scalars[1..].iter_mut().zip(x.iter()) {
+            *s += *r_i * x;
+        }
+    }
+    let mut prepared_inputs = E::G1::zero();
+    if has_commitment {
+        for (r_i, cm) in r_powers.iter().zip(&proof.cm) {
+            input_scalars[num_inputs + 1] += *r_i * commitment_challenge::<E>(cm);
+        }
+        let agg_cm = E::G1::msm_unchecked(&proof.cm, &r_powers);
+
+        // Check the proofs of knowledge of the commitments with the same linear combination.
+        if !E::multi_pairing(
+            [agg_cm, proof.agg_c[1].into_group()],
+            [pvk.vk.pedersen_g, pvk.vk.pedersen_g_inv_neg_sigma],
+        )
+        .is_zero()
+        {
+            return Ok(false);
+        }
+
+        prepared_inputs += agg_cm;
+    }
+    prepared_inputs += E::G1::msm_unchecked(&pvk.vk.gamma_abc_g1, &input_scalars);
+
+    let r_sum = r_powers.iter().sum::<E::ScalarField>();
+    let qap = E::multi_miller_loop(
+        [prepared_inputs.into_affine(), proof.agg_c[0]],
+        [pvk.gamma_g2_neg_pc.clone(), pvk.delta_g2_neg_pc.clone()],
+    );
+    let test = E::final_exponentiation(qap).ok_or(AggregationError::MalformedProof)?;
+
+    end_timer!(verifier_time);
+
+    Ok(proof.ip_ab + test == PairingOutput(pvk.alpha_g1_beta_g2) * r_sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        f64::F64Var,
+        groth16::{prepare_verifying_key, CircuitSpecificSetupSNARK, Groth16, SNARK},
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+        r1cs_std::prelude::{AllocVar, EqGadget},
+    };
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+    use rand::{RngCore, SeedableRng};
+
+    struct MulCircuit {
+        a: f64,
+        b: f64,
+    }
+
+    impl ConstraintSynthesizer<Fr> for MulCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let a = F64Var::new_input(cs.clone(), || Ok(self.a))?;
+            let b = F64Var::new_witness(cs.clone(), || Ok(self.b))?;
+            let c = F64Var::new_input(cs.clone(), || Ok(self.a * self.b))?;
+            (a * b).enforce_equal(&c)
+        }
+    }
+
+    #[test]
+    fn aggregate_and_verify() {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+        let (pk, vk) = Groth16::<Bls12_381>::setup(MulCircuit { a: 0., b: 0. }, rng).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let (ak, avk) = setup_aggregation::<Bls12_381>(8, rng);
+
+        let (proofs, mut inputs): (Vec<_>, Vec<_>) = (0..4)
+            .map(|i| {
+                let (a, b) = (1.5 + i as f64, 0.1 * i as f64);
+                let proof = Groth16::<Bls12_381>::prove(&pk, MulCircuit { a, b }, rng).unwrap();
+                let inputs = [F64Var::verifier_input(a), F64Var::verifier_input(a * b)].concat();
+                (proof, inputs)
+            })
+            .unzip();
+
+        let batch = proofs
+            .iter()
+            .zip(&inputs)
+            .map(|(p, x)| (p, &x[..]))
+            .collect::<Vec<_>>();
+        let proof = aggregate_proofs(&ak, &batch).unwrap();
+        let public_inputs = inputs.iter().map(|x| &x[..]).collect::<Vec<_>>();
+        assert!(verify_aggregate_proof(&avk, &pvk, &public_inputs, &proof).unwrap());
+
+        assert_eq!(
+            aggregate_proofs(&ak, &batch[..3]),
+            Err(AggregationError::InvalidNumberOfProofs)
+        );
+        assert_eq!(
+            verify_aggregate_proof(&avk, &pvk, &public_inputs[..2], &proof),
+            Err(AggregationError::MalformedProof)
+        );
+        let mut malformed = pvk.clone();
+        malformed.vk.gamma_abc_g1.clear();
+        assert_eq!(
+            verify_aggregate_proof(&avk, &malformed, &public_inputs, &proof),
+            Err(AggregationError::MalformedVerifyingKey)
+        );
+
+        inputs[2][3..].copy_from_slice(&F64Var::verifier_input(2.));
+        let public_inputs = inputs.iter().map(|x| &x[..]).collect::<Vec<_>>();
+        assert!(!verify_aggregate_proof(&avk, &pvk, &public_inputs, &proof).unwrap());
+
+        let batch = proofs
+            .iter()
+            .zip(&inputs)
+            .map(|(p, x)| (p, &x[..]))
+            .collect::<Vec<_>>();
+        let proof = aggregate_proofs(&ak, &batch).unwrap();
+        assert!(!verify_aggregate_proof(&avk, &pvk, &public_inputs, &proof).unwrap());
+    }
+}
@@ -0,0 +1,311 @@
+//! Verify Groth16 proofs with commitments in a circuit over the scalar field of the same curve, so
+//! that all arithmetic in G1 is emulated by `NonNativeAffineVar`.
+//!
+//! This is scaffolding for recursion: the pairing check is delegated to an implementation of
+//! `PairingVar`, and this crate does not provide one yet. Furthermore:
+//! - The challenge of the lookup argument is derived from the commitment `cm` by SHA-256 outside
+//!   the circuit, so the caller must bind the challenge passed to `verify_proof_gadget` to `cm`,
+//!   e.g., by making both of them public inputs of the outer circuit, and by recomputing the
+//!   challenge from `cm` in the outer verifier.
+//! - Points in G1 are only checked to be on the curve, which is sufficient for curves whose G1
+//!   has prime order, e.g., BN254, but not for curves with a cofactor, e.g., BLS12-381.
+
+use core::borrow::Borrow;
+
+use ark_ec::{
+    pairing::Pairing,
+    short_weierstrass::{Affine, SWCurveConfig},
+};
+use ark_ff::PrimeField;
+
+use super::{Proof, VerifyingKey};
+use crate::{
+    r1cs::{Namespace, Result as R1CSResult, SynthesisError},
+    r1cs_std::{
+        alloc::{AllocVar, AllocationMode},
+        fields::fp::FpVar,
+        groups::nonnative::NonNativeAffineVar,
+        R1CSVar, ToBitsGadget,
+    },
+};
+
+/// A gadget that checks products of pairings of `E` in a circuit over the scalar field of `E`,
+/// whose G1 is the short Weierstrass curve `P`.
+pub trait PairingVar<P: SWCurveConfig, E: Pairing<G1Affine = Affine<P>>>
+where
+    P::BaseField: PrimeField,
+{
+    /// The variable of points in G2.
+    type G2Var: AllocVar<E::G2Affine, E::ScalarField> + Clone;
+
+    /// Enforce that `prod_i e(g1_i, g2_i) = 1`.
+    fn enforce_product_of_pairings_is_one(
+        g1: &[NonNativeAffineVar<P, E::ScalarField>],
+        g2: &[Self::G2Var],
+    ) -> R1CSResult<()>;
+}
+
+/// A Groth16 proof with a commitment in a circuit.
+pub struct ProofVar<P: SWCurveConfig, E: Pairing<G1Affine = Affine<P>>, PV: PairingVar<P, E>>
+where
+    P::BaseField: PrimeField,
+{
+    /// The `A` element in G1.
+    pub a: NonNativeAffineVar<P, E::ScalarField>,
+    /// The `B` element in G2.
+    pub b: PV::G2Var,
+    /// The `C` element in G1.
+    pub c: NonNativeAffineVar<P, E::ScalarField>,
+    /// The commitment to the witnesses of the lookup argument.
+    pub cm: NonNativeAffineVar<P, E::ScalarField>,
+    /// The proof of knowledge of the opening of `cm`.
+    pub pok: NonNativeAffineVar<P, E::ScalarField>,
+}
+
+impl<P: SWCurveConfig, E: Pairing<G1Affine = Affine<P>>, PV: PairingVar<P, E>>
+    AllocVar<Proof<E>, E::ScalarField> for ProofVar<P, E, PV>
+where
+    P::BaseField: PrimeField,
+{
+    /// Allocate `proof` in `mode`.
+    /// Return `SynthesisError::Unsatisfiable` if `proof` has no commitment or no proof of
+    /// knowledge of it.
+    fn new_variable<T: Borrow<Proof<E>>>(
+        cs: impl Into<Namespace<E::ScalarField>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into().cs();
+        let proof = f().and_then(|proof| {
+            let proof = proof.borrow();
+            match (proof.cm, proof.pok) {
+                (Some(cm), Some(pok)) => Ok((proof.a, proof.b, proof.c, cm, pok)),
+                _ => Err(SynthesisError::Unsatisfiable),
+            }
+        });
+        if let Err(SynthesisError::Unsatisfiable) = proof {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(Self {
+            a: NonNativeAffineVar::new_variable(cs.clone(), || proof.map(|p| p.0), mode)?,
+            b: PV::G2Var::new_variable(cs.clone(), || proof.map(|p| p.1), mode)?,
+            c: NonNativeAffineVar::new_variable(cs.clone(), || proof.map(|p| p.2), mode)?,
+            cm: NonNativeAffineVar::new_variable(cs.clone(), || proof.map(|p| p.3), mode)?,
+            pok: NonNativeAffineVar::new_variable(cs, || proof.map(|p| p.4), mode)?,
+        })
+    }
+}
+
+/// Compute the prepared inputs `gamma_abc_0 + sum_i x_i * gamma_abc_{i + 1} + cm`, where `x` is
+/// `inputs` followed by `challenge`, which must be the challenge of the lookup argument derived
+/// from `cm`.
+/// Return `SynthesisError::MalformedVerifyingKey` if the number of inputs does not match `vk`.
+pub fn prepare_inputs_gadget<P: SWCurveConfig, E: Pairing<G1Affine = Affine<P>>>(
+    vk: &VerifyingKey<E>,
+    inputs: &[FpVar<E::ScalarField>],
+    cm: &NonNativeAffineVar<P, E::ScalarField>,
+    challenge: &FpVar<E::ScalarField>,
+) -> R1CSResult<NonNativeAffineVar<P, E::ScalarField>>
+where
+    P::BaseField: PrimeField,
+{
+    if vk.gamma_abc_g1.len() != inputs.len() + 2 {
+        return Err(SynthesisError::MalformedVerifyingKey);
+    }
+    let bases = vk.gamma_abc_g1[1..]
+        .iter()
+        .map(|&g| NonNativeAffineVar::constant(g))
+        .collect::<Result<Vec<_>, _>>()?;
+    let scalars = inputs
+        .iter()
+        .chain([challenge])
+        .map(|x| x.to_bits_le())
+        .collect::<Result<Vec<_>, _>>()?;
+    let initial = NonNativeAffineVar::constant(vk.gamma_abc_g1[0])?.add(cm)?;
+    NonNativeAffineVar::msm_le(&initial, &bases, &scalars)
+}
+
+/// Enforce that `proof` is a valid Groth16 proof with respect to `vk`, the public inputs `inputs`,
+/// and the challenge `challenge` of the lookup argument, which the caller must bind to the
+/// commitment in `proof` (see the module documentation).
+pub fn verify_proof_gadget<
+    P: SWCurveConfig,
+    E: Pairing<G1Affine = Affine<P>>,
+    PV: PairingVar<P, E>,
+>(
+    vk: &VerifyingKey<E>,
+    proof: &ProofVar<P, E, PV>,
+    inputs: &[FpVar<E::ScalarField>],
+    challenge: &FpVar<E::ScalarField>,
+) -> R1CSResult<()>
+where
+    P::BaseField: PrimeField,
+{
+    let cs = proof.a.cs();
+    let g2 = |p| PV::G2Var::new_constant(cs.clone(), p);
+
+    PV::enforce_product_of_pairings_is_one(
+        &[proof.cm.clone(), proof.pok.clone()],
+        &[g2(vk.pedersen_g)?, g2(vk.pedersen_g_inv_neg_sigma)?],
+    )?;
+
+    let prepared_inputs = prepare_inputs_gadget(vk, inputs, &proof.cm, challenge)?;
+    PV::enforce_product_of_pairings_is_one(
+        &[
+            proof.a.negate()?,
+            NonNativeAffineVar::constant(vk.alpha_g1)?,
+            prepared_inputs,
+            proof.c.clone(),
+        ],
+        &[
+            proof.b.clone(),
+            g2(vk.beta_g2)?,
+            g2(vk.gamma_g2)?,
+            g2(vk.delta_g2)?,
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        groth16::{prepare_verifying_key, verifier::commitment_challenge, Groth16},
+        r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisMode},
+        r1cs_std::prelude::{EqGadget, FieldVar},
+        traits::BitDecompose,
+    };
+    use ark_bn254::{g1::Config, Bn254, Fr, G1Affine, G2Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_std::{test_rng, One};
+
+    /// A stand-in for a pairing gadget, which checks the pairings natively and makes the circuit
+    /// unsatisfiable if they do not hold.
+    struct HintedPairing;
+
+    #[derive(Clone)]
+    struct G2Hint(Option<G2Affine>);
+
+    impl AllocVar<G2Affine, Fr> for G2Hint {
+        fn new_variable<T: Borrow<G2Affine>>(
+            _: impl Into<Namespace<Fr>>,
+            f: impl FnOnce() -> Result<T, SynthesisError>,
+            _: AllocationMode,
+        ) -> Result<Self, SynthesisError> {
+            Ok(Self(f().ok().map(|p| *p.borrow())))
+        }
+    }
+
+    impl PairingVar<Config, Bn254> for HintedPairing {
+        type G2Var = G2Hint;
+
+        fn enforce_product_of_pairings_is_one(
+            g1: &[NonNativeAffineVar<Config, Fr>],
+            g2: &[G2Hint],
+        ) -> R1CSResult<()> {
+            let cs = g1[0].cs();
+            if cs.is_in_setup_mode() {
+                return Ok(());
+            }
+            let g1 = g1
+                .iter()
+                .map(|p| p.value())
+                .collect::<Result<Vec<_>, _>>()?;
+            let g2 = g2
+                .iter()
+                .map(|p| p.0.ok_or(SynthesisError::AssignmentMissing));
+            let g2 = g2.collect::<Result<Vec<_>, _>>()?;
+            let holds = Bn254::multi_pairing(g1, g2).0.is_one();
+            FpVar::new_witness(cs, || Ok(Fr::from(!holds)))?.enforce_equal(&FpVar::zero())
+        }
+    }
+
+    /// A circuit that proves that its public input is less than `2^16`, which uses lookups.
+    struct RangeCircuit(Fr);
+
+    impl ConstraintSynthesizer<Fr> for RangeCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> R1CSResult<()> {
+            FpVar::new_input(cs, || Ok(self.0))?.enforce_bit_length(16)
+        }
+    }
+
+    type Var = ProofVar<Config, Bn254, HintedPairing>;
+
+    fn verify(
+        cs: ConstraintSystemRef<Fr>,
+        vk: &VerifyingKey<Bn254>,
+        proof: &Proof<Bn254>,
+        input: Fr,
+    ) -> R1CSResult<NonNativeAffineVar<Config, Fr>> {
+        let proof_var = Var::new_witness(cs.clone(), || Ok(proof))?;
+        let inputs = [FpVar::new_input(cs.clone(), || Ok(input))?];
+        let challenge = FpVar::new_input(cs.clone(), || {
+            Ok(commitment_challenge::<Bn254>(&proof.cm.unwrap()))
+        })?;
+        verify_proof_gadget(vk, &proof_var, &inputs, &challenge)?;
+        prepare_inputs_gadget(vk, &inputs, &proof_var.cm, &challenge)
+    }
+
+    #[test]
+    fn verify_proof() -> Result<(), SynthesisError> {
+        let rng = &mut test_rng();
+        let pk = Groth16::<Bn254>::generate_random_parameters_with_reduction(
+            RangeCircuit(Fr::from(0u8)),
+            rng,
+        )?;
+        let input = Fr::from(12345u16);
+        let proof =
+            Groth16::<Bn254>::create_random_proof_with_reduction(RangeCircuit(input), &pk, rng)?;
+        let pvk = prepare_verifying_key(&pk.vk);
+        assert!(Groth16::<Bn254>::verify_proof(&pvk, &proof, &[input])?);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let prepared_inputs = verify(cs.clone(), &pk.vk, &proof, input)?;
+        assert!(cs.is_satisfied()?);
+        let expected =
+            Groth16::<Bn254>::prepare_inputs(&pvk, &[input], proof.cm)? + proof.cm.unwrap();
+        assert_eq!(prepared_inputs.value()?, expected.into_affine());
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        verify(cs.clone(), &pk.vk, &proof, input + Fr::one())?;
+        assert!(!cs.is_satisfied()?);
+
+        let mut tampered = proof.clone();
+        tampered.c = (tampered.c + G1Affine::generator()).into_affine();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        verify(cs.clone(), &pk.vk, &tampered, input)?;
+        assert!(!cs.is_satisfied()?);
+
+        let mut uncommitted = proof;
+        uncommitted.cm = None;
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        assert!(matches!(
+            Var::new_witness(cs, || Ok(uncommitted)),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_value() -> Result<(), SynthesisError> {
+        let rng = &mut test_rng();
+        let pk = Groth16::<Bn254>::generate_random_parameters_with_reduction(
+            RangeCircuit(Fr::from(0u8)),
+            rng,
+        )?;
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        let proof = Var::new_witness(cs.clone(), || -> R1CSResult<Proof<Bn254>> {
+            Err(SynthesisError::AssignmentMissing)
+        })?;
+        let inputs = [FpVar::new_input(cs.clone(), || -> R1CSResult<Fr> {
+            Err(SynthesisError::AssignmentMissing)
+        })?];
+        let challenge = FpVar::new_input(cs.clone(), || -> R1CSResult<Fr> {
+            Err(SynthesisError::AssignmentMissing)
+        })?;
+        verify_proof_gadget(&pk.vk, &proof, &inputs, &challenge)?;
+        assert!(cs.num_constraints() > 0);
+        Ok(())
+    }
+}
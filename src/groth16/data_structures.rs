@@ -1,6 +1,9 @@
 use ark_ec::pairing::Pairing;
 use ark_serialize::*;
 use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::r1cs::ConstraintMatrices;
 
 /// A proof in the Groth16 SNARK.
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
@@ -64,6 +67,20 @@ impl<E: Pairing> Default for VerifyingKey<E> {
     }
 }
 
+impl<E: Pairing> VerifyingKey<E> {
+    /// Compute the fingerprint of the verifying key, which is the SHA-256 hash of its compressed
+    /// canonical serialization (with a domain separation prefix), so that two keys have the same
+    /// fingerprint if and only if they are equal.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut bytes = vec![];
+        self.serialize_compressed(&mut bytes).unwrap();
+        Sha256::new_with_prefix(b"r1cs_float/groth16/vk")
+            .chain_update(bytes)
+            .finalize()
+            .into()
+    }
+}
+
 /// Preprocessed verification key parameters that enable faster verification
 /// at the expense of larger size in memory.
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
@@ -121,3 +138,42 @@ pub struct ProvingKey<E: Pairing> {
     pub k_query: Vec<E::G1Affine>,
     pub k_query_sigma: Vec<E::G1Affine>,
 }
+
+/// A proving key with the constraint matrices of its circuit, which are synthesized once by
+/// `Groth16::prepare_proving_key`, so that proving many instances of the circuit only generates
+/// their witnesses instead of synthesizing the constraints every time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreparedProvingKey<E: Pairing> {
+    /// The unprepared proving key.
+    pub pk: ProvingKey<E>,
+    /// The constraint matrices of the circuit, whose columns are indexed as in the full assignment
+    /// of instance, commitment, witness, and committed variables.
+    pub(crate) matrices: ConstraintMatrices<E::ScalarField>,
+    /// The number of instance and commitment variables, including the constant 1.
+    pub(crate) num_inputs: usize,
+    /// The number of all variables, including the constant 1.
+    pub(crate) num_variables: usize,
+}
+
+impl<E: Pairing> PreparedProvingKey<E> {
+    /// The number of constraints of the circuit.
+    pub fn num_constraints(&self) -> usize {
+        self.matrices.a.len()
+    }
+
+    /// Check whether `assignment` satisfies the cached constraints.
+    pub(crate) fn is_satisfied(&self, assignment: &[E::ScalarField]) -> bool {
+        let eval = |row: &[(E::ScalarField, usize)]| -> E::ScalarField {
+            row.iter().map(|(coeff, i)| *coeff * assignment[*i]).sum()
+        };
+        (0..self.num_constraints()).all(|i| {
+            eval(&self.matrices.a[i]) * eval(&self.matrices.b[i]) == eval(&self.matrices.c[i])
+        })
+    }
+}
+
+impl<E: Pairing> From<PreparedProvingKey<E>> for ProvingKey<E> {
+    fn from(other: PreparedProvingKey<E>) -> Self {
+        other.pk
+    }
+}
@@ -1,5 +1,5 @@
 use super::logderivarg::generate_commitment;
-use super::{r1cs_to_qap::R1CSToQAP, Groth16, ProvingKey, Vec, VerifyingKey};
+use super::{r1cs_to_qap::R1CSToQAP, Groth16, PreparedProvingKey, ProvingKey, Vec, VerifyingKey};
 use crate::r1cs::{
     ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, Result as R1CSResult,
     SynthesisError, SynthesisMode,
@@ -10,6 +10,7 @@ use ark_poly::GeneralEvaluationDomain;
 use ark_std::rand::Rng;
 use ark_std::{cfg_into_iter, cfg_iter, cfg_iter_mut, end_timer, start_timer};
 
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
@@ -43,6 +44,42 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         )
     }
 
+    /// Cache the constraint matrices of `circuit` (e.g., the `shape` of a regression circuit)
+    /// with its proving key `pk`, so that `create_proof_with_prepared_key` only generates the
+    /// witnesses of each proof. The matrices only depend on the shape of the circuit, so the
+    /// values in `circuit` are ignored.
+    pub fn prepare_proving_key<C>(
+        pk: ProvingKey<E>,
+        circuit: C,
+    ) -> R1CSResult<PreparedProvingKey<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let prepare_time = start_timer!(|| "Groth16::PrepareProvingKey");
+        let cs = ConstraintSystem::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_mode(SynthesisMode::Setup);
+
+        circuit.generate_constraints(cs.clone())?;
+        generate_commitment(cs.clone(), |_| None)?;
+        cs.finalize();
+
+        let num_inputs = cs.num_instance_and_commitment_variables();
+        let num_variables = num_inputs + cs.num_witness_variables() + cs.num_committed_variables();
+        if num_inputs != pk.vk.gamma_abc_g1.len() || num_variables != pk.a_query.len() {
+            return Err(SynthesisError::MismatchedProvingKey);
+        }
+        let matrices = cs.to_matrices().unwrap();
+        end_timer!(prepare_time, || format!("({})", cs.num_constraints()));
+
+        Ok(PreparedProvingKey {
+            pk,
+            matrices,
+            num_inputs,
+            num_variables,
+        })
+    }
+
     /// Create parameters for a circuit, given some toxic waste, R1CS to QAP calculator and group generators
     pub fn generate_parameters_with_qap<C>(
         circuit: C,
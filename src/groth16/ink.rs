@@ -0,0 +1,458 @@
+use std::fmt::Write;
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_serialize::CanonicalSerialize;
+
+use super::{Proof, VerifyingKey};
+use crate::r1cs::{Result as R1CSResult, SynthesisError};
+
+/// A pairing-friendly curve whose arkworks crate can be used by the exported verifier, which is
+/// compiled to WebAssembly without the standard library.
+pub trait Curve: Pairing {
+    /// The name of the arkworks crate of the curve.
+    const CRATE: &'static str;
+    /// The name of the pairing engine in the crate.
+    const ENGINE: &'static str;
+}
+
+impl Curve for Bn254 {
+    const CRATE: &'static str = "ark_bn254";
+    const ENGINE: &'static str = "Bn254";
+}
+
+impl Curve for Bls12_381 {
+    const CRATE: &'static str = "ark_bls12_381";
+    const ENGINE: &'static str = "Bls12_381";
+}
+
+/// Get the number of public inputs expected by the verifier, excluding the challenge of the
+/// commitment, which is computed by the verifier itself.
+/// Return an error if `vk` has fewer elements in `gamma_abc_g1` than the constant term and the
+/// commitment require.
+fn num_inputs<E: Curve>(vk: &VerifyingKey<E>, has_commitment: bool) -> R1CSResult<usize> {
+    vk.gamma_abc_g1
+        .len()
+        .checked_sub(1 + has_commitment as usize)
+        .ok_or(SynthesisError::MalformedVerifyingKey)
+}
+
+/// Format `bytes` as the elements of a Rust array literal, 16 bytes per line.
+fn byte_array(source: &mut String, bytes: &[u8]) {
+    for line in bytes.chunks(16) {
+        source.push_str("   ");
+        for byte in line {
+            write!(source, " 0x{byte:02x},").unwrap();
+        }
+        source.push('\n');
+    }
+}
+
+const VERIFIER: &str = r#"
+#[derive(CanonicalDeserialize)]
+struct VerifyingKey {
+    alpha_g1: G1Affine,
+    beta_g2: G2Affine,
+    gamma_g2: G2Affine,
+    delta_g2: G2Affine,
+    gamma_abc_g1: Vec<G1Affine>,
+    pedersen_g: G2Affine,
+    pedersen_g_inv_neg_sigma: G2Affine,
+}
+
+#[derive(CanonicalDeserialize)]
+struct Proof {
+    a: G1Affine,
+    b: G2Affine,
+    c: G1Affine,
+    cm: Option<G1Affine>,
+    pok: Option<G1Affine>,
+}
+
+/// Compute the challenge from the commitment in the same way as the prover, i.e., hash the
+/// compressed encoding of the commitment to the scalar field with `expand_message_xmd` over
+/// SHA-256 and an empty domain separation tag.
+fn challenge(cm: &G1Affine) -> Fr {
+    let mut serialized = Vec::new();
+    cm.serialize_compressed(&mut serialized).unwrap();
+    let hasher = <DefaultFieldHasher<Sha256> as HashToField<Fr>>::new(&[]);
+    hasher.hash_to_field(&serialized, 1)[0]
+}
+
+/// Verify the compressed canonical serialization of a proof against the public inputs, each of
+/// which is the canonical (little-endian) encoding of a scalar.
+pub fn verify(proof: &[u8], input: &[[u8; 32]]) -> bool {
+    match input
+        .iter()
+        .map(|x| Fr::deserialize_compressed(&x[..]))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(input) => verify_scalars(proof, input),
+        Err(_) => false,
+    }
+}
+
+/// Verify the compressed canonical serialization of a proof against public inputs that are all
+/// `f64`s, each given by its bits and encoded as its sign, biased exponent, and mantissa.
+pub fn verify_f64(proof: &[u8], input: &[u64]) -> bool {
+    let input = input
+        .iter()
+        .flat_map(|&bits| {
+            [
+                Fr::from(bits >> 63),
+                Fr::from((bits >> 52) & 0x7ff),
+                Fr::from(bits & ((1 << 52) - 1)),
+            ]
+        })
+        .collect();
+    verify_scalars(proof, input)
+}
+
+fn verify_scalars(proof: &[u8], mut input: Vec<Fr>) -> bool {
+    if input.len() != NUM_INPUTS {
+        return false;
+    }
+    let vk = VerifyingKey::deserialize_uncompressed_unchecked(&VERIFYING_KEY[..]).unwrap();
+    let proof = match Proof::deserialize_compressed(proof) {
+        Ok(proof) => proof,
+        Err(_) => return false,
+    };
+
+    let mut x = vk.gamma_abc_g1[0].into_group();
+    match (proof.cm, proof.pok) {
+        (Some(cm), Some(pok)) if HAS_COMMITMENT => {
+            if !Curve::multi_pairing([cm, pok], [vk.pedersen_g, vk.pedersen_g_inv_neg_sigma])
+                .0
+                .is_one()
+            {
+                return false;
+            }
+            input.push(challenge(&cm));
+            x += cm;
+        }
+        (None, None) if !HAS_COMMITMENT => {}
+        _ => return false,
+    }
+    for (i, g) in input.iter().zip(&vk.gamma_abc_g1[1..]) {
+        x += g.mul_bigint(i.into_bigint());
+    }
+
+    Curve::multi_pairing(
+        [proof.a, -x.into_affine(), -proof.c, -vk.alpha_g1],
+        [proof.b, vk.gamma_g2, vk.delta_g2, vk.beta_g2],
+    )
+    .0
+    .is_one()
+}
+"#;
+
+/// Export a `no_std` Rust module that verifies proofs for the verifying key `vk`, which can be
+/// called from a Substrate pallet (or any other runtime) as well as from the contract exported by
+/// [`export_verifier`].
+/// `has_commitment` should be true if and only if the circuit uses lookups, i.e., the proofs
+/// contain the commitment `cm` and the proof of knowledge `pok`, in which case the verifier
+/// also checks the proof of knowledge and derives the challenge from `cm` on its own.
+///
+/// The module exposes `verify(proof: &[u8], input: &[[u8; 32]]) -> bool`, whose arguments can be
+/// produced by [`encode_proof`] and [`encode_inputs`], and `verify_f64(proof: &[u8], input: &[u64])
+/// -> bool` for circuits whose public inputs are all `f64`s, which can be produced by
+/// [`encode_f64_inputs`]. It depends on the arkworks crates of the curve (`ark-ec`, `ark-ff`,
+/// `ark-serialize` with the `derive` feature, and `ark-std`) and on `sha2`, as listed by
+/// [`export_manifest`].
+/// Return an error if `vk` is malformed, e.g., it lacks the input of the commitment although
+/// `has_commitment` is true.
+pub fn export_verifier_module<E: Curve>(
+    vk: &VerifyingKey<E>,
+    has_commitment: bool,
+) -> R1CSResult<String> {
+    let num_inputs = num_inputs(vk, has_commitment)?;
+    let mut vk_bytes = vec![];
+    vk.serialize_uncompressed(&mut vk_bytes).unwrap();
+
+    let mut source = String::new();
+    source.push_str("//! Groth16 verifier generated by `r1cs_float`.\n\n");
+    writeln!(
+        source,
+        "use {}::{{{} as Curve, Fr, G1Affine, G2Affine}};",
+        E::CRATE,
+        E::ENGINE
+    )
+    .unwrap();
+    source.push_str("use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};\n");
+    source.push_str("use ark_ff::{\n");
+    source.push_str("    field_hashers::{DefaultFieldHasher, HashToField},\n");
+    source.push_str("    One, PrimeField,\n");
+    source.push_str("};\n");
+    source.push_str("use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};\n");
+    source.push_str("use ark_std::vec::Vec;\n");
+    source.push_str("use sha2::Sha256;\n\n");
+
+    source
+        .push_str("/// The number of public inputs, excluding the challenge of the commitment.\n");
+    writeln!(source, "pub const NUM_INPUTS: usize = {num_inputs};\n").unwrap();
+    writeln!(source, "const HAS_COMMITMENT: bool = {has_commitment};\n").unwrap();
+    source.push_str("/// The uncompressed canonical serialization of the verifying key.\n");
+    writeln!(source, "const VERIFYING_KEY: [u8; {}] = [", vk_bytes.len()).unwrap();
+    byte_array(&mut source, &vk_bytes);
+    source.push_str("];\n");
+    source.push_str(VERIFIER);
+
+    Ok(source)
+}
+
+const CONTRACT: &str = r#"
+#[ink::contract]
+mod groth16_verifier {
+    use ink::prelude::vec::Vec;
+
+    /// Groth16 verifier generated by `r1cs_float`.
+    #[ink(storage)]
+    #[derive(Default)]
+    pub struct Groth16Verifier {}
+
+    impl Groth16Verifier {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {}
+        }
+
+        /// Verify the compressed proof against the public inputs, each of which is the canonical
+        /// (little-endian) encoding of a scalar.
+        #[ink(message)]
+        pub fn verify_proof(&self, proof: Vec<u8>, input: Vec<[u8; 32]>) -> bool {
+            crate::verifier::verify(&proof, &input)
+        }
+
+        /// Verify the compressed proof against public inputs that are all `f64`s, each given by
+        /// its bits.
+        #[ink(message)]
+        pub fn verify_proof_f64(&self, proof: Vec<u8>, input: Vec<u64>) -> bool {
+            crate::verifier::verify_f64(&proof, &input)
+        }
+    }
+}
+"#;
+
+/// Export an ink! smart contract named `Groth16Verifier` that verifies proofs for the verifying
+/// key `vk`, so that they can be verified on Polkadot-based chains with the contracts pallet.
+/// See [`export_verifier_module`] for the meaning of `has_commitment`.
+///
+/// The contract is a single `lib.rs` that embeds the module exported by
+/// [`export_verifier_module`] as `verifier`, and exposes the messages
+/// `verify_proof(proof: Vec<u8>, input: Vec<[u8; 32]>) -> bool` and
+/// `verify_proof_f64(proof: Vec<u8>, input: Vec<u64>) -> bool`, where `f64`s are passed by their
+/// bits since SCALE has no floating-point types.
+/// It can be built by `cargo contract build` with the manifest exported by [`export_manifest`].
+/// Return an error if `vk` is malformed, see [`export_verifier_module`].
+pub fn export_verifier<E: Curve>(vk: &VerifyingKey<E>, has_commitment: bool) -> R1CSResult<String> {
+    let mut source = String::new();
+    source.push_str("#![cfg_attr(not(feature = \"std\"), no_std, no_main)]\n\n");
+    source.push_str("mod verifier {\n");
+    for line in export_verifier_module(vk, has_commitment)?.lines() {
+        if line.is_empty() {
+            source.push('\n');
+        } else {
+            // Inner doc comments are not allowed inside an inline module.
+            writeln!(source, "    {}", line.replacen("//!", "//", 1)).unwrap();
+        }
+    }
+    source.push_str("}\n");
+    source.push_str(CONTRACT);
+
+    Ok(source)
+}
+
+/// Export the `Cargo.toml` of the contract exported by [`export_verifier`] with the package name
+/// `name`, which also lists the dependencies of the module exported by
+/// [`export_verifier_module`].
+pub fn export_manifest<E: Curve>(name: &str) -> String {
+    format!(
+        r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+ink = {{ version = "5.0", default-features = false }}
+{} = {{ version = "0.4", default-features = false, features = ["curve"] }}
+ark-ec = {{ version = "0.4", default-features = false }}
+ark-ff = {{ version = "0.4", default-features = false }}
+ark-serialize = {{ version = "0.4", default-features = false, features = ["derive"] }}
+ark-std = {{ version = "0.4", default-features = false }}
+sha2 = {{ version = "0.10", default-features = false }}
+
+[lib]
+path = "lib.rs"
+
+[features]
+default = ["std"]
+std = ["ink/std"]
+ink-as-dependency = []
+"#,
+        E::CRATE.replace('_', "-")
+    )
+}
+
+/// Encode the proof as its compressed canonical serialization.
+pub fn encode_proof<E: Curve>(proof: &Proof<E>) -> Vec<u8> {
+    let mut bytes = vec![];
+    proof.serialize_compressed(&mut bytes).unwrap();
+    bytes
+}
+
+/// Encode the public inputs as their canonical (little-endian) serializations, e.g., the
+/// concatenation of [`crate::float::FloatVar::verifier_input`] for all public `FloatVar`s in the
+/// order they are allocated.
+pub fn encode_inputs<E: Curve>(inputs: &[E::ScalarField]) -> Vec<[u8; 32]> {
+    inputs
+        .iter()
+        .map(|x| {
+            let mut word = [0u8; 32];
+            x.serialize_compressed(&mut word[..]).unwrap();
+            word
+        })
+        .collect()
+}
+
+/// Encode the public inputs of a circuit whose public inputs are all `F64Var`s allocated in the
+/// order of `values`, as the bits of the values.
+pub fn encode_f64_inputs(values: &[f64]) -> Vec<u64> {
+    values.iter().map(|v| v.to_bits()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+    use crate::{
+        f64::F64Var,
+        groth16::{prepare_verifying_key, Groth16},
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+        r1cs_std::prelude::{AllocVar, EqGadget},
+    };
+    use ark_bn254::Fr;
+    use ark_serialize::CanonicalDeserialize;
+    use ark_std::test_rng;
+
+    struct AddCircuit {
+        a: f64,
+        b: f64,
+    }
+
+    impl ConstraintSynthesizer<Fr> for AddCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let a = F64Var::new_witness(cs.clone(), || Ok(self.a))?;
+            let b = F64Var::new_input(cs.clone(), || Ok(self.b))?;
+            let c = F64Var::new_input(cs.clone(), || Ok(self.a + self.b))?;
+            (a + b).enforce_equal(&c)
+        }
+    }
+
+    /// Compute the public inputs of `f64`s from their bits as the exported verifier does.
+    fn contract_f64_input(bits: u64) -> [Fr; 3] {
+        [
+            Fr::from(bits >> 63),
+            Fr::from((bits >> 52) & 0x7ff),
+            Fr::from(bits & ((1 << 52) - 1)),
+        ]
+    }
+
+    #[test]
+    fn f64_input() {
+        for v in [
+            0.,
+            -0.,
+            1.5,
+            -0.1,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::MIN_POSITIVE / 3.,
+            -f64::from_bits(1),
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+        ] {
+            assert_eq!(
+                contract_f64_input(encode_f64_inputs(&[v])[0]),
+                F64Var::verifier_input(v),
+                "{v}"
+            );
+        }
+    }
+
+    #[test]
+    fn export() -> Result<(), Box<dyn Error>> {
+        let rng = &mut test_rng();
+
+        let pk = Groth16::<Bn254>::generate_random_parameters_with_reduction(
+            AddCircuit { a: 0., b: 0. },
+            rng,
+        )?;
+        let proof = Groth16::<Bn254>::create_random_proof_with_reduction(
+            AddCircuit { a: 1.5, b: -0.1 },
+            &pk,
+            rng,
+        )?;
+        assert!(proof.cm.is_some());
+
+        let values = [-0.1, 1.5 + -0.1];
+        let inputs = F64Var::verifier_inputs(&values);
+        assert!(Groth16::<Bn254>::verify_proof(
+            &prepare_verifying_key(&pk.vk),
+            &proof,
+            &inputs
+        )?);
+
+        let module = export_verifier_module(&pk.vk, true)?;
+        assert!(module.contains("use ark_bn254::{Bn254 as Curve, Fr, G1Affine, G2Affine};"));
+        assert!(module.contains("pub const NUM_INPUTS: usize = 6;"));
+        assert!(module.contains("const HAS_COMMITMENT: bool = true;"));
+
+        // The embedded verifying key is the one that was exported.
+        let mut vk_bytes = vec![];
+        pk.vk.serialize_uncompressed(&mut vk_bytes)?;
+        let mut embedded = String::new();
+        byte_array(&mut embedded, &vk_bytes);
+        assert!(module.contains(&format!("[u8; {}] = [\n{embedded}];", vk_bytes.len())));
+
+        let contract = export_verifier(&pk.vk, true)?;
+        assert!(contract.contains("#[ink::contract]"));
+        assert!(contract.contains("    pub const NUM_INPUTS: usize = 6;"));
+        assert!(!contract.contains("//!"));
+
+        let mut malformed = pk.vk.clone();
+        malformed.gamma_abc_g1.truncate(1);
+        assert!(export_verifier_module(&malformed, false).is_ok());
+        assert!(matches!(
+            export_verifier(&malformed, true),
+            Err(SynthesisError::MalformedVerifyingKey)
+        ));
+
+        let manifest = export_manifest::<Bls12_381>("verifier");
+        assert!(manifest.contains("ark-bls12-381 = "));
+
+        // The encoded arguments are decoded by the exported verifier into the same values.
+        assert_eq!(
+            Proof::<Bn254>::deserialize_compressed(&encode_proof(&proof)[..])?,
+            proof
+        );
+        assert_eq!(
+            encode_inputs::<Bn254>(&inputs)
+                .iter()
+                .map(|x| Fr::deserialize_compressed(&x[..]))
+                .collect::<Result<Vec<_>, _>>()?,
+            inputs
+        );
+        assert_eq!(
+            encode_f64_inputs(&values)
+                .into_iter()
+                .flat_map(contract_f64_input)
+                .collect::<Vec<_>>(),
+            inputs
+        );
+
+        Ok(())
+    }
+}
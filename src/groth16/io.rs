@@ -0,0 +1,281 @@
+use std::{
+    fmt::{Display, Formatter},
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use ark_ec::pairing::Pairing;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Validate,
+};
+
+use super::{
+    aggregation::{AggregateProof, AggregationKey, AggregationVerifyingKey},
+    mpc::Ceremony,
+    Proof, ProvingKey, VerifyingKey,
+};
+
+/// The magic bytes at the beginning of every file written by this module.
+pub const MAGIC: [u8; 4] = *b"G16F";
+
+/// The version of the file format.
+/// Files with a different version are rejected when loading.
+pub const VERSION: u8 = 1;
+
+/// The bit set in the `KIND` byte of the header if the artifact is serialized uncompressed.
+pub const UNCOMPRESSED: u8 = 0x80;
+
+/// An artifact of the Groth16 zkSNARK that can be written to and read from files.
+///
+/// Each file starts with a header `MAGIC || VERSION || KIND`, followed by the canonical
+/// serialization of the artifact, so that e.g. a verifying key cannot be mistakenly loaded as a proof.
+/// The serialization is compressed by default, or uncompressed if `KIND` has the bit
+/// `UNCOMPRESSED` set, which is about twice as large but faster to deserialize, since the
+/// coordinates of points do not need to be recovered by square roots.
+pub trait Artifact: CanonicalSerialize + CanonicalDeserialize {
+    /// The tag identifying the kind of the artifact in the header.
+    const KIND: u8;
+}
+
+impl<E: Pairing> Artifact for ProvingKey<E> {
+    const KIND: u8 = 0;
+}
+
+impl<E: Pairing> Artifact for VerifyingKey<E> {
+    const KIND: u8 = 1;
+}
+
+impl<E: Pairing> Artifact for Proof<E> {
+    const KIND: u8 = 2;
+}
+
+impl<E: Pairing> Artifact for AggregationKey<E> {
+    const KIND: u8 = 3;
+}
+
+impl<E: Pairing> Artifact for AggregationVerifyingKey<E> {
+    const KIND: u8 = 4;
+}
+
+impl<E: Pairing> Artifact for AggregateProof<E> {
+    const KIND: u8 = 5;
+}
+
+impl<E: Pairing> Artifact for Ceremony<E> {
+    const KIND: u8 = 6;
+}
+
+/// Write the header and the compressed artifact `value` to `writer`.
+pub fn write<T: Artifact, W: Write>(value: &T, writer: W) -> Result<(), SerializationError> {
+    write_with(value, writer, Compress::Yes)
+}
+
+/// Write the header and the artifact `value` to `writer`, serialized in the mode `compress`.
+pub fn write_with<T: Artifact, W: Write>(
+    value: &T,
+    mut writer: W,
+    compress: Compress,
+) -> Result<(), SerializationError> {
+    let kind = match compress {
+        Compress::Yes => T::KIND,
+        Compress::No => T::KIND | UNCOMPRESSED,
+    };
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&[VERSION, kind])?;
+    value.serialize_with_mode(&mut writer, compress)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read an artifact from `reader`, checking that the header matches the expected kind and version.
+/// The artifact may be serialized in either mode, which is determined by the header.
+pub fn read<T: Artifact, R: Read>(reader: R) -> Result<T, SerializationError> {
+    read_with(reader, Validate::Yes)
+}
+
+/// Read an artifact from `reader` as `read`, but only check that the points are on the curve and
+/// in the prime-order subgroup if `validate` is `Validate::Yes`.
+/// Skipping the checks makes deserialization much faster, but is only safe for artifacts from a
+/// trusted source, e.g., a proving key written by the same server.
+pub fn read_with<T: Artifact, R: Read>(
+    mut reader: R,
+    validate: Validate,
+) -> Result<T, SerializationError> {
+    let mut header = [0u8; 6];
+    reader.read_exact(&mut header)?;
+    if header[..4] != MAGIC || header[4] != VERSION || header[5] & !UNCOMPRESSED != T::KIND {
+        return Err(SerializationError::InvalidData);
+    }
+    let compress = match header[5] & UNCOMPRESSED {
+        0 => Compress::Yes,
+        _ => Compress::No,
+    };
+    T::deserialize_with_mode(reader, compress, validate)
+}
+
+/// Save the compressed artifact `value` to the file at `path`, overwriting the file if it exists.
+pub fn save<T: Artifact>(path: impl AsRef<Path>, value: &T) -> Result<(), SerializationError> {
+    save_with(path, value, Compress::Yes)
+}
+
+/// Save the artifact `value` serialized in the mode `compress` to the file at `path`, overwriting
+/// the file if it exists.
+pub fn save_with<T: Artifact>(
+    path: impl AsRef<Path>,
+    value: &T,
+    compress: Compress,
+) -> Result<(), SerializationError> {
+    write_with(value, BufWriter::new(File::create(path)?), compress)
+}
+
+/// Load an artifact from the file at `path`, which may be serialized in either mode.
+pub fn load<T: Artifact>(path: impl AsRef<Path>) -> Result<T, SerializationError> {
+    read(BufReader::new(File::open(path)?))
+}
+
+/// The size of an artifact serialized in one mode, and the time to write and read it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SerializationReport {
+    /// Whether the artifact is compressed.
+    pub compressed: bool,
+    /// The number of bytes written by `write_with`, including the header.
+    pub size: usize,
+    /// The time taken by `write_with` to an in-memory buffer.
+    pub write_time: Duration,
+    /// The time taken by `read` from an in-memory buffer, including the validation of points.
+    pub read_time: Duration,
+}
+
+impl Display for SerializationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} bytes, written in {:?}, read in {:?}",
+            if self.compressed {
+                "compressed"
+            } else {
+                "uncompressed"
+            },
+            self.size,
+            self.write_time,
+            self.read_time
+        )
+    }
+}
+
+/// Serialize the artifact `value` in both modes (compressed first), and report the size and the
+/// time to write and read it in each mode, e.g., to choose between smaller files for calldata and
+/// faster loading for servers.
+pub fn report<T: Artifact>(value: &T) -> Result<[SerializationReport; 2], SerializationError> {
+    let measure = |compress| -> Result<_, SerializationError> {
+        let mut bytes = vec![];
+        let start = Instant::now();
+        write_with(value, &mut bytes, compress)?;
+        let write_time = start.elapsed();
+        let start = Instant::now();
+        read::<T, _>(&bytes[..])?;
+        let read_time = start.elapsed();
+        Ok(SerializationReport {
+            compressed: compress == Compress::Yes,
+            size: bytes.len(),
+            write_time,
+            read_time,
+        })
+    };
+    Ok([measure(Compress::Yes)?, measure(Compress::No)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, fs};
+
+    use super::*;
+    use crate::{
+        f64::F64Var,
+        groth16::{prepare_verifying_key, Groth16},
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+        r1cs_std::prelude::{AllocVar, EqGadget},
+    };
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+
+    struct AddCircuit {
+        a: f64,
+        b: f64,
+    }
+
+    impl ConstraintSynthesizer<Fr> for AddCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let a = F64Var::new_witness(cs.clone(), || Ok(self.a))?;
+            let b = F64Var::new_witness(cs.clone(), || Ok(self.b))?;
+            let c = F64Var::new_input(cs.clone(), || Ok(self.a + self.b))?;
+            (a + b).enforce_equal(&c)
+        }
+    }
+
+    #[test]
+    fn save_and_load() -> Result<(), Box<dyn Error>> {
+        let rng = &mut test_rng();
+        let dir = std::env::temp_dir().join(format!("r1cs_float_io_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+
+        let (pk, vk) = Groth16::<Bls12_381>::generate_random_parameters_with_reduction(
+            AddCircuit { a: 0., b: 0. },
+            rng,
+        )
+        .map(|pk| (pk.clone(), pk.vk))?;
+        let proof = Groth16::<Bls12_381>::create_random_proof_with_reduction(
+            AddCircuit { a: 0.1, b: 0.2 },
+            &pk,
+            rng,
+        )?;
+
+        save(dir.join("pk"), &pk)?;
+        save(dir.join("vk"), &vk)?;
+        save(dir.join("proof"), &proof)?;
+
+        let loaded_pk: ProvingKey<Bls12_381> = load(dir.join("pk"))?;
+        let loaded_vk: VerifyingKey<Bls12_381> = load(dir.join("vk"))?;
+        let loaded_proof: Proof<Bls12_381> = load(dir.join("proof"))?;
+        assert_eq!(loaded_pk, pk);
+        assert_eq!(loaded_vk, vk);
+        assert_eq!(loaded_proof, proof);
+
+        assert!(Groth16::<Bls12_381>::verify_proof(
+            &prepare_verifying_key(&loaded_vk),
+            &loaded_proof,
+            &F64Var::verifier_input(0.1 + 0.2),
+        )?);
+
+        // Loading an artifact of the wrong kind must fail.
+        assert!(load::<Proof<Bls12_381>>(dir.join("vk")).is_err());
+
+        // Uncompressed artifacts are loaded by the same functions.
+        save_with(dir.join("pk_uncompressed"), &pk, Compress::No)?;
+        assert_eq!(
+            load::<ProvingKey<Bls12_381>>(dir.join("pk_uncompressed"))?,
+            pk
+        );
+        let bytes = fs::read(dir.join("pk_uncompressed"))?;
+        assert_eq!(
+            read_with::<ProvingKey<Bls12_381>, _>(&bytes[..], Validate::No)?,
+            pk
+        );
+        assert!(load::<VerifyingKey<Bls12_381>>(dir.join("pk_uncompressed")).is_err());
+
+        let [compressed, uncompressed] = report(&proof)?;
+        assert_eq!(compressed.size, 6 + proof.compressed_size());
+        assert_eq!(uncompressed.size, 6 + proof.uncompressed_size());
+        assert!(compressed.size < uncompressed.size);
+        assert_eq!(
+            fs::metadata(dir.join("proof"))?.len(),
+            compressed.size as u64
+        );
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
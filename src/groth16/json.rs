@@ -0,0 +1,401 @@
+//! JSON encoding of the keys, proofs, and public inputs, for verifiers written in other
+//! languages and for web frontends.
+//!
+//! The schema is stable and mirrors the fields of the Rust types:
+//!
+//! * A scalar or base field element is a string of its canonical value in decimal, e.g., `"42"`.
+//! * An element of an extension field (e.g., the coordinates of points in `G2`) is an array of
+//!   its components over the base prime field, e.g., `["1", "2"]` for `1 + 2u` in `Fq2`.
+//! * A point is the array `[x, y]` of its affine coordinates, where the point at infinity is
+//!   encoded as `x = y = 0`, e.g., `["1", "2"]` for the generator of `G1` of BN254.
+//! * A proof is `{"a": G1, "b": G2, "c": G1, "cm": G1 | null, "pok": G1 | null}`.
+//! * A verifying key is `{"alpha_g1": G1, "beta_g2": G2, "gamma_g2": G2, "delta_g2": G2,
+//!   "gamma_abc_g1": [G1], "pedersen_g": G2, "pedersen_g_inv_neg_sigma": G2}`.
+//! * A proving key is `{"vk": VerifyingKey, "beta_g1": G1, "delta_g1": G1, "a_query": [G1],
+//!   "b_g1_query": [G1], "b_g2_query": [G2], "h_query": [G1], "l_query": [G1],
+//!   "k_query": [G1], "k_query_sigma": [G1]}`.
+//! * The public inputs are an array of scalar field elements, in the order expected by
+//!   `Groth16::verify_proof`.
+//!
+//! Decoding rejects non-canonical field elements, points that are not in the prime-order
+//! subgroup, and unknown fields.
+
+use std::str::FromStr;
+
+use ark_ec::{
+    pairing::Pairing,
+    short_weierstrass::{Affine, SWCurveConfig},
+    AffineRepr,
+};
+use ark_ff::{Field, PrimeField, Zero};
+use num::BigUint;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Proof, ProvingKey, VerifyingKey};
+use crate::inputs::PublicInputs;
+
+/// A point that can be encoded in JSON by its affine coordinates.
+pub trait JsonPoint: AffineRepr {
+    /// Construct the point from its affine coordinates, or return `None` if `(x, y)` is not a
+    /// point in the prime-order subgroup.
+    fn from_xy(x: Self::BaseField, y: Self::BaseField) -> Option<Self>;
+}
+
+impl<P: SWCurveConfig> JsonPoint for Affine<P> {
+    fn from_xy(x: P::BaseField, y: P::BaseField) -> Option<Self> {
+        let point = Self::new_unchecked(x, y);
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+    }
+}
+
+/// The JSON form of a field element.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum JsonField {
+    Prime(String),
+    Extension(Vec<String>),
+}
+
+/// The JSON form of a point.
+type JsonAffine = [JsonField; 2];
+
+fn encode_prime<F: PrimeField>(value: &F) -> String {
+    Into::<BigUint>::into(*value).to_string()
+}
+
+fn decode_prime<F: PrimeField>(value: &str) -> Option<F> {
+    let n = BigUint::from_str(value).ok()?;
+    (n < F::MODULUS.into() && n.to_string() == value).then(|| F::from(n))
+}
+
+fn encode_field<F: Field>(value: &F) -> JsonField {
+    let mut elems = value
+        .to_base_prime_field_elements()
+        .map(|v| encode_prime(&v))
+        .collect::<Vec<_>>();
+    if F::extension_degree() == 1 {
+        JsonField::Prime(elems.remove(0))
+    } else {
+        JsonField::Extension(elems)
+    }
+}
+
+fn decode_field<F: Field>(value: &JsonField) -> Option<F> {
+    let elems = match value {
+        JsonField::Prime(v) if F::extension_degree() == 1 => vec![decode_prime(v)?],
+        JsonField::Extension(v) if F::extension_degree() > 1 => v
+            .iter()
+            .map(|v| decode_prime(v))
+            .collect::<Option<Vec<_>>>()?,
+        _ => return None,
+    };
+    F::from_base_prime_field_elems(&elems)
+}
+
+fn encode_point<G: JsonPoint>(point: &G) -> JsonAffine {
+    let zero = G::BaseField::zero();
+    let (x, y) = point.xy().unwrap_or((&zero, &zero));
+    [encode_field(x), encode_field(y)]
+}
+
+fn decode_point<G: JsonPoint, E: serde::de::Error>(point: &JsonAffine) -> Result<G, E> {
+    let [x, y] = point;
+    match (
+        decode_field::<G::BaseField>(x),
+        decode_field::<G::BaseField>(y),
+    ) {
+        (Some(x), Some(y)) if x.is_zero() && y.is_zero() => Ok(G::zero()),
+        (Some(x), Some(y)) => G::from_xy(x, y).ok_or_else(|| E::custom("invalid point")),
+        _ => Err(E::custom("invalid coordinate")),
+    }
+}
+
+fn encode_points<G: JsonPoint>(points: &[G]) -> Vec<JsonAffine> {
+    points.iter().map(encode_point).collect()
+}
+
+fn decode_points<G: JsonPoint, E: serde::de::Error>(points: &[JsonAffine]) -> Result<Vec<G>, E> {
+    points.iter().map(decode_point).collect()
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonProof {
+    a: JsonAffine,
+    b: JsonAffine,
+    c: JsonAffine,
+    cm: Option<JsonAffine>,
+    pok: Option<JsonAffine>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonVerifyingKey {
+    alpha_g1: JsonAffine,
+    beta_g2: JsonAffine,
+    gamma_g2: JsonAffine,
+    delta_g2: JsonAffine,
+    gamma_abc_g1: Vec<JsonAffine>,
+    pedersen_g: JsonAffine,
+    pedersen_g_inv_neg_sigma: JsonAffine,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonProvingKey {
+    vk: JsonVerifyingKey,
+    beta_g1: JsonAffine,
+    delta_g1: JsonAffine,
+    a_query: Vec<JsonAffine>,
+    b_g1_query: Vec<JsonAffine>,
+    b_g2_query: Vec<JsonAffine>,
+    h_query: Vec<JsonAffine>,
+    l_query: Vec<JsonAffine>,
+    k_query: Vec<JsonAffine>,
+    k_query_sigma: Vec<JsonAffine>,
+}
+
+impl JsonVerifyingKey {
+    fn encode<E: Pairing>(vk: &VerifyingKey<E>) -> Self
+    where
+        E::G1Affine: JsonPoint,
+        E::G2Affine: JsonPoint,
+    {
+        Self {
+            alpha_g1: encode_point(&vk.alpha_g1),
+            beta_g2: encode_point(&vk.beta_g2),
+            gamma_g2: encode_point(&vk.gamma_g2),
+            delta_g2: encode_point(&vk.delta_g2),
+            gamma_abc_g1: encode_points(&vk.gamma_abc_g1),
+            pedersen_g: encode_point(&vk.pedersen_g),
+            pedersen_g_inv_neg_sigma: encode_point(&vk.pedersen_g_inv_neg_sigma),
+        }
+    }
+
+    fn decode<E: Pairing, Err: serde::de::Error>(&self) -> Result<VerifyingKey<E>, Err>
+    where
+        E::G1Affine: JsonPoint,
+        E::G2Affine: JsonPoint,
+    {
+        Ok(VerifyingKey {
+            alpha_g1: decode_point(&self.alpha_g1)?,
+            beta_g2: decode_point(&self.beta_g2)?,
+            gamma_g2: decode_point(&self.gamma_g2)?,
+            delta_g2: decode_point(&self.delta_g2)?,
+            gamma_abc_g1: decode_points(&self.gamma_abc_g1)?,
+            pedersen_g: decode_point(&self.pedersen_g)?,
+            pedersen_g_inv_neg_sigma: decode_point(&self.pedersen_g_inv_neg_sigma)?,
+        })
+    }
+}
+
+impl<E: Pairing> Serialize for Proof<E>
+where
+    E::G1Affine: JsonPoint,
+    E::G2Affine: JsonPoint,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        JsonProof {
+            a: encode_point(&self.a),
+            b: encode_point(&self.b),
+            c: encode_point(&self.c),
+            cm: self.cm.as_ref().map(encode_point),
+            pok: self.pok.as_ref().map(encode_point),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, E: Pairing> Deserialize<'de> for Proof<E>
+where
+    E::G1Affine: JsonPoint,
+    E::G2Affine: JsonPoint,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let proof = JsonProof::deserialize(deserializer)?;
+        Ok(Self {
+            a: decode_point(&proof.a)?,
+            b: decode_point(&proof.b)?,
+            c: decode_point(&proof.c)?,
+            cm: proof.cm.as_ref().map(decode_point).transpose()?,
+            pok: proof.pok.as_ref().map(decode_point).transpose()?,
+        })
+    }
+}
+
+impl<E: Pairing> Serialize for VerifyingKey<E>
+where
+    E::G1Affine: JsonPoint,
+    E::G2Affine: JsonPoint,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        JsonVerifyingKey::encode(self).serialize(serializer)
+    }
+}
+
+impl<'de, E: Pairing> Deserialize<'de> for VerifyingKey<E>
+where
+    E::G1Affine: JsonPoint,
+    E::G2Affine: JsonPoint,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        JsonVerifyingKey::deserialize(deserializer)?.decode()
+    }
+}
+
+impl<E: Pairing> Serialize for ProvingKey<E>
+where
+    E::G1Affine: JsonPoint,
+    E::G2Affine: JsonPoint,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        JsonProvingKey {
+            vk: JsonVerifyingKey::encode(&self.vk),
+            beta_g1: encode_point(&self.beta_g1),
+            delta_g1: encode_point(&self.delta_g1),
+            a_query: encode_points(&self.a_query),
+            b_g1_query: encode_points(&self.b_g1_query),
+            b_g2_query: encode_points(&self.b_g2_query),
+            h_query: encode_points(&self.h_query),
+            l_query: encode_points(&self.l_query),
+            k_query: encode_points(&self.k_query),
+            k_query_sigma: encode_points(&self.k_query_sigma),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, E: Pairing> Deserialize<'de> for ProvingKey<E>
+where
+    E::G1Affine: JsonPoint,
+    E::G2Affine: JsonPoint,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pk = JsonProvingKey::deserialize(deserializer)?;
+        Ok(Self {
+            vk: pk.vk.decode()?,
+            beta_g1: decode_point(&pk.beta_g1)?,
+            delta_g1: decode_point(&pk.delta_g1)?,
+            a_query: decode_points(&pk.a_query)?,
+            b_g1_query: decode_points(&pk.b_g1_query)?,
+            b_g2_query: decode_points(&pk.b_g2_query)?,
+            h_query: decode_points(&pk.h_query)?,
+            l_query: decode_points(&pk.l_query)?,
+            k_query: decode_points(&pk.k_query)?,
+            k_query_sigma: decode_points(&pk.k_query_sigma)?,
+        })
+    }
+}
+
+/// Encode the public inputs `inputs` as a JSON array of decimal strings.
+pub fn inputs_to_json<F: PrimeField>(inputs: &[F]) -> String {
+    serde_json::to_string(&inputs.iter().map(encode_prime).collect::<Vec<_>>()).unwrap()
+}
+
+/// Encode the public inputs of the `binary64` numbers `values` allocated by `F64Var::new_input`
+/// (in this order) as a JSON array of decimal strings.
+pub fn f64_inputs_to_json<F: PrimeField>(values: &[f64]) -> String {
+    inputs_to_json(
+        &PublicInputs::<F>::new()
+            .add_f64s(values.iter().copied())
+            .build(),
+    )
+}
+
+/// Decode the public inputs from a JSON array of decimal strings.
+pub fn inputs_from_json<F: PrimeField>(json: &str) -> Result<Vec<F>, serde_json::Error> {
+    serde_json::from_str::<Vec<String>>(json)?
+        .iter()
+        .map(|v| decode_prime(v).ok_or_else(|| serde_json::Error::custom("invalid field element")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use ark_bn254::{Bn254, Fr};
+    use ark_std::test_rng;
+    use rand::{RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{
+        f64::F64Var,
+        groth16::{CircuitSpecificSetupSNARK, Groth16, SNARK},
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+        r1cs_std::prelude::{AllocVar, EqGadget},
+    };
+
+    struct AddCircuit;
+
+    impl ConstraintSynthesizer<Fr> for AddCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let a = F64Var::new_witness(cs.clone(), || Ok(0.1))?;
+            let b = F64Var::new_input(cs.clone(), || Ok(0.2))?;
+            (a + b).enforce_equal(&F64Var::new_input(cs, || Ok(0.1 + 0.2))?)
+        }
+    }
+
+    #[test]
+    fn schema() -> Result<(), Box<dyn Error>> {
+        let g1 = <Bn254 as Pairing>::G1Affine::generator();
+        let g2 = <Bn254 as Pairing>::G2Affine::generator();
+        let proof = Proof::<Bn254> {
+            a: g1,
+            b: g2,
+            c: <Bn254 as Pairing>::G1Affine::zero(),
+            cm: None,
+            pok: Some(g1),
+        };
+
+        let json = serde_json::to_string(&proof)?;
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"a":["1","2"],"#,
+                r#""b":[["10857046999023057135944570762232829481370756359578518086990519993285655852781","#,
+                r#""11559732032986387107991004021392285783925812861821192530917403151452391805634"],"#,
+                r#"["8495653923123431417604973247489272438418190587263600148770280649306958101930","#,
+                r#""4082367875863433681332203403145435568316851327593401208105741076214120093531"]],"#,
+                r#""c":["0","0"],"cm":null,"pok":["1","2"]}"#,
+            )
+        );
+        assert_eq!(serde_json::from_str::<Proof<Bn254>>(&json)?, proof);
+
+        // Points off the curve, non-canonical field elements, and unknown fields are rejected.
+        for json in [
+            json.replacen(r#"["1","2"]"#, r#"["1","3"]"#, 1),
+            json.replacen(r#"["1","2"]"#, r#"["01","2"]"#, 1),
+            json.replacen(r#""c":["0","0"]"#, r#""c":[["0","0"],["0","0"]]"#, 1),
+            json.replacen('}', r#","d":null}"#, 1),
+        ] {
+            assert!(serde_json::from_str::<Proof<Bn254>>(&json).is_err());
+        }
+
+        assert_eq!(
+            f64_inputs_to_json::<Fr>(&[1.5]),
+            r#"["0","1023","2251799813685248"]"#
+        );
+        let p = Fr::MODULUS.to_string();
+        assert!(inputs_from_json::<Fr>(&format!(r#"["{p}"]"#)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn prove_and_verify() -> Result<(), Box<dyn Error>> {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let (pk, vk) = Groth16::<Bn254>::setup(AddCircuit, rng)?;
+        let proof = Groth16::<Bn254>::prove(&pk, AddCircuit, rng)?;
+
+        let pk_json = serde_json::to_string(&pk)?;
+        assert_eq!(serde_json::from_str::<ProvingKey<Bn254>>(&pk_json)?, pk);
+
+        let vk = serde_json::from_str::<VerifyingKey<Bn254>>(&serde_json::to_string(&vk)?)?;
+        let proof = serde_json::from_str::<Proof<Bn254>>(&serde_json::to_string(&proof)?)?;
+        let inputs = inputs_from_json(&f64_inputs_to_json::<Fr>(&[0.2, 0.1 + 0.2]))?;
+        assert!(Groth16::<Bn254>::verify(&vk, &inputs, &proof)?);
+
+        Ok(())
+    }
+}
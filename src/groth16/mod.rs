@@ -13,14 +13,51 @@ pub mod prover;
 /// Verify proofs for the Groth16 zkSNARK construction.
 pub mod verifier;
 
+/// Save and load keys and proofs for the Groth16 zkSNARK construction.
+pub mod io;
+
+/// Plug alternative multi-scalar multiplication backends into the Groth16 prover.
+pub mod msm;
+
+/// Report the progress of proving, and cancel long proving jobs.
+pub mod progress;
+
+/// Encode keys, proofs, and public inputs of the Groth16 zkSNARK as JSON.
+#[cfg(feature = "serde")]
+pub mod json;
+
+/// Convert keys, proofs, and public inputs of the Groth16 zkSNARK to and from snarkjs.
+#[cfg(feature = "serde")]
+pub mod snarkjs;
+
+/// Aggregate proofs for the Groth16 zkSNARK construction.
+pub mod aggregation;
+
+/// Run multi-party ceremonies for the parameters of the Groth16 zkSNARK construction.
+pub mod mpc;
+
+/// Export verifiers for the Groth16 zkSNARK construction as Solidity contracts.
+pub mod solidity;
+
+/// Export verifiers for the Groth16 zkSNARK construction as ink! contracts and Substrate-compatible
+/// verification functions.
+pub mod ink;
+
+/// Verify proofs for the Groth16 zkSNARK construction in circuits, which is the building block of
+/// recursion.
+#[cfg(feature = "recursion")]
+pub mod constraints;
+
 mod logderivarg;
 
 use ark_ec::pairing::Pairing;
-use ark_ff::PrimeField;
+use ark_ff::{PrimeField, UniformRand};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{marker::PhantomData, rand::RngCore, vec::Vec};
+use progress::ProvingProgress;
 use r1cs_to_qap::{LibsnarkReduction, R1CSToQAP};
-use rand::CryptoRng;
+use rand::{CryptoRng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 
 pub use self::{
     data_structures::*,
@@ -152,6 +189,62 @@ impl<E: Pairing, QAP: R1CSToQAP> SNARK<E::ScalarField> for Groth16<E, QAP> {
 
 impl<E: Pairing, QAP: R1CSToQAP> CircuitSpecificSetupSNARK<E::ScalarField> for Groth16<E, QAP> {}
 
+/// A cryptographically secure RNG whose output is determined by `seed`, which is the ChaCha20
+/// stream of the seed and therefore the same on every platform and in every version of this crate.
+pub fn seeded_rng(seed: [u8; 32]) -> ChaCha20Rng {
+    ChaCha20Rng::from_seed(seed)
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Generate the keys of `circuit` from `seed`, so that the same seed always gives
+    /// byte-identical keys. Anyone who knows the seed can recover the toxic waste and forge
+    /// proofs, so this is only for tests and reproducible builds, not for production keys.
+    pub fn setup_with_seed<C: ConstraintSynthesizer<E::ScalarField>>(
+        circuit: C,
+        seed: [u8; 32],
+    ) -> Result<(ProvingKey<E>, VerifyingKey<E>), SynthesisError> {
+        <Self as SNARK<E::ScalarField>>::circuit_specific_setup(circuit, &mut seeded_rng(seed))
+    }
+
+    /// Prove `circuit` with the randomness derived from `seed`, so that the same seed, keys, and
+    /// witnesses always give a byte-identical proof. The seed should be kept secret and never
+    /// reused for different witnesses, since two proofs with the same randomness reveal relations
+    /// between their witnesses.
+    pub fn prove_with_seed<C: ConstraintSynthesizer<E::ScalarField>>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        seed: [u8; 32],
+    ) -> Result<Proof<E>, SynthesisError> {
+        <Self as SNARK<E::ScalarField>>::prove(pk, circuit, &mut seeded_rng(seed))
+    }
+
+    /// Prove `circuit` as `prove` with the constraint matrices cached in `ppk`, which is prepared
+    /// from the proving key by `prepare_proving_key`, so that only the witnesses are generated.
+    /// The proof is the same as that of `prove` with the same randomness.
+    pub fn prove_prepared<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore>(
+        ppk: &PreparedProvingKey<E>,
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<Proof<E>, SynthesisError> {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+        Self::create_proof_with_prepared_key(circuit, ppk, r, s, &mut ())
+    }
+
+    /// Prove `circuit` as `prove`, reporting each phase to `progress`, which may cancel the job
+    /// between phases, in which case `SynthesisError::Cancelled` is returned.
+    pub fn prove_with_progress<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        rng: &mut R,
+        progress: &mut impl ProvingProgress,
+    ) -> Result<Proof<E>, SynthesisError> {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+        Self::create_proof_with_progress(circuit, pk, r, s, progress)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -165,10 +258,10 @@ mod tests {
 
     use super::*;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
     use ark_ff::BigInteger;
     use ark_std::test_rng;
     use rand::SeedableRng;
-    use num::Integer;
 
     struct MySillyCircuit<F: PrimeField> {
         a: F,
@@ -182,7 +275,7 @@ mod tests {
                 let mut x = FpVar::new_witness(cs.clone(), || Ok(self.a))?;
                 x += FpVar::constant(F::from(i));
 
-                let extended_length = length.next_multiple_of(&LOOKUP_TABLE_BITS);
+                let extended_length = length.next_multiple_of(LOOKUP_TABLE_BITS);
                 let num_chunks = extended_length / LOOKUP_TABLE_BITS;
                 let mut chunks = x
                     .value()
@@ -226,6 +319,17 @@ mod tests {
         }
     }
 
+    /// A circuit with a public input, whose shape differs from that of `MySillyCircuit`.
+    struct SquareCircuit<F: PrimeField>(F);
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for SquareCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let x = FpVar::new_input(cs.clone(), || Ok(self.0))?;
+            let y = FpVar::new_witness(cs, || Ok(self.0.square()))?;
+            x.square()?.enforce_equal(&y)
+        }
+    }
+
     fn test_prove_and_verify<E>(n_iters: usize)
     where
         E: Pairing,
@@ -247,6 +351,10 @@ mod tests {
             let proof = Groth16::<E>::prove(&pk, MySillyCircuit { a }, &mut rng).unwrap();
 
             assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[], &proof).unwrap());
+
+            // A commitment without its proof of knowledge is rejected instead of panicking.
+            let malformed = Proof { pok: None, ..proof };
+            assert!(!Groth16::<E>::verify_with_processed_vk(&pvk, &[], &malformed).unwrap());
         }
     }
 
@@ -254,4 +362,142 @@ mod tests {
     fn prove_and_verify() {
         test_prove_and_verify::<Bls12_381>(1);
     }
+
+    #[test]
+    fn prove_and_verify_bn254() {
+        test_prove_and_verify::<Bn254>(1);
+    }
+
+    #[test]
+    fn prove_with_seed() {
+        let circuit = || MySillyCircuit {
+            a: <Bls12_381 as Pairing>::ScalarField::from(10000u64),
+        };
+        let serialize = |proof: &Proof<Bls12_381>| {
+            let mut bytes = vec![];
+            proof.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+
+        let (pk, vk) = Groth16::<Bls12_381>::setup_with_seed(circuit(), [1; 32]).unwrap();
+        let (_, other_vk) = Groth16::<Bls12_381>::setup_with_seed(circuit(), [1; 32]).unwrap();
+        assert_eq!(vk, other_vk);
+
+        let proof = Groth16::<Bls12_381>::prove_with_seed(&pk, circuit(), [2; 32]).unwrap();
+        assert!(Groth16::<Bls12_381>::verify(&vk, &[], &proof).unwrap());
+        let same = Groth16::<Bls12_381>::prove_with_seed(&pk, circuit(), [2; 32]).unwrap();
+        assert_eq!(serialize(&proof), serialize(&same));
+        let other = Groth16::<Bls12_381>::prove_with_seed(&pk, circuit(), [3; 32]).unwrap();
+        assert_ne!(serialize(&proof), serialize(&other));
+    }
+
+    #[test]
+    fn prove_with_progress() {
+        use progress::{CancellationToken, ProvingPhase};
+        use std::ops::ControlFlow;
+
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circuit = || MySillyCircuit {
+            a: <Bls12_381 as Pairing>::ScalarField::from(10000u64),
+        };
+        let (pk, vk) = Groth16::<Bls12_381>::setup(circuit(), rng).unwrap();
+
+        let mut phases = vec![];
+        let proof = Groth16::<Bls12_381>::prove_with_progress(&pk, circuit(), rng, &mut |phase| {
+            phases.push(phase);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(phases, ProvingPhase::ALL);
+        assert!(Groth16::<Bls12_381>::verify(&vk, &[], &proof).unwrap());
+
+        // The job stops at the phase that is cancelled.
+        let mut phases = vec![];
+        let result = Groth16::<Bls12_381>::prove_with_progress(&pk, circuit(), rng, &mut |phase| {
+            phases.push(phase);
+            match phase {
+                ProvingPhase::WitnessMap => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            }
+        });
+        assert_eq!(result, Err(SynthesisError::Cancelled));
+        assert_eq!(phases, ProvingPhase::ALL[..3]);
+
+        let token = CancellationToken::new();
+        token.clone().cancel();
+        let result =
+            Groth16::<Bls12_381>::prove_with_progress(&pk, circuit(), rng, &mut token.clone());
+        assert_eq!(result, Err(SynthesisError::Cancelled));
+    }
+
+    #[test]
+    fn prove_prepared() {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circuit = |a: u64| MySillyCircuit {
+            a: <Bls12_381 as Pairing>::ScalarField::from(a),
+        };
+        let (pk, vk) = Groth16::<Bls12_381>::setup(circuit(0), rng).unwrap();
+        let ppk = Groth16::<Bls12_381>::prepare_proving_key(pk.clone(), circuit(0)).unwrap();
+
+        for a in [10000, 20000] {
+            let proof = Groth16::<Bls12_381>::prove_prepared(&ppk, circuit(a), rng).unwrap();
+            assert!(Groth16::<Bls12_381>::verify(&vk, &[], &proof).unwrap());
+        }
+
+        // The proof is the same as the one from the unprepared key with the same randomness.
+        let (r, s) = (UniformRand::rand(rng), UniformRand::rand(rng));
+        assert_eq!(
+            Groth16::<Bls12_381>::create_proof_with_prepared_key(circuit(1), &ppk, r, s, &mut ()),
+            Groth16::<Bls12_381>::create_proof_with_reduction(circuit(1), &pk, r, s),
+        );
+
+        // The keys and the circuits of other shapes are rejected.
+        let (other_pk, _) =
+            Groth16::<Bls12_381>::setup(SquareCircuit(Default::default()), rng).unwrap();
+        assert_eq!(
+            Groth16::<Bls12_381>::prepare_proving_key(other_pk, circuit(0)),
+            Err(SynthesisError::MismatchedProvingKey)
+        );
+        assert_eq!(
+            Groth16::<Bls12_381>::prove_prepared(&ppk, SquareCircuit(2u64.into()), rng),
+            Err(SynthesisError::MismatchedProvingKey)
+        );
+    }
+
+    #[test]
+    fn batch_verify() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+        let (pk, vk) = Groth16::<Bls12_381>::setup(
+            MySillyCircuit {
+                a: Default::default(),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        let pvk = prepare_verifying_key(&vk);
+
+        let mut proofs = (0..3u64)
+            .map(|i| {
+                let a = <Bls12_381 as Pairing>::ScalarField::from(10000 + i);
+                Groth16::<Bls12_381>::prove(&pk, MySillyCircuit { a }, &mut rng).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let batch = proofs.iter().map(|p| (p, &[][..])).collect::<Vec<_>>();
+        assert!(Groth16::<Bls12_381>::batch_verify(&pvk, &batch, &mut rng).unwrap());
+        assert!(Groth16::<Bls12_381>::batch_verify(&pvk, &[], &mut rng).unwrap());
+
+        // A commitment without its proof of knowledge is rejected, as in `verify_proof`.
+        let mut stripped = proofs.clone();
+        assert!(stripped[2].cm.is_some());
+        stripped[2].pok = None;
+        assert!(!Groth16::<Bls12_381>::verify_proof(&pvk, &stripped[2], &[]).unwrap());
+        let batch = stripped.iter().map(|p| (p, &[][..])).collect::<Vec<_>>();
+        assert!(!Groth16::<Bls12_381>::batch_verify(&pvk, &batch, &mut rng).unwrap());
+
+        proofs[1].c = proofs[0].c;
+        let batch = proofs.iter().map(|p| (p, &[][..])).collect::<Vec<_>>();
+        assert!(!Groth16::<Bls12_381>::batch_verify(&pvk, &batch, &mut rng).unwrap());
+    }
 }
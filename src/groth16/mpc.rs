@@ -0,0 +1,399 @@
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_serialize::*;
+use ark_std::{cfg_iter, end_timer, fmt, rand::Rng, start_timer};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+use super::ProvingKey;
+
+/// The errors that may occur when verifying or finalizing a phase-2 ceremony.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CeremonyError {
+    /// The parameters are not derived from the initial parameters, e.g., they belong to another
+    /// circuit, or elements that no contribution may change are modified.
+    MismatchedParameters,
+    /// The contribution with the given index has an invalid proof of knowledge, or is inconsistent
+    /// with the contributions before it.
+    InvalidContribution(usize),
+    /// The ceremony has no contributions, so its parameters are still the initial ones.
+    NoContributions,
+}
+
+impl ark_std::error::Error for CeremonyError {}
+
+impl fmt::Display for CeremonyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            CeremonyError::MismatchedParameters => write!(f, "mismatched parameters"),
+            CeremonyError::InvalidContribution(i) => write!(f, "invalid contribution #{i}"),
+            CeremonyError::NoContributions => write!(f, "no contributions"),
+        }
+    }
+}
+
+/// A Schnorr proof of knowledge of the secret `x` of a contribution, which is bound to the
+/// transcript of the ceremony so that it cannot be copied from another contribution.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KnowledgeProof<E: Pairing> {
+    /// The element `x * G`, where `G` is the generator of `E::G1`.
+    pub x_g1: E::G1Affine,
+    /// The commitment `k * G` for a random nonce `k`.
+    pub r_g1: E::G1Affine,
+    /// The response `k + c * x` to the challenge `c`.
+    pub z: E::ScalarField,
+}
+
+/// The public record of a contribution, which multiplies `delta` by a secret `x` and `sigma^{-1}`
+/// (the trapdoor of the commitment key of the lookup argument) by a secret `y`.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Contribution<E: Pairing> {
+    /// The element `delta * G` in `E::G1` after the contribution.
+    pub delta_g1: E::G1Affine,
+    /// The element `delta * H` in `E::G2` after the contribution.
+    pub delta_g2: E::G2Affine,
+    /// The element `-sigma^{-1} * pedersen_g` in `E::G2` after the contribution.
+    pub pedersen_g_inv_neg_sigma: E::G2Affine,
+    /// The proof of knowledge of `x`.
+    pub delta_proof: KnowledgeProof<E>,
+    /// The proof of knowledge of `y`.
+    pub sigma_proof: KnowledgeProof<E>,
+}
+
+/// A phase-2 ceremony for the parameters of a circuit, which is passed from one participant to
+/// the next, each of whom calls `contribute` (after checking the ceremony with `verify`).
+///
+/// The parameters are secure as long as at least one participant erases their secrets, but only
+/// with respect to `delta` and `sigma`, which are the only secrets that can be changed without
+/// recomputing the whole proving key. The other secrets (`alpha`, `beta`, `gamma`, and the
+/// evaluation point of the QAP) are still those of the initial parameters, which must therefore
+/// be generated by a party trusted to erase them, since this crate has no phase-1 ceremony.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Ceremony<E: Pairing> {
+    /// The parameters after the last contribution.
+    pub pk: ProvingKey<E>,
+    /// The hash of the initial parameters.
+    pub initial_hash: [u8; 32],
+    /// The contributions in the order they were made.
+    pub contributions: Vec<Contribution<E>>,
+}
+
+/// Hash the proving key `pk`.
+fn hash_parameters<E: Pairing>(pk: &ProvingKey<E>) -> [u8; 32] {
+    let mut bytes = vec![];
+    pk.serialize_compressed(&mut bytes).unwrap();
+    Sha256::digest(bytes).into()
+}
+
+/// The Fiat-Shamir transcript of the ceremony.
+struct Transcript(Sha256);
+
+impl Transcript {
+    /// Start the transcript of the contribution after `previous`.
+    fn new<E: Pairing>(initial_hash: &[u8; 32], previous: &[Contribution<E>]) -> Self {
+        let mut transcript = Self(Sha256::new_with_prefix(b"r1cs_float/groth16/mpc"));
+        transcript.0.update(initial_hash);
+        transcript.append(&previous.to_vec());
+        transcript
+    }
+
+    fn append(&mut self, v: &impl CanonicalSerialize) {
+        let mut bytes = vec![];
+        v.serialize_compressed(&mut bytes).unwrap();
+        self.0.update(&bytes);
+    }
+
+    /// Absorb the elements of a contribution other than the responses of its proofs.
+    fn append_contribution<E: Pairing>(&mut self, c: &Contribution<E>) {
+        self.append(&c.delta_g1);
+        self.append(&c.delta_g2);
+        self.append(&c.pedersen_g_inv_neg_sigma);
+        for proof in [&c.delta_proof, &c.sigma_proof] {
+            self.append(&proof.x_g1);
+            self.append(&proof.r_g1);
+        }
+    }
+
+    fn challenge<F: PrimeField>(self) -> F {
+        F::from_le_bytes_mod_order(&self.0.finalize())
+    }
+}
+
+/// Multiply all elements of `v` by `s`.
+fn scale<G: CurveGroup>(v: &[G::Affine], s: G::ScalarField) -> Vec<G::Affine> {
+    G::normalize_batch(&cfg_iter!(v).map(|p| *p * s).collect::<Vec<_>>())
+}
+
+/// Check that `e(a, d) = e(b, c)`, i.e., `b / a = d / c` in the exponent.
+fn same_ratio<E: Pairing>(
+    (a, b): (E::G1Affine, E::G1Affine),
+    (c, d): (E::G2Affine, E::G2Affine),
+) -> bool {
+    E::multi_pairing([a, (-b.into_group()).into_affine()], [d, c]).is_zero()
+}
+
+/// Check that `e(sum(r_i * old_i), old_h) = e(sum(r_i * new_i), new_h)` for random `r_i`, i.e.,
+/// that all elements of `old` and `new` have the same ratio (with overwhelming probability).
+fn same_ratio_batch<E: Pairing>(
+    (old, new): (&[E::G1Affine], &[E::G1Affine]),
+    (old_h, new_h): (E::G2Affine, E::G2Affine),
+    rng: &mut impl Rng,
+) -> bool {
+    if old.len() != new.len() {
+        return false;
+    }
+    let r = (0..old.len())
+        .map(|_| E::ScalarField::rand(rng))
+        .collect::<Vec<_>>();
+    let old = E::G1::msm_unchecked(old, &r).into_affine();
+    let new = E::G1::msm_unchecked(new, &r).into_affine();
+    same_ratio::<E>((new, old), (old_h, new_h))
+}
+
+impl<E: Pairing> KnowledgeProof<E> {
+    fn prove(x: E::ScalarField, rng: &mut impl Rng) -> (Self, E::ScalarField) {
+        let k = E::ScalarField::rand(rng);
+        let proof = Self {
+            x_g1: (E::G1::generator() * x).into_affine(),
+            r_g1: (E::G1::generator() * k).into_affine(),
+            z: E::ScalarField::zero(),
+        };
+        (proof, k)
+    }
+
+    fn verify(&self, c: E::ScalarField) -> bool {
+        !self.x_g1.is_zero() && E::G1::generator() * self.z == self.x_g1 * c + self.r_g1
+    }
+}
+
+impl<E: Pairing> Ceremony<E> {
+    /// Start a ceremony from the initial parameters `pk`, e.g., generated by `Groth16::setup`.
+    pub fn new(pk: ProvingKey<E>) -> Self {
+        Self {
+            initial_hash: hash_parameters(&pk),
+            pk,
+            contributions: vec![],
+        }
+    }
+
+    /// Contribute fresh secrets sampled from `rng` to the parameters and record the contribution.
+    /// `rng` must be cryptographically secure, and the secrets are dropped when this function
+    /// returns.
+    pub fn contribute(&mut self, rng: &mut impl Rng) -> &Contribution<E> {
+        let contribute_time = start_timer!(|| "Ceremony::Contribute");
+
+        let (x, y) = loop {
+            let x = E::ScalarField::rand(rng);
+            let y = E::ScalarField::rand(rng);
+            if !x.is_zero() && !y.is_zero() {
+                break (x, y);
+            }
+        };
+
+        let pk = &mut self.pk;
+        pk.delta_g1 = (pk.delta_g1 * x).into_affine();
+        pk.vk.delta_g2 = (pk.vk.delta_g2 * x).into_affine();
+        pk.vk.pedersen_g_inv_neg_sigma = (pk.vk.pedersen_g_inv_neg_sigma * y).into_affine();
+
+        let x_inv = x.inverse().unwrap();
+        pk.h_query = scale::<E::G1>(&pk.h_query, x_inv);
+        pk.l_query = scale::<E::G1>(&pk.l_query, x_inv);
+        pk.k_query_sigma = scale::<E::G1>(&pk.k_query_sigma, y.inverse().unwrap());
+
+        let (delta_proof, k_x) = KnowledgeProof::prove(x, rng);
+        let (sigma_proof, k_y) = KnowledgeProof::prove(y, rng);
+        let mut contribution = Contribution {
+            delta_g1: pk.delta_g1,
+            delta_g2: pk.vk.delta_g2,
+            pedersen_g_inv_neg_sigma: pk.vk.pedersen_g_inv_neg_sigma,
+            delta_proof,
+            sigma_proof,
+        };
+        let mut transcript = Transcript::new(&self.initial_hash, &self.contributions);
+        transcript.append_contribution(&contribution);
+        let c = transcript.challenge::<E::ScalarField>();
+        contribution.delta_proof.z = k_x + c * x;
+        contribution.sigma_proof.z = k_y + c * y;
+
+        self.contributions.push(contribution);
+        end_timer!(contribute_time);
+
+        self.contributions.last().unwrap()
+    }
+
+    /// Verify that the parameters of the ceremony are derived from the `initial` parameters by
+    /// the recorded contributions, each of which proves the knowledge of its secrets.
+    /// The elements of the queries are checked by random linear combinations with coefficients
+    /// sampled from `rng`.
+    pub fn verify(&self, initial: &ProvingKey<E>, rng: &mut impl Rng) -> Result<(), CeremonyError> {
+        let verify_time = start_timer!(|| "Ceremony::Verify");
+
+        let (pk, vk) = (&self.pk, &self.pk.vk);
+        if hash_parameters(initial) != self.initial_hash
+            || vk.alpha_g1 != initial.vk.alpha_g1
+            || vk.beta_g2 != initial.vk.beta_g2
+            || vk.gamma_g2 != initial.vk.gamma_g2
+            || vk.gamma_abc_g1 != initial.vk.gamma_abc_g1
+            || vk.pedersen_g != initial.vk.pedersen_g
+            || pk.beta_g1 != initial.beta_g1
+            || pk.a_query != initial.a_query
+            || pk.b_g1_query != initial.b_g1_query
+            || pk.b_g2_query != initial.b_g2_query
+            || pk.k_query != initial.k_query
+        {
+            return Err(CeremonyError::MismatchedParameters);
+        }
+
+        let (mut delta_g1, mut delta_g2, mut g_inv) = (
+            initial.delta_g1,
+            initial.vk.delta_g2,
+            initial.vk.pedersen_g_inv_neg_sigma,
+        );
+        for (i, contribution) in self.contributions.iter().enumerate() {
+            let mut transcript = Transcript::new(&self.initial_hash, &self.contributions[..i]);
+            transcript.append_contribution(contribution);
+            let c = transcript.challenge::<E::ScalarField>();
+
+            let g = E::G1Affine::generator();
+            let (x_g1, y_g1) = (contribution.delta_proof.x_g1, contribution.sigma_proof.x_g1);
+            if !contribution.delta_proof.verify(c)
+                || !contribution.sigma_proof.verify(c)
+                || !same_ratio::<E>((g, x_g1), (delta_g2, contribution.delta_g2))
+                || !same_ratio::<E>(
+                    (delta_g1, contribution.delta_g1),
+                    (delta_g2, contribution.delta_g2),
+                )
+                || !same_ratio::<E>((g, y_g1), (g_inv, contribution.pedersen_g_inv_neg_sigma))
+            {
+                return Err(CeremonyError::InvalidContribution(i));
+            }
+
+            delta_g1 = contribution.delta_g1;
+            delta_g2 = contribution.delta_g2;
+            g_inv = contribution.pedersen_g_inv_neg_sigma;
+        }
+
+        // The queries are divided by the same secrets as the ones that `delta` and `sigma^{-1}`
+        // are multiplied by, so their pairings with these elements are unchanged.
+        if (pk.delta_g1, vk.delta_g2, vk.pedersen_g_inv_neg_sigma) != (delta_g1, delta_g2, g_inv)
+            || !same_ratio_batch::<E>(
+                (&initial.h_query, &pk.h_query),
+                (initial.vk.delta_g2, delta_g2),
+                rng,
+            )
+            || !same_ratio_batch::<E>(
+                (&initial.l_query, &pk.l_query),
+                (initial.vk.delta_g2, delta_g2),
+                rng,
+            )
+            || !same_ratio_batch::<E>(
+                (&initial.k_query_sigma, &pk.k_query_sigma),
+                (initial.vk.pedersen_g_inv_neg_sigma, g_inv),
+                rng,
+            )
+        {
+            return Err(CeremonyError::MismatchedParameters);
+        }
+
+        end_timer!(verify_time);
+        Ok(())
+    }
+
+    /// Verify the ceremony as `verify` and return its parameters, which requires at least one
+    /// contribution.
+    pub fn finalize(
+        self,
+        initial: &ProvingKey<E>,
+        rng: &mut impl Rng,
+    ) -> Result<ProvingKey<E>, CeremonyError> {
+        if self.contributions.is_empty() {
+            return Err(CeremonyError::NoContributions);
+        }
+        self.verify(initial, rng)?;
+        Ok(self.pk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        f64::F64Var,
+        groth16::{prepare_verifying_key, CircuitSpecificSetupSNARK, Groth16, SNARK},
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+        r1cs_std::prelude::{AllocVar, EqGadget},
+    };
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+    use rand::{RngCore, SeedableRng};
+
+    struct MulCircuit {
+        a: f64,
+        b: f64,
+    }
+
+    impl ConstraintSynthesizer<Fr> for MulCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let a = F64Var::new_input(cs.clone(), || Ok(self.a))?;
+            let b = F64Var::new_witness(cs.clone(), || Ok(self.b))?;
+            let c = F64Var::new_input(cs.clone(), || Ok(self.a * self.b))?;
+            (a * b).enforce_equal(&c)
+        }
+    }
+
+    #[test]
+    fn ceremony() {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+        let (initial, _) = Groth16::<Bls12_381>::setup(MulCircuit { a: 0., b: 0. }, rng).unwrap();
+        let mut ceremony = Ceremony::new(initial.clone());
+        assert_eq!(
+            ceremony.clone().finalize(&initial, rng),
+            Err(CeremonyError::NoContributions)
+        );
+        for _ in 0..3 {
+            ceremony.contribute(rng);
+            ceremony.verify(&initial, rng).unwrap();
+
+            // The ceremony is passed to the next participant in serialized form.
+            let mut bytes = vec![];
+            ceremony.serialize_compressed(&mut bytes).unwrap();
+            ceremony = Ceremony::deserialize_compressed(&bytes[..]).unwrap();
+        }
+
+        let mut forged = ceremony.clone();
+        forged.contributions[1].sigma_proof.z += Fr::from(1u8);
+        assert_eq!(
+            forged.verify(&initial, rng),
+            Err(CeremonyError::InvalidContribution(1))
+        );
+        let mut forged = ceremony.clone();
+        forged.pk.h_query[0] = forged.pk.h_query[1];
+        assert_eq!(
+            forged.verify(&initial, rng),
+            Err(CeremonyError::MismatchedParameters)
+        );
+        let (other, _) = Groth16::<Bls12_381>::setup(MulCircuit { a: 0., b: 0. }, rng).unwrap();
+        assert_eq!(
+            ceremony.verify(&other, rng),
+            Err(CeremonyError::MismatchedParameters)
+        );
+
+        let pk = ceremony.finalize(&initial, rng).unwrap();
+        assert_ne!(pk.vk.delta_g2, initial.vk.delta_g2);
+        assert_ne!(
+            pk.vk.pedersen_g_inv_neg_sigma,
+            initial.vk.pedersen_g_inv_neg_sigma
+        );
+
+        let (a, b) = (1.5, -0.1);
+        let proof = Groth16::<Bls12_381>::prove(&pk, MulCircuit { a, b }, rng).unwrap();
+        assert!(proof.cm.is_some());
+        let inputs = [F64Var::verifier_input(a), F64Var::verifier_input(a * b)].concat();
+        let pvk = prepare_verifying_key(&pk.vk);
+        assert!(Groth16::<Bls12_381>::verify_proof(&pvk, &proof, &inputs).unwrap());
+        let pvk = prepare_verifying_key(&initial.vk);
+        assert!(!Groth16::<Bls12_381>::verify_proof(&pvk, &proof, &inputs).unwrap());
+    }
+}
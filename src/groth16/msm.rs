@@ -0,0 +1,169 @@
+//! The multi-scalar multiplications (MSMs) of the prover dominate the proving time of large
+//! circuits. With the `gpu` feature, an alternative implementation of MSM, e.g., on a GPU, can be
+//! registered for a pairing by `set_msm_backend`, and is then used by all MSMs in the proving
+//! path, including those of `Groth16::prove` and the commitment of the lookup argument.
+//! Without the feature, or if no backend is registered, the MSMs of `ark-ec` are used.
+
+use ark_ec::{pairing::Pairing, VariableBaseMSM};
+use ark_ff::PrimeField;
+
+/// The big integer representation of the scalars of `E`.
+pub type BigInt<E> = <<E as Pairing>::ScalarField as PrimeField>::BigInt;
+
+/// A backend that computes the MSMs `Σ scalars[i] * bases[i]` in `G1` and `G2` of `E`.
+///
+/// `bases` and `scalars` may have different lengths, in which case the longer one is truncated.
+/// A backend may return `None` to fall back to the default implementation, e.g., when the
+/// instance is too small to benefit from the GPU.
+pub trait MsmBackend<E: Pairing>: Send + Sync {
+    /// Compute the MSM in `G1`.
+    fn msm_g1(&self, bases: &[E::G1Affine], scalars: &[BigInt<E>]) -> Option<E::G1>;
+
+    /// Compute the MSM in `G2`.
+    fn msm_g2(&self, bases: &[E::G2Affine], scalars: &[BigInt<E>]) -> Option<E::G2>;
+}
+
+#[cfg(feature = "gpu")]
+mod registry {
+    use std::{
+        any::{Any, TypeId},
+        collections::BTreeMap,
+        sync::{Arc, RwLock},
+    };
+
+    use ark_ec::pairing::Pairing;
+
+    use super::MsmBackend;
+
+    type Backends = BTreeMap<TypeId, Box<dyn Any + Send + Sync>>;
+
+    /// The registered backends, indexed by the type of the pairing, where each value is an
+    /// `Arc<dyn MsmBackend<E>>` for the corresponding `E`.
+    static BACKENDS: RwLock<Backends> = RwLock::new(BTreeMap::new());
+
+    /// Use `backend` for the MSMs of the prover over `E`, replacing the previous one (if any).
+    pub fn set_msm_backend<E: Pairing>(backend: impl MsmBackend<E> + 'static) {
+        let backend: Arc<dyn MsmBackend<E>> = Arc::new(backend);
+        BACKENDS
+            .write()
+            .unwrap()
+            .insert(TypeId::of::<E>(), Box::new(backend));
+    }
+
+    /// Remove the backend registered for `E`, so that the default implementation is used.
+    pub fn clear_msm_backend<E: Pairing>() {
+        BACKENDS.write().unwrap().remove(&TypeId::of::<E>());
+    }
+
+    /// The backend registered for `E`.
+    pub(super) fn msm_backend<E: Pairing>() -> Option<Arc<dyn MsmBackend<E>>> {
+        BACKENDS
+            .read()
+            .unwrap()
+            .get(&TypeId::of::<E>())
+            .and_then(|backend| backend.downcast_ref::<Arc<dyn MsmBackend<E>>>())
+            .cloned()
+    }
+}
+
+#[cfg(feature = "gpu")]
+pub use registry::{clear_msm_backend, set_msm_backend};
+
+/// Compute the MSM in `G1` with the registered backend, or with `ark-ec` if there is none.
+pub(crate) fn msm_g1<E: Pairing>(bases: &[E::G1Affine], scalars: &[BigInt<E>]) -> E::G1 {
+    #[cfg(feature = "gpu")]
+    if let Some(result) = registry::msm_backend::<E>().and_then(|b| b.msm_g1(bases, scalars)) {
+        return result;
+    }
+    E::G1::msm_bigint(bases, scalars)
+}
+
+/// Compute the MSM in `G2` with the registered backend, or with `ark-ec` if there is none.
+pub(crate) fn msm_g2<E: Pairing>(bases: &[E::G2Affine], scalars: &[BigInt<E>]) -> E::G2 {
+    #[cfg(feature = "gpu")]
+    if let Some(result) = registry::msm_backend::<E>().and_then(|b| b.msm_g2(bases, scalars)) {
+        return result;
+    }
+    E::G2::msm_bigint(bases, scalars)
+}
+
+#[cfg(all(test, feature = "gpu"))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::VariableBaseMSM;
+    use ark_std::test_rng;
+    use rand::{RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{
+        f64::F64Var,
+        groth16::{CircuitSpecificSetupSNARK, Groth16, SNARK},
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+        r1cs_std::prelude::{AllocVar, EqGadget},
+    };
+
+    type E = Bls12_381;
+
+    /// A backend that counts the MSMs it computes, and delegates them to `ark-ec`.
+    #[derive(Default)]
+    struct CountingMsm {
+        g1: AtomicUsize,
+        g2: AtomicUsize,
+    }
+
+    impl MsmBackend<E> for &'static CountingMsm {
+        fn msm_g1(
+            &self,
+            bases: &[<E as Pairing>::G1Affine],
+            scalars: &[BigInt<E>],
+        ) -> Option<<E as Pairing>::G1> {
+            self.g1.fetch_add(1, Ordering::SeqCst);
+            Some(VariableBaseMSM::msm_bigint(bases, scalars))
+        }
+
+        fn msm_g2(
+            &self,
+            bases: &[<E as Pairing>::G2Affine],
+            scalars: &[BigInt<E>],
+        ) -> Option<<E as Pairing>::G2> {
+            self.g2.fetch_add(1, Ordering::SeqCst);
+            Some(VariableBaseMSM::msm_bigint(bases, scalars))
+        }
+    }
+
+    struct MulCircuit;
+
+    impl ConstraintSynthesizer<<E as Pairing>::ScalarField> for MulCircuit {
+        fn generate_constraints(
+            self,
+            cs: ConstraintSystemRef<<E as Pairing>::ScalarField>,
+        ) -> Result<(), SynthesisError> {
+            let a = F64Var::new_witness(cs.clone(), || Ok(1.5))?;
+            let b = F64Var::new_input(cs.clone(), || Ok(2.5))?;
+            (a * b).enforce_equal(&F64Var::new_input(cs, || Ok(3.75))?)
+        }
+    }
+
+    #[test]
+    fn backend() {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let (pk, vk) = Groth16::<E>::setup(MulCircuit, rng).unwrap();
+        let inputs = [2.5, 3.75]
+            .into_iter()
+            .flat_map(F64Var::verifier_input)
+            .collect::<Vec<_>>();
+
+        let backend: &'static CountingMsm = Box::leak(Box::default());
+        set_msm_backend::<E>(backend);
+        let proof = Groth16::<E>::prove(&pk, MulCircuit, rng).unwrap();
+        clear_msm_backend::<E>();
+
+        // A, B in G1, the commitment, its proof of knowledge, h, and l are computed in G1.
+        // Other tests may prove over `E` concurrently, so the counts are only lower bounds.
+        assert!(backend.g1.load(Ordering::SeqCst) >= 6);
+        assert!(backend.g2.load(Ordering::SeqCst) >= 1);
+        assert!(Groth16::<E>::verify(&vk, &inputs, &proof).unwrap());
+    }
+}
@@ -0,0 +1,98 @@
+//! Proving a large circuit takes minutes, most of which is spent in a few long phases.
+//! `Groth16::prove_with_progress` reports the start and the end of each phase to a
+//! `ProvingProgress`, e.g., for showing a progress bar, which can also cancel the job by returning
+//! `ControlFlow::Break` when a phase starts. A running phase is not interrupted, so the job stops
+//! at the next phase boundary and fails with `SynthesisError::Cancelled`.
+//!
+//! Closures `FnMut(ProvingPhase) -> ControlFlow<()>` are observers that are called at the start of
+//! each phase, and a `CancellationToken` cancels the job when another thread calls `cancel`.
+
+use std::{
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// A phase of proving, in the order in which they are run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProvingPhase {
+    /// Synthesize the constraints of the circuit and generate its witnesses.
+    Synthesis,
+    /// Commit to the queries of the lookup argument and prove them, which is skipped by circuits
+    /// without lookups.
+    Commitment,
+    /// Inline the linear combinations and compute the quotient polynomial by FFTs.
+    WitnessMap,
+    /// Compute the MSMs of the quotient polynomial and the witnesses for `C`.
+    MsmC,
+    /// Compute the MSM for `A`.
+    MsmA,
+    /// Compute the MSMs for `B` in `G1` and `G2`.
+    MsmB,
+}
+
+impl ProvingPhase {
+    /// All phases in order, e.g., for computing the fraction of the finished phases.
+    pub const ALL: [ProvingPhase; 6] = [
+        ProvingPhase::Synthesis,
+        ProvingPhase::Commitment,
+        ProvingPhase::WitnessMap,
+        ProvingPhase::MsmC,
+        ProvingPhase::MsmA,
+        ProvingPhase::MsmB,
+    ];
+}
+
+/// An observer of the progress of a proving job.
+pub trait ProvingProgress {
+    /// Called when `phase` starts. Returning `ControlFlow::Break` cancels the job.
+    fn start(&mut self, phase: ProvingPhase) -> ControlFlow<()> {
+        let _ = phase;
+        ControlFlow::Continue(())
+    }
+
+    /// Called when `phase` finishes.
+    fn finish(&mut self, phase: ProvingPhase) {
+        let _ = phase;
+    }
+}
+
+/// No observer.
+impl ProvingProgress for () {}
+
+impl<F: FnMut(ProvingPhase) -> ControlFlow<()>> ProvingProgress for F {
+    fn start(&mut self, phase: ProvingPhase) -> ControlFlow<()> {
+        self(phase)
+    }
+}
+
+/// A flag shared between a proving job and the threads that may cancel it.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the jobs using this token (or its clones) when they start their next phase.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl ProvingProgress for CancellationToken {
+    fn start(&mut self, _: ProvingPhase) -> ControlFlow<()> {
+        if self.is_cancelled() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
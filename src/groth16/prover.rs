@@ -1,4 +1,4 @@
-use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, Group, VariableBaseMSM};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, Group};
 use ark_ff::{
     field_hashers::{DefaultFieldHasher, HashToField},
     PrimeField, UniformRand, Zero,
@@ -7,21 +7,37 @@ use ark_poly::GeneralEvaluationDomain;
 use ark_serialize::{CanonicalSerialize, Compress};
 use ark_std::{
     cfg_into_iter, cfg_iter, end_timer,
-    ops::{AddAssign, Mul},
+    ops::{AddAssign, ControlFlow, Mul},
     rand::Rng,
     start_timer,
     vec::Vec,
 };
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use sha2::Sha256;
 
-use super::{logderivarg::generate_commitment, r1cs_to_qap::R1CSToQAP, Groth16, Proof, ProvingKey};
+use super::{
+    logderivarg::generate_commitment,
+    msm::{msm_g1, msm_g2},
+    progress::{ProvingPhase, ProvingProgress},
+    r1cs_to_qap::R1CSToQAP,
+    Groth16, PreparedProvingKey, Proof, ProvingKey,
+};
 use crate::r1cs::{
-    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, Result as R1CSResult,
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal,
+    Result as R1CSResult, SynthesisError, SynthesisMode,
 };
 
 type D<F> = GeneralEvaluationDomain<F>;
 
+/// Report the start of `phase` to `progress`, and fail if it cancels the job.
+fn start_phase(progress: &mut impl ProvingProgress, phase: ProvingPhase) -> R1CSResult<()> {
+    match progress.start(phase) {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(()) => Err(SynthesisError::Cancelled),
+    }
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     /// Create a Groth16 proof that is zero-knowledge using the provided
     /// R1CS-to-QAP reduction.
@@ -50,6 +66,23 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         r: E::ScalarField,
         s: E::ScalarField,
     ) -> R1CSResult<Proof<E>>
+    where
+        E: Pairing,
+        C: ConstraintSynthesizer<E::ScalarField>,
+        QAP: R1CSToQAP,
+    {
+        Self::create_proof_with_progress(circuit, pk, r, s, &mut ())
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s` and the provided
+    /// R1CS-to-QAP reduction, reporting each phase to `progress`, which may cancel the job.
+    pub fn create_proof_with_progress<C>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        progress: &mut impl ProvingProgress,
+    ) -> R1CSResult<Proof<E>>
     where
         E: Pairing,
         C: ConstraintSynthesizer<E::ScalarField>,
@@ -57,6 +90,100 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     {
         let prover_time = start_timer!(|| "Groth16::Prover");
 
+        let mode = SynthesisMode::Prove {
+            construct_matrices: true,
+        };
+        let (cs, cm, pok) = Self::synthesize(circuit, pk, mode, progress)?;
+        debug_assert!(cs.is_satisfied().unwrap());
+
+        start_phase(progress, ProvingPhase::WitnessMap)?;
+        let lc_time = start_timer!(|| "Inlining LCs");
+        cs.finalize();
+        end_timer!(lc_time);
+
+        let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
+        let h = QAP::witness_map::<E::ScalarField, D<E::ScalarField>>(cs.clone())?;
+        end_timer!(witness_map_time);
+        progress.finish(ProvingPhase::WitnessMap);
+
+        let proof = Self::create_proof_from_witness_map(&cs, h, cm, pok, pk, r, s, progress);
+        end_timer!(prover_time);
+        proof
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s` and the provided
+    /// R1CS-to-QAP reduction with the constraint matrices cached in `ppk`, so that the circuit is
+    /// only synthesized for its witnesses. The job is reported to `progress` as in
+    /// `create_proof_with_progress`.
+    pub fn create_proof_with_prepared_key<C>(
+        circuit: C,
+        ppk: &PreparedProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        progress: &mut impl ProvingProgress,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let prover_time = start_timer!(|| "Groth16::Prover (prepared)");
+
+        let mode = SynthesisMode::Prove {
+            construct_matrices: false,
+        };
+        let (cs, cm, pok) = Self::synthesize(circuit, &ppk.pk, mode, progress)?;
+
+        start_phase(progress, ProvingPhase::WitnessMap)?;
+        let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
+        let full_assignment = {
+            let prover = cs.borrow().unwrap();
+            [
+                prover.instance_assignment.as_slice(),
+                prover.commitment_assignment.as_slice(),
+                prover.witness_assignment.as_slice(),
+                prover.committed_assignment.as_slice(),
+            ]
+            .concat()
+        };
+        let num_inputs = cs.num_instance_and_commitment_variables();
+        let num_constraints = cs.num_constraints();
+        if num_inputs != ppk.num_inputs
+            || num_constraints != ppk.matrices.a.len()
+            || full_assignment.len() != ppk.num_variables
+        {
+            return Err(SynthesisError::MismatchedProvingKey);
+        }
+        debug_assert!(ppk.is_satisfied(&full_assignment));
+        let h = QAP::witness_map_from_matrices::<E::ScalarField, D<E::ScalarField>>(
+            &ppk.matrices,
+            num_inputs,
+            num_constraints,
+            &full_assignment,
+        )?;
+        drop(full_assignment);
+        end_timer!(witness_map_time);
+        progress.finish(ProvingPhase::WitnessMap);
+
+        let proof = Self::create_proof_from_witness_map(&cs, h, cm, pok, &ppk.pk, r, s, progress);
+        end_timer!(prover_time);
+        proof
+    }
+
+    /// Synthesize `circuit` in `mode` and commit to the queries of its lookup argument, which
+    /// returns the constraint system, the commitment, and its proof of knowledge.
+    #[allow(clippy::type_complexity)]
+    fn synthesize<C>(
+        circuit: C,
+        pk: &ProvingKey<E>,
+        mode: SynthesisMode,
+        progress: &mut impl ProvingProgress,
+    ) -> R1CSResult<(
+        ConstraintSystemRef<E::ScalarField>,
+        Option<E::G1Affine>,
+        Option<E::G1Affine>,
+    )>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
         let mut cm = None;
         let mut pok = None;
 
@@ -64,16 +191,25 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
 
         // Set the optimization goal
         cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_mode(mode);
 
         // Synthesize the circuit.
         let synthesis_time = start_timer!(|| "Constraint synthesis");
+        start_phase(progress, ProvingPhase::Synthesis)?;
         circuit.generate_constraints(cs.clone())?;
+        progress.finish(ProvingPhase::Synthesis);
+        start_phase(progress, ProvingPhase::Commitment)?;
         generate_commitment(cs.clone(), |cs| {
-            let committed_assignment = &cs.borrow().unwrap().committed_assignment;
-            let c = E::G1::msm_unchecked(&pk.k_query, committed_assignment).into_affine();
+            let committed_assignment = cs
+                .borrow()
+                .unwrap()
+                .committed_assignment
+                .iter()
+                .map(|s| s.into_bigint())
+                .collect::<Vec<_>>();
+            let c = msm_g1::<E>(&pk.k_query, &committed_assignment).into_affine();
             cm = Some(c);
-            pok =
-                Some(E::G1::msm_unchecked(&pk.k_query_sigma, &committed_assignment).into_affine());
+            pok = Some(msm_g1::<E>(&pk.k_query_sigma, &committed_assignment).into_affine());
             let mut serialized = vec![0; c.serialized_size(Compress::Yes)];
             c.serialize_compressed(&mut serialized[..]).unwrap();
             <DefaultFieldHasher<Sha256> as HashToField<E::ScalarField>>::new(&[])
@@ -81,24 +217,32 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
                 .get(0)
                 .copied()
         })?;
-
-        debug_assert!(cs.is_satisfied().unwrap());
+        progress.finish(ProvingPhase::Commitment);
         end_timer!(synthesis_time);
 
-        let lc_time = start_timer!(|| "Inlining LCs");
-        cs.finalize();
-        end_timer!(lc_time);
-
-        let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
-        let h = QAP::witness_map::<E::ScalarField, D<E::ScalarField>>(cs.clone())?;
-        end_timer!(witness_map_time);
+        Ok((cs, cm, pok))
+    }
 
+    /// Compute the proof from the synthesized constraint system `cs` and the coefficients `h` of
+    /// the quotient polynomial.
+    #[allow(clippy::too_many_arguments)]
+    fn create_proof_from_witness_map(
+        cs: &ConstraintSystemRef<E::ScalarField>,
+        h: Vec<E::ScalarField>,
+        cm: Option<E::G1Affine>,
+        pok: Option<E::G1Affine>,
+        pk: &ProvingKey<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        progress: &mut impl ProvingProgress,
+    ) -> R1CSResult<Proof<E>> {
+        start_phase(progress, ProvingPhase::MsmC)?;
         let prover = cs.borrow().unwrap();
         let c_acc_time = start_timer!(|| "Compute C");
         let h_assignment = cfg_into_iter!(h)
             .map(|s| s.into_bigint())
             .collect::<Vec<_>>();
-        let h_acc = E::G1::msm_bigint(&pk.h_query, &h_assignment[..h_assignment.len() - 1]);
+        let h_acc = msm_g1::<E>(&pk.h_query, &h_assignment[..h_assignment.len() - 1]);
         drop(h_assignment);
 
         // Compute C
@@ -106,7 +250,7 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
             .map(|s| s.into_bigint())
             .collect::<Vec<_>>();
 
-        let l_aux_acc = E::G1::msm_bigint(&pk.l_query, &aux_assignment);
+        let l_aux_acc = msm_g1::<E>(&pk.l_query, &aux_assignment);
 
         let r_s_delta_g1 = pk
             .delta_g1
@@ -115,6 +259,7 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
             .mul_bigint(&s.into_bigint());
 
         end_timer!(c_acc_time);
+        progress.finish(ProvingPhase::MsmC);
 
         let assignment = [
             &prover.instance_assignment[1..]
@@ -133,19 +278,32 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         drop(aux_assignment);
 
         // Compute A
+        start_phase(progress, ProvingPhase::MsmA)?;
         let a_acc_time = start_timer!(|| "Compute A");
         let r_g1 = pk.delta_g1.mul(r);
 
-        let g_a = Self::calculate_coeff(r_g1, &pk.a_query, pk.vk.alpha_g1, &assignment);
+        let g_a = Self::calculate_coeff(
+            r_g1,
+            pk.a_query[0],
+            pk.vk.alpha_g1,
+            msm_g1::<E>(&pk.a_query[1..], &assignment),
+        );
 
         let s_g_a = g_a.mul_bigint(&s.into_bigint());
         end_timer!(a_acc_time);
+        progress.finish(ProvingPhase::MsmA);
 
         // Compute B in G1 if needed
+        start_phase(progress, ProvingPhase::MsmB)?;
         let g1_b = if !r.is_zero() {
             let b_g1_acc_time = start_timer!(|| "Compute B in G1");
             let s_g1 = pk.delta_g1.mul(s);
-            let g1_b = Self::calculate_coeff(s_g1, &pk.b_g1_query, pk.beta_g1, &assignment);
+            let g1_b = Self::calculate_coeff(
+                s_g1,
+                pk.b_g1_query[0],
+                pk.beta_g1,
+                msm_g1::<E>(&pk.b_g1_query[1..], &assignment),
+            );
 
             end_timer!(b_g1_acc_time);
 
@@ -157,11 +315,17 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         // Compute B in G2
         let b_g2_acc_time = start_timer!(|| "Compute B in G2");
         let s_g2 = pk.vk.delta_g2.mul(s);
-        let g2_b = Self::calculate_coeff(s_g2, &pk.b_g2_query, pk.vk.beta_g2, &assignment);
+        let g2_b = Self::calculate_coeff(
+            s_g2,
+            pk.b_g2_query[0],
+            pk.vk.beta_g2,
+            msm_g2::<E>(&pk.b_g2_query[1..], &assignment),
+        );
         let r_g1_b = g1_b.mul_bigint(&r.into_bigint());
         drop(assignment);
 
         end_timer!(b_g2_acc_time);
+        progress.finish(ProvingPhase::MsmB);
 
         let c_time = start_timer!(|| "Finish C");
         let mut g_c = s_g_a;
@@ -171,8 +335,6 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         g_c += &h_acc;
         end_timer!(c_time);
 
-        end_timer!(prover_time);
-
         Ok(Proof {
             a: g_a.into_affine(),
             b: g2_b.into_affine(),
@@ -182,18 +344,14 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         })
     }
 
+    /// Compute `initial + el + acc + vk_param`, where `acc` is the MSM of the rest of the query
+    /// and the assignment.
     fn calculate_coeff<G: AffineRepr>(
         initial: G::Group,
-        query: &[G],
+        el: G,
         vk_param: G,
-        assignment: &[<G::ScalarField as PrimeField>::BigInt],
-    ) -> G::Group
-    where
-        G::Group: VariableBaseMSM<MulBase = G>,
-    {
-        let el = query[0];
-        let acc = G::Group::msm_bigint(&query[1..], assignment);
-
+        acc: G::Group,
+    ) -> G::Group {
         let mut res = initial;
         res.add_assign(&el);
         res += &acc;
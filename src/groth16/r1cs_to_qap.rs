@@ -6,6 +6,7 @@ use rand::Rng;
 use crate::r1cs::{ConstraintMatrices, ConstraintSystemRef, Result as R1CSResult, SynthesisError};
 use core::ops::{AddAssign, Deref};
 
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 #[inline]
@@ -16,7 +17,10 @@ where
     RHS: Send + Sync + core::ops::Mul<&'a LHS, Output = RHS> + Copy,
     R: Zero + Send + Sync + AddAssign<RHS> + core::iter::Sum,
 {
-    let zero = || R::zero();
+    #[cfg(feature = "parallel")]
+    let zero = R::zero;
+    #[cfg(not(feature = "parallel"))]
+    let zero = R::zero();
 
     let res = cfg_iter!(terms).fold(zero, |mut sum, (coeff, index)| {
         let val = &assignment[*index];
@@ -30,7 +34,10 @@ where
         sum
     });
 
+    #[cfg(feature = "parallel")]
     return res.sum();
+    #[cfg(not(feature = "parallel"))]
+    return res;
 }
 
 /// Computes instance and witness reductions from R1CS to
@@ -0,0 +1,307 @@
+//! Conversion of verifying keys, proofs, and public inputs to and from the JSON files of
+//! [snarkjs](https://github.com/iden3/snarkjs) (`verification_key.json`, `proof.json`, and
+//! `public.json`), so that proofs of this crate can be verified by `snarkjs groth16 verify` and the
+//! tooling built on it, and keys and proofs of snarkjs can be verified by `Groth16::verify`.
+//!
+//! Only BN254 (`bn128` in snarkjs) is supported. Points are encoded in projective coordinates
+//! `[x, y, z]`, where finite points have `z = 1`, the point at infinity has `z = 0`, and each
+//! coordinate in `G2` is `[c0, c1]` with the real part first. `vk_alphabeta_12` is neither written
+//! nor checked, since snarkjs does not use it for verification.
+//!
+//! snarkjs implements plain Groth16, without the commitment of the lookup argument that the
+//! floating-point gadgets use, so only proofs of circuits without lookups (e.g., circuits on
+//! `FpVar`s only) can be exported. Proving keys are not converted to `.zkey` files, whose `H`
+//! points are in the basis of the QAP reduction of snarkjs rather than `LibsnarkReduction`.
+
+use ark_bn254::{Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{One, PrimeField, Zero};
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    json::{inputs_from_json, inputs_to_json, JsonPoint},
+    Proof, VerifyingKey,
+};
+use crate::Error;
+
+/// The curve of the keys and proofs of snarkjs.
+pub type Curve = ark_bn254::Bn254;
+
+/// The projective coordinates of a point in `G1`.
+type SnarkjsG1 = [String; 3];
+
+/// The projective coordinates of a point in `G2`.
+type SnarkjsG2 = [[String; 2]; 3];
+
+#[derive(Serialize, Deserialize)]
+struct SnarkjsProof {
+    pi_a: SnarkjsG1,
+    pi_b: SnarkjsG2,
+    pi_c: SnarkjsG1,
+    protocol: String,
+    curve: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[allow(non_snake_case)]
+struct SnarkjsVerifyingKey {
+    protocol: String,
+    curve: String,
+    nPublic: usize,
+    vk_alpha_1: SnarkjsG1,
+    vk_beta_2: SnarkjsG2,
+    vk_gamma_2: SnarkjsG2,
+    vk_delta_2: SnarkjsG2,
+    IC: Vec<SnarkjsG1>,
+}
+
+fn encode_fq(x: Fq) -> String {
+    BigUint::from(x).to_string()
+}
+
+fn decode_fq(x: &str) -> Result<Fq, Error> {
+    let n = x
+        .parse::<BigUint>()
+        .map_err(|_| Error::Import(format!("invalid field element `{x}`")))?;
+    if n >= Fq::MODULUS.into() {
+        return Err(Error::Import(format!("non-canonical field element `{x}`")));
+    }
+    Ok(Fq::from(n))
+}
+
+fn decode_fq2([c0, c1]: &[String; 2]) -> Result<Fq2, Error> {
+    Ok(Fq2::new(decode_fq(c0)?, decode_fq(c1)?))
+}
+
+fn encode_g1(p: &G1Affine) -> SnarkjsG1 {
+    match p.xy() {
+        Some((&x, &y)) => [x, y, Fq::one()].map(encode_fq),
+        None => [Fq::zero(), Fq::one(), Fq::zero()].map(encode_fq),
+    }
+}
+
+fn encode_g2(p: &G2Affine) -> SnarkjsG2 {
+    let [x, y, z] = match p.xy() {
+        Some((&x, &y)) => [x, y, Fq2::one()],
+        None => [Fq2::zero(), Fq2::one(), Fq2::zero()],
+    };
+    [x, y, z].map(|c| [encode_fq(c.c0), encode_fq(c.c1)])
+}
+
+/// Decode a point from its coordinates `x`, `y`, and `z`, where `z` is 1 for finite points and 0
+/// for the point at infinity.
+fn decode_point<G: JsonPoint>(
+    x: G::BaseField,
+    y: G::BaseField,
+    z: G::BaseField,
+) -> Result<G, Error> {
+    if z.is_zero() {
+        Ok(G::zero())
+    } else if z.is_one() {
+        G::from_xy(x, y).ok_or_else(|| Error::Import("invalid point".into()))
+    } else {
+        Err(Error::Import(
+            "points should be normalized to z = 0 or 1".into(),
+        ))
+    }
+}
+
+fn decode_g1([x, y, z]: &SnarkjsG1) -> Result<G1Affine, Error> {
+    decode_point(decode_fq(x)?, decode_fq(y)?, decode_fq(z)?)
+}
+
+fn decode_g2([x, y, z]: &SnarkjsG2) -> Result<G2Affine, Error> {
+    decode_point(decode_fq2(x)?, decode_fq2(y)?, decode_fq2(z)?)
+}
+
+fn check_header(protocol: &str, curve: &str) -> Result<(), Error> {
+    if protocol != "groth16" {
+        return Err(Error::Import(format!("unsupported protocol `{protocol}`")));
+    }
+    if curve != "bn128" {
+        return Err(Error::Import(format!("unsupported curve `{curve}`")));
+    }
+    Ok(())
+}
+
+/// Encode `proof` as the `proof.json` of snarkjs, or fail if it contains a commitment, which
+/// snarkjs cannot verify.
+pub fn proof_to_snarkjs(proof: &Proof<Curve>) -> Result<String, Error> {
+    if proof.cm.is_some() || proof.pok.is_some() {
+        return Err(Error::Conversion(
+            "proofs with commitments cannot be verified by snarkjs".into(),
+        ));
+    }
+    Ok(serde_json::to_string_pretty(&SnarkjsProof {
+        pi_a: encode_g1(&proof.a),
+        pi_b: encode_g2(&proof.b),
+        pi_c: encode_g1(&proof.c),
+        protocol: "groth16".into(),
+        curve: "bn128".into(),
+    })
+    .unwrap())
+}
+
+/// Decode a proof from the `proof.json` of snarkjs.
+pub fn proof_from_snarkjs(json: &str) -> Result<Proof<Curve>, Error> {
+    let proof: SnarkjsProof =
+        serde_json::from_str(json).map_err(|e| Error::Import(e.to_string()))?;
+    check_header(&proof.protocol, &proof.curve)?;
+    Ok(Proof {
+        a: decode_g1(&proof.pi_a)?,
+        b: decode_g2(&proof.pi_b)?,
+        c: decode_g1(&proof.pi_c)?,
+        cm: None,
+        pok: None,
+    })
+}
+
+/// Encode `vk` as the `verification_key.json` of snarkjs. The key of a circuit with lookups has
+/// an extra entry in `IC` for the challenge of the commitment, but its proofs cannot be exported.
+pub fn verifying_key_to_snarkjs(vk: &VerifyingKey<Curve>) -> String {
+    serde_json::to_string_pretty(&SnarkjsVerifyingKey {
+        protocol: "groth16".into(),
+        curve: "bn128".into(),
+        nPublic: vk.gamma_abc_g1.len().saturating_sub(1),
+        vk_alpha_1: encode_g1(&vk.alpha_g1),
+        vk_beta_2: encode_g2(&vk.beta_g2),
+        vk_gamma_2: encode_g2(&vk.gamma_g2),
+        vk_delta_2: encode_g2(&vk.delta_g2),
+        IC: vk.gamma_abc_g1.iter().map(encode_g1).collect(),
+    })
+    .unwrap()
+}
+
+/// Decode a verifying key from the `verification_key.json` of snarkjs, which has no commitment
+/// key, so only proofs without commitments are accepted with it.
+pub fn verifying_key_from_snarkjs(json: &str) -> Result<VerifyingKey<Curve>, Error> {
+    let vk: SnarkjsVerifyingKey =
+        serde_json::from_str(json).map_err(|e| Error::Import(e.to_string()))?;
+    check_header(&vk.protocol, &vk.curve)?;
+    if vk.IC.len() != vk.nPublic + 1 {
+        return Err(Error::Import(format!(
+            "expected {} IC points for {} public inputs, found {}",
+            vk.nPublic + 1,
+            vk.nPublic,
+            vk.IC.len()
+        )));
+    }
+    Ok(VerifyingKey {
+        alpha_g1: decode_g1(&vk.vk_alpha_1)?,
+        beta_g2: decode_g2(&vk.vk_beta_2)?,
+        gamma_g2: decode_g2(&vk.vk_gamma_2)?,
+        delta_g2: decode_g2(&vk.vk_delta_2)?,
+        gamma_abc_g1: vk.IC.iter().map(decode_g1).collect::<Result<_, _>>()?,
+        pedersen_g: G2Affine::zero(),
+        pedersen_g_inv_neg_sigma: G2Affine::zero(),
+    })
+}
+
+/// Encode the public inputs as the `public.json` of snarkjs, i.e., an array of decimal strings.
+pub fn public_to_snarkjs(inputs: &[Fr]) -> String {
+    inputs_to_json(inputs)
+}
+
+/// Decode the public inputs from the `public.json` of snarkjs.
+pub fn public_from_snarkjs(json: &str) -> Result<Vec<Fr>, Error> {
+    inputs_from_json(json).map_err(|e| Error::Import(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use ark_std::test_rng;
+    use rand::{RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{
+        f64::F64Var,
+        groth16::{CircuitSpecificSetupSNARK, Groth16, SNARK},
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+        r1cs_std::{
+            fields::{fp::FpVar, FieldVar},
+            prelude::{AllocVar, EqGadget},
+        },
+    };
+
+    /// A circuit without lookups, proving the knowledge of a square root of the public input.
+    struct SquareCircuit(Fr);
+
+    impl ConstraintSynthesizer<Fr> for SquareCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let x = FpVar::new_witness(cs.clone(), || Ok(self.0))?;
+            let y = FpVar::new_input(cs, || Ok(self.0 * self.0))?;
+            x.square()?.enforce_equal(&y)
+        }
+    }
+
+    struct AddCircuit;
+
+    impl ConstraintSynthesizer<Fr> for AddCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let a = F64Var::new_witness(cs.clone(), || Ok(0.1))?;
+            let b = F64Var::new_input(cs.clone(), || Ok(0.2))?;
+            (a + b).enforce_equal(&F64Var::new_input(cs, || Ok(0.1 + 0.2))?)
+        }
+    }
+
+    #[test]
+    fn schema() -> Result<(), Box<dyn Error>> {
+        let proof = Proof::<Curve> {
+            a: G1Affine::generator(),
+            b: G2Affine::generator(),
+            c: G1Affine::zero(),
+            cm: None,
+            pok: None,
+        };
+        let json = serde_json::to_string(&serde_json::from_str::<serde_json::Value>(
+            &proof_to_snarkjs(&proof)?,
+        )?)?;
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"curve":"bn128","pi_a":["1","2","1"],"#,
+                r#""pi_b":[["10857046999023057135944570762232829481370756359578518086990519993285655852781","#,
+                r#""11559732032986387107991004021392285783925812861821192530917403151452391805634"],"#,
+                r#"["8495653923123431417604973247489272438418190587263600148770280649306958101930","#,
+                r#""4082367875863433681332203403145435568316851327593401208105741076214120093531"],"#,
+                r#"["1","0"]],"pi_c":["0","1","0"],"protocol":"groth16"}"#,
+            )
+        );
+        assert_eq!(proof_from_snarkjs(&json)?, proof);
+
+        // Points off the curve, unnormalized points, and other curves are rejected.
+        for json in [
+            json.replacen(r#"["1","2","1"]"#, r#"["1","3","1"]"#, 1),
+            json.replacen(r#"["1","2","1"]"#, r#"["2","4","2"]"#, 1),
+            json.replacen("bn128", "bls12381", 1),
+        ] {
+            assert!(proof_from_snarkjs(&json).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip() -> Result<(), Box<dyn Error>> {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let (pk, vk) = Groth16::<Curve>::setup(SquareCircuit(Fr::zero()), rng)?;
+        let proof = Groth16::<Curve>::prove(&pk, SquareCircuit(Fr::from(3u64)), rng)?;
+
+        let vk = verifying_key_from_snarkjs(&verifying_key_to_snarkjs(&vk))?;
+        let proof = proof_from_snarkjs(&proof_to_snarkjs(&proof)?)?;
+        let inputs = public_from_snarkjs(&public_to_snarkjs(&[Fr::from(9u64)]))?;
+        assert_eq!(inputs, [Fr::from(9u64)]);
+        assert!(Groth16::<Curve>::verify(&vk, &inputs, &proof)?);
+        assert!(!Groth16::<Curve>::verify(&vk, &[Fr::from(10u64)], &proof)?);
+
+        // The proofs of circuits with lookups have commitments, which snarkjs cannot verify.
+        let (pk, _) = Groth16::<Curve>::setup(AddCircuit, rng)?;
+        let proof = Groth16::<Curve>::prove(&pk, AddCircuit, rng)?;
+        assert!(proof_to_snarkjs(&proof).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,448 @@
+use std::fmt::Write;
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use num::BigUint;
+use sha3::{Digest, Keccak256};
+
+use super::{Proof, VerifyingKey};
+use crate::r1cs::{Result as R1CSResult, SynthesisError};
+
+/// Since the only pairing-friendly curve with precompiled contracts on Ethereum is BN254,
+/// the exported verifier only supports proofs over BN254.
+pub type Curve = Bn254;
+
+/// Format an element of a prime field as a decimal literal.
+fn decimal<F: PrimeField>(x: F) -> String {
+    let x: BigUint = x.into();
+    x.to_string()
+}
+
+/// Get the coordinates of a point in G1 in the form accepted by the precompiled contracts,
+/// where the point at infinity is encoded as `(0, 0)`.
+fn g1_coordinates(p: &G1Affine) -> [Fq; 2] {
+    p.xy().map_or([Fq::zero(); 2], |(&x, &y)| [x, y])
+}
+
+/// Get the coordinates of a point in G2 in the form accepted by the precompiled contracts,
+/// where the imaginary part of each coordinate comes before the real part, and the point at
+/// infinity is encoded as `(0, 0, 0, 0)`.
+fn g2_coordinates(p: &G2Affine) -> [Fq; 4] {
+    let (x, y) = p.xy().map_or((Fq2::zero(), Fq2::zero()), |(&x, &y)| (x, y));
+    [x.c1, x.c0, y.c1, y.c0]
+}
+
+fn g1_constant(source: &mut String, name: &str, p: &G1Affine) {
+    for (coordinate, v) in ["X", "Y"].iter().zip(g1_coordinates(p)) {
+        writeln!(
+            source,
+            "    uint256 constant {name}_{coordinate} = {};",
+            decimal(v)
+        )
+        .unwrap();
+    }
+}
+
+fn g2_constant(source: &mut String, name: &str, p: &G2Affine) {
+    for (coordinate, v) in ["X_IM", "X_RE", "Y_IM", "Y_RE"]
+        .iter()
+        .zip(g2_coordinates(p))
+    {
+        writeln!(
+            source,
+            "    uint256 constant {name}_{coordinate} = {};",
+            decimal(v)
+        )
+        .unwrap();
+    }
+}
+
+/// Get the number of public inputs expected by the verifier, excluding the challenge of the
+/// commitment, which is computed by the verifier itself.
+/// Return an error if `vk` has fewer elements in `gamma_abc_g1` than the constant term and the
+/// commitment require.
+fn num_inputs(vk: &VerifyingKey<Curve>, has_commitment: bool) -> R1CSResult<usize> {
+    vk.gamma_abc_g1
+        .len()
+        .checked_sub(1 + has_commitment as usize)
+        .ok_or(SynthesisError::MalformedVerifyingKey)
+}
+
+/// Get the signature of the `verifyProof` function of the exported verifier.
+fn function_signature(num_inputs: usize, has_commitment: bool) -> String {
+    if has_commitment {
+        format!("verifyProof(uint256[8],uint256[4],uint256[{num_inputs}])")
+    } else {
+        format!("verifyProof(uint256[8],uint256[{num_inputs}])")
+    }
+}
+
+const HELPERS: &str = r#"
+    function negate(uint256[2] memory p) internal pure returns (uint256[2] memory) {
+        if (p[0] == 0 && p[1] == 0) {
+            return p;
+        }
+        return [p[0], Q - p[1]];
+    }
+
+    function ecAdd(uint256[2] memory p, uint256[2] memory q) internal view returns (uint256[2] memory r) {
+        uint256[4] memory input = [p[0], p[1], q[0], q[1]];
+        bool success;
+        assembly {
+            success := staticcall(gas(), 0x06, input, 0x80, r, 0x40)
+        }
+        require(success, "ec add failed");
+    }
+
+    function ecMul(uint256[2] memory p, uint256 s) internal view returns (uint256[2] memory r) {
+        uint256[3] memory input = [p[0], p[1], s];
+        bool success;
+        assembly {
+            success := staticcall(gas(), 0x07, input, 0x60, r, 0x40)
+        }
+        require(success, "ec mul failed");
+    }
+
+    function pairing(uint256[] memory input) internal view returns (bool) {
+        uint256[1] memory out;
+        bool success;
+        assembly {
+            success := staticcall(gas(), 0x08, add(input, 0x20), mul(mload(input), 0x20), out, 0x20)
+        }
+        return success && out[0] == 1;
+    }
+"#;
+
+const COMMITMENT_HELPERS: &str = r#"
+    function reverseBytes(uint256 v) internal pure returns (uint256) {
+        v = ((v & 0xFF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00) >> 8)
+            | ((v & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF) << 8);
+        v = ((v & 0xFFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000) >> 16)
+            | ((v & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF) << 16);
+        v = ((v & 0xFFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000) >> 32)
+            | ((v & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF) << 32);
+        v = ((v & 0xFFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF0000000000000000) >> 64)
+            | ((v & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF) << 64);
+        return (v >> 128) | (v << 128);
+    }
+
+    /// Compute the challenge from the commitment in the same way as the prover, i.e., apply
+    /// `expand_message_xmd` with SHA-256 and an empty domain separation tag to the compressed
+    /// (little-endian, with flags in the most significant bits) encoding of the commitment,
+    /// and reduce the first 48 bytes of the result modulo the scalar field order.
+    function challenge(uint256[2] memory cm) internal pure returns (uint256) {
+        uint256 x = cm[0];
+        if (cm[0] == 0 && cm[1] == 0) {
+            x = 1 << 254;
+        } else if (cm[1] > (Q - 1) / 2) {
+            x |= 1 << 255;
+        }
+        bytes32 message = bytes32(reverseBytes(x));
+        bytes32 b0 = sha256(abi.encodePacked(bytes32(0), bytes16(0), message, uint16(48), uint8(0), uint8(0)));
+        bytes32 b1 = sha256(abi.encodePacked(b0, uint8(1), uint8(0)));
+        bytes32 b2 = sha256(abi.encodePacked(b0 ^ b1, uint8(2), uint8(0)));
+        return addmod(mulmod(uint256(b1), 1 << 128, R), uint256(b2) >> 128, R);
+    }
+"#;
+
+/// Export a Solidity contract named `Groth16Verifier` that verifies proofs for the verifying
+/// key `vk`.
+/// `has_commitment` should be true if and only if the circuit uses lookups, i.e., the proofs
+/// contain the commitment `cm` and the proof of knowledge `pok`, in which case the verifier
+/// also checks the proof of knowledge and derives the challenge from `cm` on its own.
+///
+/// The contract exposes `verifyProof(uint256[8] proof, uint256[4] commitment, uint256[N] input)`
+/// (or `verifyProof(uint256[8] proof, uint256[N] input)` if `has_commitment` is false), whose
+/// arguments can be produced by [`encode_proof`] and [`encode_inputs`], or encoded together as
+/// calldata by [`encode_calldata`].
+/// Return an error if `vk` is malformed, e.g., it lacks the input of the commitment although
+/// `has_commitment` is true.
+pub fn export_verifier(vk: &VerifyingKey<Curve>, has_commitment: bool) -> R1CSResult<String> {
+    let n = num_inputs(vk, has_commitment)?;
+
+    let mut source = String::new();
+    source.push_str("// SPDX-License-Identifier: MIT\n");
+    source.push_str("pragma solidity ^0.8.0;\n\n");
+    source.push_str("/// Groth16 verifier generated by `r1cs_float`.\n");
+    source.push_str("contract Groth16Verifier {\n");
+    writeln!(source, "    uint256 constant R = {};", Fr::MODULUS).unwrap();
+    writeln!(source, "    uint256 constant Q = {};", Fq::MODULUS).unwrap();
+    source.push('\n');
+    g1_constant(&mut source, "ALPHA", &vk.alpha_g1);
+    g2_constant(&mut source, "BETA", &vk.beta_g2);
+    g2_constant(&mut source, "GAMMA", &vk.gamma_g2);
+    g2_constant(&mut source, "DELTA", &vk.delta_g2);
+    for (i, p) in vk.gamma_abc_g1.iter().enumerate() {
+        g1_constant(&mut source, &format!("IC{i}"), p);
+    }
+    if has_commitment {
+        g2_constant(&mut source, "PEDERSEN_G", &vk.pedersen_g);
+        g2_constant(
+            &mut source,
+            "PEDERSEN_G_INV_NEG_SIGMA",
+            &vk.pedersen_g_inv_neg_sigma,
+        );
+    }
+
+    source.push_str(HELPERS);
+    if has_commitment {
+        source.push_str(COMMITMENT_HELPERS);
+    }
+
+    source.push('\n');
+    if has_commitment {
+        writeln!(
+            source,
+            "    function verifyProof(uint256[8] calldata proof, uint256[4] calldata commitment, uint256[{n}] calldata input) public view returns (bool) {{"
+        )
+        .unwrap();
+    } else {
+        writeln!(
+            source,
+            "    function verifyProof(uint256[8] calldata proof, uint256[{n}] calldata input) public view returns (bool) {{"
+        )
+        .unwrap();
+    }
+    source.push_str("        for (uint256 i = 0; i < input.length; i++) {\n");
+    source.push_str("            if (input[i] >= R) {\n");
+    source.push_str("                return false;\n");
+    source.push_str("            }\n");
+    source.push_str("        }\n\n");
+    source.push_str("        uint256[2] memory x = [IC0_X, IC0_Y];\n");
+    for i in 0..n {
+        writeln!(
+            source,
+            "        x = ecAdd(x, ecMul([IC{}_X, IC{}_Y], input[{i}]));",
+            i + 1,
+            i + 1
+        )
+        .unwrap();
+    }
+    if has_commitment {
+        source.push_str("\n        uint256[2] memory cm = [commitment[0], commitment[1]];\n");
+        source.push_str("        uint256[] memory pok = new uint256[](12);\n");
+        for (i, v) in [
+            "cm[0]",
+            "cm[1]",
+            "PEDERSEN_G_X_IM",
+            "PEDERSEN_G_X_RE",
+            "PEDERSEN_G_Y_IM",
+            "PEDERSEN_G_Y_RE",
+            "commitment[2]",
+            "commitment[3]",
+            "PEDERSEN_G_INV_NEG_SIGMA_X_IM",
+            "PEDERSEN_G_INV_NEG_SIGMA_X_RE",
+            "PEDERSEN_G_INV_NEG_SIGMA_Y_IM",
+            "PEDERSEN_G_INV_NEG_SIGMA_Y_RE",
+        ]
+        .iter()
+        .enumerate()
+        {
+            writeln!(source, "        pok[{i}] = {v};").unwrap();
+        }
+        source.push_str("        if (!pairing(pok)) {\n");
+        source.push_str("            return false;\n");
+        source.push_str("        }\n");
+        writeln!(
+            source,
+            "        x = ecAdd(x, ecMul([IC{}_X, IC{}_Y], challenge(cm)));",
+            n + 1,
+            n + 1
+        )
+        .unwrap();
+        source.push_str("        x = ecAdd(x, cm);\n");
+    }
+
+    source.push_str("\n        uint256[2] memory a = negate([proof[0], proof[1]]);\n");
+    source.push_str("        uint256[] memory p = new uint256[](24);\n");
+    for (i, v) in [
+        "a[0]",
+        "a[1]",
+        "proof[2]",
+        "proof[3]",
+        "proof[4]",
+        "proof[5]",
+        "ALPHA_X",
+        "ALPHA_Y",
+        "BETA_X_IM",
+        "BETA_X_RE",
+        "BETA_Y_IM",
+        "BETA_Y_RE",
+        "x[0]",
+        "x[1]",
+        "GAMMA_X_IM",
+        "GAMMA_X_RE",
+        "GAMMA_Y_IM",
+        "GAMMA_Y_RE",
+        "proof[6]",
+        "proof[7]",
+        "DELTA_X_IM",
+        "DELTA_X_RE",
+        "DELTA_Y_IM",
+        "DELTA_Y_RE",
+    ]
+    .iter()
+    .enumerate()
+    {
+        writeln!(source, "        p[{i}] = {v};").unwrap();
+    }
+    source.push_str("        return pairing(p);\n");
+    source.push_str("    }\n");
+    source.push_str("}\n");
+
+    Ok(source)
+}
+
+/// Encode an element of a prime field as a big-endian `uint256`.
+pub fn encode_uint256<F: PrimeField>(x: F) -> [u8; 32] {
+    let bytes = x.into_bigint().to_bytes_be();
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+/// Encode the public inputs as `uint256`s, e.g., the concatenation of
+/// [`crate::float::FloatVar::verifier_input`] for all public `FloatVar`s in the order they are
+/// allocated.
+pub fn encode_inputs(inputs: &[Fr]) -> Vec<[u8; 32]> {
+    inputs.iter().map(|&x| encode_uint256(x)).collect()
+}
+
+/// Encode the proof as `uint256`s, which are the coordinates of `A`, `B`, and `C` (8 words),
+/// followed by the coordinates of `cm` and `pok` (4 words) if the proof contains a commitment.
+pub fn encode_proof(proof: &Proof<Curve>) -> Vec<[u8; 32]> {
+    let mut words = vec![];
+    words.extend(g1_coordinates(&proof.a));
+    words.extend(g2_coordinates(&proof.b));
+    words.extend(g1_coordinates(&proof.c));
+    if let (Some(cm), Some(pok)) = (proof.cm, proof.pok) {
+        words.extend(g1_coordinates(&cm));
+        words.extend(g1_coordinates(&pok));
+    }
+    words.into_iter().map(encode_uint256).collect()
+}
+
+/// Encode a call to `verifyProof` of the exported verifier, including the function selector.
+pub fn encode_calldata(proof: &Proof<Curve>, inputs: &[Fr]) -> Vec<u8> {
+    let signature = function_signature(inputs.len(), proof.cm.is_some());
+    let mut calldata = Keccak256::digest(signature.as_bytes())[..4].to_vec();
+    for word in encode_proof(proof).iter().chain(&encode_inputs(inputs)) {
+        calldata.extend_from_slice(word);
+    }
+    calldata
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+    use crate::{
+        f64::F64Var,
+        groth16::{prepare_verifying_key, Groth16},
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+        r1cs_std::prelude::{AllocVar, EqGadget},
+    };
+    use ark_ec::CurveGroup;
+    use ark_ff::field_hashers::{DefaultFieldHasher, HashToField};
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::{test_rng, UniformRand};
+    use sha2::Sha256;
+
+    struct AddCircuit {
+        a: f64,
+        b: f64,
+    }
+
+    impl ConstraintSynthesizer<Fr> for AddCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let a = F64Var::new_witness(cs.clone(), || Ok(self.a))?;
+            let b = F64Var::new_input(cs.clone(), || Ok(self.b))?;
+            let c = F64Var::new_input(cs.clone(), || Ok(self.a + self.b))?;
+            (a + b).enforce_equal(&c)
+        }
+    }
+
+    /// Compute the challenge step by step as the exported verifier does.
+    fn evm_challenge(cm: &G1Affine) -> Fr {
+        let [x, y] = g1_coordinates(cm);
+        let mut message = encode_uint256(x);
+        if cm.is_zero() {
+            message[0] |= 0x40;
+        } else if BigUint::from(y.into_bigint()) > (BigUint::from(Fq::MODULUS) - 1u8) / 2u8 {
+            message[0] |= 0x80;
+        }
+        message.reverse();
+
+        let b0 = Sha256::digest([&[0u8; 48][..], &message, &[0, 48, 0, 0]].concat());
+        let b1 = Sha256::digest([&b0[..], &[1, 0]].concat());
+        let b0_xor_b1 = b0.iter().zip(&b1).map(|(l, r)| l ^ r).collect::<Vec<_>>();
+        let b2 = Sha256::digest([&b0_xor_b1[..], &[2, 0]].concat());
+        Fr::from_be_bytes_mod_order(&[&b1[..], &b2[..16]].concat())
+    }
+
+    #[test]
+    fn challenge() {
+        let rng = &mut test_rng();
+        let hasher = <DefaultFieldHasher<Sha256> as HashToField<Fr>>::new(&[]);
+        for cm in (0..100)
+            .map(|_| ark_bn254::G1Projective::rand(rng).into_affine())
+            .chain([G1Affine::zero()])
+        {
+            let mut serialized = vec![];
+            cm.serialize_compressed(&mut serialized).unwrap();
+            let expected: Vec<Fr> = hasher.hash_to_field(&serialized, 1);
+            assert_eq!(expected[0], evm_challenge(&cm));
+        }
+    }
+
+    #[test]
+    fn export() -> Result<(), Box<dyn Error>> {
+        let rng = &mut test_rng();
+
+        let pk = Groth16::<Curve>::generate_random_parameters_with_reduction(
+            AddCircuit { a: 0., b: 0. },
+            rng,
+        )?;
+        let proof = Groth16::<Curve>::create_random_proof_with_reduction(
+            AddCircuit { a: 1.5, b: -0.1 },
+            &pk,
+            rng,
+        )?;
+        assert!(proof.cm.is_some());
+
+        let inputs = [
+            F64Var::verifier_input(-0.1),
+            F64Var::verifier_input(1.5 + -0.1),
+        ]
+        .concat();
+        assert!(Groth16::<Curve>::verify_proof(
+            &prepare_verifying_key(&pk.vk),
+            &proof,
+            &inputs
+        )?);
+
+        let source = export_verifier(&pk.vk, true)?;
+        assert!(source.contains("uint256[6] calldata input"));
+        assert!(source.contains(&format!("IC7_X = {}", decimal(pk.vk.gamma_abc_g1[7].x))));
+        assert!(!source.contains("IC8_X"));
+
+        let mut malformed = pk.vk.clone();
+        malformed.gamma_abc_g1.truncate(1);
+        assert!(export_verifier(&malformed, false).is_ok());
+        assert!(matches!(
+            export_verifier(&malformed, true),
+            Err(SynthesisError::MalformedVerifyingKey)
+        ));
+
+        let calldata = encode_calldata(&proof, &inputs);
+        assert_eq!(calldata.len(), 4 + 32 * (12 + 6));
+        assert_eq!(
+            &calldata[4 + 32 * 12..][..32],
+            &encode_uint256(Fr::from(1u8))
+        );
+
+        Ok(())
+    }
+}
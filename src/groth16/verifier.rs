@@ -1,8 +1,8 @@
-use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::field_hashers::{DefaultFieldHasher, HashToField};
-use ark_ff::PrimeField;
+use ark_ff::{Field, PrimeField};
 use ark_serialize::{CanonicalSerialize, Compress};
-use ark_std::One;
+use ark_std::{rand::RngCore, One, UniformRand, Zero};
 use sha2::Sha256;
 
 use super::{r1cs_to_qap::R1CSToQAP, Groth16};
@@ -23,6 +23,15 @@ pub fn prepare_verifying_key<E: Pairing>(vk: &VerifyingKey<E>) -> PreparedVerify
     }
 }
 
+/// Derive the challenge of the lookup argument from the commitment `cm`, which is appended to the
+/// public inputs.
+pub(crate) fn commitment_challenge<E: Pairing>(cm: &E::G1Affine) -> E::ScalarField {
+    let mut serialized = vec![0; cm.serialized_size(Compress::Yes)];
+    cm.serialize_compressed(&mut serialized[..]).unwrap();
+    let hasher = <DefaultFieldHasher<Sha256> as HashToField<E::ScalarField>>::new(&[]);
+    hasher.hash_to_field(&serialized, 1)[0]
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     /// Prepare proof inputs for use with [`verify_proof_with_prepared_inputs`], wrt the prepared
     /// verification key `pvk` and instance public inputs.
@@ -31,17 +40,9 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         public_inputs: &[E::ScalarField],
         cm: Option<E::G1Affine>,
     ) -> R1CSResult<E::G1> {
-        let public_inputs = if cm.is_none() {
-            public_inputs.to_owned()
-        } else {
-            let mut serialized = vec![0; cm.unwrap().serialized_size(Compress::Yes)];
-            cm.unwrap()
-                .serialize_compressed(&mut serialized[..])
-                .unwrap();
-            let hasher = <DefaultFieldHasher<Sha256> as HashToField<E::ScalarField>>::new(&[]);
-            let challenge: E::ScalarField = hasher.hash_to_field(&serialized, 1)[0];
-
-            vec![public_inputs, &[challenge]].concat()
+        let public_inputs = match cm {
+            Some(cm) => vec![public_inputs, &[commitment_challenge::<E>(&cm)]].concat(),
+            None => public_inputs.to_owned(),
         };
 
         if (public_inputs.len() + 1) != pvk.vk.gamma_abc_g1.len() {
@@ -90,19 +91,93 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         public_inputs: &[E::ScalarField],
     ) -> R1CSResult<bool> {
         let mut prepared_inputs = Self::prepare_inputs(pvk, public_inputs, proof.cm)?;
-        if proof.cm.is_some() {
-            if !E::multi_pairing(
-                &[proof.cm.unwrap(), proof.pok.unwrap()],
-                &[pvk.vk.pedersen_g, pvk.vk.pedersen_g_inv_neg_sigma],
-            )
-            .0
-            .is_one()
-            {
-                return Ok(false);
+        match (proof.cm, proof.pok) {
+            (Some(cm), Some(pok)) => {
+                if !E::multi_pairing(
+                    &[cm, pok],
+                    &[pvk.vk.pedersen_g, pvk.vk.pedersen_g_inv_neg_sigma],
+                )
+                .0
+                .is_one()
+                {
+                    return Ok(false);
+                }
+                prepared_inputs.add_assign(&cm);
             }
-            prepared_inputs.add_assign(&proof.cm.unwrap());
+            (None, None) => {}
+            // A commitment without its proof of knowledge (or vice versa) is malformed.
+            _ => return Ok(false),
         }
 
         Self::verify_proof_with_prepared_inputs(pvk, proof, &prepared_inputs)
     }
+
+    /// Verify a batch of Groth16 proofs against the same prepared verification key `pvk`, where
+    /// each proof comes with its instance public inputs.
+    /// The verification equations of all proofs and the proofs of knowledge of their commitments
+    /// are combined by a random linear combination with coefficients sampled from `rng`, so that
+    /// only `n + 4` pairings and a single final exponentiation are computed for `n` proofs.
+    /// If the batch is rejected, at least one of the proofs is invalid, and [`verify_proof`] can be
+    /// used to find out which ones.
+    pub fn batch_verify(
+        pvk: &PreparedVerifyingKey<E>,
+        proofs: &[(&Proof<E>, &[E::ScalarField])],
+        rng: &mut impl RngCore,
+    ) -> R1CSResult<bool> {
+        if proofs.is_empty() {
+            return Ok(true);
+        }
+
+        let mut r_sum = E::ScalarField::zero();
+        let mut r_a = vec![];
+        let mut b = vec![];
+        let mut r = vec![];
+        let mut x = vec![];
+        let mut c = vec![];
+        let mut s = vec![];
+        let mut cm = vec![];
+        let mut pok = vec![];
+        for (proof, public_inputs) in proofs {
+            let mut prepared_inputs = Self::prepare_inputs(pvk, public_inputs, proof.cm)?;
+            match (proof.cm, proof.pok) {
+                (Some(proof_cm), Some(proof_pok)) => {
+                    prepared_inputs.add_assign(&proof_cm);
+                    s.push(E::ScalarField::rand(rng));
+                    cm.push(proof_cm);
+                    pok.push(proof_pok);
+                }
+                (None, None) => {}
+                // As in `verify_proof`, a commitment without its proof of knowledge (or vice versa)
+                // is malformed.
+                _ => return Ok(false),
+            }
+
+            let r_i = E::ScalarField::rand(rng);
+            r_sum += r_i;
+            r_a.push(proof.a * r_i);
+            b.push(proof.b.into());
+            r.push(r_i);
+            x.push(prepared_inputs);
+            c.push(proof.c);
+        }
+
+        let x = E::G1::normalize_batch(&x);
+        let mut g1 = E::G1::normalize_batch(&r_a);
+        g1.push(E::G1::msm_unchecked(&x, &r).into_affine());
+        g1.push(E::G1::msm_unchecked(&c, &r).into_affine());
+        b.push(pvk.gamma_g2_neg_pc.clone());
+        b.push(pvk.delta_g2_neg_pc.clone());
+        if !s.is_empty() {
+            g1.push(E::G1::msm_unchecked(&cm, &s).into_affine());
+            g1.push(E::G1::msm_unchecked(&pok, &s).into_affine());
+            b.push(pvk.vk.pedersen_g.into());
+            b.push(pvk.vk.pedersen_g_inv_neg_sigma.into());
+        }
+
+        let qap = E::multi_miller_loop(g1, b);
+
+        let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+
+        Ok(test.0 == pvk.alpha_g1_beta_g2.pow(r_sum.into_bigint()))
+    }
 }
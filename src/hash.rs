@@ -0,0 +1,257 @@
+//! A hash function over `F` and its gadget, which are used to commit to private values (e.g., seeds
+//! and models) and to derive pseudorandom values from them in the circuits, together with Merkle
+//! trees of its hashes, which commit to datasets row by row.
+//!
+//! The hash is MiMC-p/p [AGR+16] over `F` in the Miyaguchi-Preneel mode [MOV96, §9.4.1], as in
+//! the MiMC hashes of circomlib and gnark. The MiMC block cipher `E_k(x)` applies `r` rounds of
+//! `x <- (x + k + c_i)^α` followed by the addition of the key `k`, where `α` is the smallest
+//! exponent in `{3, 5, 7}` such that `x^α` is a permutation of `F` (i.e., `gcd(α, p - 1) = 1`),
+//! which is 5 for the scalar fields of BLS12-381, BN254 and Pallas. The number of rounds is
+//! `r = ceil(log_α p)` (110 for the above fields), which is the number of rounds of MiMC-p/p in
+//! [AGR+16] with `α = 3` replaced by `α`, so that the degree `α^r` of the cipher reaches `p`
+//! and interpolation attacks fail [AGR+16]. The round constants `c_i` are derived from
+//! SHA-256, except `c_0 = 0` as in [AGR+16]. The inputs `m` are absorbed by
+//! `h <- E_h(m) + h + m`, starting from `h = 0` and ending with the number of inputs, so that
+//! inputs of different lengths have different hashes.
+//!
+//! As the rest of this crate, the hash has not been audited, and it is only as secure as MiMC
+//! with the above parameters.
+//!
+//! [AGR+16]: M. Albrecht, L. Grassi, C. Rechberger, A. Roy, and T. Tiessen. MiMC: Efficient
+//! Encryption and Cryptographic Hashing with Minimal Multiplicative Complexity. ASIACRYPT 2016.
+//! <https://eprint.iacr.org/2016/492>
+//!
+//! [MOV96]: A. Menezes, P. van Oorschot, and S. Vanstone. Handbook of Applied Cryptography. CRC
+//! Press, 1996.
+
+use std::rc::Rc;
+
+use ark_ff::PrimeField;
+use num::{BigUint, One, Zero};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    powers::cached,
+    r1cs::SynthesisError,
+    r1cs_std::{fields::fp::FpVar, prelude::FieldVar},
+};
+
+/// The parameters of MiMC over a field.
+struct Parameters<F> {
+    /// The exponent `α` of each round.
+    alpha: u64,
+    /// The round constants `c_i`, where `c_0 = 0`.
+    constants: Vec<F>,
+}
+
+impl<F: PrimeField> Parameters<F> {
+    /// The parameters of MiMC over `F`, see the module documentation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if none of 3, 5 and 7 is coprime to `p - 1`.
+    fn new() -> Self {
+        let p: BigUint = F::MODULUS.into();
+        let p_minus_one = &p - BigUint::one();
+        let alpha = [3u64, 5, 7]
+            .into_iter()
+            .find(|&alpha| !(&p_minus_one % alpha).is_zero())
+            .expect("no exponent in {3, 5, 7} is coprime to p - 1");
+        // The smallest `r` such that `α^r >= p`, i.e., `ceil(log_α p)`.
+        let mut rounds = 0;
+        let mut degree = BigUint::one();
+        while degree < p {
+            degree *= alpha;
+            rounds += 1;
+        }
+        let constants = (0..rounds)
+            .map(|i| match i {
+                0 => F::zero(),
+                _ => F::from_le_bytes_mod_order(
+                    &Sha256::new_with_prefix(b"r1cs_float/hash/mimc")
+                        .chain_update((i as u64).to_le_bytes())
+                        .finalize(),
+                ),
+            })
+            .collect();
+        Self { alpha, constants }
+    }
+}
+
+/// The cached parameters of MiMC over `F`.
+fn parameters<F: PrimeField>() -> Rc<Parameters<F>> {
+    cached(Parameters::new)
+}
+
+/// Hash `inputs` to a field element.
+pub fn hash<F: PrimeField>(inputs: &[F]) -> F {
+    let Parameters { alpha, constants } = &*parameters::<F>();
+    let length = F::from(inputs.len() as u64);
+
+    let mut h = F::zero();
+    for &m in inputs.iter().chain([&length]) {
+        let mut x = m;
+        for c in constants {
+            x = (x + h + c).pow([*alpha]);
+        }
+        // `E_h(m) = x + h`
+        h = (x + h) + h + m;
+    }
+    h
+}
+
+/// Hash `inputs` to a field element in the circuit, which is the same as `hash` on the values of
+/// `inputs`.
+pub fn hash_gadget<F: PrimeField>(inputs: &[FpVar<F>]) -> Result<FpVar<F>, SynthesisError> {
+    let parameters = parameters::<F>();
+    let Parameters { alpha, constants } = &*parameters;
+    let length = FpVar::constant(F::from(inputs.len() as u64));
+
+    let mut h = FpVar::zero();
+    for m in inputs.iter().chain([&length]) {
+        let mut x = m.clone();
+        for &c in constants {
+            x = (x + &h + c).pow_by_constant([*alpha])?;
+        }
+        h = (x + &h) + &h + m;
+    }
+    Ok(h)
+}
+
+/// A binary Merkle tree over the `leaves`, where each node is the hash of its two children, and
+/// the leaves are padded with 0 to a power of two.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerkleTree<F> {
+    /// The layers of the tree from the padded leaves to the root.
+    layers: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> MerkleTree<F> {
+    /// Build the tree over `leaves`.
+    pub fn new(mut leaves: Vec<F>) -> Self {
+        leaves.resize(leaves.len().next_power_of_two(), F::zero());
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let layer = layers.last().unwrap().chunks(2).map(|c| hash(c)).collect();
+            layers.push(layer);
+        }
+        Self { layers }
+    }
+
+    /// The leaves of the tree, including the padding.
+    pub fn leaves(&self) -> &[F] {
+        &self.layers[0]
+    }
+
+    /// The root of the tree, which commits to the leaves.
+    pub fn root(&self) -> F {
+        self.layers.last().unwrap()[0]
+    }
+
+    /// The siblings of the leaf at `index` from the bottom up, which prove that the leaf is in
+    /// the tree, see `Self::verify`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of the padded leaves.
+    pub fn path(&self, index: usize) -> Vec<F> {
+        let layers = &self.layers[..self.layers.len() - 1];
+        layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| layer[(index >> i) ^ 1])
+            .collect()
+    }
+
+    /// Check whether `leaf` is at `index` of the tree with `root`, given its `path`.
+    pub fn verify(root: F, leaf: F, index: usize, path: &[F]) -> bool {
+        let node =
+            path.iter()
+                .enumerate()
+                .fold(leaf, |node, (i, &sibling)| match (index >> i) & 1 {
+                    0 => hash(&[node, sibling]),
+                    _ => hash(&[sibling, node]),
+                });
+        index.checked_shr(path.len() as u32).unwrap_or(0) == 0 && node == root
+    }
+}
+
+/// Compute the root of `MerkleTree::new` on the values of `leaves` in the circuit.
+pub fn merkle_root_gadget<F: PrimeField>(leaves: &[FpVar<F>]) -> Result<FpVar<F>, SynthesisError> {
+    let mut layer = leaves.to_vec();
+    layer.resize(leaves.len().next_power_of_two(), FpVar::zero());
+    while layer.len() > 1 {
+        layer = layer.chunks(2).map(hash_gadget).collect::<Result<_, _>>()?;
+    }
+    Ok(layer.pop().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{r1cs::ConstraintSystem, r1cs_std::prelude::AllocVar, r1cs_std::R1CSVar};
+    use ark_bls12_381::Fr;
+
+    fn check_field<F: PrimeField>() -> Result<(), SynthesisError> {
+        let rng = &mut ark_std::test_rng();
+        let inputs = (0..3).map(|_| F::rand(rng)).collect::<Vec<_>>();
+
+        let cs = ConstraintSystem::<F>::new_ref();
+        let vars = Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(inputs.clone()))?;
+        let h = hash_gadget(&vars)?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(h.value()?, hash(&inputs));
+
+        // The hash depends on every input and on the number of inputs.
+        let mut changed = inputs.clone();
+        changed[1] += F::one();
+        assert_ne!(hash(&changed), hash(&inputs));
+        assert_ne!(hash(&inputs[..2]), hash(&inputs));
+        assert_ne!(hash(&[F::zero()]), hash(&[F::zero(), F::zero()]));
+        assert_ne!(hash::<F>(&[]), hash(&[F::zero()]));
+        Ok(())
+    }
+
+    #[test]
+    fn merkle_tree() -> Result<(), SynthesisError> {
+        let leaves = (1..=5u64).map(Fr::from).collect::<Vec<_>>();
+        let tree = MerkleTree::new(leaves.clone());
+        for (i, &leaf) in leaves.iter().enumerate() {
+            let path = tree.path(i);
+            assert_eq!(path.len(), 3);
+            assert!(MerkleTree::verify(tree.root(), leaf, i, &path));
+            assert!(!MerkleTree::verify(tree.root(), leaf + Fr::one(), i, &path));
+            assert!(!MerkleTree::verify(tree.root(), leaf, i ^ 1, &path));
+            assert!(!MerkleTree::verify(tree.root(), leaf, i + 8, &path));
+        }
+        assert_ne!(MerkleTree::new(leaves[..4].to_vec()).root(), tree.root());
+        assert_eq!(MerkleTree::new(vec![Fr::one()]).root(), Fr::one());
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let vars = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(leaves))?;
+        assert_eq!(merkle_root_gadget(&vars)?.value()?, tree.root());
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn parameters() {
+        fn check<F: PrimeField>() {
+            let parameters = super::parameters::<F>();
+            assert_eq!(parameters.alpha, 5);
+            assert_eq!(parameters.constants.len(), 110);
+            assert_eq!(parameters.constants[0], F::zero());
+            assert!(Rc::ptr_eq(&parameters, &super::parameters::<F>()));
+        }
+        check::<Fr>();
+        check::<ark_bn254::Fr>();
+        check::<ark_pallas::Fr>();
+    }
+
+    #[test]
+    fn consistency() -> Result<(), SynthesisError> {
+        check_field::<Fr>()?;
+        check_field::<ark_bn254::Fr>()?;
+        check_field::<ark_pallas::Fr>()
+    }
+}
@@ -0,0 +1,357 @@
+//! Import of linear models trained by other frameworks, so that they can be proven by the
+//! regression circuits (e.g., `InferenceCircuit`) without entering the coefficients manually.
+//!
+//! Two formats are supported:
+//!
+//! * The ONNX `LinearRegressor` operator of the `ai.onnx.ml` domain, e.g., exported from
+//!   scikit-learn by `skl2onnx`. The graph should consist of exactly one `LinearRegressor` node
+//!   with a single target and no post transform, since other nodes could change the predictions.
+//! * With the `serde` feature, the JSON object `{"coef_": [..], "intercept_": ..}` of the
+//!   attributes of a fitted scikit-learn `LinearRegression` (or `Ridge`, `Lasso`, etc.), e.g.,
+//!   written by `json.dump({"coef_": m.coef_.tolist(), "intercept_": m.intercept_}, f)`, where
+//!   `coef_` may also be a single row and `intercept_` a single element array.
+//!
+//! ONNX stores the coefficients in `binary32`, which are converted to `binary64` exactly, but the
+//! predictions of ONNX runtimes are computed in `binary32`, so they should be checked by
+//! `InferenceCircuit::with_tolerance` rather than for exact equality.
+
+use crate::{regression::LinearModel, Error};
+
+/// A field of a protobuf message, without its number.
+enum Value<'a> {
+    Varint(u64),
+    /// A 64-bit field, whose value is not used by any supported model.
+    Fixed64,
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Import(format!("invalid ONNX model: {message}"))
+}
+
+/// Read a varint from the start of `bytes`, and advance `bytes` past it.
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, Error> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes
+            .split_first()
+            .ok_or_else(|| invalid("truncated varint"))?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("varint is too long"))
+}
+
+/// Read `n` bytes from the start of `bytes`, and advance `bytes` past them.
+fn read_bytes<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], Error> {
+    if bytes.len() < n {
+        return Err(invalid("truncated field"));
+    }
+    let (value, rest) = bytes.split_at(n);
+    *bytes = rest;
+    Ok(value)
+}
+
+/// Decode the fields of a protobuf message as pairs of their numbers and values.
+fn fields(mut bytes: &[u8]) -> Result<Vec<(u64, Value<'_>)>, Error> {
+    let mut fields = vec![];
+    while !bytes.is_empty() {
+        let key = read_varint(&mut bytes)?;
+        let value = match key & 7 {
+            0 => Value::Varint(read_varint(&mut bytes)?),
+            1 => {
+                read_bytes(&mut bytes, 8)?;
+                Value::Fixed64
+            }
+            2 => {
+                let n = read_varint(&mut bytes)?;
+                Value::Bytes(read_bytes(&mut bytes, n as usize)?)
+            }
+            5 => Value::Fixed32(u32::from_le_bytes(
+                read_bytes(&mut bytes, 4)?.try_into().unwrap(),
+            )),
+            _ => return Err(invalid("unsupported wire type")),
+        };
+        fields.push((key >> 3, value));
+    }
+    Ok(fields)
+}
+
+/// The fields of protobuf messages in `onnx.proto` that are used for importing models.
+mod field {
+    pub const MODEL_GRAPH: u64 = 7;
+    pub const GRAPH_NODE: u64 = 1;
+    pub const NODE_OP_TYPE: u64 = 4;
+    pub const NODE_ATTRIBUTE: u64 = 5;
+    pub const NODE_DOMAIN: u64 = 7;
+    pub const ATTRIBUTE_NAME: u64 = 1;
+    pub const ATTRIBUTE_INT: u64 = 3;
+    pub const ATTRIBUTE_STRING: u64 = 4;
+    pub const ATTRIBUTE_FLOATS: u64 = 7;
+}
+
+/// Get the only field `number` of a message, or `None` if the message does not have it.
+fn single<'a, 'b>(
+    fields: &'b [(u64, Value<'a>)],
+    number: u64,
+) -> Result<Option<&'b Value<'a>>, Error> {
+    let mut values = fields.iter().filter(|(n, _)| *n == number).map(|(_, v)| v);
+    match (values.next(), values.next()) {
+        (value, None) => Ok(value),
+        _ => Err(invalid("repeated field")),
+    }
+}
+
+/// Get the only string field `number` of a message, or `None` if the message does not have it.
+fn string<'a>(fields: &[(u64, Value<'a>)], number: u64) -> Result<Option<&'a str>, Error> {
+    match single(fields, number)? {
+        None => Ok(None),
+        Some(Value::Bytes(bytes)) => std::str::from_utf8(bytes)
+            .map(Some)
+            .map_err(|_| invalid("string is not UTF-8")),
+        Some(_) => Err(invalid("string field has a wrong wire type")),
+    }
+}
+
+/// Get the repeated float field `number` of a message, which may be packed or not.
+fn floats(fields: &[(u64, Value)], number: u64) -> Result<Vec<f32>, Error> {
+    let mut values = vec![];
+    for (_, value) in fields.iter().filter(|(n, _)| *n == number) {
+        match value {
+            Value::Fixed32(v) => values.push(f32::from_bits(*v)),
+            Value::Bytes(bytes) if bytes.len() % 4 == 0 => values.extend(
+                bytes
+                    .chunks(4)
+                    .map(|v| f32::from_le_bytes(v.try_into().unwrap())),
+            ),
+            _ => return Err(invalid("float field has a wrong wire type")),
+        }
+    }
+    Ok(values)
+}
+
+/// Import a linear model from the serialized ONNX `ModelProto` `bytes`, whose graph consists of
+/// a single `LinearRegressor` node.
+pub fn from_onnx(bytes: &[u8]) -> Result<LinearModel, Error> {
+    let model = fields(bytes)?;
+    let graph = match single(&model, field::MODEL_GRAPH)? {
+        Some(Value::Bytes(graph)) => fields(graph)?,
+        _ => return Err(invalid("missing graph")),
+    };
+    let nodes = graph
+        .iter()
+        .filter(|(n, _)| *n == field::GRAPH_NODE)
+        .map(|(_, node)| match node {
+            Value::Bytes(node) => fields(node),
+            _ => Err(invalid("node has a wrong wire type")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let [node] = &nodes[..] else {
+        return Err(invalid("the graph should have exactly one node"));
+    };
+    let op_type = string(node, field::NODE_OP_TYPE)?;
+    let domain = string(node, field::NODE_DOMAIN)?;
+    if (op_type, domain) != (Some("LinearRegressor"), Some("ai.onnx.ml")) {
+        return Err(invalid(
+            "the node should be an `ai.onnx.ml.LinearRegressor`",
+        ));
+    }
+
+    let (mut coefficients, mut intercepts) = (None, vec![]);
+    for (_, attribute) in node.iter().filter(|(n, _)| *n == field::NODE_ATTRIBUTE) {
+        let Value::Bytes(attribute) = attribute else {
+            return Err(invalid("attribute has a wrong wire type"));
+        };
+        let attribute = fields(attribute)?;
+        match string(&attribute, field::ATTRIBUTE_NAME)? {
+            Some("coefficients") => {
+                coefficients = Some(floats(&attribute, field::ATTRIBUTE_FLOATS)?)
+            }
+            Some("intercepts") => intercepts = floats(&attribute, field::ATTRIBUTE_FLOATS)?,
+            Some("targets") => match single(&attribute, field::ATTRIBUTE_INT)? {
+                Some(Value::Varint(1)) => {}
+                _ => return Err(invalid("only a single target is supported")),
+            },
+            Some("post_transform") => match string(&attribute, field::ATTRIBUTE_STRING)? {
+                Some("NONE") => {}
+                _ => return Err(invalid("post transforms are not supported")),
+            },
+            _ => return Err(invalid("unknown attribute")),
+        }
+    }
+    let weights = coefficients.ok_or_else(|| invalid("missing coefficients"))?;
+    let bias = match intercepts[..] {
+        [] => 0.,
+        [bias] => bias,
+        _ => return Err(invalid("only a single target is supported")),
+    };
+    checked_model(
+        weights.into_iter().map(f64::from).collect(),
+        f64::from(bias),
+    )
+}
+
+/// Import a linear model from the JSON object `{"coef_": [..], "intercept_": ..}` of the
+/// attributes of a fitted scikit-learn model.
+#[cfg(feature = "serde")]
+pub fn from_sklearn_json(json: &str) -> Result<LinearModel, Error> {
+    use serde_json::Value;
+
+    let invalid = |message: &str| Error::Import(format!("invalid scikit-learn model: {message}"));
+    let value: Value = serde_json::from_str(json).map_err(|e| invalid(&e.to_string()))?;
+    // A number or an array of numbers, which may be nested in a single row.
+    let numbers = |value: &Value| -> Option<Vec<f64>> {
+        match value {
+            Value::Number(v) => Some(vec![v.as_f64()?]),
+            Value::Array(values) => match &values[..] {
+                [row @ Value::Array(_)] => row.as_array()?.iter().map(Value::as_f64).collect(),
+                values => values.iter().map(Value::as_f64).collect(),
+            },
+            _ => None,
+        }
+    };
+
+    let weights = match value.get("coef_") {
+        Some(coef @ Value::Array(_)) => numbers(coef),
+        _ => None,
+    }
+    .ok_or_else(|| invalid("`coef_` should be an array of numbers"))?;
+    let bias = match value.get("intercept_").map(numbers) {
+        None => 0.,
+        Some(Some(intercept)) if intercept.len() == 1 => intercept[0],
+        Some(_) => return Err(invalid("only a single target is supported")),
+    };
+    checked_model(weights, bias)
+}
+
+/// Construct the model, which should have finite coefficients.
+fn checked_model(weights: Vec<f64>, bias: f64) -> Result<LinearModel, Error> {
+    if weights.is_empty() {
+        return Err(Error::Import("the model has no weights".into()));
+    }
+    if !bias.is_finite() || !weights.iter().all(|w| w.is_finite()) {
+        return Err(Error::Import("the coefficients should be finite".into()));
+    }
+    Ok(LinearModel { weights, bias })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode a length-delimited field.
+    fn message(number: u64, bytes: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![];
+        for mut v in [number << 3 | 2, bytes.len() as u64] {
+            while v >= 0x80 {
+                encoded.push(v as u8 | 0x80);
+                v >>= 7;
+            }
+            encoded.push(v as u8);
+        }
+        [encoded, bytes.to_vec()].concat()
+    }
+
+    fn floats_attribute(name: &str, values: &[f32]) -> Vec<u8> {
+        let packed = values
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        [
+            message(field::ATTRIBUTE_NAME, name.as_bytes()),
+            message(field::ATTRIBUTE_FLOATS, &packed),
+        ]
+        .concat()
+    }
+
+    fn onnx(op_type: &str, attributes: &[Vec<u8>]) -> Vec<u8> {
+        let mut node = [
+            message(1, b"X"),
+            message(2, b"Y"),
+            message(field::NODE_OP_TYPE, op_type.as_bytes()),
+            message(field::NODE_DOMAIN, b"ai.onnx.ml"),
+        ]
+        .concat();
+        for attribute in attributes {
+            node.extend(message(field::NODE_ATTRIBUTE, attribute));
+        }
+        // `ir_version = 8` followed by the graph.
+        [
+            vec![0x08, 0x08],
+            message(field::MODEL_GRAPH, &message(field::GRAPH_NODE, &node)),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn onnx_linear_regressor() -> Result<(), Error> {
+        let coefficients = floats_attribute("coefficients", &[0.5, -1.25, 3.]);
+        let intercepts = floats_attribute("intercepts", &[0.1]);
+        let targets = [
+            message(field::ATTRIBUTE_NAME, b"targets"),
+            vec![(field::ATTRIBUTE_INT << 3) as u8, 1],
+        ]
+        .concat();
+        let model = from_onnx(&onnx(
+            "LinearRegressor",
+            &[coefficients.clone(), intercepts.clone(), targets],
+        ))?;
+        assert_eq!(
+            model,
+            LinearModel {
+                weights: vec![0.5, -1.25, 3.],
+                bias: f64::from(0.1f32),
+            }
+        );
+
+        assert!(from_onnx(&onnx(
+            "LinearClassifier",
+            std::slice::from_ref(&coefficients)
+        ))
+        .is_err());
+        let two_targets = floats_attribute("intercepts", &[0.1, 0.2]);
+        assert!(from_onnx(&onnx(
+            "LinearRegressor",
+            &[coefficients.clone(), two_targets]
+        ))
+        .is_err());
+        let transform = [
+            message(field::ATTRIBUTE_NAME, b"post_transform"),
+            message(field::ATTRIBUTE_STRING, b"SOFTMAX"),
+        ]
+        .concat();
+        assert!(from_onnx(&onnx("LinearRegressor", &[coefficients, transform])).is_err());
+        assert!(from_onnx(&onnx("LinearRegressor", &[intercepts])).is_err());
+        assert!(from_onnx(&[0x3a, 0x10]).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn sklearn_json() -> Result<(), Error> {
+        let expected = LinearModel {
+            weights: vec![0.5, -1.25],
+            bias: 0.1,
+        };
+        assert_eq!(
+            from_sklearn_json(r#"{"coef_": [0.5, -1.25], "intercept_": 0.1}"#)?,
+            expected
+        );
+        assert_eq!(
+            from_sklearn_json(
+                r#"{"coef_": [[0.5, -1.25]], "intercept_": [0.1], "n_features_in_": 2}"#
+            )?,
+            expected
+        );
+        assert!(
+            from_sklearn_json(r#"{"coef_": [[0.5], [1.0]], "intercept_": [0.1, 0.2]}"#).is_err()
+        );
+        assert!(from_sklearn_json(r#"{"coef_": ["0.5"]}"#).is_err());
+        assert!(from_sklearn_json(r#"{"intercept_": 0.1}"#).is_err());
+        Ok(())
+    }
+}
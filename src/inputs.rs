@@ -0,0 +1,138 @@
+use ark_ff::PrimeField;
+use num::traits::float::FloatCore;
+
+use crate::{f32::F32Var, f64::F64Var, float::FloatVar};
+
+/// `PublicInputs` accumulates the values of the public inputs of a circuit, and produces the
+/// field elements that the verifier should provide.
+///
+/// The values should be added in the same order as the circuit allocates them by `new_input`,
+/// where each kind of value has the same encoding as the corresponding gadget:
+///
+/// * a `binary64`/`binary32` number of `F64Var`/`F32Var` takes 3 field elements, see
+///   `FloatVar::verifier_input`;
+/// * an integer or another field element (e.g., a commitment) of `FpVar` takes 1 field element;
+/// * a `bool` of `Boolean` takes 1 field element, which is either 0 or 1.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PublicInputs<F: PrimeField> {
+    inputs: Vec<F>,
+}
+
+impl<F: PrimeField> PublicInputs<F> {
+    /// Create an empty list of public inputs.
+    pub fn new() -> Self {
+        Self { inputs: vec![] }
+    }
+
+    /// Add a floating point number allocated by `FloatVar::<F, E, M>::new_input`.
+    pub fn add_float<const E: usize, const M: usize>(
+        &mut self,
+        value: impl FloatCore,
+    ) -> &mut Self {
+        self.inputs
+            .extend(FloatVar::<F, E, M>::verifier_input(value));
+        self
+    }
+
+    /// Add a `binary64` number allocated by `F64Var::new_input`.
+    pub fn add_f64(&mut self, value: f64) -> &mut Self {
+        self.inputs.extend(F64Var::<F>::verifier_input(value));
+        self
+    }
+
+    /// Add `binary64` numbers allocated by `F64Var::new_input`, in this order.
+    pub fn add_f64s(&mut self, values: impl IntoIterator<Item = f64>) -> &mut Self {
+        for v in values {
+            self.add_f64(v);
+        }
+        self
+    }
+
+    /// Add a `binary32` number allocated by `F32Var::new_input`.
+    pub fn add_f32(&mut self, value: f32) -> &mut Self {
+        self.inputs.extend(F32Var::<F>::verifier_input(value));
+        self
+    }
+
+    /// Add an integer allocated by `FpVar::new_input`, where negative integers are encoded as
+    /// their negation in `F`.
+    pub fn add_integer(&mut self, value: impl Into<F>) -> &mut Self {
+        self.inputs.push(value.into());
+        self
+    }
+
+    /// Add a field element allocated by `FpVar::new_input`, e.g., a commitment to private data.
+    pub fn add_commitment(&mut self, value: F) -> &mut Self {
+        self.inputs.push(value);
+        self
+    }
+
+    /// Add a boolean allocated by `Boolean::new_input`.
+    pub fn add_bool(&mut self, value: bool) -> &mut Self {
+        self.inputs.push(F::from(value));
+        self
+    }
+
+    /// The number of field elements added so far.
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Check whether no public input has been added.
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Produce the field elements that the verifier should provide.
+    pub fn build(&self) -> Vec<F> {
+        self.inputs.clone()
+    }
+}
+
+impl<F: PrimeField> From<PublicInputs<F>> for Vec<F> {
+    fn from(inputs: PublicInputs<F>) -> Self {
+        inputs.inputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        r1cs::{ConstraintSystem, SynthesisError},
+        r1cs_std::{alloc::AllocVar, boolean::Boolean, fields::fp::FpVar},
+    };
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn matches_allocation() -> Result<(), SynthesisError> {
+        let commitment = Fr::from(12345u64);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        F64Var::new_input(cs.clone(), || Ok(-0.1))?;
+        F32Var::new_input(cs.clone(), || Ok(1.5f32))?;
+        let _ = FpVar::new_input(cs.clone(), || Ok(Fr::from(-3i64)))?;
+        let _ = Boolean::new_input(cs.clone(), || Ok(true))?;
+        let _ = FpVar::new_input(cs.clone(), || Ok(commitment))?;
+        for v in [f64::INFINITY, 0., 1e-310] {
+            F64Var::new_input(cs.clone(), || Ok(v))?;
+        }
+
+        let inputs = PublicInputs::new()
+            .add_f64(-0.1)
+            .add_float::<8, 23>(1.5f32)
+            .add_integer(-3i64)
+            .add_bool(true)
+            .add_commitment(commitment)
+            .add_f64s([f64::INFINITY, 0., 1e-310])
+            .build();
+        assert_eq!(inputs, cs.borrow().unwrap().instance_assignment[1..]);
+
+        let mut other = PublicInputs::<Fr>::new();
+        other.add_f64(-0.1).add_f32(1.5);
+        assert_eq!(other.len(), 6);
+        assert_eq!(Vec::from(other), inputs[..6]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use ark_ff::PrimeField;
+
+use crate::{
+    float::FloatVar,
+    impl_ops,
+    r1cs::{ConstraintSystemRef, SynthesisError},
+    r1cs_std::{alloc::AllocationMode, boolean::Boolean},
+};
+
+/// `IntervalFloatVar` encloses an exact value in the interval `[lower, upper]` of two `FloatVar`s,
+/// which is carried through the arithmetic so that the result of a computation comes with
+/// certified bounds on its rounding error, instead of relying on the prover reproducing the exact
+/// order of operations.
+///
+/// Each bound is computed by the correctly rounded operation followed by a step of one ulp
+/// outwards, which is a valid bound of the exact result because the rounding error is at most half
+/// an ulp. The bounds are therefore about one ulp looser than those of directed rounding, but
+/// reuse the operations of `FloatVar`. If a bound is NaN (e.g., in `0 * Inf`), the result is the
+/// whole `[-Inf, +Inf]`.
+#[derive(Clone, Debug)]
+pub struct IntervalFloatVar<F: PrimeField, const E: usize, const M: usize> {
+    pub lower: FloatVar<F, E, M>,
+    pub upper: FloatVar<F, E, M>,
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> IntervalFloatVar<F, E, M> {
+    /// Create the interval `[lower, upper]`, which the caller should guarantee to enclose the value,
+    /// e.g., a measurement with a known error.
+    pub fn new(lower: FloatVar<F, E, M>, upper: FloatVar<F, E, M>) -> Self {
+        Self { lower, upper }
+    }
+
+    /// Create the interval that contains exactly `x`, e.g., an input of the computation.
+    pub fn point(x: FloatVar<F, E, M>) -> Self {
+        Self {
+            lower: x.clone(),
+            upper: x,
+        }
+    }
+
+    /// Round the exact bounds `[lower, upper]` computed by correctly rounded operations outwards,
+    /// or return the whole interval if either of them is NaN.
+    fn outward(lower: FloatVar<F, E, M>, upper: FloatVar<F, E, M>) -> Result<Self, SynthesisError> {
+        let infinity = |sign| {
+            FloatVar::new_variable_from_components(
+                ConstraintSystemRef::None,
+                || Ok((sign, (1 << E) - 1, 0)),
+                AllocationMode::Constant,
+            )
+        };
+        let is_nan = lower.is_nan()?.or(&upper.is_nan()?)?;
+        Ok(Self {
+            lower: is_nan.select(&infinity(true)?, &lower.widen(false)?)?,
+            upper: is_nan.select(&infinity(false)?, &upper.widen(true)?)?,
+        })
+    }
+
+    fn add(x: &Self, y: &Self) -> Result<Self, SynthesisError> {
+        Self::outward(&x.lower + &y.lower, &x.upper + &y.upper)
+    }
+
+    fn sub(x: &Self, y: &Self) -> Result<Self, SynthesisError> {
+        Self::outward(&x.lower - &y.upper, &x.upper - &y.lower)
+    }
+
+    /// Multiply two intervals, whose bounds are the minimum and the maximum of the products of
+    /// their bounds.
+    fn mul(x: &Self, y: &Self) -> Result<Self, SynthesisError> {
+        let products = [
+            &x.lower * &y.lower,
+            &x.lower * &y.upper,
+            &x.upper * &y.lower,
+            &x.upper * &y.upper,
+        ];
+        let (mut lower, mut upper) = (products[0].clone(), products[0].clone());
+        for p in &products[1..] {
+            lower = FloatVar::is_lt(p, &lower)?.select(p, &lower)?;
+            upper = FloatVar::is_gt(p, &upper)?.select(p, &upper)?;
+        }
+        // A NaN product is not selected by the comparisons, so it is checked separately.
+        let has_nan = Boolean::kary_or(
+            &products
+                .iter()
+                .map(FloatVar::is_nan)
+                .collect::<Result<Vec<_>, _>>()?,
+        )?;
+        let nan = FloatVar::new_variable_from_components(
+            ConstraintSystemRef::None,
+            || Ok((false, (1 << E) - 1, 1)),
+            AllocationMode::Constant,
+        )?;
+        Self::outward(has_nan.select(&nan, &lower)?, upper)
+    }
+
+    /// Check whether `x` is in the interval.
+    pub fn contains(&self, x: &FloatVar<F, E, M>) -> Result<Boolean<F>, SynthesisError> {
+        FloatVar::is_le(&self.lower, x)?.and(&FloatVar::is_le(x, &self.upper)?)
+    }
+
+    /// An upper bound of the width `upper - lower` of the interval, which bounds the error of any
+    /// number in the interval (e.g., the rounded result) from the exact value.
+    pub fn width(&self) -> Result<FloatVar<F, E, M>, SynthesisError> {
+        (&self.upper - &self.lower).widen(true)
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> From<FloatVar<F, E, M>>
+    for IntervalFloatVar<F, E, M>
+{
+    fn from(x: FloatVar<F, E, M>) -> Self {
+        Self::point(x)
+    }
+}
+
+impl_ops!(
+    IntervalFloatVar<F, E, M>,
+    Add,
+    add,
+    AddAssign,
+    add_assign,
+    |a, b| { IntervalFloatVar::add(a, b).unwrap() },
+    F: PrimeField, const E: usize, const M: usize
+);
+
+impl_ops!(
+    IntervalFloatVar<F, E, M>,
+    Sub,
+    sub,
+    SubAssign,
+    sub_assign,
+    |a, b| { IntervalFloatVar::sub(a, b).unwrap() },
+    F: PrimeField, const E: usize, const M: usize
+);
+
+impl_ops!(
+    IntervalFloatVar<F, E, M>,
+    Mul,
+    mul,
+    MulAssign,
+    mul_assign,
+    |a, b| { IntervalFloatVar::mul(a, b).unwrap() },
+    F: PrimeField, const E: usize, const M: usize
+);
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+    use crate::{
+        f64::F64Var,
+        r1cs::ConstraintSystem,
+        r1cs_std::{alloc::AllocVar, R1CSVar},
+    };
+    use ark_bls12_381::Fr;
+    use num::{BigRational, FromPrimitive};
+
+    type Interval = IntervalFloatVar<Fr, 11, 52>;
+
+    #[test]
+    fn enclosure() -> Result<(), Box<dyn Error>> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let values = [0.1, -3.7, 1e-310, 2.5e300, -0.];
+        let vars = F64Var::new_witness_vec(cs.clone(), &values)?;
+        let x = vars
+            .iter()
+            .cloned()
+            .map(Interval::point)
+            .collect::<Vec<_>>();
+
+        // `((0.1 - 3.7) * 0.1 + 1e-310) * 2.5e300 - 0` in exact arithmetic.
+        let r = |v: f64| BigRational::from_f64(v).unwrap();
+        let exact = ((r(values[0]) + r(values[1])) * r(values[0]) + r(values[2])) * r(values[3]);
+        let result = (((&x[0] + &x[1]) * &x[0] + &x[2]) * &x[3]) - &x[4];
+        let (lower, upper) = (result.lower.to_f64()?, result.upper.to_f64()?);
+        assert!(r(lower) <= exact && exact <= r(upper), "{lower} {upper}");
+        assert!(lower < upper && upper - lower < 1e-14 * upper.abs());
+
+        // The result of the same operations in `f64` is enclosed, and the width bounds its error.
+        let rounded = ((&vars[0] + &vars[1]) * &vars[0] + &vars[2]) * &vars[3] - &vars[4];
+        assert!(result.contains(&rounded)?.value()?);
+        let width = result.width()?.to_f64()?;
+        assert!(width >= upper - lower);
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn special_values() -> Result<(), Box<dyn Error>> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let new = |v: f64| -> Result<Interval, SynthesisError> {
+            Ok(Interval::point(F64Var::new_witness(cs.clone(), || Ok(v))?))
+        };
+        let bounds = |x: Interval| -> Result<(f64, f64), Box<dyn Error>> {
+            Ok((x.lower.to_f64()?, x.upper.to_f64()?))
+        };
+
+        // An overflow is bounded below by the largest finite number.
+        assert_eq!(
+            bounds(new(f64::MAX)? * new(2.)?)?,
+            (f64::MAX, f64::INFINITY)
+        );
+        assert_eq!(
+            bounds(new(-f64::MAX)? - new(f64::MAX)?)?,
+            (f64::NEG_INFINITY, -f64::MAX)
+        );
+        // An exact 0 is widened to the smallest subnormal numbers.
+        let tiny = f64::from_bits(1);
+        assert_eq!(bounds(new(1.)? - new(1.)?)?, (-tiny, tiny));
+        let unbounded = Interval::new(new(0.)?.lower, new(f64::INFINITY)?.upper) * new(1.)?;
+        assert_eq!(bounds(unbounded)?, (-tiny, f64::INFINITY));
+        // `0 * Inf` is undefined, so the result is the whole line.
+        let whole = new(0.)? * new(f64::INFINITY)?;
+        assert_eq!(bounds(whole)?, (f64::NEG_INFINITY, f64::INFINITY));
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+}
@@ -1,9 +1,35 @@
+pub mod data;
+pub mod decimal;
+pub mod error;
 pub mod float;
+pub mod f16;
+pub mod bf16;
 pub mod f32;
 pub mod f64;
+pub mod hash;
+pub mod import;
+pub mod inputs;
+pub mod interval;
+pub mod pedersen;
+pub mod rational;
+pub mod regression;
+pub mod report;
+pub mod shaped;
+pub mod statement;
+pub mod stochastic;
+pub mod unnormalized;
+pub mod utils;
 
-mod groth16;
-mod r1cs;
-mod r1cs_std;
+pub mod groth16;
+pub mod r1cs;
+pub mod r1cs_std;
+mod powers;
 mod traits;
-mod macros;
\ No newline at end of file
+mod macros;
+
+pub use error::Error;
+
+#[cfg(feature = "profile")]
+pub mod profile;
+#[cfg(feature = "wasm")]
+pub mod wasm;
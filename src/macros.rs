@@ -60,3 +60,14 @@ macro_rules! impl_ops {
         }
     };
 }
+
+/// Enter a span named `$name` until the end of the current scope, which is recorded by
+/// `profile::profile` if the `profile` feature is enabled.
+macro_rules! profile_span {
+    ($name: literal) => {
+        #[cfg(feature = "profile")]
+        let _span = tracing::info_span!(target: $crate::profile::TARGET, $name).entered();
+    };
+}
+
+pub(crate) use profile_span;
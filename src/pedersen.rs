@@ -0,0 +1,381 @@
+//! Pedersen commitments to `binary64` numbers over the twisted Edwards curve embedded in the
+//! scalar field `F` of the proving curve, and the gadget that opens them in the circuit, so that
+//! numbers committed outside the circuit (e.g., published ahead of time) can be bound into the
+//! statements of the regression circuits.
+//!
+//! The commitment to the numbers `v_1, ..., v_n` with the private `blinding` `r` is the point
+//! `C = Σ b(v_i) G_i + r H`, where `b(v)` is the canonical encoding of `v` as a 64-bit integer
+//! (i.e., `f64::to_bits`, which consists of the sign, the biased exponent, and the mantissa of
+//! `FloatVar::verifier_input`), and the generators `G_i` and `H` are derived from SHA-256 by
+//! try-and-increment, so that no discrete logarithm relation between them is known. Unlike the
+//! commitments of `regression::f64_commitment`, they are additively homomorphic, and they can be
+//! computed and verified by any implementation of the curve.
+//!
+//! The supported curves are Jubjub over the scalar field of BLS12-381 and Baby Jubjub over the
+//! scalar field of BN254, see `EmbeddedCurve`. As the rest of this crate, the commitments have not
+//! been audited.
+
+use ark_ff::{BigInteger, PrimeField};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    f64::F64Var,
+    r1cs::{ConstraintSystemRef, Namespace, SynthesisError},
+    r1cs_std::{
+        alloc::{AllocVar, AllocationMode},
+        boolean::Boolean,
+        fields::fp::FpVar,
+        prelude::{EqGadget, FieldVar},
+        R1CSVar,
+    },
+};
+
+/// A prime field with a twisted Edwards curve `a x² + y² = 1 + d x² y²` over it, where `a` is a
+/// square and `d` is not, so that the addition law is complete.
+pub trait EmbeddedCurve: PrimeField {
+    /// The coefficients `(a, d)` of the curve.
+    fn coefficients() -> (Self, Self);
+
+    /// The base-2 logarithm of the cofactor of the prime-order subgroup.
+    const LOG_COFACTOR: usize;
+}
+
+/// Jubjub, see <https://zips.z.cash/protocol/protocol.pdf#jubjub>.
+impl EmbeddedCurve for ark_bls12_381::Fr {
+    fn coefficients() -> (Self, Self) {
+        (
+            -Self::from(1u8),
+            -(Self::from(10240u64) / Self::from(10241u64)),
+        )
+    }
+
+    const LOG_COFACTOR: usize = 3;
+}
+
+/// Baby Jubjub, see <https://eips.ethereum.org/EIPS/eip-2494>.
+impl EmbeddedCurve for ark_bn254::Fr {
+    fn coefficients() -> (Self, Self) {
+        (Self::from(168700u64), Self::from(168696u64))
+    }
+
+    const LOG_COFACTOR: usize = 3;
+}
+
+/// A point `(x, y)` on the curve of `F`, whose identity is `(0, 1)`. The public inputs of a
+/// commitment allocated by `PointVar::new_input` are `x` followed by `y`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Point<F> {
+    pub x: F,
+    pub y: F,
+}
+
+impl<F: EmbeddedCurve> Point<F> {
+    /// The identity of the curve.
+    pub fn identity() -> Self {
+        Self {
+            x: F::zero(),
+            y: F::one(),
+        }
+    }
+
+    /// Check whether the point is on the curve.
+    pub fn is_on_curve(&self) -> bool {
+        let (a, d) = F::coefficients();
+        let (x2, y2) = (self.x.square(), self.y.square());
+        a * x2 + y2 == F::one() + d * x2 * y2
+    }
+
+    /// Add `other` to the point.
+    pub fn add(&self, other: &Self) -> Self {
+        let (a, d) = F::coefficients();
+        let t = d * self.x * other.x * self.y * other.y;
+        Self {
+            x: (self.x * other.y + self.y * other.x) / (F::one() + t),
+            y: (self.y * other.y - a * self.x * other.x) / (F::one() - t),
+        }
+    }
+
+    /// Multiply the point by the integer whose little-endian bits are `bits`.
+    pub fn mul_bits(&self, bits: impl IntoIterator<Item = bool>) -> Self {
+        let mut result = Self::identity();
+        let mut base = *self;
+        for b in bits {
+            if b {
+                result = result.add(&base);
+            }
+            base = base.add(&base);
+        }
+        result
+    }
+
+    /// Derive the `index`-th generator of the prime-order subgroup from SHA-256, where the
+    /// generator `H` of the blinding is the 0-th, and `G_i` is the `i`-th.
+    fn generator(index: usize) -> Self {
+        let (a, d) = F::coefficients();
+        (0u64..)
+            .find_map(|counter| {
+                let y = F::from_le_bytes_mod_order(
+                    &Sha256::new_with_prefix(b"r1cs_float/pedersen/generator")
+                        .chain_update((index as u64).to_le_bytes())
+                        .chain_update(counter.to_le_bytes())
+                        .finalize(),
+                );
+                // `x² = (1 - y²) / (a - d y²)`, where the denominator is never 0 as `d` is not
+                // a square.
+                let y2 = y.square();
+                let x = ((F::one() - y2) / (a - d * y2)).sqrt()?;
+                // Clear the cofactor, and reject the points of small order.
+                let mut p = Self { x, y };
+                for _ in 0..F::LOG_COFACTOR {
+                    p = p.add(&p);
+                }
+                (p != Self::identity()).then_some(p)
+            })
+            .unwrap()
+    }
+
+    /// The multiples `2^j P` of the `index`-th generator `P` for `j < bits`, which are added in
+    /// the circuit according to the bits of the scalar.
+    fn generator_powers(index: usize, bits: usize) -> Vec<Self> {
+        let mut p = Self::generator(index);
+        (0..bits)
+            .map(|_| {
+                let q = p;
+                p = p.add(&p);
+                q
+            })
+            .collect()
+    }
+}
+
+/// The point of the canonical encodings of `values` before blinding, i.e., `Σ b(v_i) G_i`.
+fn unblinded<F: EmbeddedCurve>(values: &[f64]) -> Point<F> {
+    values
+        .iter()
+        .enumerate()
+        .fold(Point::identity(), |c, (i, v)| {
+            let bits = v.to_bits();
+            c.add(&Point::generator(i + 1).mul_bits((0..64).map(|j| bits >> j & 1 == 1)))
+        })
+}
+
+/// Compute the Pedersen commitment to `values` with the random `blinding`, which should be
+/// sampled uniformly (e.g., by `F::rand`) and kept private, see the module documentation.
+pub fn pedersen_commitment<F: EmbeddedCurve>(values: &[f64], blinding: F) -> Point<F> {
+    let h = Point::generator(0).mul_bits(blinding.into_bigint().to_bits_le());
+    unblinded(values).add(&h)
+}
+
+/// A point on the curve of `F` in the circuit.
+#[derive(Clone, Debug)]
+pub struct PointVar<F: PrimeField> {
+    pub x: FpVar<F>,
+    pub y: FpVar<F>,
+}
+
+impl<F: EmbeddedCurve> PointVar<F> {
+    /// Add `other` to the point in the circuit, which is the same as `Point::add` on the values.
+    pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let (a, d) = F::coefficients();
+        let (u, v) = (&self.x * &other.y, &self.y * &other.x);
+        let (xx, yy) = (&self.x * &other.x, &self.y * &other.y);
+        let t = &xx * &yy * d;
+        let cs = self
+            .x
+            .cs()
+            .or(self.y.cs())
+            .or(other.x.cs())
+            .or(other.y.cs());
+
+        // The denominators are never 0 as the addition law is complete.
+        let (numerator, denominator) = (u + v, FpVar::one() + &t);
+        let x = FpVar::new_hint(cs.clone(), || {
+            Ok(numerator.value()? * denominator.value()?.inverse().unwrap())
+        })?;
+        x.mul_equals(&denominator, &numerator)?;
+        let (numerator, denominator) = (yy - xx * a, FpVar::one() - t);
+        let y = FpVar::new_hint(cs, || {
+            Ok(numerator.value()? * denominator.value()?.inverse().unwrap())
+        })?;
+        y.mul_equals(&denominator, &numerator)?;
+
+        Ok(Self { x, y })
+    }
+
+    /// Compute `Σ b_j 2^j P` in the circuit, where `powers` are `2^j P` for the constant `P`, and
+    /// `bits` are the little-endian bits `b_j` of the scalar.
+    fn mul_bits(powers: &[Point<F>], bits: &[Boolean<F>]) -> Result<Self, SynthesisError> {
+        // `b P` is either `P` or the identity `(0, 1)`, both of which are linear in `b`.
+        let select = |p: &Point<F>, b: &Boolean<F>| {
+            let b = FpVar::from(b.clone());
+            Self {
+                x: &b * p.x,
+                y: b * (p.y - F::one()) + F::one(),
+            }
+        };
+        let mut result = select(&powers[0], &bits[0]);
+        for (p, b) in powers.iter().zip(bits).skip(1) {
+            result = result.add(&select(p, b))?;
+        }
+        Ok(result)
+    }
+}
+
+impl<F: EmbeddedCurve> AllocVar<Point<F>, F> for PointVar<F> {
+    fn new_variable<T: std::borrow::Borrow<Point<F>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into().cs();
+        let point = f().map(|p| *p.borrow());
+        Ok(Self {
+            x: FpVar::new_variable(cs.clone(), || point.map(|p| p.x), mode)?,
+            y: FpVar::new_variable(cs, || point.map(|p| p.y), mode)?,
+        })
+    }
+}
+
+impl<F: PrimeField> EqGadget<F> for PointVar<F> {
+    fn is_eq(&self, other: &Self) -> Result<Boolean<F>, SynthesisError> {
+        self.x.is_eq(&other.x)?.and(&self.y.is_eq(&other.y)?)
+    }
+
+    fn enforce_equal(&self, other: &Self) -> Result<(), SynthesisError> {
+        self.x.enforce_equal(&other.x)?;
+        self.y.enforce_equal(&other.y)
+    }
+}
+
+/// Decompose `v`, which is known to have at most `length` bits, into its little-endian bits.
+fn bits_of<F: PrimeField>(
+    cs: &ConstraintSystemRef<F>,
+    v: &FpVar<F>,
+    length: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let bits = (0..length)
+        .map(|j| Boolean::new_witness(cs.clone(), || Ok(v.value()?.into_bigint().get_bit(j))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut sum = FpVar::zero();
+    let mut power = F::one();
+    for b in &bits {
+        sum += FpVar::from(b.clone()) * power;
+        power.double_in_place();
+    }
+    sum.enforce_equal(v)?;
+    Ok(bits)
+}
+
+/// Allocate `values` as witnesses, and enforce that `commitment` is their Pedersen commitment
+/// with `blinding` (see `pedersen_commitment`), i.e., open the commitment in the circuit.
+pub fn open_pedersen_commitment<F: EmbeddedCurve>(
+    cs: ConstraintSystemRef<F>,
+    values: &[f64],
+    blinding: F,
+    commitment: &PointVar<F>,
+) -> Result<Vec<F64Var<F>>, SynthesisError> {
+    let mut vars = vec![];
+    let blinding_bits = blinding.into_bigint().to_bits_le();
+    let bits = (0..F::MODULUS_BIT_SIZE as usize)
+        .map(|j| Boolean::new_witness(cs.clone(), || Ok(blinding_bits[j])))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut c = PointVar::mul_bits(&Point::generator_powers(0, bits.len()), &bits)?;
+    for (i, &v) in values.iter().enumerate() {
+        let (v, [s, e, m]) = F64Var::new_variable_with_components(
+            cs.clone(),
+            || F64Var::<F>::decode(v).map_err(|_| SynthesisError::AssignmentMissing),
+            AllocationMode::Witness,
+        )?;
+        vars.push(v);
+        // The canonical encoding `m + e 2^52 + s 2^63`, whose components have been range checked.
+        let bits = [
+            bits_of(&cs, &m, 52)?,
+            bits_of(&cs, &e, 11)?,
+            bits_of(&cs, &s, 1)?,
+        ]
+        .concat();
+        c = c.add(&PointVar::mul_bits(
+            &Point::generator_powers(i + 1, 64),
+            &bits,
+        )?)?;
+    }
+    c.enforce_equal(commitment)?;
+    Ok(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inputs::PublicInputs, r1cs::ConstraintSystem};
+    use num::BigUint;
+
+    fn check_curve<F: EmbeddedCurve>(order: &str) -> Result<(), SynthesisError> {
+        // The generators are in the subgroup of the prime `order`.
+        let order: BigUint = order.parse().unwrap();
+        for i in 0..3 {
+            let g = Point::<F>::generator(i);
+            assert!(g.is_on_curve());
+            assert_ne!(g, Point::identity());
+            assert_eq!(
+                g.mul_bits((0..order.bits()).map(|j| order.bit(j))),
+                Point::identity()
+            );
+        }
+
+        let rng = &mut ark_std::test_rng();
+        let values = [0.1, -2.5, f64::INFINITY];
+        let blinding = F::rand(rng);
+        let commitment = pedersen_commitment(&values, blinding);
+        assert!(commitment.is_on_curve());
+        assert_ne!(
+            commitment,
+            pedersen_commitment(&values, blinding + F::one())
+        );
+        assert_ne!(commitment, pedersen_commitment(&[0.1, -2.5], blinding));
+        assert_ne!(
+            commitment,
+            pedersen_commitment(&[-2.5, 0.1, f64::INFINITY], blinding)
+        );
+
+        // The commitments are additively homomorphic in the encodings and the blindings.
+        assert_eq!(
+            pedersen_commitment(&[0.], blinding).add(&pedersen_commitment(&[1.], F::one())),
+            pedersen_commitment(&[1.], blinding + F::one())
+        );
+
+        let open = |values: &[f64], blinding| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let c = PointVar::new_input(cs.clone(), || Ok(commitment))?;
+            let vars = open_pedersen_commitment(cs.clone(), values, blinding, &c)?;
+            for (v, &expected) in vars.iter().zip(values) {
+                assert_eq!(v.value()?, BigUint::from(expected.to_bits()));
+            }
+            assert_eq!(
+                cs.borrow().unwrap().instance_assignment[1..],
+                PublicInputs::new()
+                    .add_commitment(commitment.x)
+                    .add_commitment(commitment.y)
+                    .build()
+            );
+            cs.is_satisfied()
+        };
+        assert!(open(&values, blinding)?);
+        assert!(!open(&[0.1, -2.5, f64::NEG_INFINITY], blinding)?);
+        assert!(!open(&values, blinding + F::one())?);
+        Ok(())
+    }
+
+    #[test]
+    fn jubjub() -> Result<(), SynthesisError> {
+        check_curve::<ark_bls12_381::Fr>(
+            "6554484396890773809930967563523245729705921265872317281365359162392183254199",
+        )
+    }
+
+    #[test]
+    fn baby_jubjub() -> Result<(), SynthesisError> {
+        check_curve::<ark_bn254::Fr>(
+            "2736030358979909402780800718157159386076813972158567259200215660948447373041",
+        )
+    }
+}
@@ -0,0 +1,95 @@
+//! Powers of two in `F`, which are used by almost every gadget, either as constants for shifting
+//! and recomposing, or as hints of `2^shift`. Computing `2^shift` from a field element by
+//! `F::from(2u8).pow(shift.into_bigint())` costs a multiplication for each bit of the modulus, so
+//! the powers are computed once for each field (and thread) and shared by all gadgets. Other
+//! per-field constants (e.g., the parameters of the hash) are cached in the same way by `cached`.
+
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::BTreeMap,
+    rc::Rc,
+};
+
+use ark_ff::PrimeField;
+
+type Cache = BTreeMap<TypeId, Rc<dyn Any>>;
+
+thread_local! {
+    /// The cached values of each type `T`, indexed by the type of `T`, where each value is an
+    /// `Rc<T>` for the corresponding `T`.
+    static CACHE: RefCell<Cache> = RefCell::new(BTreeMap::new());
+}
+
+/// The value of type `T` computed by `init`, which is only called the first time `T` is requested
+/// in each thread, so `T` should identify the value, e.g., by being generic over the field.
+pub(crate) fn cached<T: 'static>(init: impl FnOnce() -> T) -> Rc<T> {
+    let key = TypeId::of::<T>();
+    let value = match CACHE.with(|cache| cache.borrow().get(&key).cloned()) {
+        Some(value) => value,
+        // `init` is called outside of the borrow, so that it may use other cached values.
+        None => {
+            let value: Rc<dyn Any> = Rc::new(init());
+            CACHE.with(|cache| cache.borrow_mut().insert(key, value.clone()));
+            value
+        }
+    };
+    value.downcast::<T>().unwrap()
+}
+
+/// The cached powers `2^0, 2^1, ..., 2^(F::MODULUS_BIT_SIZE - 1)` of two in `F`.
+fn powers<F: PrimeField>() -> Rc<Vec<F>> {
+    cached(|| {
+        let mut powers = Vec::with_capacity(F::MODULUS_BIT_SIZE as usize);
+        let mut power = F::one();
+        for _ in 0..F::MODULUS_BIT_SIZE {
+            powers.push(power);
+            power.double_in_place();
+        }
+        powers
+    })
+}
+
+/// Compute `2^exponent` in `F`.
+pub(crate) fn pow2<F: PrimeField>(exponent: usize) -> F {
+    powers::<F>()
+        .get(exponent)
+        .copied()
+        .unwrap_or_else(|| F::from(2u8).pow([exponent as u64]))
+}
+
+/// Compute `2^exponent` in `F`, where `exponent` is a field element, e.g., the value of a shift.
+/// Small exponents are looked up in the cache, and others (which only appear in unsatisfiable
+/// assignments) are computed from scratch.
+pub(crate) fn pow2_of<F: PrimeField>(exponent: F) -> F {
+    let exponent = exponent.into_bigint();
+    let limbs = exponent.as_ref();
+    if limbs[1..].iter().all(|&limb| limb == 0) {
+        if let Some(&power) = powers::<F>().get(limbs[0] as usize) {
+            return power;
+        }
+    }
+    F::from(2u8).pow(exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_ff::Field;
+
+    #[test]
+    fn cached() {
+        for e in [0u64, 1, 52, 128, 254, 255, 300] {
+            let expected = Fr::from(2u8).pow([e]);
+            assert_eq!(pow2::<Fr>(e as usize), expected);
+            assert_eq!(pow2_of(Fr::from(e)), expected);
+        }
+        assert_eq!(
+            pow2_of(-Fr::from(1u8)),
+            Fr::from(2u8).pow((-Fr::from(1u8)).into_bigint())
+        );
+        assert!(Rc::ptr_eq(&powers::<Fr>(), &powers::<Fr>()));
+        assert_eq!(powers::<ark_bn254::Fr>()[3], ark_bn254::Fr::from(8u8));
+    }
+}
@@ -0,0 +1,303 @@
+//! With the `profile` feature, the gadgets of `FloatVar` (e.g., `add` and `mul`) and the
+//! sub-circuits of the regression circuits (e.g., the products of each row and the dot products)
+//! are instrumented with `tracing` spans of target `r1cs_float`, and `profile` reports the size
+//! of the constraints and variables added in each span, so that one can see which parts of a big
+//! circuit dominate the cost.
+//!
+//! The spans are identified by their paths, e.g., `residuals/dot/mul` is a `mul` in a dot product
+//! when computing the residuals. With the `parallel` feature, the spans of the rows synthesized on
+//! other threads are also recorded, so the profile is the same as that of sequential synthesis.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    sync::{Arc, Mutex},
+};
+
+use ark_ff::PrimeField;
+use tracing::{
+    dispatcher::{self, get_default, Dispatch},
+    span::{Attributes, Id, Record},
+    subscriber::with_default,
+    Event, Metadata, Subscriber,
+};
+
+use crate::{
+    r1cs::{
+        ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal,
+        SynthesisError, SynthesisMode,
+    },
+    report::ConstraintReport,
+};
+
+/// The target of the spans of this crate.
+pub const TARGET: &str = "r1cs_float";
+
+/// The size of the constraints and variables added in all the spans with the same path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpanReport {
+    /// The names of the span and its ancestors, separated by `/`.
+    pub path: String,
+    /// The number of times the span is entered.
+    pub calls: usize,
+    /// The total size of the span, including its descendants.
+    pub report: ConstraintReport,
+}
+
+/// The reports of the spans, in the order they are first entered.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub spans: Vec<SpanReport>,
+}
+
+impl Profile {
+    /// The report of the spans with the path `path`.
+    pub fn get(&self, path: &str) -> Option<&SpanReport> {
+        self.spans.iter().find(|span| span.path == path)
+    }
+
+    /// Synthesize `circuit` in setup mode and report the size of each span.
+    pub fn circuit<F: PrimeField>(
+        circuit: impl ConstraintSynthesizer<F>,
+    ) -> Result<Self, SynthesisError> {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_mode(SynthesisMode::Setup);
+
+        let (result, profile) = profile(&cs, || circuit.generate_constraints(cs.clone()));
+        result?;
+        Ok(profile)
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for span in &self.spans {
+            writeln!(f, "{} (x{}): {}", span.path, span.calls, span.report)?;
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    /// The frame of the constraint system being profiled on this thread.
+    static FRAME: RefCell<Option<Frame>> = const { RefCell::new(None) };
+}
+
+/// The state of profiling a constraint system on a thread.
+struct Frame {
+    /// Report the current size of the constraint system.
+    size: Box<dyn Fn() -> ConstraintReport>,
+    /// The path of the span that the frame is forked from, if any.
+    prefix: Option<String>,
+    /// The spans being entered on this thread, with their paths and the sizes of the constraint
+    /// system when they are entered.
+    stack: Vec<(String, ConstraintReport)>,
+}
+
+impl Frame {
+    fn new<F: PrimeField>(cs: &ConstraintSystemRef<F>, prefix: Option<String>) -> Self {
+        let cs = cs.clone();
+        Self {
+            size: Box::new(move || ConstraintReport::of(&cs)),
+            prefix,
+            stack: vec![],
+        }
+    }
+
+    fn path(&self) -> Option<&String> {
+        self.stack
+            .last()
+            .map(|(path, _)| path)
+            .or(self.prefix.as_ref())
+    }
+
+    /// Run `f` with `self` as the frame of this thread.
+    fn run<R>(self, f: impl FnOnce() -> R) -> R {
+        let previous = FRAME.with(|frame| frame.borrow_mut().replace(self));
+        let result = f();
+        FRAME.with(|frame| *frame.borrow_mut() = previous);
+        result
+    }
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    /// The names of the spans that are not closed yet.
+    names: HashMap<u64, &'static str>,
+    profile: Profile,
+    indices: HashMap<String, usize>,
+}
+
+/// A `Subscriber` that records the spans of this crate.
+#[derive(Clone, Default)]
+struct Profiler(Arc<Mutex<State>>);
+
+impl Subscriber for Profiler {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.is_span() && metadata.target() == TARGET
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut state = self.0.lock().unwrap();
+        state.next_id += 1;
+        let id = state.next_id;
+        state.names.insert(id, span.metadata().name());
+        Id::from_u64(id)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, span: &Id) {
+        let name = self.0.lock().unwrap().names[&span.into_u64()];
+        FRAME.with(|frame| {
+            // Spans entered on a thread without a frame are not a part of any profiled
+            // constraint system.
+            if let Some(frame) = frame.borrow_mut().as_mut() {
+                let path = match frame.path() {
+                    Some(parent) => format!("{parent}/{name}"),
+                    None => name.to_string(),
+                };
+                let size = (frame.size)();
+                frame.stack.push((path, size));
+            }
+        })
+    }
+
+    fn exit(&self, _: &Id) {
+        let Some((path, report)) = FRAME.with(|frame| {
+            frame.borrow_mut().as_mut().map(|frame| {
+                let (path, before) = frame.stack.pop().unwrap();
+                (path, (frame.size)() - before)
+            })
+        }) else {
+            return;
+        };
+
+        let mut state = self.0.lock().unwrap();
+        let State {
+            profile, indices, ..
+        } = &mut *state;
+        let index = *indices.entry(path.clone()).or_insert_with(|| {
+            profile.spans.push(SpanReport {
+                path,
+                calls: 0,
+                report: ConstraintReport::default(),
+            });
+            profile.spans.len() - 1
+        });
+        let span = &mut profile.spans[index];
+        span.calls += 1;
+        span.report = span.report + report;
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        self.0.lock().unwrap().names.remove(&span.into_u64());
+        true
+    }
+}
+
+/// Run `f` and report the size of the constraints and variables added to `cs` in each span.
+pub fn profile<F: PrimeField, R>(
+    cs: &ConstraintSystemRef<F>,
+    f: impl FnOnce() -> R,
+) -> (R, Profile) {
+    let profiler = Profiler::default();
+    let result = with_default(profiler.clone(), || Frame::new(cs, None).run(f));
+
+    let profile = std::mem::take(&mut profiler.0.lock().unwrap().profile);
+    (result, profile)
+}
+
+/// `Fork` continues profiling the current span on other threads, where a part of the circuit is
+/// synthesized into a separate constraint system, e.g., a fragment of the rows.
+#[derive(Clone)]
+pub(crate) struct Fork(Option<(Dispatch, Option<String>)>);
+
+impl Fork {
+    /// Fork the current span, if it is being profiled.
+    pub(crate) fn current() -> Self {
+        Self(FRAME.with(|frame| {
+            frame
+                .borrow()
+                .as_ref()
+                .map(|frame| (get_default(Dispatch::clone), frame.path().cloned()))
+        }))
+    }
+
+    /// Run `f`, which synthesizes into `cs`, as a part of the forked span.
+    pub(crate) fn run<F: PrimeField, R>(
+        &self,
+        cs: &ConstraintSystemRef<F>,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        match &self.0 {
+            Some((dispatch, prefix)) => {
+                dispatcher::with_default(dispatch, || Frame::new(cs, prefix.clone()).run(f))
+            }
+            None => f(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        f64::F64Var,
+        r1cs_std::{alloc::AllocVar, eq::EqGadget},
+        regression::{InferenceCircuit, LinearModel},
+        report::float_gadgets,
+        shaped::Shape,
+    };
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn gadgets() -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = F64Var::new_witness(cs.clone(), || Ok(1.5))?;
+        let y = F64Var::new_witness(cs.clone(), || Ok(2.5))?;
+
+        let (z, profile) = profile(&cs, || &(&x * &y) + &x);
+        let (_, mul) = ConstraintReport::measure(&cs, || &x * &y);
+        let (_, add) = ConstraintReport::measure(&cs, || &z + &x);
+        z.enforce_equal(&F64Var::new_witness(cs.clone(), || Ok(5.25))?)?;
+        assert!(cs.is_satisfied()?);
+
+        assert_eq!(profile.spans.len(), 2);
+        assert_eq!(profile.get("mul").unwrap().report, mul);
+        assert_eq!(profile.get("add").unwrap().report, add);
+        Ok(())
+    }
+
+    #[test]
+    fn circuit() -> Result<(), SynthesisError> {
+        let model = LinearModel {
+            weights: vec![0.; 3],
+            bias: 0.,
+        };
+        let circuit = InferenceCircuit::new(model.clone(), vec![vec![0.; 3]; 4]);
+        let profile = Profile::circuit::<Fr>(circuit)?;
+
+        let total = InferenceCircuit::constraint_report::<Fr>(4, 3)?;
+        let mul = float_gadgets::<Fr, 11, 52>()?
+            .into_iter()
+            .find(|(name, _)| *name == "mul")
+            .unwrap()
+            .1;
+        let rows = profile.get("rows").unwrap();
+        assert_eq!(rows.calls, 1);
+        assert!(rows.report.num_constraints <= total.num_constraints);
+        assert_eq!(profile.get("rows/dot").unwrap().calls, 4);
+        let muls = profile.get("rows/dot/mul").unwrap();
+        assert_eq!(muls.calls, 12);
+        assert_eq!(muls.report.num_constraints, 12 * mul.num_constraints);
+        Ok(())
+    }
+}
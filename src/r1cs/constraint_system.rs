@@ -537,6 +537,93 @@ impl<F: Field> ConstraintSystem<F> {
             }
         }
     }
+
+    /// Turn `self` into a `Fragment` that can be appended to another constraint system, where
+    /// the first `num_shared` witnesses of `self` stand for variables in that constraint system.
+    ///
+    /// # Panics
+    /// This method panics if `self` has commitments or has fewer than `num_shared` witnesses.
+    pub fn into_fragment(self, num_shared: usize) -> Fragment<F> {
+        assert_eq!(self.num_commitments, 0);
+        assert!(self.num_witness_variables >= num_shared);
+
+        Fragment {
+            num_shared,
+            num_instance_variables: self.num_instance_variables - 1,
+            num_witness_variables: self.num_witness_variables - num_shared,
+            num_committed_variables: self.num_committed_variables,
+            num_constraints: self.num_constraints,
+            instance_assignment: self.instance_assignment.into_iter().skip(1).collect(),
+            witness_assignment: self
+                .witness_assignment
+                .into_iter()
+                .skip(num_shared)
+                .collect(),
+            committed_assignment: self.committed_assignment,
+            lc_map: self.lc_map,
+            a_constraints: self.a_constraints,
+            b_constraints: self.b_constraints,
+            c_constraints: self.c_constraints,
+        }
+    }
+
+    /// Append the variables and constraints of `fragment` to `self`, where the shared witnesses
+    /// of `fragment` are replaced by `shared`. The result is the same as if `fragment` was
+    /// synthesized in `self` directly.
+    ///
+    /// Returns the map from the variables of `fragment` to the variables of `self`.
+    ///
+    /// # Panics
+    /// This method panics if the number of `shared` variables does not match `fragment`.
+    pub fn append(&mut self, fragment: Fragment<F>, shared: &[Variable]) -> VariableMap {
+        assert_eq!(shared.len(), fragment.num_shared);
+
+        let map = VariableMap {
+            shared: shared.to_vec(),
+            instance_offset: self.num_instance_variables,
+            witness_offset: self.num_witness_variables,
+            committed_offset: self.num_committed_variables,
+            lc_offset: self.num_linear_combinations,
+        };
+
+        self.num_instance_variables += fragment.num_instance_variables;
+        self.num_witness_variables += fragment.num_witness_variables;
+        self.num_committed_variables += fragment.num_committed_variables;
+        self.num_constraints += fragment.num_constraints;
+        self.num_linear_combinations += fragment.lc_map.len();
+
+        if !self.is_in_setup_mode() {
+            self.instance_assignment
+                .extend(fragment.instance_assignment);
+            self.witness_assignment.extend(fragment.witness_assignment);
+            self.committed_assignment
+                .extend(fragment.committed_assignment);
+        }
+
+        self.lc_map
+            .extend(fragment.lc_map.into_iter().map(|LinearCombination(terms)| {
+                LinearCombination(
+                    terms
+                        .into_iter()
+                        .map(|(coeff, var)| (coeff, map.map(var)))
+                        .collect(),
+                )
+            }));
+        if self.should_construct_matrices() {
+            for (constraints, fragment_constraints) in [
+                (&mut self.a_constraints, fragment.a_constraints),
+                (&mut self.b_constraints, fragment.b_constraints),
+                (&mut self.c_constraints, fragment.c_constraints),
+            ] {
+                constraints.extend(fragment_constraints.into_iter().map(|i| map.lc_offset + i));
+            }
+        }
+        #[cfg(feature = "std")]
+        self.constraint_traces
+            .extend((0..fragment.num_constraints).map(|_| None));
+
+        map
+    }
 }
 /// The A, B and C matrices of a Rank-One `ConstraintSystem`.
 /// Also contains metadata on the structure of the constraint system
@@ -554,6 +641,54 @@ pub struct ConstraintMatrices<F: Field> {
     pub c: Matrix<F>,
 }
 
+/// A part of a constraint system that is synthesized separately, e.g., in another thread, and
+/// is then appended to the main constraint system by `ConstraintSystem::append`.
+///
+/// Unlike `ConstraintSystem`, a `Fragment` can be sent across threads.
+#[derive(Debug, Clone)]
+pub struct Fragment<F: Field> {
+    num_shared: usize,
+    num_instance_variables: usize,
+    num_witness_variables: usize,
+    num_committed_variables: usize,
+    num_constraints: usize,
+    instance_assignment: Vec<F>,
+    witness_assignment: Vec<F>,
+    committed_assignment: Vec<F>,
+    lc_map: Vec<LinearCombination<F>>,
+    a_constraints: Vec<usize>,
+    b_constraints: Vec<usize>,
+    c_constraints: Vec<usize>,
+}
+
+/// The map from the variables of an appended `Fragment` to the variables of the constraint
+/// system it is appended to.
+#[derive(Debug, Clone)]
+pub struct VariableMap {
+    shared: Vec<Variable>,
+    instance_offset: usize,
+    witness_offset: usize,
+    committed_offset: usize,
+    lc_offset: usize,
+}
+
+impl VariableMap {
+    /// Map the variable `v` of the fragment to the corresponding variable.
+    pub fn map(&self, v: Variable) -> Variable {
+        match v {
+            Variable::Zero | Variable::One => v,
+            Variable::Instance(i) => Variable::Instance(self.instance_offset + i - 1),
+            Variable::Witness(i) => match self.shared.get(i) {
+                Some(&shared) => shared,
+                None => Variable::Witness(self.witness_offset + i - self.shared.len()),
+            },
+            Variable::Committed(i) => Variable::Committed(self.committed_offset + i),
+            Variable::Commitment(_) => panic!("Fragments cannot have commitments"),
+            Variable::SymbolicLc(i) => Variable::SymbolicLc(self.lc_offset + i),
+        }
+    }
+}
+
 /// A shared reference to a constraint system that can be stored in high level
 /// variables.
 #[derive(Debug, Clone)]
@@ -885,6 +1020,24 @@ impl<F: Field> ConstraintSystemRef<F> {
         self.inner().and_then(|cs| cs.borrow().assigned_value(v))
     }
 
+    /// Consumes self to return the inner `ConstraintSystem<F>` as a `Fragment`, see
+    /// `ConstraintSystem::into_fragment`. Returns `None` if `Self::CS` is `None` or if any other
+    /// references to `Self::CS` exist.
+    pub fn into_fragment(self, num_shared: usize) -> Option<Fragment<F>> {
+        self.into_inner().map(|cs| cs.into_fragment(num_shared))
+    }
+
+    /// Append `fragment` to `self`, see `ConstraintSystem::append`.
+    pub fn append(
+        &self,
+        fragment: Fragment<F>,
+        shared: &[Variable],
+    ) -> crate::r1cs::Result<VariableMap> {
+        self.inner()
+            .ok_or(SynthesisError::MissingCS)
+            .map(|cs| cs.borrow_mut().append(fragment, shared))
+    }
+
     /// Get trace information about all constraints in the system
     pub fn constraint_names(&self) -> Option<Vec<String>> {
         #[cfg(feature = "std")]
@@ -972,6 +1125,46 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn append_fragment() -> crate::r1cs::Result<()> {
+        let two = Fr::one() + Fr::one();
+        let synthesize = |cs: &ConstraintSystemRef<Fr>, a: Variable| {
+            let b = cs.new_input_variable(|| Ok(two))?;
+            let c = cs.new_witness_variable(|| Ok(two))?;
+            let d = cs.new_lc(lc!() + a + b)?;
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+            cs.enforce_constraint(lc!() + d, lc!() + Variable::One, lc!() + c + a)?;
+            Ok(c)
+        };
+
+        let expected = ConstraintSystem::<Fr>::new_ref();
+        let a = expected.new_witness_variable(|| Ok(Fr::one()))?;
+        synthesize(&expected, a)?;
+        expected.finalize();
+
+        let fragment = {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a = cs.new_witness_variable(|| Ok(Fr::one()))?;
+            let c = synthesize(&cs, a)?;
+            assert_eq!(c, Variable::Witness(1));
+            cs.into_fragment(1).unwrap()
+        };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let a = cs.new_witness_variable(|| Ok(Fr::one()))?;
+        let map = cs.append(fragment, &[a])?;
+        assert_eq!(map.map(Variable::Witness(0)), a);
+        assert_eq!(map.map(Variable::Witness(1)), Variable::Witness(1));
+        assert_eq!(map.map(Variable::Instance(1)), Variable::Instance(1));
+        cs.finalize();
+
+        assert!(cs.is_satisfied()?);
+        assert_eq!(cs.to_matrices(), expected.to_matrices());
+        let (cs, expected) = (cs.into_inner().unwrap(), expected.into_inner().unwrap());
+        assert_eq!(cs.instance_assignment, expected.instance_assignment);
+        assert_eq!(cs.witness_assignment, expected.witness_assignment);
+        Ok(())
+    }
+
     /// Example meant to follow as closely as possible the excellent R1CS
     /// write-up by [Vitalik Buterin](https://vitalik.ca/general/2016/12/10/qap.html)
     /// and demonstrate how to construct such matrices in arkworks.
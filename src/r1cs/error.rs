@@ -21,6 +21,12 @@ pub enum SynthesisError {
     MalformedVerifyingKey,
     /// During CRS generation, we observed an unconstrained auxiliary variable
     UnconstrainedVariable,
+    /// During proof generation, the job was cancelled by its progress observer.
+    Cancelled,
+    /// During proof generation, the circuit did not have the shape of the prepared proving key.
+    MismatchedProvingKey,
+    /// During verification, the proof was generated for another verifying key.
+    MismatchedVerifyingKey,
 }
 
 impl ark_std::error::Error for SynthesisError {}
@@ -42,6 +48,13 @@ impl fmt::Display for SynthesisError {
             SynthesisError::UnconstrainedVariable => {
                 write!(f, "auxiliary variable was unconstrained")
             }
+            SynthesisError::Cancelled => write!(f, "proving was cancelled"),
+            SynthesisError::MismatchedProvingKey => {
+                write!(f, "the circuit does not match the proving key")
+            }
+            SynthesisError::MismatchedVerifyingKey => {
+                write!(f, "the proof was generated for another verifying key")
+            }
         }
     }
 }
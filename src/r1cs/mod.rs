@@ -12,8 +12,8 @@ mod error;
 
 pub use ark_ff::{Field, ToConstraintField};
 pub use constraint_system::{
-    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, Namespace,
-    OptimizationGoal, SynthesisMode,
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, Fragment,
+    Namespace, OptimizationGoal, SynthesisMode, VariableMap,
 };
 pub use error::SynthesisError;
 
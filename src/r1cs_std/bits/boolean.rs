@@ -47,6 +47,12 @@ impl<F: Field> AllocatedBool<F> {
         self.variable
     }
 
+    /// Construct an `AllocatedBool` from a variable in `cs` that is already constrained to be
+    /// boolean.
+    pub(crate) fn from_variable(variable: Variable, cs: ConstraintSystemRef<F>) -> Self {
+        Self { variable, cs }
+    }
+
     /// Allocate a witness variable without a booleanity check.
     pub(crate) fn new_witness_without_booleanity_check<T: Borrow<bool>>(
         cs: ConstraintSystemRef<F>,
@@ -1,6 +1,7 @@
 use crate::{
     lc,
-    r1cs::{ConstraintSystemRef, LinearCombination, Namespace, SynthesisError, Variable}, r1cs_std::boolean::AllocatedBool,
+    r1cs::{ConstraintSystemRef, LinearCombination, Namespace, SynthesisError, Variable},
+    r1cs_std::boolean::AllocatedBool,
 };
 use ark_ff::PrimeField;
 
@@ -11,6 +11,10 @@ use crate::r1cs_std::prelude::*;
 /// That is, it implements the R1CS equivalent of `ark_ff::Fp*`.
 pub mod fp;
 
+/// This module implements emulated arithmetic in prime fields other than the constraint field.
+#[cfg(feature = "recursion")]
+pub mod nonnative;
+
 /// This trait is a hack used to work around the lack of implied bounds.
 pub trait FieldOpsBounds<'a, F, T: 'a>:
     Sized
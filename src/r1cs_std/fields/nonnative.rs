@@ -0,0 +1,482 @@
+//! Emulated arithmetic in a prime field `T` whose modulus `q` differs from the modulus of the
+//! constraint field `F`, e.g., in the base field of a curve whose points are handled by a circuit
+//! over its scalar field.
+//!
+//! An element is represented by `k = ceil(log q / LIMB_BITS)` limbs of `LIMB_BITS` bits in
+//! little-endian order, each of which is range-checked by lookups, so the represented integer is
+//! less than `2^(k * LIMB_BITS)`, but not necessarily less than `q`. Each operation witnesses its
+//! result `r` and a quotient `t`, and enforces the integer equation `e = t * q + r` for the integer
+//! `e` computed from the operands (e.g., `a * b` for a multiplication) limb by limb with carries.
+
+use core::{borrow::Borrow, marker::PhantomData};
+
+use ark_ff::PrimeField;
+use num::{BigInt, BigUint, Integer, Signed, Zero};
+
+use crate::{
+    powers::pow2,
+    r1cs::{Namespace, SynthesisError},
+    r1cs_std::{
+        alloc::{AllocVar, AllocationMode},
+        fields::{fp::FpVar, FieldVar},
+        prelude::{Boolean, CondSelectGadget, EqGadget},
+        R1CSVar,
+    },
+    traits::BitDecompose,
+};
+
+/// The number of bits of each limb.
+const LIMB_BITS: usize = 64;
+
+/// The smallest `b` such that `n <= 2^b`.
+fn ceil_log2(n: usize) -> usize {
+    n.next_power_of_two().ilog2() as usize
+}
+
+/// An element of the prime field `T` in a circuit over the prime field `F`.
+#[derive(Clone, Debug)]
+pub struct NonNativeFieldVar<T: PrimeField, F: PrimeField> {
+    /// The limbs in little-endian order, each of which has at most `LIMB_BITS` bits.
+    limbs: Vec<FpVar<F>>,
+    _target: PhantomData<T>,
+}
+
+impl<T: PrimeField, F: PrimeField> NonNativeFieldVar<T, F> {
+    /// The number `k` of limbs of an element.
+    fn num_limbs() -> usize {
+        (T::MODULUS_BIT_SIZE as usize).div_ceil(LIMB_BITS)
+    }
+
+    /// The number of bits `s` such that the represented integers are less than `2^s * q`.
+    fn slack() -> usize {
+        Self::num_limbs() * LIMB_BITS + 1 - T::MODULUS_BIT_SIZE as usize
+    }
+
+    /// The modulus `q` of `T`.
+    fn modulus() -> BigUint {
+        T::MODULUS.into()
+    }
+
+    /// Split `x < 2^(n * LIMB_BITS)` into `n` limbs.
+    fn limbs_of(x: &BigUint, n: usize) -> Vec<F> {
+        let digits = x.to_u64_digits();
+        (0..n)
+            .map(|i| F::from(digits.get(i).copied().unwrap_or(0)))
+            .collect()
+    }
+
+    /// Interpret `v` as a signed integer in `(-p / 2, p / 2)`.
+    fn signed(v: F) -> BigInt {
+        let v: BigUint = v.into();
+        if v > F::MODULUS_MINUS_ONE_DIV_TWO.into() {
+            let p: BigUint = F::MODULUS.into();
+            BigInt::from(v) - BigInt::from(p)
+        } else {
+            v.into()
+        }
+    }
+
+    /// Convert the signed integer `v` to `F`.
+    fn from_signed(v: &BigInt) -> F {
+        let magnitude = F::from(v.magnitude().clone());
+        if v.is_negative() {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// The integer `sum_i coeffs_i * 2^(i * LIMB_BITS)`, where each coefficient is signed.
+    fn integer(coeffs: &[FpVar<F>]) -> Result<BigInt, SynthesisError> {
+        coeffs
+            .iter()
+            .enumerate()
+            .try_fold(BigInt::zero(), |acc, (i, c)| {
+                Ok(acc + (Self::signed(c.value()?) << (i * LIMB_BITS)))
+            })
+    }
+
+    fn from_limbs(limbs: Vec<FpVar<F>>) -> Self {
+        Self {
+            limbs,
+            _target: PhantomData,
+        }
+    }
+
+    /// Allocate the `n` limbs of `x` in `mode`, where the last limb has at most `last_bits` bits
+    /// and the others have at most `LIMB_BITS` bits.
+    fn new_limbs(
+        cs: impl Into<Namespace<F>>,
+        x: Result<BigUint, SynthesisError>,
+        n: usize,
+        last_bits: usize,
+        mode: AllocationMode,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let cs = cs.into().cs();
+        // The limbs are allocated one by one, so that a missing value is only needed when proving.
+        let limbs = x.map(|x| Self::limbs_of(&x, n));
+        (0..n)
+            .map(|i| {
+                let limb = FpVar::new_variable(
+                    cs.clone(),
+                    || limbs.as_ref().map(|l| l[i]).map_err(|e| *e),
+                    mode,
+                )?;
+                limb.enforce_bit_length(if i == n - 1 { last_bits } else { LIMB_BITS })?;
+                Ok(limb)
+            })
+            .collect()
+    }
+
+    /// Enforce that the integer `sum_i coeffs_i * 2^(i * LIMB_BITS)` is 0, where
+    /// `|coeffs_i| < 2^bits`, by enforcing `coeffs_i + c_(i - 1) = c_i * 2^LIMB_BITS` for the
+    /// carries `|c_i| < 2^(bits - LIMB_BITS + 1)`, where `c_(-1)` and the last carry are 0.
+    /// Both sides are less than `p / 2` in absolute value, so the equations hold over integers.
+    fn enforce_zero(coeffs: &[FpVar<F>], bits: usize) -> Result<(), SynthesisError> {
+        assert!(
+            bits + 2 < F::MODULUS_BIT_SIZE as usize,
+            "cannot emulate a field of {} bits in a field of {} bits",
+            T::MODULUS_BIT_SIZE,
+            F::MODULUS_BIT_SIZE
+        );
+        let cs = coeffs.cs();
+        let carry_bits = bits - LIMB_BITS + 1;
+
+        let mut carry = FpVar::zero();
+        for (i, c) in coeffs.iter().enumerate() {
+            let sum = c + &carry;
+            if i == coeffs.len() - 1 {
+                sum.enforce_equal(&FpVar::zero())?;
+                break;
+            }
+            let next = sum.value().map(|v| Self::signed(v) >> LIMB_BITS);
+            let next = FpVar::new_witness(cs.clone(), || next.map(|v| Self::from_signed(&v)))?;
+            (&next + pow2::<F>(carry_bits)).enforce_bit_length(carry_bits + 1)?;
+            sum.enforce_equal(&(&next * pow2::<F>(LIMB_BITS)))?;
+            carry = next;
+        }
+        Ok(())
+    }
+
+    /// Enforce `e = t * q + r` for the integers `e = sum_i coeffs_i * 2^(i * LIMB_BITS)` and `r`
+    /// represented by `r`, and a witnessed quotient `0 <= t < 2^quotient_bits`, where
+    /// `|coeffs_i| < 2^coeff_bits`.
+    fn enforce_congruent(
+        coeffs: &[FpVar<F>],
+        coeff_bits: usize,
+        quotient_bits: usize,
+        r: &[FpVar<F>],
+    ) -> Result<(), SynthesisError> {
+        let cs = coeffs.cs().or(r.cs());
+        let q = Self::modulus();
+        let k = Self::num_limbs();
+
+        let num_quotient_limbs = quotient_bits.div_ceil(LIMB_BITS);
+        let t = Self::integer(coeffs).and_then(|e| {
+            Ok((e - Self::integer(r)?)
+                .to_biguint()
+                .map(|v| v / &q)
+                .unwrap_or_default())
+        });
+        let t = Self::new_limbs(
+            cs,
+            t,
+            num_quotient_limbs,
+            quotient_bits - (num_quotient_limbs - 1) * LIMB_BITS,
+            AllocationMode::Witness,
+        )?;
+
+        let q = Self::limbs_of(&q, k);
+        let n = coeffs.len().max(r.len()).max(num_quotient_limbs + k - 1);
+        let diff = (0..n)
+            .map(|i| {
+                let mut d = coeffs.get(i).cloned().unwrap_or_else(FpVar::zero);
+                if let Some(r) = r.get(i) {
+                    d -= r;
+                }
+                for (j, t) in t.iter().enumerate().take(i + 1) {
+                    if let Some(&q) = q.get(i - j) {
+                        d -= t * q;
+                    }
+                }
+                d
+            })
+            .collect::<Vec<_>>();
+        let bits = coeff_bits.max(2 * LIMB_BITS + ceil_log2(num_quotient_limbs)) + 2;
+        Self::enforce_zero(&diff, bits)
+    }
+
+    /// Reduce the integer `e = sum_i coeffs_i * 2^(i * LIMB_BITS)` in `[0, 2^quotient_bits * q)`
+    /// modulo `q`, where `|coeffs_i| < 2^coeff_bits`.
+    fn reduce(
+        coeffs: &[FpVar<F>],
+        coeff_bits: usize,
+        quotient_bits: usize,
+    ) -> Result<Self, SynthesisError> {
+        let cs = coeffs.cs();
+        let q = BigInt::from(Self::modulus());
+        let r = Self::integer(coeffs).map(|e| e.mod_floor(&q).to_biguint().unwrap());
+        if cs.is_none() {
+            return Ok(Self::constant(T::from(r?)));
+        }
+
+        let k = Self::num_limbs();
+        let r = Self::new_limbs(cs, r, k, LIMB_BITS, AllocationMode::Witness)?;
+        Self::enforce_congruent(coeffs, coeff_bits, quotient_bits, &r)?;
+        Ok(Self::from_limbs(r))
+    }
+
+    /// The coefficients of `self - other + m`, where `m` is the smallest multiple of `q` that is
+    /// at least `2^(k * LIMB_BITS)`, so that the result is non-negative and less than
+    /// `2^(s + 2) * q`.
+    fn difference(&self, other: &Self) -> Vec<FpVar<F>> {
+        let k = Self::num_limbs();
+        let q = Self::modulus();
+        let m = ((BigUint::from(1u8) << (k * LIMB_BITS)) - 1u8) / &q * &q + &q;
+        Self::limbs_of(&m, k + 1)
+            .into_iter()
+            .enumerate()
+            .map(|(i, m)| match (self.limbs.get(i), other.limbs.get(i)) {
+                (Some(a), Some(b)) => a - b + m,
+                _ => FpVar::constant(m),
+            })
+            .collect()
+    }
+
+    /// The constant `v`.
+    pub fn constant(v: T) -> Self {
+        let limbs = Self::limbs_of(&v.into(), Self::num_limbs());
+        Self::from_limbs(limbs.into_iter().map(FpVar::constant).collect())
+    }
+
+    /// The constant 0.
+    pub fn zero() -> Self {
+        Self::constant(T::zero())
+    }
+
+    /// The constant 1.
+    pub fn one() -> Self {
+        Self::constant(T::one())
+    }
+
+    /// Compute `self + other`.
+    pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let coeffs = self
+            .limbs
+            .iter()
+            .zip(&other.limbs)
+            .map(|(a, b)| a + b)
+            .collect::<Vec<_>>();
+        Self::reduce(&coeffs, LIMB_BITS + 1, Self::slack() + 1)
+    }
+
+    /// Compute `self - other`.
+    pub fn sub(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Self::reduce(&self.difference(other), LIMB_BITS + 1, Self::slack() + 2)
+    }
+
+    /// Compute `-self`.
+    pub fn negate(&self) -> Result<Self, SynthesisError> {
+        Self::zero().sub(self)
+    }
+
+    /// Compute `self * other`, which costs `k^2` constraints for the products of the limbs and
+    /// `O(k)` range checks.
+    pub fn mul(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let k = Self::num_limbs();
+        let coeffs = (0..2 * k - 1)
+            .map(|i| {
+                (i.saturating_sub(k - 1)..=i.min(k - 1)).fold(FpVar::zero(), |sum, j| {
+                    sum + &self.limbs[j] * &other.limbs[i - j]
+                })
+            })
+            .collect::<Vec<_>>();
+        Self::reduce(
+            &coeffs,
+            2 * LIMB_BITS + ceil_log2(k),
+            k * LIMB_BITS + Self::slack(),
+        )
+    }
+
+    /// Compute `self^-1`.
+    /// The circuit is unsatisfiable if `self` is 0.
+    pub fn inverse(&self) -> Result<Self, SynthesisError> {
+        if self.is_constant() {
+            return Ok(Self::constant(
+                self.value()?
+                    .inverse()
+                    .ok_or(SynthesisError::Unsatisfiable)?,
+            ));
+        }
+        let inverse = self.value().map(|v| v.inverse().unwrap_or_default().into());
+        let inverse = Self::from_limbs(Self::new_limbs(
+            self.cs(),
+            inverse,
+            Self::num_limbs(),
+            LIMB_BITS,
+            AllocationMode::Witness,
+        )?);
+        self.mul(&inverse)?.enforce_equal(&Self::one())?;
+        Ok(inverse)
+    }
+
+    /// Enforce that `self` and `other` are equal modulo `q`, even if they are represented by
+    /// different integers.
+    pub fn enforce_equal(&self, other: &Self) -> Result<(), SynthesisError> {
+        if self.is_constant() && other.is_constant() {
+            return match self.value()? == other.value()? {
+                true => Ok(()),
+                false => Err(SynthesisError::Unsatisfiable),
+            };
+        }
+        Self::enforce_congruent(
+            &self.difference(other),
+            LIMB_BITS + 1,
+            Self::slack() + 2,
+            &[],
+        )
+    }
+}
+
+impl<T: PrimeField, F: PrimeField> R1CSVar<F> for NonNativeFieldVar<T, F> {
+    type Value = T;
+
+    fn cs(&self) -> crate::r1cs::ConstraintSystemRef<F> {
+        self.limbs.cs()
+    }
+
+    fn value(&self) -> Result<T, SynthesisError> {
+        let integer = self
+            .limbs
+            .iter()
+            .rev()
+            .try_fold(BigUint::zero(), |acc, limb| {
+                let limb: BigUint = limb.value()?.into();
+                Ok((acc << LIMB_BITS) + limb)
+            })?;
+        Ok(T::from(integer))
+    }
+}
+
+impl<T: PrimeField, F: PrimeField> AllocVar<T, F> for NonNativeFieldVar<T, F> {
+    fn new_variable<V: Borrow<T>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<V, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        if mode == AllocationMode::Constant {
+            return Ok(Self::constant(*f()?.borrow()));
+        }
+        let value = f().map(|v| (*v.borrow()).into());
+        Ok(Self::from_limbs(Self::new_limbs(
+            cs,
+            value,
+            Self::num_limbs(),
+            LIMB_BITS,
+            mode,
+        )?))
+    }
+}
+
+impl<T: PrimeField, F: PrimeField> CondSelectGadget<F> for NonNativeFieldVar<T, F> {
+    fn conditionally_select(
+        cond: &Boolean<F>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let limbs = true_value
+            .limbs
+            .iter()
+            .zip(&false_value.limbs)
+            .map(|(t, f)| cond.select(t, f))
+            .collect::<Result<_, _>>()?;
+        Ok(Self::from_limbs(limbs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::{ConstraintSystem, SynthesisMode};
+    use ark_std::{test_rng, UniformRand};
+
+    fn check_fields<T: PrimeField, F: PrimeField>() -> Result<(), SynthesisError> {
+        let rng = &mut test_rng();
+        let cs = ConstraintSystem::<F>::new_ref();
+        let (a, b) = (T::rand(rng), T::rand(rng));
+        let x = NonNativeFieldVar::<T, F>::new_witness(cs.clone(), || Ok(a))?;
+        let y = NonNativeFieldVar::new_witness(cs.clone(), || Ok(b))?;
+        let c = NonNativeFieldVar::constant(b);
+
+        assert_eq!(x.add(&y)?.value()?, a + b);
+        assert_eq!(x.sub(&y)?.value()?, a - b);
+        assert_eq!(y.sub(&x)?.value()?, b - a);
+        assert_eq!(x.negate()?.value()?, -a);
+        assert_eq!(x.mul(&y)?.value()?, a * b);
+        assert_eq!(x.mul(&c)?.value()?, a * b);
+        assert_eq!(x.inverse()?.value()?, a.inverse().unwrap());
+        x.mul(&y)?.enforce_equal(&y.mul(&x)?)?;
+        x.sub(&y)?.add(&y)?.enforce_equal(&x)?;
+        assert!(cs.is_satisfied()?);
+
+        // Operations on constants need no constraints.
+        let num_constraints = cs.num_constraints();
+        assert_eq!(
+            c.mul(&c)?.add(&c)?.inverse()?.value()?,
+            (b * b + b).inverse().unwrap()
+        );
+        assert_eq!(cs.num_constraints(), num_constraints);
+        Ok(())
+    }
+
+    #[test]
+    fn arithmetic() -> Result<(), SynthesisError> {
+        check_fields::<ark_bls12_381::Fq, ark_bls12_381::Fr>()?;
+        check_fields::<ark_bn254::Fq, ark_bn254::Fr>()?;
+        check_fields::<ark_bn254::Fr, ark_bls12_381::Fr>()
+    }
+
+    #[test]
+    fn equality() -> Result<(), SynthesisError> {
+        type Var = NonNativeFieldVar<ark_bn254::Fq, ark_bn254::Fr>;
+        let rng = &mut test_rng();
+        let (a, b) = (ark_bn254::Fq::rand(rng), ark_bn254::Fq::rand(rng));
+        let q = BigUint::from(ark_bn254::Fq::MODULUS);
+
+        // `a * b` is equal to `a * b + q`, which is also represented by 4 limbs, but not to
+        // `a * b + 1`.
+        for (offset, satisfied) in [(0u8, true), (1, false)] {
+            let cs = ConstraintSystem::new_ref();
+            let x = Var::new_witness(cs.clone(), || Ok(a))?;
+            let y = Var::new_witness(cs.clone(), || Ok(b))?;
+            let product: BigUint = (a * b).into();
+            let z = Var::from_limbs(Var::new_limbs(
+                cs.clone(),
+                Ok(product + &q + offset),
+                4,
+                LIMB_BITS,
+                AllocationMode::Witness,
+            )?);
+            x.mul(&y)?.enforce_equal(&z)?;
+            assert_eq!(cs.is_satisfied()?, satisfied);
+        }
+
+        // The inverse of 0 does not exist.
+        let cs = ConstraintSystem::new_ref();
+        Var::new_witness(cs.clone(), || Ok(ark_bn254::Fq::zero()))?.inverse()?;
+        assert!(!cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn missing_value() -> Result<(), SynthesisError> {
+        type Var = NonNativeFieldVar<ark_bn254::Fq, ark_bn254::Fr>;
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        let x = Var::new_witness(cs.clone(), || Ok(ark_bn254::Fq::from(3u8)))?;
+        let y = Var::new_input(cs.clone(), || Ok(ark_bn254::Fq::from(5u8)))?;
+        x.mul(&y)?.inverse()?.sub(&x)?;
+        assert!(cs.num_constraints() > 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,2 @@
+/// This module implements points on short Weierstrass curves whose base fields are emulated.
+pub mod nonnative;
@@ -0,0 +1,303 @@
+//! Points on a short Weierstrass curve `y^2 = x^3 + ax + b` whose base field is emulated by
+//! `NonNativeFieldVar`, e.g., points in G1 of a pairing-friendly curve in a circuit over its scalar
+//! field.
+//!
+//! The point at infinity cannot be represented, and the additions are incomplete: adding two
+//! points with the same x-coordinate makes the circuit unsatisfiable rather than computing a wrong
+//! result. `NonNativeAffineVar::msm_le` starts from an offset point whose discrete logarithm is
+//! unknown, so that this only happens with negligible probability for honest inputs.
+
+use core::borrow::Borrow;
+
+use ark_ec::{
+    short_weierstrass::{Affine, SWCurveConfig},
+    AffineRepr, CurveGroup, Group,
+};
+use ark_ff::{PrimeField, Zero};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    r1cs::{ConstraintSystemRef, Namespace, SynthesisError},
+    r1cs_std::{
+        alloc::{AllocVar, AllocationMode},
+        fields::nonnative::NonNativeFieldVar,
+        prelude::{Boolean, CondSelectGadget},
+        R1CSVar,
+    },
+};
+
+/// A point other than the point at infinity on the curve `P` in a circuit over `F`.
+pub struct NonNativeAffineVar<P: SWCurveConfig, F: PrimeField>
+where
+    P::BaseField: PrimeField,
+{
+    /// The x-coordinate.
+    pub x: NonNativeFieldVar<P::BaseField, F>,
+    /// The y-coordinate.
+    pub y: NonNativeFieldVar<P::BaseField, F>,
+}
+
+impl<P: SWCurveConfig, F: PrimeField> Clone for NonNativeAffineVar<P, F>
+where
+    P::BaseField: PrimeField,
+{
+    fn clone(&self) -> Self {
+        Self {
+            x: self.x.clone(),
+            y: self.y.clone(),
+        }
+    }
+}
+
+/// A point on `P` with unknown discrete logarithm, which is hashed to the curve by SHA-256 with
+/// try-and-increment, and then multiplied by the cofactor.
+fn offset<P: SWCurveConfig>() -> Affine<P>
+where
+    P::BaseField: PrimeField,
+{
+    (0u64..)
+        .find_map(|i| {
+            let x = P::BaseField::from_le_bytes_mod_order(
+                &Sha256::new_with_prefix(b"r1cs_float/groups/offset")
+                    .chain_update(i.to_le_bytes())
+                    .finalize(),
+            );
+            Affine::<P>::get_point_from_x_unchecked(x, false)
+                .map(|p| p.clear_cofactor())
+                .filter(|p| !p.is_zero())
+        })
+        .unwrap()
+}
+
+impl<P: SWCurveConfig, F: PrimeField> NonNativeAffineVar<P, F>
+where
+    P::BaseField: PrimeField,
+{
+    /// The constant `p`.
+    /// Return `SynthesisError::Unsatisfiable` if `p` is the point at infinity.
+    pub fn constant(p: Affine<P>) -> Result<Self, SynthesisError> {
+        let (&x, &y) = p.xy().ok_or(SynthesisError::Unsatisfiable)?;
+        Ok(Self {
+            x: NonNativeFieldVar::constant(x),
+            y: NonNativeFieldVar::constant(y),
+        })
+    }
+
+    /// Enforce that `self` is on the curve, i.e., `y^2 = x^3 + ax + b`.
+    fn enforce_on_curve(&self) -> Result<(), SynthesisError> {
+        let mut rhs = self.x.mul(&self.x)?.mul(&self.x)?;
+        if !P::COEFF_A.is_zero() {
+            rhs = rhs.add(&self.x.mul(&NonNativeFieldVar::constant(P::COEFF_A))?)?;
+        }
+        rhs = rhs.add(&NonNativeFieldVar::constant(P::COEFF_B))?;
+        self.y.mul(&self.y)?.enforce_equal(&rhs)
+    }
+
+    /// Compute `-self`.
+    pub fn negate(&self) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: self.x.clone(),
+            y: self.y.negate()?,
+        })
+    }
+
+    /// Compute `self + other`.
+    /// The circuit is unsatisfiable if `self` and `other` have the same x-coordinate, i.e., if
+    /// they are equal or opposite.
+    pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let lambda = other
+            .y
+            .sub(&self.y)?
+            .mul(&other.x.sub(&self.x)?.inverse()?)?;
+        let x = lambda.mul(&lambda)?.sub(&self.x)?.sub(&other.x)?;
+        let y = lambda.mul(&self.x.sub(&x)?)?.sub(&self.y)?;
+        Ok(Self { x, y })
+    }
+
+    /// Compute `2 * self`.
+    /// The circuit is unsatisfiable if the y-coordinate of `self` is 0, i.e., if `self` has
+    /// order 2.
+    pub fn double(&self) -> Result<Self, SynthesisError> {
+        let xx = self.x.mul(&self.x)?;
+        let mut numerator = xx.add(&xx)?.add(&xx)?;
+        if !P::COEFF_A.is_zero() {
+            numerator = numerator.add(&NonNativeFieldVar::constant(P::COEFF_A))?;
+        }
+        let lambda = numerator.mul(&self.y.add(&self.y)?.inverse()?)?;
+        let x = lambda.mul(&lambda)?.sub(&self.x)?.sub(&self.x)?;
+        let y = lambda.mul(&self.x.sub(&x)?)?.sub(&self.y)?;
+        Ok(Self { x, y })
+    }
+
+    /// Compute `initial + sum_i scalars_i * bases_i`, where each scalar is given by its bits in
+    /// little-endian order.
+    ///
+    /// The bits of all scalars are processed together by double-and-add from the most significant
+    /// one, starting from an offset point `h` instead of the point at infinity, and the offset
+    /// `2^n * h` is removed at the end, where `n` is the maximum number of bits.
+    pub fn msm_le(
+        initial: &Self,
+        bases: &[Self],
+        scalars: &[Vec<Boolean<F>>],
+    ) -> Result<Self, SynthesisError> {
+        assert_eq!(bases.len(), scalars.len());
+        let h = offset::<P>();
+        let n = scalars.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut acc = Self::constant(h)?;
+        for i in (0..n).rev() {
+            acc = acc.double()?;
+            for (base, bits) in bases.iter().zip(scalars) {
+                if let Some(bit) = bits.get(i) {
+                    acc = bit.select(&acc.add(base)?, &acc)?;
+                }
+            }
+        }
+
+        let shift = (0..n).fold(h.into_group(), |p, _| p.double());
+        let correction = initial.add(&Self::constant((-shift).into_affine())?)?;
+        acc.add(&correction)
+    }
+
+    /// Enforce that `self` and `other` are the same point.
+    pub fn enforce_equal(&self, other: &Self) -> Result<(), SynthesisError> {
+        self.x.enforce_equal(&other.x)?;
+        self.y.enforce_equal(&other.y)
+    }
+}
+
+impl<P: SWCurveConfig, F: PrimeField> R1CSVar<F> for NonNativeAffineVar<P, F>
+where
+    P::BaseField: PrimeField,
+{
+    type Value = Affine<P>;
+
+    fn cs(&self) -> ConstraintSystemRef<F> {
+        self.x.cs().or(self.y.cs())
+    }
+
+    fn value(&self) -> Result<Affine<P>, SynthesisError> {
+        Ok(Affine::new_unchecked(self.x.value()?, self.y.value()?))
+    }
+}
+
+impl<P: SWCurveConfig, F: PrimeField> AllocVar<Affine<P>, F> for NonNativeAffineVar<P, F>
+where
+    P::BaseField: PrimeField,
+{
+    /// Allocate a point in `mode`, which is enforced to be on the curve, but not to be in the
+    /// prime-order subgroup, which is only sufficient for curves of prime order, e.g., G1 of
+    /// BN254.
+    /// The circuit is unsatisfiable if the point is the point at infinity.
+    fn new_variable<T: Borrow<Affine<P>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into().cs();
+        let p = f().map(|p| *p.borrow());
+        if mode == AllocationMode::Constant {
+            return Self::constant(p?);
+        }
+        // The point at infinity is allocated as `(0, 0)`, which is not on the curve.
+        let xy = p.map(|p| {
+            p.xy()
+                .map_or((Default::default(), Default::default()), |(&x, &y)| (x, y))
+        });
+        let x = NonNativeFieldVar::new_variable(cs.clone(), || xy.map(|(x, _)| x), mode)?;
+        let y = NonNativeFieldVar::new_variable(cs, || xy.map(|(_, y)| y), mode)?;
+        let p = Self { x, y };
+        p.enforce_on_curve()?;
+        Ok(p)
+    }
+}
+
+impl<P: SWCurveConfig, F: PrimeField> CondSelectGadget<F> for NonNativeAffineVar<P, F>
+where
+    P::BaseField: PrimeField,
+{
+    fn conditionally_select(
+        cond: &Boolean<F>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: cond.select(&true_value.x, &false_value.x)?,
+            y: cond.select(&true_value.y, &false_value.y)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        r1cs::ConstraintSystem,
+        r1cs_std::{fields::fp::FpVar, ToBitsGadget},
+    };
+    use ark_bn254::{g1::Config, Fr, G1Affine, G1Projective};
+    use ark_std::{test_rng, UniformRand};
+
+    type Var = NonNativeAffineVar<Config, Fr>;
+
+    #[test]
+    fn arithmetic() -> Result<(), SynthesisError> {
+        let rng = &mut test_rng();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let (p, q) = (G1Affine::rand(rng), G1Affine::rand(rng));
+        let x = Var::new_witness(cs.clone(), || Ok(p))?;
+        let y = Var::new_witness(cs.clone(), || Ok(q))?;
+
+        assert_eq!(x.add(&y)?.value()?, (p + q).into_affine());
+        assert_eq!(x.double()?.value()?, (p + p).into_affine());
+        assert_eq!(x.negate()?.value()?, -p);
+        assert_eq!(x.add(&Var::constant(q)?)?.value()?, (p + q).into_affine());
+        x.add(&y)?.enforce_equal(&y.add(&x)?)?;
+        assert!(cs.is_satisfied()?);
+
+        // Adding a point to itself is rejected by the incomplete addition.
+        x.add(&x)?;
+        assert!(!cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn not_on_curve() -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        Var::new_witness(cs.clone(), || Ok(G1Affine::zero()))?;
+        assert!(!cs.is_satisfied()?);
+        assert!(Var::constant(G1Affine::zero()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn msm() -> Result<(), SynthesisError> {
+        let rng = &mut test_rng();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let initial = G1Affine::rand(rng);
+        let bases = [G1Affine::rand(rng), G1Affine::rand(rng)];
+        for scalars in [
+            [Fr::from(5u8), Fr::from(0u8)],
+            [Fr::rand(rng), -Fr::from(1u8)],
+        ] {
+            let bits = scalars
+                .iter()
+                .map(|&s| FpVar::new_witness(cs.clone(), || Ok(s))?.to_bits_le())
+                .collect::<Result<Vec<_>, _>>()?;
+            let bases = bases
+                .iter()
+                .map(|&b| Var::constant(b))
+                .collect::<Result<Vec<_>, _>>()?;
+            let result = Var::msm_le(
+                &Var::new_witness(cs.clone(), || Ok(initial))?,
+                &bases,
+                &bits,
+            )?;
+            let expected =
+                initial + bases[0].value()? * scalars[0] + bases[1].value()? * scalars[1];
+            assert_eq!(result.value()?, G1Projective::from(expected).into_affine());
+        }
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+}
@@ -14,6 +14,10 @@ pub use self::bits::*;
 /// This module implements gadgets related to field arithmetic.
 pub mod fields;
 
+/// This module implements gadgets related to group arithmetic.
+#[cfg(feature = "recursion")]
+pub mod groups;
+
 /// This module describes a trait for allocating new variables in a constraint
 /// system.
 pub mod alloc;
@@ -0,0 +1,284 @@
+//! Allocation of `FloatVar`s from exact values, i.e., decimal strings and rational numbers, which
+//! are rounded to the format of `FloatVar` with an explicitly specified rounding mode, instead of
+//! going through a lossy conversion to `f64`.
+
+use ark_ff::PrimeField;
+use num::{BigInt, BigRational, Integer, One, Signed, ToPrimitive, Zero};
+
+use crate::{
+    error::Error,
+    float::FloatVar,
+    r1cs::{Namespace, SynthesisError},
+    r1cs_std::alloc::AllocationMode,
+};
+
+/// The rounding modes of IEEE 754, which determine the number that an exact value is rounded to if
+/// it is not representable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round to the nearest number, or to the one with an even mantissa on a tie.
+    #[default]
+    NearestEven,
+    /// Round towards zero, i.e., truncate the value.
+    TowardZero,
+    /// Round towards positive infinity.
+    TowardPositive,
+    /// Round towards negative infinity.
+    TowardNegative,
+}
+
+/// `2^e` as a rational number.
+fn two_to(e: i64) -> BigRational {
+    let power = BigInt::one() << e.unsigned_abs();
+    if e >= 0 {
+        BigRational::from_integer(power)
+    } else {
+        BigRational::new(BigInt::one(), power)
+    }
+}
+
+/// Parse a decimal string, e.g., `-123.456e-7`, into its sign and absolute value.
+/// As the value is only used for rounding, the decimal exponent is clamped to `[-bound - d, bound]`,
+/// where `d` is the number of digits, so that values far out of the range of a format with `bound`
+/// or fewer binary exponents are still out of the range, but are cheap to compute.
+pub(crate) fn parse_decimal(value: &str, bound: i64) -> Result<(bool, BigRational), Error> {
+    let invalid = || Error::Conversion(format!("invalid decimal number {value:?}"));
+
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (significand, exponent) = match rest.split_once(['e', 'E']) {
+        Some((significand, exponent)) => {
+            (significand, exponent.parse::<i64>().map_err(|_| invalid())?)
+        }
+        None => (rest, 0),
+    };
+    let (integer, fraction) = significand.split_once('.').unwrap_or((significand, ""));
+    let digits = [integer, fraction].concat();
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let significand = BigInt::parse_bytes(digits.as_bytes(), 10).ok_or_else(invalid)?;
+    let exponent = exponent
+        .saturating_sub(fraction.len() as i64)
+        .clamp(-bound - digits.len() as i64, bound);
+    let power = BigInt::from(10u8).pow(exponent.unsigned_abs() as u32);
+    let value = if exponent >= 0 {
+        BigRational::from_integer(significand * power)
+    } else {
+        BigRational::new(significand, power)
+    };
+
+    Ok((sign, value))
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> FloatVar<F, E, M> {
+    /// Round `value` (whose sign is `sign`) by `rounding`, and return the sign, the biased exponent,
+    /// and the mantissa without the leading 1 of the result, as `Self::decode` does.
+    fn encode(sign: bool, value: &BigRational, rounding: Rounding) -> (bool, u128, u128) {
+        let value = value.abs();
+        let bias = (1i64 << (E - 1)) - 1;
+        if value.is_zero() {
+            return (sign, 0, 0);
+        }
+
+        // Find the exponent `k = floor(log2(value))`, which is no less than the minimum exponent of
+        // normal numbers, so that `value * 2^(M - k)` is the mantissa with the leading 1 (or the
+        // mantissa of a subnormal number) followed by the bits to be rounded.
+        let mut k = value.numer().bits() as i64 - value.denom().bits() as i64;
+        if value < two_to(k) {
+            k -= 1;
+        }
+        let mut k = k.max(1 - bias);
+        let scaled = &value * two_to(M as i64 - k);
+        let mut mantissa = scaled.to_integer();
+        let remainder = scaled.fract();
+
+        let half = BigRational::new(BigInt::one(), BigInt::from(2u8));
+        let round_up = match rounding {
+            Rounding::NearestEven => remainder > half || (remainder == half && mantissa.is_odd()),
+            Rounding::TowardZero => false,
+            Rounding::TowardPositive => !sign && !remainder.is_zero(),
+            Rounding::TowardNegative => sign && !remainder.is_zero(),
+        };
+        if round_up {
+            mantissa += 1;
+        }
+        // Rounding up may carry into a new bit, in which case the exponent is incremented.
+        if mantissa == BigInt::one() << (M + 1) {
+            mantissa >>= 1;
+            k += 1;
+        }
+
+        if k > bias {
+            // The result overflows to infinity, unless it is rounded towards zero.
+            let to_infinity = match rounding {
+                Rounding::NearestEven => true,
+                Rounding::TowardZero => false,
+                Rounding::TowardPositive => !sign,
+                Rounding::TowardNegative => sign,
+            };
+            return if to_infinity {
+                (sign, (1 << E) - 1, 0)
+            } else {
+                (sign, (1 << E) - 2, (1 << M) - 1)
+            };
+        }
+        let mantissa = mantissa.to_u128().unwrap();
+        if mantissa < 1 << M {
+            (sign, 0, mantissa)
+        } else {
+            (sign, (k + bias) as u128, mantissa - (1 << M))
+        }
+    }
+
+    /// Round a decimal string to the format of `Self`, see `Self::new_decimal`.
+    fn encode_decimal(value: &str, rounding: Rounding) -> Result<(bool, u128, u128), Error> {
+        let (sign, value) = parse_decimal(value, (1 << (E - 1)) + M as i64 + 2)?;
+        Ok(Self::encode(sign, &value, rounding))
+    }
+
+    /// Allocate a variable from a rational number, which is rounded to the format of `Self` by
+    /// `rounding`. A zero value is allocated as `+0`.
+    pub fn new_rational(
+        cs: impl Into<Namespace<F>>,
+        value: &BigRational,
+        rounding: Rounding,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let sign = value.is_negative();
+        Self::new_variable_from_components(cs, || Ok(Self::encode(sign, value, rounding)), mode)
+    }
+
+    /// Allocate a variable from a decimal string with an optional sign, fraction, and exponent,
+    /// e.g., `-0`, `0.1`, or `1.5e-10`, which is rounded to the format of `Self` by `rounding`.
+    /// Return an error if the string is not a valid decimal number.
+    pub fn new_decimal(
+        cs: impl Into<Namespace<F>>,
+        value: &str,
+        rounding: Rounding,
+        mode: AllocationMode,
+    ) -> Result<Self, Error> {
+        let components = Self::encode_decimal(value, rounding)?;
+        Ok(Self::new_variable_from_components(
+            cs,
+            || Ok(components),
+            mode,
+        )?)
+    }
+
+    /// Compute the public inputs for a number allocated by `Self::new_rational` in the input mode.
+    pub fn verifier_input_rational(value: &BigRational, rounding: Rounding) -> [F; 3] {
+        let (s, e, m) = Self::encode(value.is_negative(), value, rounding);
+
+        [F::from(s), F::from(e), F::from(m)]
+    }
+
+    /// Compute the public inputs for a number allocated by `Self::new_decimal` in the input mode,
+    /// or return an error if the string is not a valid decimal number.
+    pub fn verifier_input_decimal(value: &str, rounding: Rounding) -> Result<[F; 3], Error> {
+        let (s, e, m) = Self::encode_decimal(value, rounding)?;
+
+        Ok([F::from(s), F::from(e), F::from(m)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{f32::F32Var, f64::F64Var, r1cs::ConstraintSystem};
+    use ark_bls12_381::Fr;
+    use rand::{thread_rng, Rng};
+
+    fn decimal(value: &str, rounding: Rounding) -> Result<f64, Error> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = F64Var::new_decimal(cs.clone(), value, rounding, AllocationMode::Witness)?;
+        assert!(cs.is_satisfied()?);
+        x.to_f64()
+    }
+
+    #[test]
+    fn decimals() -> Result<(), Error> {
+        let tiny = f64::from_bits(1);
+        let below = |v: f64| f64::from_bits(v.to_bits() - 1);
+        for (value, rounding, expected) in [
+            ("0.1", Rounding::NearestEven, 0.1),
+            ("0.1", Rounding::TowardPositive, 0.1),
+            ("0.1", Rounding::TowardZero, below(0.1)),
+            ("-0.1", Rounding::TowardNegative, -0.1),
+            ("-0.1", Rounding::TowardPositive, -below(0.1)),
+            ("+1.5e3", Rounding::TowardZero, 1500.),
+            ("-0", Rounding::NearestEven, -0.),
+            ("0.000", Rounding::TowardNegative, 0.),
+            ("1e400", Rounding::NearestEven, f64::INFINITY),
+            ("1e400", Rounding::TowardZero, f64::MAX),
+            ("-1e400", Rounding::TowardPositive, -f64::MAX),
+            ("-1e400", Rounding::TowardNegative, f64::NEG_INFINITY),
+            ("1e-400", Rounding::NearestEven, 0.),
+            ("1e-400", Rounding::TowardPositive, tiny),
+            ("-1e-99999999", Rounding::TowardNegative, -tiny),
+            (
+                "2.2250738585072011e-308",
+                Rounding::NearestEven,
+                below(f64::MIN_POSITIVE),
+            ),
+            // `2^53 + 1` is a tie between `2^53` and `2^53 + 2`.
+            ("9007199254740993", Rounding::NearestEven, 2f64.powi(53)),
+            (
+                "9007199254740995",
+                Rounding::NearestEven,
+                2f64.powi(53) + 4.,
+            ),
+        ] {
+            let result = decimal(value, rounding)?;
+            assert_eq!(result.to_bits(), expected.to_bits(), "{value} {rounding:?}");
+        }
+
+        // The shortest representations printed by Rust are rounded back to the same numbers.
+        let rng = &mut thread_rng();
+        for _ in 0..100 {
+            let v = f64::from_bits(rng.gen());
+            if v.is_finite() {
+                assert_eq!(decimal(&format!("{v:e}"), Rounding::NearestEven)?, v);
+                assert_eq!(
+                    F64Var::<Fr>::verifier_input_decimal(&v.to_string(), Rounding::NearestEven)?,
+                    F64Var::<Fr>::verifier_input(v)
+                );
+            }
+        }
+
+        for value in ["", "-", ".", "1.2.3", "abc", "1e", "--1", "0x10", "inf"] {
+            assert!(decimal(value, Rounding::NearestEven).is_err(), "{value}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn rationals() -> Result<(), Error> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let third = BigRational::new(BigInt::one(), BigInt::from(3u8));
+        let x = F64Var::new_rational(
+            cs.clone(),
+            &third,
+            Rounding::NearestEven,
+            AllocationMode::Witness,
+        )?;
+        let y = F32Var::new_rational(
+            cs.clone(),
+            &-third.clone(),
+            Rounding::TowardZero,
+            AllocationMode::Input,
+        )?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(x.to_f64()?, 1. / 3.);
+        // `1f32 / 3.` is rounded up, and hence `-1/3` rounded towards zero is one ulp closer to zero.
+        assert_eq!(y.to_f32()?, -f32::from_bits((1f32 / 3.).to_bits() - 1));
+        assert_eq!(
+            F32Var::<Fr>::verifier_input_rational(&-third, Rounding::TowardZero),
+            F32Var::<Fr>::verifier_input(y.to_f32()?)
+        );
+        Ok(())
+    }
+}
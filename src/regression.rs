@@ -0,0 +1,4814 @@
+use std::cmp::Ordering;
+
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::{
+    f64::F64Var,
+    hash::{hash, hash_gadget, merkle_root_gadget, MerkleTree},
+    inputs::PublicInputs,
+    macros::profile_span,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+    r1cs_std::{
+        alloc::{AllocVar, AllocationMode},
+        bits::ToBitsGadget,
+        boolean::Boolean,
+        fields::fp::FpVar,
+        prelude::{EqGadget, FieldVar},
+        R1CSVar,
+    },
+    report::ConstraintReport,
+    traits::BitDecompose,
+};
+
+/// A linear model `y = weights · x + bias`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearModel {
+    pub weights: Vec<f64>,
+    pub bias: f64,
+}
+
+impl LinearModel {
+    /// Get the coefficients of the model, i.e., the bias followed by the weights, which
+    /// correspond to the columns of the data matrix augmented with a leading column of ones.
+    pub fn coefficients(&self) -> Vec<f64> {
+        [&[self.bias], &self.weights[..]].concat()
+    }
+
+    /// Predict the target of the features `x`.
+    /// The operations are performed in the same order as in the circuit, so the result is
+    /// bit-identical to the prediction computed in the circuit.
+    pub fn predict(&self, x: &[f64]) -> f64 {
+        assert_eq!(x.len(), self.weights.len());
+        let mut y = self.bias;
+        for (w, x) in self.weights.iter().zip(x) {
+            y += w * x;
+        }
+        y
+    }
+
+    /// Fit the model to the features `x` and the targets `y` by ordinary least squares, i.e.,
+    /// by solving the normal equations `(XᵀX) β = Xᵀy` with Gaussian elimination.
+    /// Return `None` if the normal equations are singular.
+    pub fn fit(x: &[Vec<f64>], y: &[f64]) -> Option<Self> {
+        let (g, h) = normal_equations(x, y);
+        Some(Self::from_coefficients(&solve(g, h)?))
+    }
+
+    /// Fit the model to the features `x` and the targets `y` by minimizing the Huber loss with
+    /// threshold `delta`, i.e., `r² / 2` for the residuals `|r| <= delta` and `delta (|r| -
+    /// delta / 2)` for the others, by iteratively reweighted least squares starting from the
+    /// ordinary least squares fit.
+    /// Return `None` if the weighted normal equations become singular.
+    pub fn fit_huber(x: &[Vec<f64>], y: &[f64], delta: f64) -> Option<Self> {
+        let mut model = Self::fit(x, y)?;
+        for _ in 0..100 {
+            let n = model.weights.len() + 1;
+            let mut g = vec![vec![0.; n]; n];
+            let mut h = vec![0.; n];
+            for (x, &y) in x.iter().zip(y) {
+                let r = (y - model.predict(x)).abs();
+                let w = if r <= delta { 1. } else { delta / r };
+                let row = [&[1.][..], x].concat();
+                for j in 0..n {
+                    for k in 0..n {
+                        g[j][k] += w * row[j] * row[k];
+                    }
+                    h[j] += w * row[j] * y;
+                }
+            }
+            let next = Self::from_coefficients(&solve(g, h)?);
+            if next == model {
+                break;
+            }
+            model = next;
+        }
+        Some(model)
+    }
+
+    fn from_coefficients(beta: &[f64]) -> Self {
+        Self {
+            bias: beta[0],
+            weights: beta[1..].to_vec(),
+        }
+    }
+}
+
+/// Solve `G β = h` by Gaussian elimination with partial pivoting, or return `None` if `G` is
+/// singular.
+fn solve(mut g: Vec<Vec<f64>>, mut h: Vec<f64>) -> Option<Vec<f64>> {
+    let n = h.len();
+
+    for i in 0..n {
+        // Partial pivoting
+        let p = (i..n).max_by(|&a, &b| g[a][i].abs().total_cmp(&g[b][i].abs()))?;
+        if g[p][i] == 0. {
+            return None;
+        }
+        g.swap(i, p);
+        h.swap(i, p);
+        let (g_upper, g_lower) = g.split_at_mut(i + 1);
+        let (h_upper, h_lower) = h.split_at_mut(i + 1);
+        for (g_j, h_j) in g_lower.iter_mut().zip(h_lower) {
+            let f = g_j[i] / g_upper[i][i];
+            for (a, b) in g_j[i..].iter_mut().zip(&g_upper[i][i..]) {
+                *a -= f * b;
+            }
+            *h_j -= f * h_upper[i];
+        }
+    }
+
+    let mut beta = vec![0.; n];
+    for i in (0..n).rev() {
+        let mut v = h[i];
+        for (g, b) in g[i][i + 1..].iter().zip(&beta[i + 1..]) {
+            v -= g * b;
+        }
+        beta[i] = v / g[i][i];
+    }
+
+    Some(beta)
+}
+
+/// Compute the normal equations `G = XᵀX` and `h = Xᵀy`, where `X` is `x` augmented with a
+/// leading column of ones.
+/// The operations are performed in the same order as in [`TrainingCircuit`].
+pub fn normal_equations(x: &[Vec<f64>], y: &[f64]) -> (Vec<Vec<f64>>, Vec<f64>) {
+    assert_eq!(x.len(), y.len());
+    assert!(!x.is_empty());
+    let n = x[0].len() + 1;
+
+    let mut g = vec![vec![0.; n]; n];
+    let mut h = vec![0.; n];
+    for (r, (x, &y)) in x.iter().zip(y).enumerate() {
+        assert_eq!(x.len(), n - 1);
+        let row = [&[1.][..], x].concat();
+        for j in 0..n {
+            for k in j..n {
+                let v = row[j] * row[k];
+                g[j][k] = if r == 0 { v } else { g[j][k] + v };
+            }
+            let v = row[j] * y;
+            h[j] = if r == 0 { v } else { h[j] + v };
+        }
+    }
+    let g = (0..n)
+        .map(|j| (0..n).map(|k| g[j.min(k)][j.max(k)]).collect())
+        .collect();
+
+    (g, h)
+}
+
+/// Compute the maximum absolute residual of the normal equations `|Gβ - h|` for the model
+/// fitted to the features `x` and the targets `y`, which is the minimum tolerance accepted
+/// by [`TrainingCircuit`].
+/// The operations are performed in the same order as in [`TrainingCircuit`].
+pub fn optimality_gap(model: &LinearModel, x: &[Vec<f64>], y: &[f64]) -> f64 {
+    let (g, h) = normal_equations(x, y);
+    normal_equations_gap(model, &g, &h)
+}
+
+/// Compute the maximum absolute residual of the normal equations `|Gβ - h|` for `model`.
+fn normal_equations_gap(model: &LinearModel, g: &[Vec<f64>], h: &[f64]) -> f64 {
+    let beta = model.coefficients();
+    assert_eq!(beta.len(), h.len());
+
+    g.iter()
+        .zip(h)
+        .map(|(g, h)| {
+            let mut v = g[0] * beta[0];
+            for (g, b) in g.iter().zip(&beta).skip(1) {
+                v += g * b;
+            }
+            (v - h).abs()
+        })
+        .fold(0., f64::max)
+}
+
+/// Clip the residual `r` to `[-delta, delta]`, which is the derivative of the Huber loss.
+fn huber_clip(r: f64, delta: f64) -> f64 {
+    if r > delta {
+        delta
+    } else if r < -delta {
+        -delta
+    } else {
+        r
+    }
+}
+
+/// Compute the maximum absolute component of the gradient `Xᵀψ(y - Xβ)` of the Huber loss with
+/// threshold `delta` for the model fitted to the features `x` and the targets `y`, where `ψ`
+/// clips the residuals to `[-delta, delta]`, which is the minimum tolerance accepted by
+/// [`HuberCircuit`].
+/// The operations are performed in the same order as in [`HuberCircuit`].
+///
+/// # Panics
+///
+/// Panics if `x` is empty or if `x` and `y` have different lengths.
+pub fn huber_optimality_gap(model: &LinearModel, x: &[Vec<f64>], y: &[f64], delta: f64) -> f64 {
+    assert_eq!(x.len(), y.len());
+    assert!(!x.is_empty());
+    let mut gradient: Option<Vec<f64>> = None;
+    for (x, &y) in x.iter().zip(y) {
+        let r = huber_clip(y - model.predict(x), delta);
+        let products = [&[r][..], &x.iter().map(|x| r * x).collect::<Vec<_>>()].concat();
+        gradient = Some(match gradient {
+            Some(s) => s.iter().zip(products).map(|(s, v)| s + v).collect(),
+            None => products,
+        });
+    }
+
+    gradient.unwrap().iter().map(|v| v.abs()).fold(0., f64::max)
+}
+
+/// Compute the subgradients of the quantile loss `ρ_τ(r) = r (τ - [r < 0])` for the residuals
+/// of the model fitted to the features `x` and the targets `y`, which are chosen such that the
+/// subgradient of the total loss `Σ a_i X_i` is as close to 0 as possible, and hence can be used
+/// by [`QuantileCircuit`].
+///
+/// The subgradient `a_i` is `tau` for the residuals greater than `epsilon`, `tau - 1` for those
+/// less than `-epsilon`, and in `[tau - 1, tau]` for the others (which are considered zero),
+/// where the latter are solved by least norm and clipped to the range.
+/// Return `None` if the samples with zero residuals do not span all coefficients.
+pub fn quantile_subgradients(
+    model: &LinearModel,
+    x: &[Vec<f64>],
+    y: &[f64],
+    tau: f64,
+    epsilon: f64,
+) -> Option<Vec<f64>> {
+    assert_eq!(x.len(), y.len());
+    let n = model.weights.len() + 1;
+    let lower = tau - 1.;
+
+    let mut a = vec![0.; x.len()];
+    let mut zeros = vec![];
+    // The part of the subgradient `b = -Σ a_i X_i` that the zero residuals should cancel.
+    let mut b = vec![0.; n];
+    for (i, (x, &y)) in x.iter().zip(y).enumerate() {
+        let r = y - model.predict(x);
+        let row = [&[1.][..], x].concat();
+        if r > epsilon || r < -epsilon {
+            a[i] = if r > epsilon { tau } else { lower };
+            for (b, x) in b.iter_mut().zip(&row) {
+                *b -= a[i] * x;
+            }
+        } else {
+            zeros.push((i, row));
+        }
+    }
+
+    // Solve `X_Zᵀ a_Z = b` by least norm, i.e., `a_Z = X_Z c` where `(X_Zᵀ X_Z) c = b`.
+    let g = (0..n)
+        .map(|j| {
+            (0..n)
+                .map(|k| zeros.iter().map(|(_, row)| row[j] * row[k]).sum())
+                .collect()
+        })
+        .collect();
+    let c = solve(g, b)?;
+    for (i, row) in zeros {
+        let v = row.iter().zip(&c).map(|(x, c)| x * c).sum::<f64>();
+        a[i] = v.clamp(lower, tau);
+    }
+
+    Some(a)
+}
+
+/// Compute the maximum absolute component of the subgradient `Σ a_i X_i` of the quantile loss
+/// for the features `x` and the `subgradients` of each sample, which is the minimum tolerance
+/// accepted by [`QuantileCircuit`].
+/// The operations are performed in the same order as in [`QuantileCircuit`].
+///
+/// # Panics
+///
+/// Panics if `x` is empty or if `x` and `subgradients` have different lengths.
+pub fn quantile_optimality_gap(x: &[Vec<f64>], subgradients: &[f64]) -> f64 {
+    assert_eq!(x.len(), subgradients.len());
+    assert!(!x.is_empty());
+    let mut gradient: Option<Vec<f64>> = None;
+    for (x, &a) in x.iter().zip(subgradients) {
+        let products = [&[a][..], &x.iter().map(|x| a * x).collect::<Vec<_>>()].concat();
+        gradient = Some(match gradient {
+            Some(s) => s.iter().zip(products).map(|(s, v)| s + v).collect(),
+            None => products,
+        });
+    }
+
+    gradient.unwrap().iter().map(|v| v.abs()).fold(0., f64::max)
+}
+
+/// Invert the matrix `g` by Gauss-Jordan elimination, or return `None` if it is singular.
+fn invert(g: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = g.len();
+    let mut g = g.to_vec();
+    let mut inverse = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| if i == j { 1. } else { 0. })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    for i in 0..n {
+        // Partial pivoting
+        let p = (i..n).max_by(|&a, &b| g[a][i].abs().total_cmp(&g[b][i].abs()))?;
+        if g[p][i] == 0. {
+            return None;
+        }
+        g.swap(i, p);
+        inverse.swap(i, p);
+        let d = g[i][i];
+        for (a, b) in g[i].iter_mut().zip(&mut inverse[i]) {
+            *a /= d;
+            *b /= d;
+        }
+        let (g_i, inverse_i) = (g[i].clone(), inverse[i].clone());
+        for (j, (g_j, inverse_j)) in g.iter_mut().zip(&mut inverse).enumerate() {
+            let f = g_j[i];
+            if j != i && f != 0. {
+                for (a, b) in g_j.iter_mut().zip(&g_i) {
+                    *a -= f * b;
+                }
+                for (a, b) in inverse_j.iter_mut().zip(&inverse_i) {
+                    *a -= f * b;
+                }
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+/// The standard errors of the coefficients of a linear model fitted by ordinary least squares,
+/// i.e., `sqrt(σ² C_jj)` for each coefficient `j`, where `C = (XᵀX)⁻¹`, and `σ² = RSS / (m - n)`
+/// is the residual variance of `m` samples and `n` coefficients.
+///
+/// As `C` cannot be computed exactly in floating point, it is provided by the prover, and
+/// [`TrainingCircuit`] checks that `|(XᵀX) C - I|` is at most `tolerance` in every entry, in the
+/// same way as the normal equations are checked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StandardErrors {
+    /// The standard errors, in the same order as the coefficients.
+    pub errors: Vec<f64>,
+    /// The private inverse `C` of `XᵀX`.
+    pub inverse: Vec<Vec<f64>>,
+    /// The maximum accepted `|(XᵀX) C - I|` in every entry.
+    pub tolerance: f64,
+}
+
+impl StandardErrors {
+    /// Create the standard errors of `num_coefficients` coefficients, whose values are all 0,
+    /// e.g., for generating the keys.
+    pub fn shape(num_coefficients: usize) -> Self {
+        Self {
+            errors: vec![0.; num_coefficients],
+            inverse: vec![vec![0.; num_coefficients]; num_coefficients],
+            tolerance: 0.,
+        }
+    }
+
+    /// Compute the standard errors of `model` fitted to the features `x` and the targets `y`,
+    /// where the tolerance is the minimum one accepted by [`TrainingCircuit`].
+    /// Return `None` if `XᵀX` is singular, or if there are no more samples than coefficients.
+    /// The operations are performed in the same order as in [`TrainingCircuit`].
+    pub fn compute(model: &LinearModel, x: &[Vec<f64>], y: &[f64]) -> Option<Self> {
+        let (g, _) = normal_equations(x, y);
+        let n = g.len();
+        if x.len() <= n {
+            return None;
+        }
+        let inverse = invert(&g)?;
+
+        let mut rss = 0.;
+        for (x, &y) in x.iter().zip(y) {
+            let r = y - model.predict(x);
+            rss += r * r;
+        }
+        let variance = rss / (x.len() - n) as f64;
+
+        let mut tolerance = 0f64;
+        for j in 0..n {
+            for (i, g) in g.iter().enumerate() {
+                let mut v = g[0] * inverse[0][j];
+                for (g, c) in g.iter().zip(&inverse).skip(1) {
+                    v += g * c[j];
+                }
+                tolerance = tolerance.max(if i == j { v - 1. } else { v }.abs());
+            }
+        }
+
+        Some(Self {
+            errors: (0..n).map(|j| (variance * inverse[j][j]).sqrt()).collect(),
+            inverse,
+            tolerance,
+        })
+    }
+}
+
+/// Compute the standard errors `sqrt(rss / degrees_of_freedom * c)` of the coefficients, where
+/// `rss` is the residual sum of squares, and each `c` is the corresponding diagonal entry of
+/// `(XᵀX)⁻¹`.
+pub fn standard_errors<F: PrimeField>(
+    rss: &F64Var<F>,
+    degrees_of_freedom: usize,
+    inverse_diagonal: &[F64Var<F>],
+) -> Result<Vec<F64Var<F>>, SynthesisError> {
+    let degrees_of_freedom =
+        F64Var::new_constant(ConstraintSystemRef::None, degrees_of_freedom as f64)?;
+    standard_errors_with(rss, &degrees_of_freedom, inverse_diagonal)
+}
+
+/// Compute the standard errors as in [`standard_errors`], where the degrees of freedom are
+/// computed in the circuit, e.g., from the number of unmasked samples.
+fn standard_errors_with<F: PrimeField>(
+    rss: &F64Var<F>,
+    degrees_of_freedom: &F64Var<F>,
+    inverse_diagonal: &[F64Var<F>],
+) -> Result<Vec<F64Var<F>>, SynthesisError> {
+    let variance = rss / degrees_of_freedom;
+    inverse_diagonal
+        .iter()
+        .map(|c| F64Var::sqrt(&(&variance * c)))
+        .collect()
+}
+
+/// Enforce that each of the features `x` is within the corresponding `bounds`, i.e.,
+/// `lower <= x <= upper`, where NaNs are never within bounds.
+pub fn enforce_within_bounds<F: PrimeField>(
+    x: &[F64Var<F>],
+    bounds: &[(F64Var<F>, F64Var<F>)],
+) -> Result<(), SynthesisError> {
+    if x.len() != bounds.len() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    for (x, (lower, upper)) in x.iter().zip(bounds) {
+        F64Var::is_le(lower, x)?.enforce_equal(&Boolean::TRUE)?;
+        F64Var::is_le(x, upper)?.enforce_equal(&Boolean::TRUE)?;
+    }
+    Ok(())
+}
+
+/// Compute the coefficient of determination `R² = 1 - RSS / TSS` of `model` on the features
+/// `x` and the targets `y`, i.e., the fraction of the variance of the targets explained by the
+/// model, where `RSS = Σ (y - ŷ)²` and `TSS = Σ (y - ȳ)²`.
+/// The operations are performed in the same order as in [`r_squared_gadget`].
+pub fn r_squared(model: &LinearModel, x: &[Vec<f64>], y: &[f64]) -> f64 {
+    let predictions = x.iter().map(|x| model.predict(x)).collect::<Vec<_>>();
+    let mut rss = 0.;
+    let mut sum = 0.;
+    for (&y, p) in y.iter().zip(&predictions) {
+        let r = y - p;
+        rss += r * r;
+        sum += y;
+    }
+    let mean = sum / y.len() as f64;
+    let mut tss = 0.;
+    for &y in y {
+        let d = y - mean;
+        tss += d * d;
+    }
+    1. - rss / tss
+}
+
+/// Compute the coefficient of determination `R² = 1 - RSS / TSS` of the `predictions` of the
+/// targets `y`, which is NaN or -inf if the targets are constant.
+pub fn r_squared_gadget<F: PrimeField>(
+    y: &[F64Var<F>],
+    predictions: &[F64Var<F>],
+) -> Result<F64Var<F>, SynthesisError> {
+    if y.is_empty() || y.len() != predictions.len() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    let mut rss = F64Var::new_constant(ConstraintSystemRef::None, 0.)?;
+    let mut sum = F64Var::new_constant(ConstraintSystemRef::None, 0.)?;
+    for (y, p) in y.iter().zip(predictions) {
+        let r = y - p;
+        rss += &r * &r;
+        sum += y;
+    }
+    let count = F64Var::new_constant(ConstraintSystemRef::None, y.len() as f64)?;
+    let mean = sum / count;
+    let mut tss = F64Var::new_constant(ConstraintSystemRef::None, 0.)?;
+    for y in y {
+        let d = y - &mean;
+        tss += &d * &d;
+    }
+    let one = F64Var::new_constant(ConstraintSystemRef::None, 1.)?;
+    Ok(one - rss / tss)
+}
+
+/// The cofactors `C_ij` of the 2×2 or 3×3 matrix `a`, or `None` if `a` has another shape.
+/// The operations are performed in the same order as in [`cofactors_gadget`].
+fn cofactors(a: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    if !(n == 2 || n == 3) || a.iter().any(|row| row.len() != n) {
+        return None;
+    }
+    Some(
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| match n {
+                        2 if (i + j) % 2 == 0 => a[1 - i][1 - j],
+                        2 => -a[1 - i][1 - j],
+                        _ => {
+                            let (i1, i2, j1, j2) =
+                                ((i + 1) % 3, (i + 2) % 3, (j + 1) % 3, (j + 2) % 3);
+                            a[i1][j1] * a[i2][j2] - a[i1][j2] * a[i2][j1]
+                        }
+                    })
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+/// The determinant of `a` by the expansion `a_00 C_00 + a_01 C_01 (+ a_02 C_02)` along the first
+/// row, given the cofactors `c` of `a`.
+fn cofactor_expansion(a: &[Vec<f64>], c: &[Vec<f64>]) -> f64 {
+    let mut det = a[0][0] * c[0][0];
+    for (a, c) in a[0].iter().zip(&c[0]).skip(1) {
+        det += a * c;
+    }
+    det
+}
+
+/// Compute the determinant of the 2×2 or 3×3 matrix `a` in the same way as
+/// [`determinant_gadget`], or return `None` if `a` has another shape.
+pub fn determinant(a: &[Vec<f64>]) -> Option<f64> {
+    Some(cofactor_expansion(a, &cofactors(a)?))
+}
+
+/// Invert the 2×2 or 3×3 matrix `a` by its cofactors, i.e., `a⁻¹_ij = C_ji / det(a)`, in the same
+/// way as [`cofactor_inverse_gadget`]. Return `None` if `a` has another shape, or if the
+/// determinant is not a nonzero number.
+pub fn cofactor_inverse(a: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let c = cofactors(a)?;
+    let det = cofactor_expansion(a, &c);
+    if det == 0. || det.is_nan() {
+        return None;
+    }
+    Some(
+        (0..a.len())
+            .map(|i| c.iter().map(|c| c[i] / det).collect())
+            .collect(),
+    )
+}
+
+/// The cofactors of the 2×2 or 3×3 matrix `a`. See [`cofactors`].
+fn cofactors_gadget<F: PrimeField>(
+    a: &[Vec<F64Var<F>>],
+) -> Result<Vec<Vec<F64Var<F>>>, SynthesisError> {
+    let n = a.len();
+    if !(n == 2 || n == 3) || a.iter().any(|row| row.len() != n) {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    Ok((0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| match n {
+                    2 if (i + j) % 2 == 0 => a[1 - i][1 - j].clone(),
+                    2 => -&a[1 - i][1 - j],
+                    _ => {
+                        let (i1, i2, j1, j2) = ((i + 1) % 3, (i + 2) % 3, (j + 1) % 3, (j + 2) % 3);
+                        &a[i1][j1] * &a[i2][j2] - &a[i1][j2] * &a[i2][j1]
+                    }
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// See [`cofactor_expansion`].
+fn cofactor_expansion_gadget<F: PrimeField>(
+    a: &[Vec<F64Var<F>>],
+    c: &[Vec<F64Var<F>>],
+) -> F64Var<F> {
+    let mut det = &a[0][0] * &c[0][0];
+    for (a, c) in a[0].iter().zip(&c[0]).skip(1) {
+        det += a * c;
+    }
+    det
+}
+
+/// Compute the determinant of the 2×2 or 3×3 matrix `a`, e.g., of `XᵀX` for checking that the
+/// normal equations of a low-dimensional regression have a unique solution.
+/// The circuit is unsatisfiable if `a` has another shape.
+pub fn determinant_gadget<F: PrimeField>(
+    a: &[Vec<F64Var<F>>],
+) -> Result<F64Var<F>, SynthesisError> {
+    Ok(cofactor_expansion_gadget(a, &cofactors_gadget(a)?))
+}
+
+/// Invert the 2×2 or 3×3 matrix `a` by its cofactors, which allows checking closed-form solutions
+/// `a⁻¹ b` of low-dimensional regressions.
+/// The circuit is unsatisfiable if `a` has another shape, or if the determinant is 0 or NaN, so
+/// this also enforces that `a` is invertible.
+pub fn cofactor_inverse_gadget<F: PrimeField>(
+    a: &[Vec<F64Var<F>>],
+) -> Result<Vec<Vec<F64Var<F>>>, SynthesisError> {
+    let c = cofactors_gadget(a)?;
+    let det = cofactor_expansion_gadget(a, &c);
+    let zero = F64Var::new_constant(ConstraintSystemRef::None, 0.)?;
+    F64Var::is_gt(&det.abs(), &zero)?.enforce_equal(&Boolean::TRUE)?;
+    Ok((0..a.len())
+        .map(|i| c.iter().map(|c| &c[i] / &det).collect())
+        .collect())
+}
+
+/// Check that `a` is a nonempty `m × n` matrix and `p` is an `n × m` matrix.
+fn is_transposed_shape<T>(a: &[Vec<T>], p: &[Vec<T>]) -> bool {
+    let (m, n) = (a.len(), a.first().map_or(0, |row| row.len()));
+    m > 0
+        && n > 0
+        && p.len() == n
+        && a.iter().all(|row| row.len() == n)
+        && p.iter().all(|row| row.len() == m)
+}
+
+/// The product of the matrices `a` and `b`, where the operations are performed in the same order as
+/// in [`matrix_product_gadget`].
+fn matrix_product(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    a.iter()
+        .map(|a| {
+            (0..b[0].len())
+                .map(|j| {
+                    let mut v = a[0] * b[0][j];
+                    for (a, b) in a.iter().zip(b).skip(1) {
+                        v += a * b[j];
+                    }
+                    v
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The residuals of the four Penrose conditions `APA = A`, `PAP = P`, `(AP)ᵀ = AP`, and
+/// `(PA)ᵀ = PA` in every entry.
+fn penrose_residuals(a: &[Vec<f64>], p: &[Vec<f64>]) -> Vec<f64> {
+    let (ap, pa) = (matrix_product(a, p), matrix_product(p, a));
+    let (apa, pap) = (matrix_product(&ap, a), matrix_product(&pa, p));
+
+    let mut residuals = vec![];
+    for (x, y) in [(&apa[..], a), (&pap[..], p)] {
+        for (x, y) in x.iter().zip(y) {
+            residuals.extend(x.iter().zip(y).map(|(x, y)| (x - y).abs()));
+        }
+    }
+    for s in [&ap, &pa] {
+        for i in 0..s.len() {
+            residuals.extend((i + 1..s.len()).map(|j| (s[i][j] - s[j][i]).abs()));
+        }
+    }
+    residuals
+}
+
+/// Compute the maximum residual of the Penrose conditions, which is the minimum tolerance accepted
+/// by [`enforce_pseudoinverse`] for the pseudoinverse `p` of `a`.
+/// The operations are performed in the same order as in [`enforce_pseudoinverse`].
+pub fn pseudoinverse_gap(a: &[Vec<f64>], p: &[Vec<f64>]) -> f64 {
+    assert!(
+        is_transposed_shape(a, p),
+        "the pseudoinverse of an m × n matrix should be an n × m matrix"
+    );
+    penrose_residuals(a, p).into_iter().fold(0., f64::max)
+}
+
+/// See [`matrix_product`].
+fn matrix_product_gadget<F: PrimeField>(
+    a: &[Vec<F64Var<F>>],
+    b: &[Vec<F64Var<F>>],
+) -> Vec<Vec<F64Var<F>>> {
+    a.iter()
+        .map(|a| {
+            (0..b[0].len())
+                .map(|j| {
+                    let mut v = &a[0] * &b[0][j];
+                    for (a, b) in a.iter().zip(b).skip(1) {
+                        v += a * &b[j];
+                    }
+                    v
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Enforce that the private `p` is the Moore-Penrose pseudoinverse of `a` up to `tolerance`, i.e.,
+/// that the residuals of the four Penrose conditions `APA = A`, `PAP = P`, `(AP)ᵀ = AP`, and
+/// `(PA)ᵀ = PA` are at most `tolerance` in every entry.
+/// Since the pseudoinverse exists and is unique even if `AᵀA` is singular, `P y` certifies the
+/// minimum-norm least squares solution of `A β = y` for rank-deficient features.
+/// The circuit is unsatisfiable if `a` is not a nonempty `m × n` matrix or `p` is not `n × m`.
+pub fn enforce_pseudoinverse<F: PrimeField>(
+    a: &[Vec<F64Var<F>>],
+    p: &[Vec<F64Var<F>>],
+    tolerance: &F64Var<F>,
+) -> Result<(), SynthesisError> {
+    if !is_transposed_shape(a, p) {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    let (ap, pa) = (matrix_product_gadget(a, p), matrix_product_gadget(p, a));
+    let (apa, pap) = (matrix_product_gadget(&ap, a), matrix_product_gadget(&pa, p));
+
+    let mut residuals = vec![];
+    for (x, y) in [(&apa[..], a), (&pap[..], p)] {
+        for (x, y) in x.iter().zip(y) {
+            residuals.extend(x.iter().zip(y).map(|(x, y)| (x - y).abs()));
+        }
+    }
+    for s in [&ap, &pa] {
+        for i in 0..s.len() {
+            residuals.extend((i + 1..s.len()).map(|j| (&s[i][j] - &s[j][i]).abs()));
+        }
+    }
+    for r in residuals {
+        F64Var::is_le(&r, tolerance)?.enforce_equal(&Boolean::TRUE)?;
+    }
+    Ok(())
+}
+
+/// Check that `x` is a nonempty `m × n` matrix with `m ≥ n`, `q` is `m × n`, `r` is `n × n`, and
+/// `y` has length `m`.
+fn is_qr_shape<T>(x: &[Vec<T>], y: &[T], q: &[Vec<T>], r: &[Vec<T>]) -> bool {
+    let (m, n) = (x.len(), x.first().map_or(0, |row| row.len()));
+    n > 0
+        && m >= n
+        && y.len() == m
+        && q.len() == m
+        && r.len() == n
+        && x.iter().chain(q).chain(r).all(|row| row.len() == n)
+}
+
+/// Decompose the `m × n` matrix `x` into an `m × n` matrix `Q` with orthonormal columns and an
+/// `n × n` upper triangular matrix `R` such that `QR = X` by modified Gram-Schmidt, e.g., for
+/// witnessing the factors of [`qr_least_squares_gadget`].
+/// Return `None` if `x` does not have full column rank or `m < n`.
+#[allow(clippy::type_complexity)]
+pub fn qr_decompose(x: &[Vec<f64>]) -> Option<(Vec<Vec<f64>>, Vec<Vec<f64>>)> {
+    let (m, n) = (x.len(), x.first().map_or(0, |row| row.len()));
+    if n == 0 || m < n {
+        return None;
+    }
+    let mut q = x.to_vec();
+    let mut r = vec![vec![0.; n]; n];
+    for i in 0..n {
+        let norm = q.iter().map(|row| row[i] * row[i]).sum::<f64>().sqrt();
+        if norm == 0. || norm.is_nan() {
+            return None;
+        }
+        r[i][i] = norm;
+        q.iter_mut().for_each(|row| row[i] /= norm);
+        for j in i + 1..n {
+            let dot = q.iter().map(|row| row[i] * row[j]).sum::<f64>();
+            r[i][j] = dot;
+            q.iter_mut().for_each(|row| row[j] -= dot * row[i]);
+        }
+    }
+    Some((q, r))
+}
+
+/// The residuals of `QᵀQ = I` in the upper triangle and of `QR = X` in every entry, where only the
+/// upper triangle of `r` is used.
+fn qr_residuals(x: &[Vec<f64>], q: &[Vec<f64>], r: &[Vec<f64>]) -> Vec<f64> {
+    let n = r.len();
+    let mut residuals = vec![];
+    for i in 0..n {
+        for j in i..n {
+            let mut v = q[0][i] * q[0][j];
+            for row in &q[1..] {
+                v += row[i] * row[j];
+            }
+            residuals.push((v - if i == j { 1. } else { 0. }).abs());
+        }
+    }
+    for (x, q) in x.iter().zip(q) {
+        for j in 0..n {
+            let mut v = q[0] * r[0][j];
+            for i in 1..=j {
+                v += q[i] * r[i][j];
+            }
+            residuals.push((v - x[j]).abs());
+        }
+    }
+    residuals
+}
+
+/// Compute the maximum residual of `QᵀQ = I` and `QR = X`, which is the minimum tolerance accepted
+/// by [`qr_least_squares_gadget`] for the factors `q` and `r` of `x`.
+/// The operations are performed in the same order as in [`qr_least_squares_gadget`].
+pub fn qr_gap(x: &[Vec<f64>], q: &[Vec<f64>], r: &[Vec<f64>]) -> f64 {
+    assert!(
+        is_qr_shape(x, &vec![0.; x.len()], q, r),
+        "the factors of an m × n matrix should be m × n and n × n with m ≥ n"
+    );
+    qr_residuals(x, q, r).into_iter().fold(0., f64::max)
+}
+
+/// Solve `Rβ = Qᵀy` by back-substitution, where only the upper triangle of `r` is used, which is
+/// the least squares solution of `Xβ = y` if `QR = X` is a QR decomposition.
+/// The operations are performed in the same order as in [`qr_least_squares_gadget`].
+pub fn qr_least_squares(q: &[Vec<f64>], r: &[Vec<f64>], y: &[f64]) -> Vec<f64> {
+    let n = r.len();
+    let c = (0..n)
+        .map(|i| {
+            let mut v = q[0][i] * y[0];
+            for (q, y) in q.iter().zip(y).skip(1) {
+                v += q[i] * y;
+            }
+            v
+        })
+        .collect::<Vec<_>>();
+    let mut beta = vec![0.; n];
+    for i in (0..n).rev() {
+        let mut v = c[i];
+        for j in i + 1..n {
+            v -= r[i][j] * beta[j];
+        }
+        beta[i] = v / r[i][i];
+    }
+    beta
+}
+
+/// Enforce that the private `q` and `r` are the QR decomposition of the design matrix `x` up to
+/// `tolerance`, i.e., that the residuals of `QᵀQ = I` and `QR = X` are at most `tolerance` in every
+/// entry and that the diagonal of `R` is nonzero, and return the coefficients `β` solving
+/// `Rβ = Qᵀy` by back-substitution, where only the upper triangle of `r` is used.
+/// Since `XᵀX = RᵀR` and `Xᵀy = RᵀQᵀy`, `β` solves the normal equations, but without squaring the
+/// condition number of `X` as in [`normal_equations`], so the coefficients remain accurate for
+/// nearly collinear features.
+/// The circuit is unsatisfiable if `x` is not a nonempty `m × n` matrix with `m ≥ n`, `y` does not
+/// have length `m`, or `q` and `r` are not `m × n` and `n × n`.
+pub fn qr_least_squares_gadget<F: PrimeField>(
+    x: &[Vec<F64Var<F>>],
+    y: &[F64Var<F>],
+    q: &[Vec<F64Var<F>>],
+    r: &[Vec<F64Var<F>>],
+    tolerance: &F64Var<F>,
+) -> Result<Vec<F64Var<F>>, SynthesisError> {
+    if !is_qr_shape(x, y, q, r) {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    let n = r.len();
+    let zero = F64Var::new_constant(ConstraintSystemRef::None, 0.)?;
+    let one = F64Var::new_constant(ConstraintSystemRef::None, 1.)?;
+
+    let mut residuals = vec![];
+    for i in 0..n {
+        for j in i..n {
+            let mut v = &q[0][i] * &q[0][j];
+            for row in &q[1..] {
+                v += &row[i] * &row[j];
+            }
+            residuals.push((v - if i == j { &one } else { &zero }).abs());
+        }
+    }
+    for (x, q) in x.iter().zip(q) {
+        for j in 0..n {
+            let mut v = &q[0] * &r[0][j];
+            for i in 1..=j {
+                v += &q[i] * &r[i][j];
+            }
+            residuals.push((v - &x[j]).abs());
+        }
+    }
+    for v in residuals {
+        F64Var::enforce_le(&v, tolerance)?;
+    }
+    for (i, row) in r.iter().enumerate() {
+        F64Var::enforce_gt(&row[i].abs(), &zero)?;
+    }
+
+    let c = (0..n)
+        .map(|i| {
+            let mut v = &q[0][i] * &y[0];
+            for (q, y) in q.iter().zip(y).skip(1) {
+                v += &q[i] * y;
+            }
+            v
+        })
+        .collect::<Vec<_>>();
+    let mut beta = vec![zero; n];
+    for i in (0..n).rev() {
+        let mut v = c[i].clone();
+        for j in i + 1..n {
+            v -= &r[i][j] * &beta[j];
+        }
+        beta[i] = &v / &r[i][i];
+    }
+    Ok(beta)
+}
+
+/// Encode the category `category` among `num_categories` categories as a vector of one 1 at
+/// `category` and 0s elsewhere, which is the same as the result of [`one_hot`].
+pub fn one_hot_encode(category: usize, num_categories: usize) -> Vec<f64> {
+    assert!(category < num_categories);
+    (0..num_categories)
+        .map(|i| if i == category { 1. } else { 0. })
+        .collect()
+}
+
+/// Expand the private categorical value `category` into the one-hot features of
+/// `num_categories` categories, i.e., 1 at `category` and 0 elsewhere, which can be used as the
+/// features of the regression circuits.
+/// The features are enforced to be exactly one 1 and 0s, whose position is `category`, so
+/// `category` is also enforced to be in `[0, num_categories)`.
+pub fn one_hot<F: PrimeField>(
+    category: &FpVar<F>,
+    num_categories: usize,
+) -> Result<Vec<F64Var<F>>, SynthesisError> {
+    let cs = category.cs();
+    let bits = (0..num_categories)
+        .map(|i| Boolean::new_witness(cs.clone(), || Ok(category.value()? == F::from(i as u64))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut count = FpVar::zero();
+    let mut position = FpVar::zero();
+    for (i, b) in bits.iter().enumerate() {
+        let b = FpVar::from(b.clone());
+        position += &b * F::from(i as u64);
+        count += b;
+    }
+    count.enforce_equal(&FpVar::one())?;
+    position.enforce_equal(category)?;
+
+    let zero = F64Var::new_constant(ConstraintSystemRef::None, 0.)?;
+    let one = F64Var::new_constant(ConstraintSystemRef::None, 1.)?;
+    bits.iter().map(|b| b.select(&one, &zero)).collect()
+}
+
+/// Compute the monomials of the features `x` of degrees `1..=degree` with `mul`, where the
+/// monomials of each degree are in the lexicographic order of their indices, e.g., `x_0², x_0 x_1,
+/// x_1²` for degree 2, and each monomial of degree `d > 1` is a monomial of degree `d - 1` times a
+/// feature.
+fn monomials<T: Clone>(x: &[T], degree: usize, mul: impl Fn(&T, &T) -> T) -> Vec<T> {
+    if degree == 0 {
+        return vec![];
+    }
+    let mut monomials = x.to_vec();
+    // The monomials of the current degree, each with the index of its last feature.
+    let mut last = x.iter().cloned().enumerate().collect::<Vec<_>>();
+    for _ in 1..degree {
+        last = last
+            .iter()
+            .flat_map(|(i, m)| {
+                (*i..x.len())
+                    .map(|k| (k, mul(m, &x[k])))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        monomials.extend(last.iter().map(|(_, m)| m.clone()));
+    }
+    monomials
+}
+
+/// Expand the features `x` into the polynomial features of degrees `1..=degree`, i.e., the
+/// features, followed by their pairwise products (including the squares) for degree 2, and so
+/// on, which is the same as the result of [`polynomial_features_gadget`].
+/// The order is the same as `PolynomialFeatures(degree, include_bias=False)` of scikit-learn.
+pub fn polynomial_features(x: &[f64], degree: usize) -> Vec<f64> {
+    monomials(x, degree, |a, b| a * b)
+}
+
+/// Expand the private features `x` into the polynomial features of degrees `1..=degree` as in
+/// [`polynomial_features`], which can be used as the features of the regression circuits, so that
+/// the expansion of the raw features is also proven.
+pub fn polynomial_features_gadget<F: PrimeField>(x: &[F64Var<F>], degree: usize) -> Vec<F64Var<F>> {
+    monomials(x, degree, |a, b| a * b)
+}
+
+/// Replace the missing entries of the column `values` with the mean of the other entries, which
+/// is the same as the result of [`impute_mean_gadget`].
+/// The values of the missing entries are ignored, and the mean is NaN if all entries are missing.
+pub fn impute_mean(values: &[f64], missing: &[bool]) -> Vec<f64> {
+    assert_eq!(values.len(), missing.len());
+    let (mut sum, mut count) = (0., 0.);
+    for (i, (&v, &m)) in values.iter().zip(missing).enumerate() {
+        let (v, c) = if m { (0., 0.) } else { (v, 1.) };
+        if i == 0 {
+            (sum, count) = (v, c);
+        } else {
+            (sum, count) = (sum + v, count + c);
+        }
+    }
+    let mean = sum / count;
+    values
+        .iter()
+        .zip(missing)
+        .map(|(&v, &m)| if m { mean } else { v })
+        .collect()
+}
+
+/// Replace the entries of the column `values` whose `missing` flags are set with the mean of
+/// the other entries computed in the circuit, which implements the mean imputation policy for
+/// datasets with missing values.
+/// The values of the missing entries are ignored, and the mean is NaN if all entries are missing.
+pub fn impute_mean_gadget<F: PrimeField>(
+    values: &[F64Var<F>],
+    missing: &[Boolean<F>],
+) -> Result<Vec<F64Var<F>>, SynthesisError> {
+    if values.is_empty() || values.len() != missing.len() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    let zero = F64Var::new_constant(ConstraintSystemRef::None, 0.)?;
+    let one = F64Var::new_constant(ConstraintSystemRef::None, 1.)?;
+
+    let (mut sum, mut count): (Option<F64Var<F>>, Option<F64Var<F>>) = (None, None);
+    for (v, m) in values.iter().zip(missing) {
+        let v = m.select(&zero, v)?;
+        let c = m.select(&zero, &one)?;
+        accumulate(&mut sum, v);
+        accumulate(&mut count, c);
+    }
+    let mean = sum.unwrap() / count.unwrap();
+    values
+        .iter()
+        .zip(missing)
+        .map(|(v, m)| m.select(&mean, v))
+        .collect()
+}
+
+/// Compute the products of a row with features `x` and targets `y` that are summed in `XᵀX` and
+/// `XᵀY`, i.e., for each `j`, the entries `X_j X_k` of `XᵀX` with `k >= max(j, 1)` followed by the
+/// entries `X_j y_t` of `XᵀY` for each target `y_t`, where `X` is `x` augmented with a leading 1.
+fn row_products<F: PrimeField>(x: &[F64Var<F>], y: &[F64Var<F>]) -> Vec<F64Var<F>> {
+    let n = x.len() + 1;
+    let mut products = vec![];
+    for j in 0..n {
+        for k in j.max(1)..n {
+            products.push(if j == 0 {
+                x[k - 1].clone()
+            } else {
+                &x[j - 1] * &x[k - 1]
+            });
+        }
+        for y in y {
+            products.push(if j == 0 { y.clone() } else { &x[j - 1] * y });
+        }
+    }
+    products
+}
+
+/// Add `v` to the running sum `sum`, which is `None` before the first term, so that the sum
+/// starts from the first term instead of adding it to 0.
+fn accumulate<F: PrimeField>(sum: &mut Option<F64Var<F>>, v: F64Var<F>) {
+    *sum = Some(match sum.take() {
+        Some(s) => s + v,
+        None => v,
+    });
+}
+
+/// Synthesize `f` in `cs` for each of the `rows`, where `f` may use the `shared` variables, and
+/// collect the variables returned by `f`.
+#[cfg(not(feature = "parallel"))]
+fn synthesize_rows<F, T, S>(
+    cs: &ConstraintSystemRef<F>,
+    shared: &[F64Var<F>],
+    rows: &[T],
+    f: S,
+) -> Result<Vec<Vec<F64Var<F>>>, SynthesisError>
+where
+    F: PrimeField,
+    T: Sync,
+    S: Fn(&ConstraintSystemRef<F>, &[F64Var<F>], &T) -> Result<Vec<F64Var<F>>, SynthesisError>
+        + Sync,
+{
+    rows.iter().map(|row| f(cs, shared, row)).collect()
+}
+
+/// Synthesize `f` in `cs` for each of the `rows`, where `f` may use the `shared` variables, and
+/// collect the variables returned by `f`.
+///
+/// The rows are split into chunks, each of which is synthesized in a separate constraint system
+/// in parallel. The resulting fragments are then appended to `cs` in order, so `cs` ends up
+/// exactly the same as if the rows were synthesized sequentially.
+#[cfg(feature = "parallel")]
+fn synthesize_rows<F, T, S>(
+    cs: &ConstraintSystemRef<F>,
+    shared: &[F64Var<F>],
+    rows: &[T],
+    f: S,
+) -> Result<Vec<Vec<F64Var<F>>>, SynthesisError>
+where
+    F: PrimeField,
+    T: Sync,
+    S: Fn(&ConstraintSystemRef<F>, &[F64Var<F>], &T) -> Result<Vec<F64Var<F>>, SynthesisError>
+        + Sync,
+{
+    use crate::r1cs::ConstraintSystem;
+    use rayon::prelude::*;
+
+    let (mode, goal) = match cs.borrow() {
+        Some(cs) => (cs.mode, cs.optimization_goal),
+        None => return rows.iter().map(|row| f(cs, shared, row)).collect(),
+    };
+    let shared = shared.iter().map(F64Var::detach).collect::<Vec<_>>();
+    let shared_variables = shared
+        .iter()
+        .flat_map(|v| v.variables())
+        .collect::<Vec<_>>();
+
+    // Continue profiling the current span in the fragments.
+    #[cfg(feature = "profile")]
+    let fork = crate::profile::Fork::current();
+
+    let chunk_size = rows.len().div_ceil(rayon::current_num_threads()).max(1);
+    let chunks = rows
+        .par_chunks(chunk_size)
+        .map(|chunk| {
+            let cs = ConstraintSystem::new_ref();
+            cs.set_optimization_goal(goal);
+            cs.set_mode(mode);
+
+            let shared = shared
+                .iter()
+                .map(|v| F64Var::attach(cs.clone(), v))
+                .collect::<Result<Vec<_>, _>>()?;
+            let synthesize = || {
+                chunk
+                    .iter()
+                    .map(|row| Ok(f(&cs, &shared, row)?.iter().map(F64Var::detach).collect()))
+                    .collect::<Result<Vec<Vec<_>>, SynthesisError>>()
+            };
+            #[cfg(feature = "profile")]
+            let outputs = fork.run(&cs, synthesize)?;
+            #[cfg(not(feature = "profile"))]
+            let outputs = synthesize()?;
+            drop(shared);
+
+            let fragment = cs
+                .into_fragment(shared_variables.len())
+                .ok_or(SynthesisError::MissingCS)?;
+            Ok((fragment, outputs))
+        })
+        .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+    let mut outputs = vec![];
+    for (fragment, chunk) in chunks {
+        let map = cs.append(fragment, &shared_variables)?;
+        outputs.extend(chunk.iter().map(|row| {
+            row.iter()
+                .map(|v| F64Var::restore(cs.clone(), v, |v| map.map(v)))
+                .collect()
+        }));
+    }
+    Ok(outputs)
+}
+
+/// `InferenceCircuit` proves that the targets `y` are the predictions of a private linear
+/// model on the features `x`, where both `x` and `y` are public.
+///
+/// If `tolerance` is provided, the circuit instead proves that each prediction `ŷ` lies within
+/// the public tolerance `ε` of the claimed target, i.e., `|y - ŷ| < ε`, so that the targets can
+/// be rounded or computed by other implementations of the model.
+pub struct InferenceCircuit {
+    pub model: LinearModel,
+    pub x: Vec<Vec<f64>>,
+    pub y: Vec<f64>,
+    pub tolerance: Option<f64>,
+}
+
+impl InferenceCircuit {
+    /// Create the circuit for the predictions of `model` on the features `x`.
+    pub fn new(model: LinearModel, x: Vec<Vec<f64>>) -> Self {
+        let y = x.iter().map(|x| model.predict(x)).collect();
+        Self {
+            model,
+            x,
+            y,
+            tolerance: None,
+        }
+    }
+
+    /// Create the circuit for the claimed targets `y` of `model` on the features `x`, which are
+    /// within `tolerance` of the predictions.
+    pub fn with_tolerance(
+        model: LinearModel,
+        x: Vec<Vec<f64>>,
+        y: Vec<f64>,
+        tolerance: f64,
+    ) -> Self {
+        Self {
+            model,
+            x,
+            y,
+            tolerance: Some(tolerance),
+        }
+    }
+
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::new(
+            LinearModel {
+                weights: vec![0.; num_features],
+                bias: 0.,
+            },
+            vec![vec![0.; num_features]; num_samples],
+        )
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the features of each
+    /// sample followed by its target.
+    pub fn public_inputs<F: PrimeField>(x: &[Vec<f64>], y: &[f64]) -> Vec<F> {
+        let mut inputs = PublicInputs::new();
+        for (x, &y) in x.iter().zip(y) {
+            inputs.add_f64s(x.iter().copied()).add_f64(y);
+        }
+        inputs.into()
+    }
+
+    /// Compute the public inputs that the verifier should provide if the circuit has
+    /// `tolerance`, i.e., the tolerance followed by the public inputs of `Self::public_inputs`.
+    pub fn public_inputs_with_tolerance<F: PrimeField>(
+        x: &[Vec<f64>],
+        y: &[f64],
+        tolerance: f64,
+    ) -> Vec<F> {
+        [
+            PublicInputs::new().add_f64(tolerance).build(),
+            Self::public_inputs(x, y),
+        ]
+        .concat()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for InferenceCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.x.len() != self.y.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let weights = F64Var::new_witness_vec(cs.clone(), &self.model.weights)?;
+        let bias = F64Var::new_witness(cs.clone(), || Ok(self.model.bias))?;
+        // The tolerance (if any) is shared by the rows after the model.
+        let tolerance = self
+            .tolerance
+            .map(|t| F64Var::new_input(cs.clone(), || Ok(t)))
+            .transpose()?;
+        let has_tolerance = tolerance.is_some();
+        let shared = [weights, vec![bias], tolerance.into_iter().collect()].concat();
+
+        let rows = self.x.iter().zip(self.y).collect::<Vec<_>>();
+        profile_span!("rows");
+        synthesize_rows(&cs, &shared, &rows, |cs, shared, (x, y)| {
+            let (model, tolerance) = if has_tolerance {
+                (&shared[..shared.len() - 1], shared.last())
+            } else {
+                (shared, None)
+            };
+            let (bias, weights) = model.split_last().unwrap();
+            if x.len() != weights.len() {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            let x = F64Var::new_input_vec(cs.clone(), x)?;
+            let y = F64Var::new_input(cs.clone(), || Ok(*y))?;
+
+            let prediction = {
+                profile_span!("dot");
+                let mut prediction = bias.clone();
+                for (w, x) in weights.iter().zip(&x) {
+                    prediction += w * x;
+                }
+                prediction
+            };
+            match tolerance {
+                Some(tolerance) => {
+                    let error = (y - prediction).abs();
+                    F64Var::is_lt(&error, tolerance)?.enforce_equal(&Boolean::TRUE)?;
+                }
+                None => prediction.enforce_equal(&y)?,
+            }
+
+            Ok(vec![])
+        })?;
+
+        Ok(())
+    }
+}
+
+/// `TrainingCircuit` proves that a public linear model is the least squares fit of private
+/// features `x` and targets `y`, in the sense that the residual of the normal equations
+/// `|(XᵀX) β - Xᵀy|` is at most the public `tolerance` in every component.
+///
+/// If `standard_errors` is provided, the circuit also proves that they are the public standard
+/// errors of the coefficients, so that the verifier can derive confidence intervals of the
+/// coefficients without learning the data.
+///
+/// If `feature_bounds` is provided, the circuit also proves that each feature of every sample is
+/// within the public bounds `[lower, upper]` of the feature, so that the verifier can trust that
+/// the model is not fitted to invalid or adversarial values.
+///
+/// If `mask` is provided, each sample has a private flag, and the samples whose flags are unset
+/// are padding, which are excluded from `XᵀX`, `Xᵀy`, the residuals, and the feature bounds. The
+/// number of the other samples is private, but it is enforced to be at least the number of
+/// coefficients (or more than that with `standard_errors`). Hence, the keys of a circuit with `N`
+/// samples (see `Self::padded`) can be used for any dataset of at most `N` samples.
+pub struct TrainingCircuit {
+    pub x: Vec<Vec<f64>>,
+    pub y: Vec<f64>,
+    pub model: LinearModel,
+    pub tolerance: f64,
+    pub standard_errors: Option<StandardErrors>,
+    pub feature_bounds: Option<Vec<(f64, f64)>>,
+    pub mask: Option<Vec<bool>>,
+}
+
+impl TrainingCircuit {
+    /// Create a circuit proving that `model` is the least squares fit of `x` and `y` up to
+    /// `tolerance`, without standard errors, feature bounds, or a mask, which can be added by
+    /// `Self::with_standard_errors`, `Self::with_feature_bounds`, and `Self::with_mask`.
+    pub fn new(x: Vec<Vec<f64>>, y: Vec<f64>, model: LinearModel, tolerance: f64) -> Self {
+        Self {
+            x,
+            y,
+            model,
+            tolerance,
+            standard_errors: None,
+            feature_bounds: None,
+            mask: None,
+        }
+    }
+
+    /// Also prove that `standard_errors` are the standard errors of the coefficients.
+    pub fn with_standard_errors(mut self, standard_errors: StandardErrors) -> Self {
+        self.standard_errors = Some(standard_errors);
+        self
+    }
+
+    /// Also prove that each feature is within its bounds `[lower, upper]` in `feature_bounds`.
+    pub fn with_feature_bounds(mut self, feature_bounds: Vec<(f64, f64)>) -> Self {
+        self.feature_bounds = Some(feature_bounds);
+        self
+    }
+
+    /// Exclude the samples whose flags in `mask` are unset, see `Self::padded`.
+    pub fn with_mask(mut self, mask: Vec<bool>) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::new(
+            vec![vec![0.; num_features]; num_samples],
+            vec![0.; num_samples],
+            LinearModel {
+                weights: vec![0.; num_features],
+                bias: 0.,
+            },
+            0.,
+        )
+    }
+
+    /// Pad the samples with rows of zeros to `num_samples` samples, which are masked out, so that
+    /// the circuit has the same shape for any dataset of at most `num_samples` samples, e.g., the
+    /// shape of `Self::shape(num_samples, num_features).padded(num_samples)` for the keys.
+    ///
+    /// The model, the tolerance, and the standard errors should be computed on the unpadded
+    /// samples, as in `TrainingCircuit` without a mask.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than `num_samples` samples.
+    pub fn padded(mut self, num_samples: usize) -> Self {
+        let len = self.x.len();
+        assert!(len <= num_samples, "{len} samples exceed {num_samples}");
+        let num_features = self.model.weights.len();
+        let mut mask = self.mask.take().unwrap_or_else(|| vec![true; len]);
+        mask.resize(num_samples, false);
+        self.x.resize(num_samples, vec![0.; num_features]);
+        self.y.resize(num_samples, 0.);
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the coefficients of
+    /// the model (the bias followed by the weights) and the tolerance.
+    pub fn public_inputs<F: PrimeField>(model: &LinearModel, tolerance: f64) -> Vec<F> {
+        PublicInputs::new()
+            .add_f64s(model.coefficients())
+            .add_f64(tolerance)
+            .build()
+    }
+
+    /// Compute the public inputs that the verifier should provide if the circuit has
+    /// `standard_errors`, i.e., the public inputs of `Self::public_inputs` followed by the
+    /// standard errors and their tolerance.
+    pub fn public_inputs_with_standard_errors<F: PrimeField>(
+        model: &LinearModel,
+        tolerance: f64,
+        standard_errors: &StandardErrors,
+    ) -> Vec<F> {
+        PublicInputs::new()
+            .add_f64s(model.coefficients())
+            .add_f64(tolerance)
+            .add_f64s(standard_errors.errors.iter().copied())
+            .add_f64(standard_errors.tolerance)
+            .build()
+    }
+
+    /// Compute the public inputs of `feature_bounds`, i.e., the lower and upper bounds of each
+    /// feature, which should be appended to the public inputs of `Self::public_inputs` or
+    /// `Self::public_inputs_with_standard_errors` if the circuit has `feature_bounds`.
+    pub fn feature_bounds_inputs<F: PrimeField>(bounds: &[(f64, f64)]) -> Vec<F> {
+        PublicInputs::new()
+            .add_f64s(bounds.iter().flat_map(|&(lower, upper)| [lower, upper]))
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for TrainingCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.x.is_empty() || self.x.len() != self.y.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let beta = F64Var::new_input_vec(cs.clone(), &self.model.coefficients())?;
+        let tolerance = F64Var::new_input(cs.clone(), || Ok(self.tolerance))?;
+        self.enforce_fit(cs, &beta, &tolerance)
+    }
+}
+
+impl TrainingCircuit {
+    /// Enforce that the coefficients `beta` are the least squares fit of the data up to
+    /// `tolerance`, and check the standard errors and the feature bounds if provided, where `beta`
+    /// is either public (as in `TrainingCircuit`) or private (as in `NoisyTrainingCircuit`).
+    fn enforce_fit<F: PrimeField>(
+        self,
+        cs: ConstraintSystemRef<F>,
+        beta: &[F64Var<F>],
+        tolerance: &F64Var<F>,
+    ) -> Result<(), SynthesisError> {
+        self.fit(cs, beta, tolerance, false).map(|_| ())
+    }
+
+    /// Enforce the fit as in `Self::enforce_fit`, and return the variables of each row (the
+    /// features followed by the target) if `keep_rows` is set, e.g., to commit to the rows.
+    fn fit<F: PrimeField>(
+        self,
+        cs: ConstraintSystemRef<F>,
+        beta: &[F64Var<F>],
+        tolerance: &F64Var<F>,
+        keep_rows: bool,
+    ) -> Result<Vec<Vec<F64Var<F>>>, SynthesisError> {
+        let n = beta.len();
+        let masked = self.mask.is_some();
+        if self.mask.as_ref().is_some_and(|m| m.len() != self.x.len()) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let errors = match &self.standard_errors {
+            Some(e) => {
+                // With a mask, the number of samples is checked in the circuit instead.
+                if (!masked && self.x.len() <= n)
+                    || e.errors.len() != n
+                    || e.inverse.len() != n
+                    || e.inverse.iter().any(|c| c.len() != n)
+                {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+                let values = F64Var::new_input_vec(cs.clone(), &e.errors)?;
+                let tolerance = F64Var::new_input(cs.clone(), || Ok(e.tolerance))?;
+                let inverse = e
+                    .inverse
+                    .iter()
+                    .map(|c| F64Var::new_witness_vec(cs.clone(), c))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Some((values, tolerance, inverse))
+            }
+            None => None,
+        };
+        let bounds = match &self.feature_bounds {
+            Some(bounds) => {
+                if bounds.len() != n - 1 {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+                let bounds = bounds
+                    .iter()
+                    .flat_map(|&(lower, upper)| [lower, upper])
+                    .collect::<Vec<_>>();
+                F64Var::new_input_vec(cs.clone(), &bounds)?
+            }
+            None => vec![],
+        };
+
+        // Compute `G = XᵀX` and `h = Xᵀy`, where the leading column of ones in `X` is handled
+        // separately to avoid unnecessary multiplications.
+        let mut g: Vec<Vec<Option<F64Var<F>>>> = vec![vec![None; n]; n];
+        let mut h: Vec<Option<F64Var<F>>> = vec![None; n];
+        // With a mask, the number of samples is instead accumulated from the flags.
+        if !masked {
+            g[0][0] = Some(F64Var::new_constant(cs.clone(), self.x.len() as f64)?);
+        }
+
+        // The products of each row are independent of the other rows, so we compute them first,
+        // and then accumulate them row by row. For the standard errors, the squared residual of
+        // each row is also computed with the coefficients, and the features of each row are
+        // checked against the bounds. For the padding rows, all products are replaced by 0,
+        // which are then followed by the flag as a number.
+        let mask = match &self.mask {
+            Some(mask) => mask.iter().map(|&m| Some(m)).collect(),
+            None => vec![None; self.x.len()],
+        };
+        let rows = self.x.iter().zip(&self.y).zip(mask).collect::<Vec<_>>();
+        let num_shared_coefficients = if errors.is_some() { n } else { 0 };
+        let shared = [&beta[..num_shared_coefficients], &bounds].concat();
+        let products = {
+            profile_span!("products");
+            synthesize_rows(&cs, &shared, &rows, |cs, shared, ((x, y), m)| {
+                if x.len() != n - 1 {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+                let (beta, bounds) = shared.split_at(num_shared_coefficients);
+                let x = F64Var::new_witness_vec(cs.clone(), x)?;
+                let y = F64Var::new_witness(cs.clone(), || Ok(**y))?;
+                let m = m
+                    .map(|m| Boolean::new_witness(cs.clone(), || Ok(m)))
+                    .transpose()?;
+                let row = match keep_rows {
+                    true => [&x[..], std::slice::from_ref(&y)].concat(),
+                    false => vec![],
+                };
+                if !bounds.is_empty() {
+                    profile_span!("bounds");
+                    let bounds = bounds
+                        .chunks(2)
+                        .map(|b| (b[0].clone(), b[1].clone()))
+                        .collect::<Vec<_>>();
+                    // The features of a padding row are replaced by the lower bounds, which are
+                    // within any valid bounds.
+                    let x = match &m {
+                        Some(m) => x
+                            .iter()
+                            .zip(&bounds)
+                            .map(|(x, (lower, _))| m.select(x, lower))
+                            .collect::<Result<Vec<_>, _>>()?,
+                        None => x.clone(),
+                    };
+                    enforce_within_bounds(&x, &bounds)?;
+                }
+
+                let mut products = row_products(&x, std::slice::from_ref(&y));
+                if let Some((bias, weights)) = beta.split_first() {
+                    let mut prediction = bias.clone();
+                    for (w, x) in weights.iter().zip(&x) {
+                        prediction += w * x;
+                    }
+                    let r = y - prediction;
+                    products.push(&r * &r);
+                }
+                if let Some(m) = m {
+                    let zero = F64Var::new_constant(ConstraintSystemRef::None, 0.)?;
+                    let one = F64Var::new_constant(ConstraintSystemRef::None, 1.)?;
+                    products = products
+                        .iter()
+                        .map(|v| m.select(v, &zero))
+                        .collect::<Result<_, _>>()?;
+                    products.push(m.select(&one, &zero)?);
+                }
+                products.extend(row);
+                Ok(products)
+            })?
+        };
+        let (products, rows): (Vec<_>, Vec<_>) = products
+            .into_iter()
+            .map(|mut products| {
+                let row = products.split_off(products.len() - if keep_rows { n } else { 0 });
+                (products, row)
+            })
+            .unzip();
+
+        let mut rss: Option<F64Var<F>> = None;
+        {
+            profile_span!("accumulate");
+            for products in products {
+                let mut products = products.into_iter();
+                for (j, (g, h)) in g.iter_mut().zip(&mut h).enumerate() {
+                    for g in g.iter_mut().skip(j.max(1)) {
+                        let v = products.next().unwrap();
+                        accumulate(g, v);
+                    }
+                    let v = products.next().unwrap();
+                    accumulate(h, v);
+                }
+                if errors.is_some() {
+                    let v = products.next().unwrap();
+                    accumulate(&mut rss, v);
+                }
+                if let Some(v) = products.next() {
+                    accumulate(&mut g[0][0], v);
+                }
+            }
+        }
+        if masked {
+            // The normal equations of fewer samples than coefficients are singular, which would
+            // be satisfied by any model, e.g., if all samples are padding.
+            let count = g[0][0].as_ref().unwrap();
+            let min = F64Var::new_constant(cs.clone(), n as f64)?;
+            match errors {
+                Some(_) => F64Var::is_lt(&min, count)?,
+                None => F64Var::is_le(&min, count)?,
+            }
+            .enforce_equal(&Boolean::TRUE)?;
+        }
+
+        let g_ij = |i: usize, j: usize| g[i.min(j)][i.max(j)].as_ref().unwrap();
+        {
+            profile_span!("residuals");
+            for (i, h) in h.iter().enumerate() {
+                let v = {
+                    profile_span!("dot");
+                    let mut v = g_ij(i, 0) * &beta[0];
+                    for (j, b) in beta.iter().enumerate().skip(1) {
+                        v += g_ij(i, j) * b;
+                    }
+                    v
+                };
+                let residual = (v - h.as_ref().unwrap()).abs();
+                F64Var::is_le(&residual, tolerance)?.enforce_equal(&Boolean::TRUE)?;
+            }
+        }
+
+        if let Some((values, tolerance, inverse)) = errors {
+            profile_span!("standard_errors");
+            // Check that `C` is the inverse of `G` up to `tolerance`, column by column.
+            let one = F64Var::new_constant(cs.clone(), 1.)?;
+            for j in 0..n {
+                for i in 0..n {
+                    let mut v = g_ij(i, 0) * &inverse[0][j];
+                    for (k, c) in inverse.iter().enumerate().skip(1) {
+                        v += g_ij(i, k) * &c[j];
+                    }
+                    let residual = if i == j { v - &one } else { v }.abs();
+                    F64Var::is_le(&residual, &tolerance)?.enforce_equal(&Boolean::TRUE)?;
+                }
+            }
+
+            let diagonal = (0..n).map(|j| inverse[j][j].clone()).collect::<Vec<_>>();
+            let rss = rss.as_ref().unwrap();
+            let expected = if masked {
+                let degrees_of_freedom = g_ij(0, 0) - F64Var::new_constant(cs.clone(), n as f64)?;
+                standard_errors_with(rss, &degrees_of_freedom, &diagonal)?
+            } else {
+                standard_errors(rss, self.x.len() - n, &diagonal)?
+            };
+            for (e, expected) in values.iter().zip(&expected) {
+                e.enforce_equal(expected)?;
+            }
+        }
+
+        Ok(if keep_rows { rows } else { vec![] })
+    }
+}
+
+/// `MultiTargetTrainingCircuit` proves that each of the public linear models is the least squares
+/// fit of the private features `x` and the corresponding column of the private targets `y`, in
+/// the same sense as `TrainingCircuit` with the shared public `tolerance`.
+///
+/// As `XᵀX` only depends on the features, it is computed once for all targets, so the circuit is
+/// much smaller than a `TrainingCircuit` per target.
+pub struct MultiTargetTrainingCircuit {
+    pub x: Vec<Vec<f64>>,
+    /// The targets of each sample, one for each of the `models`.
+    pub y: Vec<Vec<f64>>,
+    pub models: Vec<LinearModel>,
+    pub tolerance: f64,
+}
+
+impl MultiTargetTrainingCircuit {
+    /// Fit a model to each target of the features `x` and the targets `y`, where the tolerance is
+    /// the maximum optimality gap of the models. Return `None` if the normal equations are
+    /// singular, or if the samples have different numbers of targets.
+    pub fn new(x: Vec<Vec<f64>>, y: Vec<Vec<f64>>) -> Option<Self> {
+        let num_targets = y.first()?.len();
+        if y.iter().any(|y| y.len() != num_targets) {
+            return None;
+        }
+        let columns = (0..num_targets)
+            .map(|t| y.iter().map(|y| y[t]).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let models = columns
+            .iter()
+            .map(|y| LinearModel::fit(&x, y))
+            .collect::<Option<Vec<_>>>()?;
+        let tolerance = models
+            .iter()
+            .zip(&columns)
+            .map(|(model, y)| optimality_gap(model, &x, y))
+            .fold(0., f64::max);
+        Some(Self {
+            x,
+            y,
+            models,
+            tolerance,
+        })
+    }
+
+    /// Create a circuit with `num_samples` samples of `num_features` features and `num_targets`
+    /// targets, whose values are all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize, num_targets: usize) -> Self {
+        Self {
+            x: vec![vec![0.; num_features]; num_samples],
+            y: vec![vec![0.; num_targets]; num_samples],
+            models: vec![
+                LinearModel {
+                    weights: vec![0.; num_features],
+                    bias: 0.,
+                };
+                num_targets
+            ],
+            tolerance: 0.,
+        }
+    }
+
+    /// Report the size of the circuit with `num_samples` samples of `num_features` features and
+    /// `num_targets` targets.
+    pub fn constraint_report<F: PrimeField>(
+        num_samples: usize,
+        num_features: usize,
+        num_targets: usize,
+    ) -> Result<ConstraintReport, SynthesisError> {
+        ConstraintReport::circuit::<F>(Self::shape(num_samples, num_features, num_targets))
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the coefficients of
+    /// each model (the bias followed by the weights) and the tolerance.
+    pub fn public_inputs<F: PrimeField>(models: &[LinearModel], tolerance: f64) -> Vec<F> {
+        let mut inputs = PublicInputs::new();
+        for model in models {
+            inputs.add_f64s(model.coefficients());
+        }
+        inputs.add_f64(tolerance).build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for MultiTargetTrainingCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let num_targets = self.models.len();
+        if self.x.is_empty()
+            || self.x.len() != self.y.len()
+            || num_targets == 0
+            || self.y.iter().any(|y| y.len() != num_targets)
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let n = self.models[0].weights.len() + 1;
+        if self.models.iter().any(|m| m.weights.len() != n - 1) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let betas = self
+            .models
+            .iter()
+            .map(|m| F64Var::new_input_vec(cs.clone(), &m.coefficients()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let tolerance = F64Var::new_input(cs.clone(), || Ok(self.tolerance))?;
+
+        // Compute `G = XᵀX` once and `h_t = Xᵀy_t` for each target `y_t` as in `TrainingCircuit`.
+        let mut g: Vec<Vec<Option<F64Var<F>>>> = vec![vec![None; n]; n];
+        let mut h: Vec<Vec<Option<F64Var<F>>>> = vec![vec![None; n]; num_targets];
+        g[0][0] = Some(F64Var::new_constant(cs.clone(), self.x.len() as f64)?);
+
+        let rows = self.x.iter().zip(&self.y).collect::<Vec<_>>();
+        let products = {
+            profile_span!("products");
+            synthesize_rows(&cs, &[], &rows, |cs, _, (x, y)| {
+                let x = F64Var::new_witness_vec(cs.clone(), x)?;
+                let y = F64Var::new_witness_vec(cs.clone(), y)?;
+                Ok(row_products(&x, &y))
+            })?
+        };
+        {
+            profile_span!("accumulate");
+            for products in products {
+                let mut products = products.into_iter();
+                for (j, g) in g.iter_mut().enumerate() {
+                    for g in g.iter_mut().skip(j.max(1)) {
+                        let v = products.next().unwrap();
+                        accumulate(g, v);
+                    }
+                    for h in h.iter_mut() {
+                        let v = products.next().unwrap();
+                        accumulate(&mut h[j], v);
+                    }
+                }
+            }
+        }
+
+        let g_ij = |i: usize, j: usize| g[i.min(j)][i.max(j)].as_ref().unwrap();
+        profile_span!("residuals");
+        for (beta, h) in betas.iter().zip(&h) {
+            for (i, h) in h.iter().enumerate() {
+                let mut v = g_ij(i, 0) * &beta[0];
+                for (j, b) in beta.iter().enumerate().skip(1) {
+                    v += g_ij(i, j) * b;
+                }
+                let residual = (v - h.as_ref().unwrap()).abs();
+                F64Var::enforce_le(&residual, &tolerance)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `HuberCircuit` proves that a public linear model minimizes the Huber loss with the public
+/// threshold `delta` on private features `x` and targets `y`, which is robust to outliers, in the
+/// sense that the gradient of the loss `|Xᵀψ(y - Xβ)|` is at most the public `tolerance` in every
+/// component, where `ψ` clips the residuals to `[-delta, delta]`.
+pub struct HuberCircuit {
+    pub x: Vec<Vec<f64>>,
+    pub y: Vec<f64>,
+    pub model: LinearModel,
+    pub delta: f64,
+    pub tolerance: f64,
+}
+
+impl HuberCircuit {
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0 (except for a threshold of 1), e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            x: vec![vec![0.; num_features]; num_samples],
+            y: vec![0.; num_samples],
+            model: LinearModel {
+                weights: vec![0.; num_features],
+                bias: 0.,
+            },
+            delta: 1.,
+            tolerance: 0.,
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the coefficients of
+    /// the model (the bias followed by the weights), the threshold, and the tolerance.
+    pub fn public_inputs<F: PrimeField>(model: &LinearModel, delta: f64, tolerance: f64) -> Vec<F> {
+        PublicInputs::new()
+            .add_f64s(model.coefficients())
+            .add_f64(delta)
+            .add_f64(tolerance)
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for HuberCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.x.is_empty() || self.x.len() != self.y.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let beta = F64Var::new_input_vec(cs.clone(), &self.model.coefficients())?;
+        let delta = F64Var::new_input(cs.clone(), || Ok(self.delta))?;
+        let tolerance = F64Var::new_input(cs.clone(), || Ok(self.tolerance))?;
+        let n = beta.len();
+
+        // The threshold should be positive, otherwise the clipped residuals are meaningless.
+        let zero = F64Var::new_constant(cs.clone(), 0.)?;
+        F64Var::is_gt(&delta, &zero)?.enforce_equal(&Boolean::TRUE)?;
+
+        // The summands `ψ(r) X_ij` of the gradient of each row are independent of the other
+        // rows, so we compute them first, and then accumulate them row by row.
+        let rows = self.x.iter().zip(&self.y).collect::<Vec<_>>();
+        let shared = [beta, vec![delta]].concat();
+        let products = {
+            profile_span!("products");
+            synthesize_rows(&cs, &shared, &rows, |cs, shared, (x, y)| {
+                if x.len() != n - 1 {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+                let (delta, beta) = shared.split_last().unwrap();
+                let (bias, weights) = beta.split_first().unwrap();
+                let x = F64Var::new_witness_vec(cs.clone(), x)?;
+                let y = F64Var::new_witness(cs.clone(), || Ok(**y))?;
+
+                let prediction = {
+                    profile_span!("dot");
+                    let mut prediction = bias.clone();
+                    for (w, x) in weights.iter().zip(&x) {
+                        prediction += w * x;
+                    }
+                    prediction
+                };
+                let r = {
+                    profile_span!("clip");
+                    let r = y - prediction;
+                    let r = F64Var::is_gt(&r, delta)?.select(delta, &r)?;
+                    F64Var::is_lt(&r, &-delta)?.select(&-delta, &r)?
+                };
+
+                let mut products = vec![r.clone()];
+                products.extend(x.iter().map(|x| &r * x));
+                Ok(products)
+            })?
+        };
+
+        let mut gradient: Vec<Option<F64Var<F>>> = vec![None; n];
+        {
+            profile_span!("accumulate");
+            for products in products {
+                for (s, v) in gradient.iter_mut().zip(products) {
+                    accumulate(s, v);
+                }
+            }
+        }
+
+        {
+            profile_span!("gradient");
+            for v in gradient {
+                let v = v.unwrap().abs();
+                F64Var::is_le(&v, &tolerance)?.enforce_equal(&Boolean::TRUE)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `QuantileCircuit` proves that a public linear model minimizes the quantile loss
+/// `Σ ρ_τ(y - Xβ)` with the public quantile `tau` on private features `x` and targets `y`, e.g.,
+/// the median regression for `tau = 0.5`.
+///
+/// As the loss is not differentiable, the circuit checks the optimality condition that 0 is a
+/// subgradient up to the public `tolerance`, i.e., `|Σ a_i X_i|` is at most `tolerance` in every
+/// component, where the private subgradient `a_i` of each sample is `tau` if its residual is
+/// greater than the public `epsilon`, `tau - 1` if it is less than `-epsilon`, and in
+/// `[tau - 1, tau]` otherwise.
+pub struct QuantileCircuit {
+    pub x: Vec<Vec<f64>>,
+    pub y: Vec<f64>,
+    pub model: LinearModel,
+    pub tau: f64,
+    pub epsilon: f64,
+    pub tolerance: f64,
+    pub subgradients: Vec<f64>,
+}
+
+impl QuantileCircuit {
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0 (except for a quantile of 0.5), e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            x: vec![vec![0.; num_features]; num_samples],
+            y: vec![0.; num_samples],
+            model: LinearModel {
+                weights: vec![0.; num_features],
+                bias: 0.,
+            },
+            tau: 0.5,
+            epsilon: 0.,
+            tolerance: 0.,
+            subgradients: vec![0.; num_samples],
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the coefficients of
+    /// the model (the bias followed by the weights), the quantile, `epsilon`, and the tolerance.
+    pub fn public_inputs<F: PrimeField>(
+        model: &LinearModel,
+        tau: f64,
+        epsilon: f64,
+        tolerance: f64,
+    ) -> Vec<F> {
+        PublicInputs::new()
+            .add_f64s(model.coefficients())
+            .add_f64(tau)
+            .add_f64(epsilon)
+            .add_f64(tolerance)
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for QuantileCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.x.is_empty()
+            || self.x.len() != self.y.len()
+            || self.x.len() != self.subgradients.len()
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let beta = F64Var::new_input_vec(cs.clone(), &self.model.coefficients())?;
+        let tau = F64Var::new_input(cs.clone(), || Ok(self.tau))?;
+        let epsilon = F64Var::new_input(cs.clone(), || Ok(self.epsilon))?;
+        let tolerance = F64Var::new_input(cs.clone(), || Ok(self.tolerance))?;
+        let n = beta.len();
+
+        // The quantile should be in `(0, 1)`.
+        let zero = F64Var::new_constant(cs.clone(), 0.)?;
+        let one = F64Var::new_constant(cs.clone(), 1.)?;
+        F64Var::is_gt(&tau, &zero)?.enforce_equal(&Boolean::TRUE)?;
+        F64Var::is_lt(&tau, &one)?.enforce_equal(&Boolean::TRUE)?;
+        let lower = &tau - &one;
+
+        // The summands `a_i X_ij` of the subgradient of each row are independent of the other
+        // rows, so we compute them first, and then accumulate them row by row.
+        let rows = self
+            .x
+            .iter()
+            .zip(&self.y)
+            .zip(&self.subgradients)
+            .collect::<Vec<_>>();
+        let shared = [beta, vec![tau, lower, epsilon]].concat();
+        let products = {
+            profile_span!("products");
+            synthesize_rows(&cs, &shared, &rows, |cs, shared, ((x, y), a)| {
+                if x.len() != n - 1 {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+                let (beta, params) = shared.split_at(n);
+                let (tau, lower, epsilon) = (&params[0], &params[1], &params[2]);
+                let (bias, weights) = beta.split_first().unwrap();
+                let x = F64Var::new_witness_vec(cs.clone(), x)?;
+                let y = F64Var::new_witness(cs.clone(), || Ok(**y))?;
+                let a = F64Var::new_witness(cs.clone(), || Ok(**a))?;
+
+                let prediction = {
+                    profile_span!("dot");
+                    let mut prediction = bias.clone();
+                    for (w, x) in weights.iter().zip(&x) {
+                        prediction += w * x;
+                    }
+                    prediction
+                };
+                {
+                    profile_span!("subgradient");
+                    // `a` is in `[tau, tau]` for positive residuals, `[tau - 1, tau - 1]` for
+                    // negative ones, and `[tau - 1, tau]` for zero ones.
+                    let r = y - prediction;
+                    let min = F64Var::is_gt(&r, epsilon)?.select(tau, lower)?;
+                    let max = F64Var::is_lt(&r, &-epsilon)?.select(lower, tau)?;
+                    F64Var::is_le(&min, &a)?.enforce_equal(&Boolean::TRUE)?;
+                    F64Var::is_le(&a, &max)?.enforce_equal(&Boolean::TRUE)?;
+                }
+
+                let mut products = vec![a.clone()];
+                products.extend(x.iter().map(|x| &a * x));
+                Ok(products)
+            })?
+        };
+
+        let mut gradient: Vec<Option<F64Var<F>>> = vec![None; n];
+        {
+            profile_span!("accumulate");
+            for products in products {
+                for (s, v) in gradient.iter_mut().zip(products) {
+                    accumulate(s, v);
+                }
+            }
+        }
+
+        {
+            profile_span!("gradient");
+            for v in gradient {
+                let v = v.unwrap().abs();
+                F64Var::is_le(&v, &tolerance)?.enforce_equal(&Boolean::TRUE)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The number of uniform numbers summed in each noise of `NoisyTrainingCircuit`.
+const NOISE_UNIFORMS: usize = 12;
+/// The number of random bits of each uniform number in each noise of `NoisyTrainingCircuit`.
+const NOISE_UNIFORM_BITS: usize = 16;
+
+/// Compute the commitment to `seed` that is verified by `NoisyTrainingCircuit`, i.e., the hash of
+/// `seed`. The commitment is hiding only if `seed` is chosen uniformly at random.
+pub fn seed_commitment<F: PrimeField>(seed: u128) -> F {
+    hash(&[F::from(seed)])
+}
+
+/// Derive `count` noises in `F` from `seed`, which are the same as those derived in
+/// `NoisyTrainingCircuit`.
+///
+/// The `j`-th noise is the sum of `NOISE_UNIFORMS = 12` numbers, each of which is uniformly
+/// distributed in `(-1/2, 1/2)` with `NOISE_UNIFORM_BITS = 16` random bits taken from the hash of
+/// `seed` and `j`. By the central limit theorem, the noise approximately follows the standard
+/// normal distribution (the Irwin-Hall approximation), but it is bounded by 6 in magnitude.
+/// Each noise is a multiple of `2^-17` and hence exactly representable as `f64`.
+pub fn noise<F: PrimeField>(seed: u128, count: usize) -> Vec<f64> {
+    (0..count)
+        .map(|j| {
+            let bits = hash(&[F::from(seed), F::from(j as u64)])
+                .into_bigint()
+                .to_bits_le();
+            // Each uniform number is `(2t + 1 - 2^16) / 2^17` for the random integer `t`.
+            let n = bits
+                .chunks(NOISE_UNIFORM_BITS)
+                .take(NOISE_UNIFORMS)
+                .map(|chunk| {
+                    let t = chunk.iter().rev().fold(0i64, |t, &b| t << 1 | b as i64);
+                    2 * t + 1 - (1 << NOISE_UNIFORM_BITS)
+                })
+                .sum::<i64>();
+            n as f64 / (1u64 << (NOISE_UNIFORM_BITS + 1)) as f64
+        })
+        .collect()
+}
+
+/// Derive the `index`-th noise from `seed` in the circuit, see `noise`.
+fn noise_gadget<F: PrimeField>(seed: &FpVar<F>, index: usize) -> Result<F64Var<F>, SynthesisError> {
+    let h = hash_gadget(&[seed.clone(), FpVar::constant(F::from(index as u64))])?;
+    // The decomposition is unique, so that the prover cannot choose between the bits of `h` and
+    // those of `h + p`.
+    let bits = h.to_bits_le()?;
+    let mut n = FpVar::constant(-F::from((NOISE_UNIFORMS << NOISE_UNIFORM_BITS) as u64));
+    for chunk in bits.chunks(NOISE_UNIFORM_BITS).take(NOISE_UNIFORMS) {
+        n += Boolean::le_bits_to_fp_var(chunk)?.double()? + F::one();
+    }
+    // `|n| < 12 * 2^16 < 2^20`
+    F64Var::from_fixed(&n, NOISE_UNIFORM_BITS + 1, NOISE_UNIFORM_BITS + 4)
+}
+
+/// `NoisyTrainingCircuit` proves that the public coefficients are a differentially private
+/// release of a least squares fit, i.e., that they are the private coefficients of `training`
+/// plus noises of the public `scale`, where `training` is checked as in `TrainingCircuit` (except
+/// that its model is private), and the noises are derived from a private seed by `noise`, whose
+/// commitment by `seed_commitment` is public.
+///
+/// As the seed is committed before the release, the noises cannot be chosen after seeing the
+/// result, and hence the released coefficients are verifiably noised rather than fabricated.
+pub struct NoisyTrainingCircuit {
+    /// The data and the exact fit, whose `model` is private.
+    pub training: TrainingCircuit,
+    pub seed: u128,
+    pub scale: f64,
+}
+
+impl NoisyTrainingCircuit {
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            training: TrainingCircuit::shape(num_samples, num_features),
+            seed: 0,
+            scale: 0.,
+        }
+    }
+
+    /// Compute the released model, i.e., `β_j + scale * noise_j` for each coefficient `β_j` of the
+    /// exact fit, in the same way as the circuit.
+    pub fn released_model<F: PrimeField>(&self) -> LinearModel {
+        let beta = self.training.model.coefficients();
+        let noise = noise::<F>(self.seed, beta.len());
+        let released = beta
+            .iter()
+            .zip(noise)
+            .map(|(b, z)| b + self.scale * z)
+            .collect::<Vec<_>>();
+        LinearModel {
+            bias: released[0],
+            weights: released[1..].to_vec(),
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the commitment to the
+    /// seed, the scale, the coefficients of the released model (the bias followed by the
+    /// weights), and the tolerance. If `training` has standard errors or feature bounds, their
+    /// public inputs follow in the same way as in `TrainingCircuit`.
+    pub fn public_inputs<F: PrimeField>(
+        commitment: F,
+        scale: f64,
+        released: &LinearModel,
+        tolerance: f64,
+    ) -> Vec<F> {
+        PublicInputs::new()
+            .add_commitment(commitment)
+            .add_f64(scale)
+            .add_f64s(released.coefficients())
+            .add_f64(tolerance)
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for NoisyTrainingCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.training.x.is_empty() || self.training.x.len() != self.training.y.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let released = self.released_model::<F>();
+        let coefficients = self.training.model.coefficients();
+        let training = self.training;
+
+        let commitment = FpVar::new_input(cs.clone(), || Ok(seed_commitment::<F>(self.seed)))?;
+        let scale = F64Var::new_input(cs.clone(), || Ok(self.scale))?;
+        let released = F64Var::new_input_vec(cs.clone(), &released.coefficients())?;
+        let beta = F64Var::new_witness_vec(cs.clone(), &coefficients)?;
+        let tolerance = F64Var::new_input(cs.clone(), || Ok(training.tolerance))?;
+
+        {
+            profile_span!("noise");
+            let seed = FpVar::new_witness(cs.clone(), || Ok(F::from(self.seed)))?;
+            hash_gadget(std::slice::from_ref(&seed))?.enforce_equal(&commitment)?;
+            for (j, (b, r)) in beta.iter().zip(&released).enumerate() {
+                let z = noise_gadget(&seed, j)?;
+                (b + &(&scale * &z)).enforce_equal(r)?;
+            }
+        }
+
+        training.enforce_fit(cs, &beta, &tolerance)
+    }
+}
+
+/// Compute the commitment to `values` with the random `blinding`, i.e., the hash of the encoded
+/// values (the public inputs of the values in `PublicInputs::add_f64s`) followed by `blinding`.
+pub fn f64_commitment<F: PrimeField>(values: &[f64], blinding: u128) -> F {
+    let mut inputs = F64Var::<F>::verifier_inputs(values);
+    inputs.push(F::from(blinding));
+    hash(&inputs)
+}
+
+/// Compute the commitment to `model` with the random `blinding`, i.e., the commitment to its
+/// coefficients by `f64_commitment`, which is verified by `FederatedAveragingCircuit`.
+pub fn model_commitment<F: PrimeField>(model: &LinearModel, blinding: u128) -> F {
+    f64_commitment(&model.coefficients(), blinding)
+}
+
+/// Allocate `values` as witnesses, and also return the components of their encoding, i.e., the
+/// variables of `F64Var::verifier_inputs(values)`.
+#[allow(clippy::type_complexity)]
+fn new_encoded_f64s<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    values: &[f64],
+) -> Result<(Vec<F64Var<F>>, Vec<FpVar<F>>), SynthesisError> {
+    let mut vars = vec![];
+    let mut components = vec![];
+    for &v in values {
+        let (v, c) = F64Var::new_variable_with_components(
+            cs.clone(),
+            || F64Var::<F>::decode(v).map_err(|_| SynthesisError::AssignmentMissing),
+            AllocationMode::Witness,
+        )?;
+        vars.push(v);
+        components.extend(c);
+    }
+    Ok((vars, components))
+}
+
+/// Allocate `values` as witnesses, and compute their commitment with `blinding` in the circuit,
+/// see `f64_commitment`.
+fn new_f64s_with_commitment<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    values: &[f64],
+    blinding: u128,
+) -> Result<(Vec<F64Var<F>>, FpVar<F>), SynthesisError> {
+    let (vars, mut inputs) = new_encoded_f64s(cs.clone(), values)?;
+    inputs.push(FpVar::new_witness(cs, || Ok(F::from(blinding)))?);
+    Ok((vars, hash_gadget(&inputs)?))
+}
+
+/// Allocate `values` as witnesses, and enforce that `commitment` is their commitment with
+/// `blinding`, see `f64_commitment`.
+fn new_committed_f64s<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    values: &[f64],
+    blinding: u128,
+    commitment: &FpVar<F>,
+) -> Result<Vec<F64Var<F>>, SynthesisError> {
+    let (vars, c) = new_f64s_with_commitment(cs, values, blinding)?;
+    c.enforce_equal(commitment)?;
+    Ok(vars)
+}
+
+/// `FederatedAveragingCircuit` proves that the public model is the weighted average
+/// `Σ w_k β_k / Σ w_k` of the private model updates `β_k` of the parties with the public weights
+/// `w_k` (e.g., the numbers of samples of the parties), where the update of each party is bound
+/// to its public commitment by `model_commitment`, so that the aggregator cannot replace or omit
+/// any update without seeing the local datasets.
+pub struct FederatedAveragingCircuit {
+    /// The model update of each party, and the blinding of its commitment.
+    pub updates: Vec<(LinearModel, u128)>,
+    pub weights: Vec<f64>,
+}
+
+impl FederatedAveragingCircuit {
+    /// Create a circuit with `num_parties` parties of models with `num_features` features, whose
+    /// values are all 0 (except for weights of 1), e.g., for generating the keys.
+    pub fn shape(num_parties: usize, num_features: usize) -> Self {
+        let model = LinearModel {
+            weights: vec![0.; num_features],
+            bias: 0.,
+        };
+        Self {
+            updates: vec![(model, 0); num_parties],
+            weights: vec![1.; num_parties],
+        }
+    }
+
+    /// Report the size of the circuit with `num_parties` parties of models with `num_features`
+    /// features.
+    pub fn constraint_report<F: PrimeField>(
+        num_parties: usize,
+        num_features: usize,
+    ) -> Result<ConstraintReport, SynthesisError> {
+        ConstraintReport::circuit::<F>(Self::shape(num_parties, num_features))
+    }
+
+    /// Compute the weighted average of `models` with `weights`.
+    /// The operations are performed in the same order as in the circuit, so the result is
+    /// bit-identical to the average computed in the circuit.
+    pub fn average(models: &[LinearModel], weights: &[f64]) -> LinearModel {
+        assert_eq!(models.len(), weights.len());
+        assert!(!models.is_empty());
+        let mut sum = vec![];
+        let mut total = 0.;
+        for (k, (model, &w)) in models.iter().zip(weights).enumerate() {
+            let products = model
+                .coefficients()
+                .iter()
+                .map(|b| w * b)
+                .collect::<Vec<_>>();
+            if k == 0 {
+                sum = products;
+                total = w;
+            } else {
+                sum = sum.iter().zip(products).map(|(s, v)| s + v).collect();
+                total += w;
+            }
+        }
+        let average = sum.iter().map(|s| s / total).collect::<Vec<_>>();
+        LinearModel {
+            bias: average[0],
+            weights: average[1..].to_vec(),
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the commitment of each
+    /// party, the weights, and the coefficients of the average model (the bias followed by the
+    /// weights).
+    pub fn public_inputs<F: PrimeField>(
+        commitments: &[F],
+        weights: &[f64],
+        average: &LinearModel,
+    ) -> Vec<F> {
+        let mut inputs = PublicInputs::new();
+        for &c in commitments {
+            inputs.add_commitment(c);
+        }
+        inputs
+            .add_f64s(weights.iter().copied())
+            .add_f64s(average.coefficients())
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for FederatedAveragingCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.updates.is_empty() || self.updates.len() != self.weights.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let n = self.updates[0].0.weights.len() + 1;
+        if self.updates.iter().any(|(m, _)| m.weights.len() != n - 1) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let models = self
+            .updates
+            .iter()
+            .map(|(m, _)| m.clone())
+            .collect::<Vec<_>>();
+
+        let commitments = self
+            .updates
+            .iter()
+            .map(|(m, b)| FpVar::new_input(cs.clone(), || Ok(model_commitment::<F>(m, *b))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let weights = F64Var::new_input_vec(cs.clone(), &self.weights)?;
+        let average = F64Var::new_input_vec(
+            cs.clone(),
+            &Self::average(&models, &self.weights).coefficients(),
+        )?;
+
+        let updates = {
+            profile_span!("commitments");
+            self.updates
+                .iter()
+                .zip(&commitments)
+                .map(|((m, b), c)| new_committed_f64s(cs.clone(), &m.coefficients(), *b, c))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        profile_span!("average");
+        let mut sum: Vec<Option<F64Var<F>>> = vec![None; n];
+        let mut total: Option<F64Var<F>> = None;
+        for (beta, w) in updates.iter().zip(&weights) {
+            for (s, b) in sum.iter_mut().zip(beta) {
+                let v = w * b;
+                accumulate(s, v);
+            }
+            accumulate(&mut total, w.clone());
+        }
+        let total = total.unwrap();
+        for (s, a) in sum.into_iter().zip(&average) {
+            (s.unwrap() / &total).enforce_equal(a)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `CoefficientSignCircuit` proves that the coefficient at the public `index` (0 for the bias, and
+/// `j + 1` for the weight of feature `j`) of a private linear model has the public sign, i.e., it
+/// is positive if `positive` is set and negative otherwise, where the model is the least squares
+/// fit of `training` (checked as in `TrainingCircuit`, except that its model is private), and it
+/// is bound to the public commitment `model_commitment(model, blinding)`.
+///
+/// Hence, the prover can show that a feature is positively (or negatively) associated with the
+/// target in the private data, without revealing the coefficients. As the index is public rather
+/// than a part of the circuit, the same keys can be used for every coefficient.
+pub struct CoefficientSignCircuit {
+    /// The data and the fit, whose `model` is private.
+    pub training: TrainingCircuit,
+    pub blinding: u128,
+    pub index: usize,
+    pub positive: bool,
+}
+
+impl CoefficientSignCircuit {
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            training: TrainingCircuit::shape(num_samples, num_features),
+            blinding: 0,
+            index: 0,
+            positive: false,
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the commitment to the
+    /// model, the tolerance, the index of the coefficient, and its sign. If `training` has
+    /// standard errors or feature bounds, their public inputs follow in the same way as in
+    /// `TrainingCircuit`.
+    pub fn public_inputs<F: PrimeField>(
+        commitment: F,
+        tolerance: f64,
+        index: usize,
+        positive: bool,
+    ) -> Vec<F> {
+        PublicInputs::new()
+            .add_commitment(commitment)
+            .add_f64(tolerance)
+            .add_integer(index as u64)
+            .add_bool(positive)
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for CoefficientSignCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.training.x.is_empty() || self.training.x.len() != self.training.y.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let training = self.training;
+
+        let commitment = FpVar::new_input(cs.clone(), || {
+            Ok(model_commitment::<F>(&training.model, self.blinding))
+        })?;
+        let tolerance = F64Var::new_input(cs.clone(), || Ok(training.tolerance))?;
+        let index = FpVar::new_input(cs.clone(), || Ok(F::from(self.index as u64)))?;
+        let positive = Boolean::new_input(cs.clone(), || Ok(self.positive))?;
+        let beta = new_committed_f64s(
+            cs.clone(),
+            &training.model.coefficients(),
+            self.blinding,
+            &commitment,
+        )?;
+
+        {
+            profile_span!("sign");
+            // Select the coefficient at `index`, which is enforced to be in `[0, n)`.
+            let is_index = (0..beta.len())
+                .map(|j| index.is_eq(&FpVar::constant(F::from(j as u64))))
+                .collect::<Result<Vec<_>, _>>()?;
+            Boolean::kary_or(&is_index)?.enforce_equal(&Boolean::TRUE)?;
+            let mut coefficient = beta[0].clone();
+            for (b, is_index) in beta.iter().zip(&is_index).skip(1) {
+                coefficient = is_index.select(b, &coefficient)?;
+            }
+
+            // Both comparisons are false for 0 and NaN, which have no sign.
+            let zero = F64Var::new_constant(cs.clone(), 0.)?;
+            let is_positive = F64Var::is_gt(&coefficient, &zero)?;
+            let is_negative = F64Var::is_lt(&coefficient, &zero)?;
+            positive
+                .select(&is_positive, &is_negative)?
+                .enforce_equal(&Boolean::TRUE)?;
+        }
+
+        training.enforce_fit(cs, &beta, &tolerance)
+    }
+}
+
+/// `CoefficientBoundsCircuit` proves that each coefficient (the bias followed by the weights) of a
+/// private linear model is within the corresponding public bounds `[lower, upper]`, where the
+/// model is the least squares fit of `training` (checked as in `TrainingCircuit`, except that its
+/// model is private), and it is bound to the public commitment `model_commitment(model, blinding)`.
+///
+/// Hence, the prover can show statements like "no feature's weight exceeds `X`" without revealing
+/// the coefficients, where the coefficients that are not constrained can have the bounds
+/// `(-inf, inf)`.
+pub struct CoefficientBoundsCircuit {
+    /// The data and the fit, whose `model` is private.
+    pub training: TrainingCircuit,
+    pub blinding: u128,
+    pub bounds: Vec<(f64, f64)>,
+}
+
+impl CoefficientBoundsCircuit {
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            training: TrainingCircuit::shape(num_samples, num_features),
+            blinding: 0,
+            bounds: vec![(0., 0.); num_features + 1],
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the commitment to the
+    /// model, the tolerance, and the lower and upper bounds of each coefficient. If `training` has
+    /// standard errors or feature bounds, their public inputs follow in the same way as in
+    /// `TrainingCircuit`.
+    pub fn public_inputs<F: PrimeField>(
+        commitment: F,
+        tolerance: f64,
+        bounds: &[(f64, f64)],
+    ) -> Vec<F> {
+        PublicInputs::new()
+            .add_commitment(commitment)
+            .add_f64(tolerance)
+            .add_f64s(bounds.iter().flat_map(|&(lower, upper)| [lower, upper]))
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for CoefficientBoundsCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.training.x.is_empty()
+            || self.training.x.len() != self.training.y.len()
+            || self.bounds.len() != self.training.model.weights.len() + 1
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let training = self.training;
+
+        let commitment = FpVar::new_input(cs.clone(), || {
+            Ok(model_commitment::<F>(&training.model, self.blinding))
+        })?;
+        let tolerance = F64Var::new_input(cs.clone(), || Ok(training.tolerance))?;
+        let bounds = self
+            .bounds
+            .iter()
+            .map(|&(lower, upper)| {
+                Ok((
+                    F64Var::new_input(cs.clone(), || Ok(lower))?,
+                    F64Var::new_input(cs.clone(), || Ok(upper))?,
+                ))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+        let beta = new_committed_f64s(
+            cs.clone(),
+            &training.model.coefficients(),
+            self.blinding,
+            &commitment,
+        )?;
+
+        {
+            profile_span!("bounds");
+            enforce_within_bounds(&beta, &bounds)?;
+        }
+
+        training.enforce_fit(cs, &beta, &tolerance)
+    }
+}
+
+/// `SelectiveDisclosureCircuit` proves that the coefficients (the bias followed by the weights)
+/// selected by the public bitmask `revealed` are the public values, where the model is the least
+/// squares fit of `training` (checked as in `TrainingCircuit`, except that its model is private),
+/// and the whole model is bound to the public commitment `model_commitment(model, blinding)`.
+///
+/// Hence, the revealed and the hidden coefficients come from the same fitted model, and the
+/// hidden ones can be disclosed later by opening the commitment. Each coefficient has a public
+/// value, which is 0 if it is hidden, so the same keys can be used for every bitmask.
+pub struct SelectiveDisclosureCircuit {
+    /// The data and the fit, whose `model` is private.
+    pub training: TrainingCircuit,
+    pub blinding: u128,
+    pub revealed: Vec<bool>,
+}
+
+impl SelectiveDisclosureCircuit {
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            training: TrainingCircuit::shape(num_samples, num_features),
+            blinding: 0,
+            revealed: vec![false; num_features + 1],
+        }
+    }
+
+    /// Get the coefficients as seen by the verifier, i.e., the revealed coefficients and `None`
+    /// for the hidden ones.
+    pub fn disclosed(&self) -> Vec<Option<f64>> {
+        self.training
+            .model
+            .coefficients()
+            .into_iter()
+            .zip(&self.revealed)
+            .map(|(b, &r)| r.then_some(b))
+            .collect()
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the commitment to the
+    /// model, the tolerance, the bitmask of the `disclosed` coefficients, and the value of each
+    /// coefficient (0 if it is hidden). If `training` has standard errors or feature bounds,
+    /// their public inputs follow in the same way as in `TrainingCircuit`.
+    pub fn public_inputs<F: PrimeField>(
+        commitment: F,
+        tolerance: f64,
+        disclosed: &[Option<f64>],
+    ) -> Vec<F> {
+        let mut inputs = PublicInputs::new();
+        inputs.add_commitment(commitment).add_f64(tolerance);
+        for b in disclosed {
+            inputs.add_bool(b.is_some());
+        }
+        inputs
+            .add_f64s(disclosed.iter().map(|b| b.unwrap_or(0.)))
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for SelectiveDisclosureCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.training.x.is_empty()
+            || self.training.x.len() != self.training.y.len()
+            || self.revealed.len() != self.training.model.weights.len() + 1
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let disclosed = self.disclosed();
+        let training = self.training;
+
+        let commitment = FpVar::new_input(cs.clone(), || {
+            Ok(model_commitment::<F>(&training.model, self.blinding))
+        })?;
+        let tolerance = F64Var::new_input(cs.clone(), || Ok(training.tolerance))?;
+        let revealed = self
+            .revealed
+            .iter()
+            .map(|&r| Boolean::new_input(cs.clone(), || Ok(r)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let values = disclosed
+            .iter()
+            .map(|b| b.unwrap_or(0.))
+            .collect::<Vec<_>>();
+        let values = F64Var::new_input_vec(cs.clone(), &values)?;
+        let beta = new_committed_f64s(
+            cs.clone(),
+            &training.model.coefficients(),
+            self.blinding,
+            &commitment,
+        )?;
+
+        {
+            profile_span!("disclosure");
+            let zero = F64Var::new_constant(cs.clone(), 0.)?;
+            for ((b, v), r) in beta.iter().zip(&values).zip(&revealed) {
+                b.conditional_enforce_equal(v, r)?;
+                zero.conditional_enforce_equal(v, &r.not())?;
+            }
+        }
+
+        training.enforce_fit(cs, &beta, &tolerance)
+    }
+}
+
+/// `RSquaredCircuit` proves that a public linear model explains at least the public `threshold`
+/// of the variance of the private targets `y` given the private features `x`, i.e., its
+/// coefficient of determination `R²` (see [`r_squared`]) is at least `threshold`.
+///
+/// The circuit fails for constant targets, whose `R²` is NaN or -inf, unless the threshold is
+/// -inf. As `R²` is computed in
+/// floating point, the claim holds for the value computed by `r_squared`, which may differ from
+/// other implementations in the last bits.
+pub struct RSquaredCircuit {
+    pub x: Vec<Vec<f64>>,
+    pub y: Vec<f64>,
+    pub model: LinearModel,
+    pub threshold: f64,
+}
+
+impl RSquaredCircuit {
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            x: vec![vec![0.; num_features]; num_samples],
+            y: vec![0.; num_samples],
+            model: LinearModel {
+                weights: vec![0.; num_features],
+                bias: 0.,
+            },
+            threshold: 0.,
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the coefficients of the
+    /// model (the bias followed by the weights) and the threshold.
+    pub fn public_inputs<F: PrimeField>(model: &LinearModel, threshold: f64) -> Vec<F> {
+        PublicInputs::new()
+            .add_f64s(model.coefficients())
+            .add_f64(threshold)
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for RSquaredCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.x.is_empty() || self.x.len() != self.y.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let beta = F64Var::new_input_vec(cs.clone(), &self.model.coefficients())?;
+        let threshold = F64Var::new_input(cs.clone(), || Ok(self.threshold))?;
+        let n = beta.len();
+
+        // Each row yields its target and the prediction of the target.
+        let rows = self.x.iter().zip(&self.y).collect::<Vec<_>>();
+        let outputs = {
+            profile_span!("predictions");
+            synthesize_rows(&cs, &beta, &rows, |cs, beta, (x, y)| {
+                if x.len() != n - 1 {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+                let (bias, weights) = beta.split_first().unwrap();
+                let x = F64Var::new_witness_vec(cs.clone(), x)?;
+                let y = F64Var::new_witness(cs.clone(), || Ok(**y))?;
+
+                let mut prediction = bias.clone();
+                for (w, x) in weights.iter().zip(&x) {
+                    prediction += w * x;
+                }
+                Ok(vec![y, prediction])
+            })?
+        };
+        let (y, predictions): (Vec<_>, Vec<_>) = outputs
+            .into_iter()
+            .map(|mut v| {
+                let prediction = v.pop().unwrap();
+                (v.pop().unwrap(), prediction)
+            })
+            .unzip();
+
+        profile_span!("r_squared");
+        let r_squared = r_squared_gadget(&y, &predictions)?;
+        F64Var::enforce_ge(&r_squared, &threshold)
+    }
+}
+
+/// Compute the leaf of a sample with the features `x` and the target `y` in the Merkle tree of a
+/// dataset, i.e., the hash of the encoded features followed by the target.
+pub fn row_leaf<F: PrimeField>(x: &[f64], y: f64) -> F {
+    let mut inputs = F64Var::<F>::verifier_inputs(x);
+    inputs.extend(F64Var::<F>::verifier_input(y));
+    hash(&inputs)
+}
+
+/// Build the Merkle tree of the dataset with the features `x` and the targets `y`, whose leaves
+/// are the `row_leaf` of the samples in ascending order (as integers), so that the tree does not
+/// depend on the order of the samples, and equal samples have adjacent leaves. Its root commits
+/// to the dataset, and the path of a leaf (see `MerkleTree::path`) proves that the sample is in
+/// the dataset.
+pub fn dataset_tree<F: PrimeField>(x: &[Vec<f64>], y: &[f64]) -> MerkleTree<F> {
+    let mut leaves = x
+        .iter()
+        .zip(y)
+        .map(|(x, &y)| row_leaf::<F>(x, y))
+        .collect::<Vec<_>>();
+    leaves.sort_by_key(|l| l.into_bigint());
+    MerkleTree::new(leaves)
+}
+
+/// `DatasetSizeCircuit` proves that a public linear model is the least squares fit of `training`
+/// (checked as in `TrainingCircuit`), whose samples are committed to by the public root of
+/// `dataset_tree`, and there are at least `min_samples` distinct samples among them, so that
+/// the model is not fitted to a handful of points that are repeated to look like a large dataset.
+///
+/// The samples should be in the order of their leaves, see `Self::new`. Duplicate samples are
+/// allowed but only counted once, and `training` should not have a mask, since padding rows are
+/// committed to as samples.
+pub struct DatasetSizeCircuit {
+    pub training: TrainingCircuit,
+    pub min_samples: usize,
+}
+
+impl DatasetSizeCircuit {
+    /// Create the circuit by sorting the samples of `training` in the order of their leaves in
+    /// `dataset_tree` over `F`.
+    pub fn new<F: PrimeField>(mut training: TrainingCircuit, min_samples: usize) -> Self {
+        let mut rows = training
+            .x
+            .drain(..)
+            .zip(training.y.drain(..))
+            .map(|(x, y)| (row_leaf::<F>(&x, y).into_bigint(), x, y))
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|(l, _, _)| *l);
+        (training.x, training.y) = rows.into_iter().map(|(_, x, y)| (x, y)).unzip();
+        Self {
+            training,
+            min_samples,
+        }
+    }
+
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            training: TrainingCircuit::shape(num_samples, num_features),
+            min_samples: 0,
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the root of
+    /// `dataset_tree`, the minimum number of distinct samples, and the public inputs of
+    /// `TrainingCircuit::public_inputs`. If `training` has standard errors or feature bounds,
+    /// their public inputs follow in the same way as in `TrainingCircuit`.
+    pub fn public_inputs<F: PrimeField>(
+        root: F,
+        min_samples: usize,
+        model: &LinearModel,
+        tolerance: f64,
+    ) -> Vec<F> {
+        [
+            PublicInputs::new()
+                .add_commitment(root)
+                .add_integer(min_samples as u64)
+                .build(),
+            TrainingCircuit::public_inputs(model, tolerance),
+        ]
+        .concat()
+    }
+}
+
+/// Determine whether the little-endian bits `a` are at most `b` as integers.
+fn is_le_bits<F: PrimeField>(
+    a: &[Boolean<F>],
+    b: &[Boolean<F>],
+) -> Result<Boolean<F>, SynthesisError> {
+    // From the least significant bit up, the highest bit where `a` and `b` differ decides.
+    let mut is_le = Boolean::TRUE;
+    for (a, b) in a.iter().zip(b) {
+        is_le = a.xor(b)?.select(b, &is_le)?;
+    }
+    Ok(is_le)
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for DatasetSizeCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let training = self.training;
+        if training.x.is_empty() || training.x.len() != training.y.len() || training.mask.is_some()
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let samples = training
+            .x
+            .iter()
+            .zip(&training.y)
+            .map(|(x, &y)| [&x[..], &[y]].concat())
+            .collect::<Vec<_>>();
+        let leaves = training
+            .x
+            .iter()
+            .zip(&training.y)
+            .map(|(x, &y)| row_leaf::<F>(x, y))
+            .collect();
+
+        let root = FpVar::new_input(cs.clone(), || Ok(MerkleTree::new(leaves).root()))?;
+        let min_samples = FpVar::new_input(cs.clone(), || Ok(F::from(self.min_samples as u64)))?;
+        let beta = F64Var::new_input_vec(cs.clone(), &training.model.coefficients())?;
+        let tolerance = F64Var::new_input(cs.clone(), || Ok(training.tolerance))?;
+        let rows = training.fit(cs.clone(), &beta, &tolerance, true)?;
+
+        profile_span!("dataset");
+        // The leaves are computed from the encoding of the samples, which is equal to the samples
+        // in the fit.
+        let leaves = rows
+            .iter()
+            .zip(&samples)
+            .map(|(row, sample)| {
+                let (vars, components) = new_encoded_f64s(cs.clone(), sample)?;
+                for (v, r) in vars.iter().zip(row) {
+                    v.enforce_equal(r)?;
+                }
+                hash_gadget(&components)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        merkle_root_gadget(&leaves)?.enforce_equal(&root)?;
+
+        // As the leaves are sorted, equal samples are adjacent, so each leaf that differs from
+        // the previous one is a new sample.
+        let bits = leaves
+            .iter()
+            .map(|l| l.to_bits_le())
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut count = FpVar::one();
+        for (i, leaf) in leaves.iter().enumerate().skip(1) {
+            is_le_bits(&bits[i - 1], &bits[i])?.enforce_equal(&Boolean::TRUE)?;
+            count += FpVar::from(leaf.is_neq(&leaves[i - 1])?);
+        }
+        (count - min_samples).enforce_bit_length(64)
+    }
+}
+
+/// The sufficient statistics of ordinary least squares on a dataset, i.e., `G = XᵀX` and
+/// `h = Xᵀy`, where `X` is the features augmented with a leading column of ones, and hence `G_00`
+/// is the number of samples.
+///
+/// The statistics can be updated incrementally when samples are appended to the dataset, which is
+/// proven by `StatisticsUpdateCircuit`, so that the fit can be proven on the whole dataset by
+/// `StatisticsTrainingCircuit` without re-proving the samples that were already appended.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SufficientStatistics {
+    pub g: Vec<Vec<f64>>,
+    pub h: Vec<f64>,
+}
+
+impl SufficientStatistics {
+    /// Create the statistics of an empty dataset with `num_features` features.
+    pub fn new(num_features: usize) -> Self {
+        let n = num_features + 1;
+        Self {
+            g: vec![vec![0.; n]; n],
+            h: vec![0.; n],
+        }
+    }
+
+    /// Compute the statistics after appending the features `x` and the targets `y`.
+    /// The operations are performed in the same order as in [`StatisticsUpdateCircuit`], and
+    /// the result is the same as `normal_equations` on the whole dataset (except for the signs
+    /// of zeros), since the samples are summed in the same order.
+    pub fn append(&self, x: &[Vec<f64>], y: &[f64]) -> Self {
+        assert_eq!(x.len(), y.len());
+        let n = self.h.len();
+        let (mut g, mut h) = (self.g.clone(), self.h.clone());
+        for (x, &y) in x.iter().zip(y) {
+            assert_eq!(x.len(), n - 1);
+            let row = [&[1.][..], x].concat();
+            for (j, (g, h)) in g.iter_mut().zip(&mut h).enumerate() {
+                for (k, g) in g.iter_mut().enumerate().skip(j.max(1)) {
+                    *g += row[j] * row[k];
+                }
+                *h += row[j] * y;
+            }
+        }
+        g[0][0] += x.len() as f64;
+        let g = (0..n)
+            .map(|j| (0..n).map(|k| g[j.min(k)][j.max(k)]).collect())
+            .collect();
+        Self { g, h }
+    }
+
+    /// Check whether `G` is an `n × n` matrix and `h` has length `n` for some `n > 0`.
+    fn is_well_formed(&self) -> bool {
+        let n = self.h.len();
+        n > 0 && self.g.len() == n && self.g.iter().all(|g| g.len() == n)
+    }
+
+    /// The number of samples of the dataset.
+    pub fn num_samples(&self) -> f64 {
+        self.g[0][0]
+    }
+
+    /// The entries that are committed to, i.e., the upper triangle of `G` row by row, followed
+    /// by `h`.
+    fn entries(&self) -> Vec<f64> {
+        let n = self.h.len();
+        let mut entries = vec![];
+        for (j, g) in self.g.iter().enumerate() {
+            entries.extend(&g[j..]);
+        }
+        assert_eq!(entries.len(), n * (n + 1) / 2);
+        entries.extend(&self.h);
+        entries
+    }
+
+    /// Compute the commitment to the statistics with the random `blinding`, which is the
+    /// commitment to their entries by `f64_commitment`.
+    pub fn commitment<F: PrimeField>(&self, blinding: u128) -> F {
+        f64_commitment(&self.entries(), blinding)
+    }
+
+    /// Compute the maximum absolute residual of the normal equations `|Gβ - h|` for `model`,
+    /// which is the minimum tolerance accepted by [`StatisticsTrainingCircuit`].
+    pub fn optimality_gap(&self, model: &LinearModel) -> f64 {
+        normal_equations_gap(model, &self.g, &self.h)
+    }
+}
+
+/// Split the committed entries of `SufficientStatistics` with `n` coefficients into the upper
+/// triangle of `G` (where the `j`-th row starts from `G_jj`) and `h`.
+fn split_entries<F: PrimeField>(
+    entries: Vec<F64Var<F>>,
+    n: usize,
+) -> (Vec<Vec<F64Var<F>>>, Vec<F64Var<F>>) {
+    let mut entries = entries.into_iter();
+    let g = (0..n)
+        .map(|j| entries.by_ref().take(n - j).collect())
+        .collect();
+    (g, entries.collect())
+}
+
+/// `StatisticsUpdateCircuit` proves that the committed statistics `next` are the committed
+/// statistics `previous` after appending the private features `x` and targets `y`, where only
+/// the commitments are public.
+///
+/// Starting from the public commitment to `SufficientStatistics::new` with blinding 0, a chain of
+/// such proofs (where each `previous` commitment is the `next` commitment of the last proof)
+/// proves the statistics of the whole dataset, and each proof only depends on the appended
+/// samples.
+pub struct StatisticsUpdateCircuit {
+    pub previous: SufficientStatistics,
+    pub previous_blinding: u128,
+    pub x: Vec<Vec<f64>>,
+    pub y: Vec<f64>,
+    pub blinding: u128,
+}
+
+impl StatisticsUpdateCircuit {
+    /// Create a circuit appending `num_samples` samples of `num_features` features, whose values
+    /// are all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            previous: SufficientStatistics::new(num_features),
+            previous_blinding: 0,
+            x: vec![vec![0.; num_features]; num_samples],
+            y: vec![0.; num_samples],
+            blinding: 0,
+        }
+    }
+
+    /// Compute the statistics after appending the samples, i.e., the statistics committed to by
+    /// the next commitment.
+    pub fn next(&self) -> SufficientStatistics {
+        self.previous.append(&self.x, &self.y)
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the previous commitment
+    /// and the next commitment.
+    pub fn public_inputs<F: PrimeField>(previous: F, next: F) -> Vec<F> {
+        PublicInputs::new()
+            .add_commitment(previous)
+            .add_commitment(next)
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for StatisticsUpdateCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // The shapes are checked before computing the next statistics, which panics on malformed
+        // samples.
+        let n = self.previous.h.len();
+        if !self.previous.is_well_formed()
+            || self.x.len() != self.y.len()
+            || self.x.iter().any(|x| x.len() != n - 1)
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let next = self.next();
+        let previous_commitment = FpVar::new_input(cs.clone(), || {
+            Ok(self.previous.commitment::<F>(self.previous_blinding))
+        })?;
+        let next_commitment =
+            FpVar::new_input(cs.clone(), || Ok(next.commitment::<F>(self.blinding)))?;
+
+        let previous = new_committed_f64s(
+            cs.clone(),
+            &self.previous.entries(),
+            self.previous_blinding,
+            &previous_commitment,
+        )?;
+        let (mut g, mut h) = split_entries(previous, n);
+
+        let rows = self.x.iter().zip(&self.y).collect::<Vec<_>>();
+        let products = {
+            profile_span!("products");
+            synthesize_rows(&cs, &[], &rows, |cs, _, (x, y)| {
+                let x = F64Var::new_witness_vec(cs.clone(), x)?;
+                let y = F64Var::new_witness(cs.clone(), || Ok(**y))?;
+                Ok(row_products(&x, std::slice::from_ref(&y)))
+            })?
+        };
+        {
+            profile_span!("accumulate");
+            for products in products {
+                let mut products = products.into_iter();
+                for (j, (g, h)) in g.iter_mut().zip(&mut h).enumerate() {
+                    for g in g.iter_mut().skip(if j == 0 { 1 } else { 0 }) {
+                        *g += products.next().unwrap();
+                    }
+                    *h += products.next().unwrap();
+                }
+            }
+            g[0][0] += F64Var::new_constant(cs.clone(), self.x.len() as f64)?;
+        }
+
+        let (next, commitment) = new_f64s_with_commitment(cs, &next.entries(), self.blinding)?;
+        commitment.enforce_equal(&next_commitment)?;
+        let computed = g.into_iter().flatten().chain(h);
+        for (v, computed) in next.iter().zip(computed) {
+            v.enforce_equal(&computed)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `StatisticsTrainingCircuit` proves that a public linear model is the least squares fit of the
+/// dataset whose statistics are committed to by the public commitment (e.g., the last commitment
+/// of a chain of `StatisticsUpdateCircuit` proofs), in the same sense as `TrainingCircuit`.
+///
+/// The number of samples `G_00` is public, and it should be at least the number of coefficients,
+/// since the normal equations of fewer samples are singular and would be satisfied by any model,
+/// e.g., for the commitment to `SufficientStatistics::new` that starts the chain.
+pub struct StatisticsTrainingCircuit {
+    pub statistics: SufficientStatistics,
+    pub blinding: u128,
+    pub model: LinearModel,
+    pub tolerance: f64,
+}
+
+impl StatisticsTrainingCircuit {
+    /// Create a circuit with `num_features` features, whose values are all 0, e.g., for
+    /// generating the keys.
+    pub fn shape(num_features: usize) -> Self {
+        Self {
+            statistics: SufficientStatistics::new(num_features),
+            blinding: 0,
+            model: LinearModel {
+                weights: vec![0.; num_features],
+                bias: 0.,
+            },
+            tolerance: 0.,
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the commitment to the
+    /// statistics, the number of samples, the coefficients of the model (the bias followed by the
+    /// weights), and the tolerance.
+    pub fn public_inputs<F: PrimeField>(
+        commitment: F,
+        num_samples: usize,
+        model: &LinearModel,
+        tolerance: f64,
+    ) -> Vec<F> {
+        PublicInputs::new()
+            .add_commitment(commitment)
+            .add_f64(num_samples as f64)
+            .add_f64s(model.coefficients())
+            .add_f64(tolerance)
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for StatisticsTrainingCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let n = self.statistics.h.len();
+        if !self.statistics.is_well_formed() || self.model.weights.len() != n - 1 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let commitment = FpVar::new_input(cs.clone(), || {
+            Ok(self.statistics.commitment::<F>(self.blinding))
+        })?;
+        let num_samples = F64Var::new_input(cs.clone(), || Ok(self.statistics.num_samples()))?;
+        let beta = F64Var::new_input_vec(cs.clone(), &self.model.coefficients())?;
+        let tolerance = F64Var::new_input(cs.clone(), || Ok(self.tolerance))?;
+
+        let statistics = new_committed_f64s(
+            cs.clone(),
+            &self.statistics.entries(),
+            self.blinding,
+            &commitment,
+        )?;
+        let (g, h) = split_entries(statistics, n);
+        let g_ij = |i: usize, j: usize| &g[i.min(j)][i.max(j) - i.min(j)];
+
+        // The normal equations of fewer samples than coefficients are singular, which would be
+        // satisfied by any model, e.g., if the statistics are those of the empty dataset.
+        g_ij(0, 0).enforce_equal(&num_samples)?;
+        F64Var::enforce_ge(&num_samples, &F64Var::new_constant(cs.clone(), n as f64)?)?;
+
+        profile_span!("residuals");
+        for (i, h) in h.iter().enumerate() {
+            let mut v = g_ij(i, 0) * &beta[0];
+            for (j, b) in beta.iter().enumerate().skip(1) {
+                v += g_ij(i, j) * b;
+            }
+            let residual = (v - h).abs();
+            F64Var::is_le(&residual, &tolerance)?.enforce_equal(&Boolean::TRUE)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compute the demographic parity gap of `model` on the features `x`, i.e., the absolute
+/// difference `|mean_1 - mean_0|` between the mean predictions of the samples in the protected
+/// group (whose `group` is `true`) and of the others, which should be less than the threshold of
+/// [`FairnessCircuit`].
+/// The operations are performed in the same order as in [`FairnessCircuit`].
+pub fn fairness_gap(model: &LinearModel, x: &[Vec<f64>], group: &[bool]) -> f64 {
+    assert_eq!(x.len(), group.len());
+    assert!(!x.is_empty());
+    let (mut sum_1, mut sum_0, mut count_1) = (0., 0., 0.);
+    for (i, (x, &g)) in x.iter().zip(group).enumerate() {
+        let p = model.predict(x);
+        let (p_1, p_0, c) = if g { (p, 0., 1.) } else { (0., p, 0.) };
+        if i == 0 {
+            (sum_1, sum_0, count_1) = (p_1, p_0, c);
+        } else {
+            (sum_1, sum_0, count_1) = (sum_1 + p_1, sum_0 + p_0, count_1 + c);
+        }
+    }
+    let count_0 = x.len() as f64 - count_1;
+    (sum_1 / count_1 - sum_0 / count_0).abs()
+}
+
+/// `FairnessCircuit` proves that a public linear model is fair on a private dataset in the
+/// sense of demographic parity, i.e., the absolute difference between the mean predictions of
+/// the two groups identified by the private attribute `group` is less than the public
+/// `threshold`, without revealing the features or the group of any sample.
+///
+/// Both groups should be non-empty, otherwise the mean of the empty group is NaN, and the
+/// circuit is unsatisfiable.
+pub struct FairnessCircuit {
+    pub x: Vec<Vec<f64>>,
+    pub group: Vec<bool>,
+    pub model: LinearModel,
+    pub threshold: f64,
+}
+
+impl FairnessCircuit {
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0 or `false`, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            x: vec![vec![0.; num_features]; num_samples],
+            group: vec![false; num_samples],
+            model: LinearModel {
+                weights: vec![0.; num_features],
+                bias: 0.,
+            },
+            threshold: 0.,
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the coefficients of
+    /// the model (the bias followed by the weights) and the threshold.
+    pub fn public_inputs<F: PrimeField>(model: &LinearModel, threshold: f64) -> Vec<F> {
+        PublicInputs::new()
+            .add_f64s(model.coefficients())
+            .add_f64(threshold)
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for FairnessCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.x.is_empty() || self.x.len() != self.group.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let beta = F64Var::new_input_vec(cs.clone(), &self.model.coefficients())?;
+        let threshold = F64Var::new_input(cs.clone(), || Ok(self.threshold))?;
+        let n = beta.len();
+
+        // For each row, compute the prediction in the protected group (or 0), the prediction in
+        // the other group (or 0), and the membership of the protected group (as 1 or 0).
+        let rows = self.x.iter().zip(&self.group).collect::<Vec<_>>();
+        let terms = {
+            profile_span!("rows");
+            synthesize_rows(&cs, &beta, &rows, |cs, beta, (x, g)| {
+                if x.len() != n - 1 {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+                let (bias, weights) = beta.split_first().unwrap();
+                let x = F64Var::new_witness_vec(cs.clone(), x)?;
+                let g = Boolean::new_witness(cs.clone(), || Ok(**g))?;
+
+                let prediction = {
+                    profile_span!("dot");
+                    let mut prediction = bias.clone();
+                    for (w, x) in weights.iter().zip(&x) {
+                        prediction += w * x;
+                    }
+                    prediction
+                };
+                let zero = F64Var::new_constant(cs.clone(), 0.)?;
+                let one = F64Var::new_constant(cs.clone(), 1.)?;
+                Ok(vec![
+                    g.select(&prediction, &zero)?,
+                    g.select(&zero, &prediction)?,
+                    g.select(&one, &zero)?,
+                ])
+            })?
+        };
+
+        let mut sums: Vec<Option<F64Var<F>>> = vec![None; 3];
+        {
+            profile_span!("accumulate");
+            for terms in terms {
+                for (s, v) in sums.iter_mut().zip(terms) {
+                    accumulate(s, v);
+                }
+            }
+        }
+        let [sum_1, sum_0, count_1] = [0, 1, 2].map(|i| sums[i].take().unwrap());
+        let count_0 = F64Var::new_constant(cs.clone(), self.x.len() as f64)? - &count_1;
+        let gap = (sum_1 / count_1 - sum_0 / count_0).abs();
+        F64Var::is_lt(&gap, &threshold)?.enforce_equal(&Boolean::TRUE)?;
+
+        Ok(())
+    }
+}
+
+/// Count the samples whose absolute residual `|y - prediction|` under `model` is not at most
+/// `threshold` (including NaN residuals), which should be less than the bound of
+/// [`OutlierCountCircuit`].
+/// The operations are performed in the same order as in [`OutlierCountCircuit`].
+pub fn outlier_count(model: &LinearModel, x: &[Vec<f64>], y: &[f64], threshold: f64) -> usize {
+    assert_eq!(x.len(), y.len());
+    x.iter()
+        .zip(y)
+        .filter(|(x, &y)| {
+            let r = (y - model.predict(x)).abs();
+            !matches!(
+                r.partial_cmp(&threshold),
+                Some(Ordering::Less | Ordering::Equal)
+            )
+        })
+        .count()
+}
+
+/// `OutlierCountCircuit` proves that fewer than the public `bound` samples of a private dataset are
+/// outliers of a public linear model, i.e., have an absolute residual `|y - prediction|` exceeding
+/// the public `threshold`, without revealing the residuals, the count, or which samples are
+/// outliers.
+///
+/// NaN residuals are counted as outliers, so the claim also attests that the model has a finite
+/// prediction for all other samples.
+pub struct OutlierCountCircuit {
+    pub x: Vec<Vec<f64>>,
+    pub y: Vec<f64>,
+    pub model: LinearModel,
+    pub threshold: f64,
+    pub bound: usize,
+}
+
+impl OutlierCountCircuit {
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            x: vec![vec![0.; num_features]; num_samples],
+            y: vec![0.; num_samples],
+            model: LinearModel {
+                weights: vec![0.; num_features],
+                bias: 0.,
+            },
+            threshold: 0.,
+            bound: 0,
+        }
+    }
+
+    /// Compute the public inputs that the verifier should provide, i.e., the coefficients of
+    /// the model (the bias followed by the weights), the threshold, and the bound.
+    pub fn public_inputs<F: PrimeField>(
+        model: &LinearModel,
+        threshold: f64,
+        bound: usize,
+    ) -> Vec<F> {
+        PublicInputs::new()
+            .add_f64s(model.coefficients())
+            .add_f64(threshold)
+            .add_integer(bound as u64)
+            .build()
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for OutlierCountCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        if self.x.len() != self.y.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let beta = F64Var::new_input_vec(cs.clone(), &self.model.coefficients())?;
+        let threshold = F64Var::new_input(cs.clone(), || Ok(self.threshold))?;
+        let bound = FpVar::new_input(cs.clone(), || Ok(F::from(self.bound as u64)))?;
+        let n = beta.len();
+
+        // Each row yields its absolute residual.
+        let rows = self.x.iter().zip(&self.y).collect::<Vec<_>>();
+        let residuals = {
+            profile_span!("residuals");
+            synthesize_rows(&cs, &beta, &rows, |cs, beta, (x, y)| {
+                if x.len() != n - 1 {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+                let (bias, weights) = beta.split_first().unwrap();
+                let x = F64Var::new_witness_vec(cs.clone(), x)?;
+                let y = F64Var::new_witness(cs.clone(), || Ok(**y))?;
+
+                let mut prediction = bias.clone();
+                for (w, x) in weights.iter().zip(&x) {
+                    prediction += w * x;
+                }
+                Ok(vec![(y - prediction).abs()])
+            })?
+        };
+
+        profile_span!("count");
+        let mut count = FpVar::zero();
+        for r in residuals.iter().flatten() {
+            count += FpVar::from(F64Var::is_le(r, &threshold)?.not());
+        }
+        // The count is at most the number of samples, so `bound - 1 - count` fits in 64 bits iff
+        // the count is less than the bound.
+        (bound - count - FpVar::one()).enforce_bit_length(64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+    use crate::{
+        groth16::{CircuitSpecificSetupSNARK, Groth16, SNARK},
+        r1cs::ConstraintSystem,
+        shaped::Shape,
+    };
+    use ark_bls12_381::Fr;
+    use ark_bn254::Bn254;
+    use ark_std::test_rng;
+    use rand::{RngCore, SeedableRng};
+
+    fn dataset() -> (Vec<Vec<f64>>, Vec<f64>) {
+        let x = vec![
+            vec![0.5, 1.25],
+            vec![-1.5, 2.],
+            vec![3.75, -0.5],
+            vec![2.25, 0.125],
+            vec![-0.75, -3.],
+        ];
+        let y = x
+            .iter()
+            .zip([0.01, -0.02, 0.015, 0., -0.005])
+            .map(|(x, noise)| 2. * x[0] - 3. * x[1] + 1. + noise)
+            .collect();
+        (x, y)
+    }
+
+    #[test]
+    fn fit() {
+        let x = vec![vec![1., 2.], vec![2., 1.], vec![3., 5.], vec![0., -1.]];
+        let y = x
+            .iter()
+            .map(|x| 2. * x[0] - 3. * x[1] + 1.)
+            .collect::<Vec<_>>();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        assert!((model.bias - 1.).abs() < 1e-9);
+        assert!((model.weights[0] - 2.).abs() < 1e-9);
+        assert!((model.weights[1] + 3.).abs() < 1e-9);
+
+        assert!(LinearModel::fit(&[vec![1.], vec![1.]], &[0., 1.]).is_none());
+    }
+
+    #[test]
+    fn inference() -> Result<(), Box<dyn Error>> {
+        let (x, _) = dataset();
+        let model = LinearModel {
+            weights: vec![0.1, -0.3],
+            bias: 0.7,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        InferenceCircuit::new(model.clone(), x.clone()).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+
+        let mut circuit = InferenceCircuit::new(model, x);
+        circuit.y[2] = f64::from_bits(circuit.y[2].to_bits() + 1);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone())?;
+        assert!(!cs.is_satisfied()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn inference_with_tolerance() -> Result<(), Box<dyn Error>> {
+        let (x, _) = dataset();
+        let model = LinearModel {
+            weights: vec![0.1, -0.3],
+            bias: 0.7,
+        };
+        // Claim the predictions rounded to 2 decimal places, which are off by at most 0.005.
+        let y = x
+            .iter()
+            .map(|x| (model.predict(x) * 100.).round() / 100.)
+            .collect::<Vec<_>>();
+        let circuit = |y: Vec<f64>, tolerance| {
+            InferenceCircuit::with_tolerance(model.clone(), x.clone(), y, tolerance)
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(y.clone(), 0.01).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        let inputs = InferenceCircuit::public_inputs_with_tolerance::<Fr>(&x, &y, 0.01);
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], inputs);
+
+        assert!(!is_satisfied::<Fr>(circuit(y.clone(), 0.001))?);
+        let mut far = y.clone();
+        far[1] += 0.02;
+        assert!(!is_satisfied::<Fr>(circuit(far, 0.01))?);
+        // The error is strictly less than the tolerance, so a zero tolerance is never satisfied.
+        let exact = x.iter().map(|x| model.predict(x)).collect::<Vec<_>>();
+        assert!(!is_satisfied::<Fr>(circuit(exact, 0.))?);
+        assert!(!is_satisfied::<Fr>(circuit(y, f64::NAN))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn training() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        TrainingCircuit::new(x.clone(), y.clone(), model.clone(), tolerance)
+            .generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        TrainingCircuit::new(
+            x,
+            y,
+            LinearModel {
+                bias: model.bias + 0.01,
+                ..model
+            },
+            tolerance,
+        )
+        .generate_constraints(cs.clone())?;
+        assert!(!cs.is_satisfied()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn standard_errors() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+        let errors = StandardErrors::compute(&model, &x, &y).unwrap();
+        // The noise of the dataset is about 0.01.
+        assert!(errors.errors.iter().all(|&e| e > 0. && e < 0.1));
+        assert!(errors.tolerance < 1e-12);
+
+        let circuit = |errors| {
+            TrainingCircuit::new(x.clone(), y.clone(), model.clone(), tolerance)
+                .with_standard_errors(errors)
+        };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(errors.clone()).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            TrainingCircuit::public_inputs_with_standard_errors(&model, tolerance, &errors)
+        );
+
+        // Both the standard errors and the inverse are checked.
+        let mut wrong = errors.clone();
+        wrong.errors[1] *= 1.01;
+        let mut inexact = errors.clone();
+        inexact.inverse[2][2] *= 1.01;
+        for errors in [wrong, inexact] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit(errors).generate_constraints(cs.clone())?;
+            assert!(!cs.is_satisfied()?);
+        }
+
+        // There should be more samples than coefficients.
+        assert!(StandardErrors::compute(&model, &x[..3], &y[..3]).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn feature_bounds() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+        let circuit = |x: &[Vec<f64>], bounds: &[(f64, f64)]| {
+            TrainingCircuit::new(x.to_vec(), y.clone(), model.clone(), tolerance)
+                .with_feature_bounds(bounds.to_vec())
+        };
+
+        // The bounds are inclusive.
+        let bounds = [(-1.5, 3.75), (-3., 2.)];
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(&x, &bounds).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            [
+                TrainingCircuit::public_inputs(&model, tolerance),
+                TrainingCircuit::feature_bounds_inputs(&bounds),
+            ]
+            .concat()
+        );
+
+        assert!(!is_satisfied::<Fr>(circuit(&x, &[(-1., 3.75), (-3., 2.)]))?);
+        assert!(!is_satisfied::<Fr>(circuit(
+            &x,
+            &[(-1.5, 3.75), (-3., 1.)]
+        ))?);
+        let mut nan = x.clone();
+        nan[0][1] = f64::NAN;
+        assert!(!is_satisfied::<Fr>(circuit(&nan, &bounds))?);
+        assert!(is_satisfied::<Fr>(circuit(&x, &bounds[..1])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn multi_target() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let y = x
+            .iter()
+            .zip(y)
+            .zip([0., 0.02, -0.01, 0.005, -0.015])
+            .map(|((x, y), noise)| vec![y, 0.5 * x[1] - 2. + noise])
+            .collect::<Vec<_>>();
+        let circuit = MultiTargetTrainingCircuit::new(x.clone(), y.clone()).unwrap();
+        let (models, tolerance) = (circuit.models.clone(), circuit.tolerance);
+        assert_eq!(models[0], LinearModel::fit(&x, &dataset().1).unwrap());
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            MultiTargetTrainingCircuit::public_inputs(&models, tolerance)
+        );
+
+        // Each model is checked against its own target.
+        let circuit = |models: Vec<LinearModel>| MultiTargetTrainingCircuit {
+            x: x.clone(),
+            y: y.clone(),
+            models,
+            tolerance,
+        };
+        let mut wrong = models.clone();
+        wrong[1].weights[1] += 0.01;
+        assert!(!is_satisfied::<Fr>(circuit(wrong))?);
+        let swapped = vec![models[1].clone(), models[0].clone()];
+        assert!(!is_satisfied::<Fr>(circuit(swapped))?);
+        assert!(is_satisfied::<Fr>(circuit(models[..1].to_vec())).is_err());
+
+        // Sharing `XᵀX` is cheaper than a circuit per target.
+        let multi = MultiTargetTrainingCircuit::constraint_report::<Fr>(5, 2, 2)?;
+        let single = TrainingCircuit::constraint_report::<Fr>(5, 2)?;
+        assert!(multi.num_constraints < 2 * single.num_constraints);
+
+        Ok(())
+    }
+
+    #[test]
+    fn polynomial_features() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            super::polynomial_features(&[2., 3.], 2),
+            [2., 3., 4., 6., 9.]
+        );
+        assert_eq!(
+            super::polynomial_features(&[2., 3.], 3),
+            [2., 3., 4., 6., 9., 8., 12., 18., 27.]
+        );
+        assert_eq!(super::polynomial_features(&[2., 3., 5.], 1), [2., 3., 5.]);
+        assert!(super::polynomial_features(&[2.], 0).is_empty());
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        for x in dataset().0 {
+            let x = [x, vec![0.1]].concat();
+            let expected = super::polynomial_features(&x, 3);
+            assert_eq!(expected.len(), 19);
+            let features = polynomial_features_gadget(&F64Var::new_witness_vec(cs.clone(), &x)?, 3);
+            let values = features
+                .iter()
+                .map(|v| v.to_f64())
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(values, expected);
+        }
+        assert!(cs.is_satisfied()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn coefficient_sign() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+        let blinding = 42;
+        let circuit = |model: &LinearModel, index, positive| CoefficientSignCircuit {
+            training: TrainingCircuit::new(x.clone(), y.clone(), model.clone(), tolerance),
+            blinding,
+            index,
+            positive,
+        };
+
+        // The coefficients are about `[1, 2, -3]`.
+        let commitment = model_commitment::<Fr>(&model, blinding);
+        for (index, positive) in [(0, true), (1, true), (2, false)] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit(&model, index, positive).generate_constraints(cs.clone())?;
+            assert!(cs.is_satisfied()?);
+            assert_eq!(
+                cs.borrow().unwrap().instance_assignment[1..],
+                CoefficientSignCircuit::public_inputs(commitment, tolerance, index, positive)
+            );
+            assert!(!is_satisfied::<Fr>(circuit(&model, index, !positive))?);
+        }
+        assert!(!is_satisfied::<Fr>(circuit(&model, 3, true))?);
+        assert!(!is_satisfied::<Fr>(circuit(&model, 3, false))?);
+
+        // The model should still be the fit of the data.
+        let flipped = LinearModel {
+            weights: vec![model.weights[0], 3.],
+            ..model.clone()
+        };
+        assert!(!is_satisfied::<Fr>(circuit(&flipped, 2, true))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn coefficient_bounds() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+        let blinding = 7;
+        let circuit = |bounds: &[(f64, f64)]| CoefficientBoundsCircuit {
+            training: TrainingCircuit::new(x.clone(), y.clone(), model.clone(), tolerance),
+            blinding,
+            bounds: bounds.to_vec(),
+        };
+
+        // The coefficients are about `[1, 2, -3]`, and the bounds are inclusive.
+        let bounds = [
+            (f64::NEG_INFINITY, f64::INFINITY),
+            (0., 2.5),
+            (-3.5, model.weights[1]),
+        ];
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(&bounds).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            CoefficientBoundsCircuit::public_inputs(
+                model_commitment::<Fr>(&model, blinding),
+                tolerance,
+                &bounds
+            )
+        );
+
+        assert!(!is_satisfied::<Fr>(circuit(&[
+            bounds[0],
+            (0., 1.5),
+            bounds[2]
+        ]))?);
+        assert!(!is_satisfied::<Fr>(circuit(&[
+            bounds[0],
+            bounds[1],
+            (-2.5, 0.)
+        ]))?);
+        assert!(!is_satisfied::<Fr>(circuit(&[
+            (f64::NAN, 2.),
+            bounds[1],
+            bounds[2]
+        ]))?);
+        assert!(is_satisfied::<Fr>(circuit(&bounds[1..])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn selective_disclosure() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+        let blinding = 11;
+        let circuit = |model: &LinearModel, revealed: &[bool]| SelectiveDisclosureCircuit {
+            training: TrainingCircuit::new(x.clone(), y.clone(), model.clone(), tolerance),
+            blinding,
+            revealed: revealed.to_vec(),
+        };
+
+        let commitment = model_commitment::<Fr>(&model, blinding);
+        for revealed in [[false, true, false], [true, false, true], [false; 3]] {
+            let circuit = circuit(&model, &revealed);
+            let disclosed = circuit.disclosed();
+            assert_eq!(disclosed[1], revealed[1].then_some(model.weights[0]));
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit.generate_constraints(cs.clone())?;
+            assert!(cs.is_satisfied()?);
+            assert_eq!(
+                cs.borrow().unwrap().instance_assignment[1..],
+                SelectiveDisclosureCircuit::public_inputs(commitment, tolerance, &disclosed)
+            );
+        }
+
+        // The revealed values should be the coefficients of the model, and the hidden ones 0.
+        let satisfied = |disclosed: &[Option<f64>]| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let revealed = disclosed.iter().map(Option::is_some).collect::<Vec<_>>();
+            circuit(&model, &revealed).generate_constraints(cs.clone())?;
+            let inputs =
+                SelectiveDisclosureCircuit::public_inputs(commitment, tolerance, disclosed);
+            cs.borrow_mut().unwrap().instance_assignment[1..].copy_from_slice(&inputs);
+            Ok::<_, Box<dyn Error>>(cs.is_satisfied()?)
+        };
+        assert!(satisfied(&[None, Some(model.weights[0]), None])?);
+        assert!(!satisfied(&[None, Some(model.weights[0] + 0.01), None])?);
+        // Claim that the bias is hidden while publishing a value for it.
+        let forged = PublicInputs::<Fr>::new()
+            .add_commitment(commitment)
+            .add_f64(tolerance)
+            .add_bool(false)
+            .add_bool(true)
+            .add_bool(false)
+            .add_f64s([1., model.weights[0], 0.])
+            .build();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(&model, &[false, true, false]).generate_constraints(cs.clone())?;
+        cs.borrow_mut().unwrap().instance_assignment[1..].copy_from_slice(&forged);
+        assert!(!cs.is_satisfied()?);
+
+        // The model should still be the fit of the data.
+        let wrong = LinearModel {
+            bias: model.bias + 0.01,
+            ..model.clone()
+        };
+        assert!(!is_satisfied::<Fr>(circuit(&wrong, &[false, true, false]))?);
+        assert!(is_satisfied::<Fr>(circuit(&model, &[true, true])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn r_squared_threshold() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let r2 = r_squared(&model, &x, &y);
+        assert!(r2 > 0. && r2 <= 1.);
+        let circuit = |model: &LinearModel, threshold| RSquaredCircuit {
+            x: x.clone(),
+            y: y.clone(),
+            model: model.clone(),
+            threshold,
+        };
+
+        // The threshold is attained exactly by the value computed natively.
+        for threshold in [0., r2 / 2., r2] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit(&model, threshold).generate_constraints(cs.clone())?;
+            assert!(cs.is_satisfied()?);
+            assert_eq!(
+                cs.borrow().unwrap().instance_assignment[1..],
+                RSquaredCircuit::public_inputs(&model, threshold)
+            );
+        }
+        assert!(!is_satisfied::<Fr>(circuit(&model, r2.next_up()))?);
+
+        // A worse model explains less of the variance.
+        let worse = LinearModel {
+            bias: model.bias + 1.,
+            ..model.clone()
+        };
+        assert!(r_squared(&worse, &x, &y) < r2);
+        assert!(!is_satisfied::<Fr>(circuit(&worse, r2))?);
+
+        // `R²` is undefined for constant targets.
+        let constant = RSquaredCircuit {
+            y: vec![1.; y.len()],
+            ..circuit(&model, f64::MIN)
+        };
+        assert!(!is_satisfied::<Fr>(constant)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn dataset_size() -> Result<(), Box<dyn Error>> {
+        let (mut x, mut y) = dataset();
+        // Repeat a sample, which is only counted once.
+        x.push(x[1].clone());
+        y.push(y[1]);
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+        let circuit = |min_samples| {
+            DatasetSizeCircuit::new::<Fr>(
+                TrainingCircuit::new(x.clone(), y.clone(), model.clone(), tolerance),
+                min_samples,
+            )
+        };
+
+        let tree = dataset_tree::<Fr>(&x, &y);
+        let leaf = row_leaf(&x[3], y[3]);
+        let index = tree.leaves().iter().position(|&l| l == leaf).unwrap();
+        assert!(MerkleTree::verify(
+            tree.root(),
+            leaf,
+            index,
+            &tree.path(index)
+        ));
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(5).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            DatasetSizeCircuit::public_inputs(tree.root(), 5, &model, tolerance)
+        );
+        assert!(!is_satisfied::<Fr>(circuit(6))?);
+
+        // The samples should be sorted by their leaves.
+        let mut unsorted = circuit(5);
+        unsorted.training.x.swap(0, 1);
+        unsorted.training.y.swap(0, 1);
+        assert!(!is_satisfied::<Fr>(unsorted)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn padding() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+        let errors = StandardErrors::compute(&model, &x, &y).unwrap();
+        let circuit = |model: &LinearModel, standard_errors, feature_bounds| {
+            TrainingCircuit {
+                standard_errors,
+                feature_bounds,
+                ..TrainingCircuit::new(x.clone(), y.clone(), model.clone(), tolerance)
+            }
+            .padded(8)
+        };
+
+        // The padded circuit has the same shape as the padded circuit of the shape.
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(&model, None, None).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        let shape = ConstraintSystem::<Fr>::new_ref();
+        TrainingCircuit::shape(8, 2)
+            .padded(8)
+            .generate_constraints(shape.clone())?;
+        assert_eq!(cs.num_constraints(), shape.num_constraints());
+        assert_eq!(cs.num_witness_variables(), shape.num_witness_variables());
+
+        // The values of the padding rows are ignored, even if they are out of bounds.
+        let mut garbage = circuit(&model, Some(errors), Some(vec![(-1.5, 3.75), (-3., 2.)]));
+        garbage.x[6] = vec![100., f64::NAN];
+        garbage.y[7] = f64::INFINITY;
+        assert!(is_satisfied::<Fr>(garbage)?);
+
+        let wrong = LinearModel {
+            bias: model.bias + 0.01,
+            ..model.clone()
+        };
+        assert!(!is_satisfied::<Fr>(circuit(&wrong, None, None))?);
+
+        // There should be enough unmasked samples, e.g., an empty dataset would fit any model.
+        let empty = circuit(&wrong, None, None).with_mask(vec![false; 8]);
+        assert!(!is_satisfied::<Fr>(empty)?);
+        // The model interpolates the first 3 samples, so it also fits the first 2 samples, which
+        // are however fewer than the coefficients.
+        let interpolation = LinearModel::fit(&x[..3], &y[..3]).unwrap();
+        let few = |num_samples: usize| {
+            TrainingCircuit::new(
+                x[..3].to_vec(),
+                y[..3].to_vec(),
+                interpolation.clone(),
+                optimality_gap(&interpolation, &x[..3], &y[..3]),
+            )
+            .padded(8)
+            .with_mask((0..8).map(|i| i < num_samples).collect())
+        };
+        assert!(is_satisfied::<Fr>(few(3))?);
+        assert!(!is_satisfied::<Fr>(few(2))?);
+
+        Ok(())
+    }
+
+    /// The matrices and the assignments of a constraint system.
+    #[cfg(feature = "parallel")]
+    type Synthesized = (crate::r1cs::ConstraintMatrices<Fr>, Vec<Vec<Fr>>);
+
+    /// Synthesize `circuit` with `num_threads` threads.
+    #[cfg(feature = "parallel")]
+    fn synthesize(
+        num_threads: usize,
+        circuit: impl ConstraintSynthesizer<Fr> + Send,
+    ) -> Result<Synthesized, Box<dyn Error>> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()?;
+        Ok(pool.install(|| {
+            let cs = ConstraintSystem::new_ref();
+            circuit.generate_constraints(cs.clone())?;
+            cs.finalize();
+            assert!(cs.is_satisfied()?);
+            let matrices = cs.to_matrices().unwrap();
+            let cs = cs.into_inner().unwrap();
+            Ok::<_, SynthesisError>((
+                matrices,
+                vec![
+                    cs.instance_assignment,
+                    cs.witness_assignment,
+                    cs.committed_assignment,
+                ],
+            ))
+        })?)
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+
+        let inference = || InferenceCircuit::new(model.clone(), x.clone());
+        assert_eq!(synthesize(1, inference())?, synthesize(3, inference())?);
+
+        let training = |standard_errors, feature_bounds| TrainingCircuit {
+            standard_errors,
+            feature_bounds,
+            ..TrainingCircuit::new(x.clone(), y.clone(), model.clone(), tolerance)
+        };
+        assert_eq!(
+            synthesize(1, training(None, None))?,
+            synthesize(3, training(None, None))?
+        );
+        let errors = StandardErrors::compute(&model, &x, &y);
+        let bounds = Some(vec![(-4., 4.); 2]);
+        assert_eq!(
+            synthesize(1, training(errors.clone(), bounds.clone()))?,
+            synthesize(3, training(errors, bounds))?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn noisy_training() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+        let seed = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        let circuit = |model: &LinearModel, seed| NoisyTrainingCircuit {
+            training: TrainingCircuit::new(x.clone(), y.clone(), model.clone(), tolerance),
+            seed,
+            scale: 0.1,
+        };
+
+        // The noises are bounded, distinct, and roughly of unit variance.
+        let noise = noise::<Fr>(seed, 1000);
+        assert!(noise.iter().all(|z| z.abs() < 6.));
+        let variance = noise.iter().map(|z| z * z).sum::<f64>() / noise.len() as f64;
+        assert!((variance - 1.).abs() < 0.2);
+        assert_ne!(noise[0], noise[1]);
+
+        let released = circuit(&model, seed).released_model::<Fr>();
+        assert_ne!(released, model);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(&model, seed).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        let inputs = NoisyTrainingCircuit::public_inputs::<Fr>(
+            seed_commitment(seed),
+            0.1,
+            &released,
+            tolerance,
+        );
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], inputs);
+
+        // The released model is bound to the committed seed.
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(&model, seed + 1).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        assert_ne!(cs.borrow().unwrap().instance_assignment[1..], inputs);
+
+        // The private model should still be the least squares fit.
+        let mut wrong = model.clone();
+        wrong.bias += 0.01;
+        assert!(!is_satisfied::<Fr>(circuit(&wrong, seed))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn federated_averaging() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        // Each party fits the model to its own part of the dataset.
+        let parties = [(0..3, 0x1234), (2..5, 0x5678), (0..5, 0x9abc)];
+        let updates = parties
+            .iter()
+            .map(|(r, b)| (LinearModel::fit(&x[r.clone()], &y[r.clone()]).unwrap(), *b))
+            .collect::<Vec<_>>();
+        let weights = vec![3., 3., 5.];
+        let models = updates.iter().map(|(m, _)| m.clone()).collect::<Vec<_>>();
+        let average = FederatedAveragingCircuit::average(&models, &weights);
+        assert!((average.weights[0] - 2.).abs() < 0.1);
+        let commitments = updates
+            .iter()
+            .map(|(m, b)| model_commitment::<Fr>(m, *b))
+            .collect::<Vec<_>>();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        FederatedAveragingCircuit {
+            updates: updates.clone(),
+            weights: weights.clone(),
+        }
+        .generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        let inputs = FederatedAveragingCircuit::public_inputs(&commitments, &weights, &average);
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], inputs);
+
+        // The commitments hide the models, but bind them with the blindings.
+        assert_ne!(
+            model_commitment::<Fr>(&models[0], 0x1234),
+            model_commitment::<Fr>(&models[0], 0x1235)
+        );
+        let mut other = models[0].clone();
+        other.bias = f64::from_bits(other.bias.to_bits() + 1);
+        assert_ne!(model_commitment::<Fr>(&other, 0x1234), commitments[0]);
+
+        // An average with a replaced update cannot be proven against the original commitments.
+        let mut replaced = updates.clone();
+        replaced[1].0 = other;
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        FederatedAveragingCircuit {
+            updates: replaced,
+            weights: weights.clone(),
+        }
+        .generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        cs.borrow_mut().unwrap().instance_assignment[1..1 + commitments.len()]
+            .copy_from_slice(&commitments);
+        assert!(!cs.is_satisfied()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn incremental_statistics() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let empty = SufficientStatistics::new(2);
+        let statistics = empty.append(&x, &y);
+        let (g, h) = normal_equations(&x, &y);
+        assert_eq!(statistics, SufficientStatistics { g, h });
+        assert_eq!(statistics.num_samples(), 5.);
+        let tolerance = statistics.optimality_gap(&model);
+        assert_eq!(tolerance, optimality_gap(&model, &x, &y));
+
+        // Append the first 2 samples, and then the other 3 samples.
+        let first = StatisticsUpdateCircuit {
+            previous: empty.clone(),
+            previous_blinding: 0,
+            x: x[..2].to_vec(),
+            y: y[..2].to_vec(),
+            blinding: 0x1234,
+        };
+        let second = StatisticsUpdateCircuit {
+            previous: first.next(),
+            previous_blinding: 0x1234,
+            x: x[2..].to_vec(),
+            y: y[2..].to_vec(),
+            blinding: 0x5678,
+        };
+        assert_eq!(second.next(), statistics);
+        let commitments = [
+            empty.commitment::<Fr>(0),
+            first.next().commitment(0x1234),
+            statistics.commitment(0x5678),
+        ];
+        for (i, step) in [first, second].into_iter().enumerate() {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            step.generate_constraints(cs.clone())?;
+            assert!(cs.is_satisfied()?);
+            assert_eq!(
+                cs.borrow().unwrap().instance_assignment[1..],
+                StatisticsUpdateCircuit::public_inputs(commitments[i], commitments[i + 1])
+            );
+        }
+
+        let training = |model: &LinearModel| StatisticsTrainingCircuit {
+            statistics: statistics.clone(),
+            blinding: 0x5678,
+            model: model.clone(),
+            tolerance,
+        };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        training(&model).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            StatisticsTrainingCircuit::public_inputs(commitments[2], 5, &model, tolerance)
+        );
+        let mut wrong = model.clone();
+        wrong.bias += 0.01;
+        assert!(!is_satisfied::<Fr>(training(&wrong))?);
+
+        // Any model satisfies the normal equations of the empty dataset or of fewer samples than
+        // coefficients, which are rejected.
+        let arbitrary = LinearModel {
+            weights: vec![12., -34.],
+            bias: 56.,
+        };
+        for statistics in [empty.clone(), empty.append(&x[..2], &y[..2])] {
+            let circuit = StatisticsTrainingCircuit {
+                tolerance: statistics.optimality_gap(&arbitrary),
+                statistics,
+                blinding: 0,
+                model: arbitrary.clone(),
+            };
+            assert!(!is_satisfied::<Fr>(circuit)?);
+        }
+
+        // The samples cannot be appended to statistics other than the committed ones.
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        StatisticsUpdateCircuit {
+            previous: empty.append(&x[..1], &y[..1]),
+            previous_blinding: 0x1234,
+            x: x[2..].to_vec(),
+            y: y[2..].to_vec(),
+            blinding: 0x5678,
+        }
+        .generate_constraints(cs.clone())?;
+        cs.borrow_mut().unwrap().instance_assignment[1] = commitments[1];
+        assert!(!cs.is_satisfied()?);
+
+        // Malformed statistics or samples are rejected instead of panicking.
+        let mut truncated = statistics.clone();
+        truncated.g.pop();
+        let malformed = [
+            (truncated.clone(), x.clone()),
+            (statistics.clone(), vec![vec![0.; 3]; 5]),
+            (
+                SufficientStatistics {
+                    g: vec![],
+                    h: vec![],
+                },
+                x.clone(),
+            ),
+        ];
+        for (previous, x) in malformed {
+            let circuit = StatisticsUpdateCircuit {
+                previous,
+                previous_blinding: 0,
+                x,
+                y: y.clone(),
+                blinding: 0,
+            };
+            assert!(circuit
+                .generate_constraints(ConstraintSystem::<Fr>::new_ref())
+                .is_err());
+        }
+        let circuit = StatisticsTrainingCircuit {
+            statistics: truncated,
+            ..training(&model)
+        };
+        assert!(circuit
+            .generate_constraints(ConstraintSystem::<Fr>::new_ref())
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn fairness() -> Result<(), Box<dyn Error>> {
+        let (x, _) = dataset();
+        let model = LinearModel {
+            weights: vec![0.1, -0.3],
+            bias: 0.7,
+        };
+        let group = vec![true, false, true, false, false];
+        let gap = fairness_gap(&model, &x, &group);
+        let p = x.iter().map(|x| model.predict(x)).collect::<Vec<_>>();
+        let expected = ((p[0] + p[2]) / 2. - (p[1] + p[3] + p[4]) / 3.).abs();
+        assert!((gap - expected).abs() < 1e-12);
+
+        let circuit = |group: &[bool], threshold| FairnessCircuit {
+            x: x.clone(),
+            group: group.to_vec(),
+            model: model.clone(),
+            threshold,
+        };
+        let threshold = f64::from_bits(gap.to_bits() + 1);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(&group, threshold).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            FairnessCircuit::public_inputs(&model, threshold)
+        );
+
+        // The gap should be strictly less than the threshold.
+        assert!(!is_satisfied::<Fr>(circuit(&group, gap))?);
+        let other = [false, true, true, false, false];
+        assert!(!is_satisfied::<Fr>(circuit(&other, threshold))?);
+        assert!(is_satisfied::<Fr>(circuit(
+            &other,
+            f64::from_bits(fairness_gap(&model, &x, &other).to_bits() + 1)
+        ))?);
+        // Both groups should be non-empty.
+        assert!(!is_satisfied::<Fr>(circuit(&[false; 5], f64::MAX))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn outliers() -> Result<(), Box<dyn Error>> {
+        let (x, mut y) = dataset();
+        y[1] += 1.;
+        y[3] -= 2.;
+        let model = LinearModel::fit(&dataset().0, &dataset().1).unwrap();
+        let threshold = 0.5;
+        assert_eq!(outlier_count(&model, &x, &y, threshold), 2);
+        assert_eq!(outlier_count(&model, &x, &y, 1.5), 1);
+
+        let circuit = |y: &[f64], threshold, bound| OutlierCountCircuit {
+            x: x.clone(),
+            y: y.to_vec(),
+            model: model.clone(),
+            threshold,
+            bound,
+        };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(&y, threshold, 3).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            OutlierCountCircuit::public_inputs(&model, threshold, 3)
+        );
+
+        // The count should be strictly less than the bound.
+        assert!(!is_satisfied::<Fr>(circuit(&y, threshold, 2))?);
+        assert!(!is_satisfied::<Fr>(circuit(&y, threshold, 0))?);
+        assert!(is_satisfied::<Fr>(circuit(&y, 1.5, 2))?);
+        // A residual equal to the threshold is not an outlier.
+        let residual = (y[3] - model.predict(&x[3])).abs();
+        assert!(is_satisfied::<Fr>(circuit(&y, residual, 1))?);
+        assert!(!is_satisfied::<Fr>(circuit(
+            &y,
+            f64::from_bits(residual.to_bits() - 1),
+            1
+        ))?);
+        // NaN residuals are outliers.
+        let mut nan = dataset().1;
+        nan[0] = f64::NAN;
+        assert_eq!(outlier_count(&model, &x, &nan, f64::INFINITY), 1);
+        assert!(!is_satisfied::<Fr>(circuit(&nan, f64::INFINITY, 1))?);
+        assert!(is_satisfied::<Fr>(circuit(&nan, f64::INFINITY, 2))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn one_hot_features() -> Result<(), Box<dyn Error>> {
+        // Encode `category`, and move the hot entry to the next position if `tamper` is set.
+        let check = |category: u64, tamper: bool| -> Result<bool, Box<dyn Error>> {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let c = FpVar::new_witness(cs.clone(), || Ok(Fr::from(category)))?;
+            let features = one_hot(&c, 4)?;
+            if category < 4 {
+                let values = features
+                    .iter()
+                    .map(|f| f.to_f64())
+                    .collect::<Result<Vec<_>, _>>()?;
+                assert_eq!(values, one_hot_encode(category as usize, 4));
+            }
+            if tamper {
+                // The bits follow the witness of `category`.
+                let i = 1 + category as usize;
+                cs.borrow_mut().unwrap().witness_assignment[i..].swap(0, 1);
+            }
+            Ok(cs.is_satisfied()?)
+        };
+        for category in 0..4 {
+            assert!(check(category, false)?);
+        }
+        assert!(!check(1, true)?);
+        // Categories out of range have no valid encoding.
+        assert!(!check(4, false)?);
+        assert!(!check(u64::MAX, false)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn mean_imputation() -> Result<(), Box<dyn Error>> {
+        let values = [1.5, f64::NAN, -0.25, 7., 0.];
+        let missing = [false, true, false, true, false];
+        let expected = impute_mean(&values, &missing);
+        assert_eq!(expected, [1.5, 1.25 / 3., -0.25, 1.25 / 3., 0.]);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let v = F64Var::new_witness_vec(cs.clone(), &values)?;
+        let m = Vec::<Boolean<Fr>>::new_witness(cs.clone(), || Ok(missing.to_vec()))?;
+        let imputed = impute_mean_gadget(&v, &m)?;
+        assert!(cs.is_satisfied()?);
+        let imputed = imputed
+            .iter()
+            .map(|f| f.to_f64())
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(imputed, expected);
+
+        assert!(impute_mean(&values, &[true; 5]).iter().all(|v| v.is_nan()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn pseudoinverse() -> Result<(), Box<dyn Error>> {
+        // The second feature is twice the first one, so `XᵀX` is singular, and the pseudoinverse of
+        // the rank-1 matrix `X` is `Xᵀ / |X|²`.
+        let x = vec![vec![1., 2.], vec![2., 4.], vec![3., 6.]];
+        let p = (0..2)
+            .map(|j| x.iter().map(|row| row[j] / 70.).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(invert(&normal_equations(&x, &[0.; 3]).0), None);
+        let tolerance = pseudoinverse_gap(&x, &p);
+        assert!(tolerance < 1e-15);
+
+        // `P y` satisfies the normal equations `Xᵀ X β = Xᵀ y`.
+        let y = [1., 2., 3.5];
+        let beta = p
+            .iter()
+            .map(|p| p.iter().zip(&y).map(|(p, y)| p * y).sum::<f64>())
+            .collect::<Vec<_>>();
+        for j in 0..2 {
+            let gradient = x
+                .iter()
+                .zip(&y)
+                .map(|(x, y)| x[j] * (x[0] * beta[0] + x[1] * beta[1] - y))
+                .sum::<f64>();
+            assert!(gradient.abs() < 1e-12);
+        }
+
+        let prove = |p: &[Vec<f64>], tolerance: f64| -> Result<bool, Box<dyn Error>> {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a = x
+                .iter()
+                .map(|row| F64Var::new_witness_vec(cs.clone(), row))
+                .collect::<Result<Vec<_>, _>>()?;
+            let p = p
+                .iter()
+                .map(|row| F64Var::new_witness_vec(cs.clone(), row))
+                .collect::<Result<Vec<_>, _>>()?;
+            let tolerance = F64Var::new_input(cs.clone(), || Ok(tolerance))?;
+            enforce_pseudoinverse(&a, &p, &tolerance)?;
+            Ok(cs.is_satisfied()?)
+        };
+        assert!(prove(&p, tolerance)?);
+        // The tolerance is tight, and other matrices are rejected.
+        if tolerance > 0. {
+            assert!(!prove(&p, tolerance / 2.)?);
+        }
+        let doubled = p
+            .iter()
+            .map(|row| row.iter().map(|v| v * 2.).collect())
+            .collect::<Vec<_>>();
+        assert!(!prove(&doubled, 1e-9)?);
+        assert!(prove(&p[..1], 1.).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn qr_least_squares() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let x = x
+            .iter()
+            .map(|x| [&[1.][..], x].concat())
+            .collect::<Vec<_>>();
+        let (q, r) = qr_decompose(&x).unwrap();
+        let tolerance = qr_gap(&x, &q, &r);
+        assert!(tolerance < 1e-14);
+
+        // The back-substitution agrees with the solution of the normal equations.
+        let beta = super::qr_least_squares(&q, &r, &y);
+        let model = LinearModel::fit(&dataset().0, &y).unwrap();
+        for (b, c) in beta.iter().zip(model.coefficients()) {
+            assert!((b - c).abs() < 1e-9);
+        }
+
+        let prove = |q: &[Vec<f64>], r: &[Vec<f64>], tolerance: f64| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let witness = |m: &[Vec<f64>]| {
+                m.iter()
+                    .map(|row| F64Var::new_witness_vec(cs.clone(), row))
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            let (x, q, r) = (witness(&x)?, witness(q)?, witness(r)?);
+            let y = F64Var::new_witness_vec(cs.clone(), &y)?;
+            let tolerance = F64Var::new_input(cs.clone(), || Ok(tolerance))?;
+            let beta = qr_least_squares_gadget(&x, &y, &q, &r, &tolerance)?
+                .iter()
+                .map(|v| v.to_f64())
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok::<_, Box<dyn Error>>((cs.is_satisfied()?, beta))
+        };
+        assert_eq!(prove(&q, &r, tolerance)?, (true, beta));
+        // The tolerance is tight, and other factors are rejected.
+        if tolerance > 0. {
+            assert!(!prove(&q, &r, tolerance / 2.)?.0);
+        }
+        // Scaling `Q` by 2 and `R` by 1/2 preserves `QR = X` but not `QᵀQ = I`.
+        let scale = |m: &[Vec<f64>], s: f64| {
+            m.iter()
+                .map(|row| row.iter().map(|v| v * s).collect())
+                .collect::<Vec<_>>()
+        };
+        assert!(!prove(&scale(&q, 2.), &scale(&r, 0.5), 1e-9)?.0);
+        let mut singular = r.clone();
+        singular[2][2] = 0.;
+        assert!(!prove(&q, &singular, 1.)?.0);
+        assert!(prove(&q[..2], &r, 1.).is_err());
+
+        // Rank-deficient matrices cannot be decomposed.
+        assert!(qr_decompose(&[vec![1., 2.], vec![2., 4.], vec![3., 6.]]).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn cofactor_inverse_small() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let (g, h) = normal_equations(&x, &y);
+        assert_eq!(g.len(), 3);
+        let a = vec![vec![4., 7.], vec![2., 6.]];
+        assert_eq!(determinant(&a), Some(10.));
+        assert_eq!(
+            cofactor_inverse(&a),
+            Some(vec![vec![0.6, -0.7], vec![-0.2, 0.4]])
+        );
+
+        for a in [a, g.clone()] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a_var = a
+                .iter()
+                .map(|row| F64Var::new_witness_vec(cs.clone(), row))
+                .collect::<Result<Vec<_>, _>>()?;
+            let det = determinant_gadget(&a_var)?.to_f64()?;
+            let inverse = cofactor_inverse_gadget(&a_var)?
+                .iter()
+                .map(|row| row.iter().map(|v| v.to_f64()).collect())
+                .collect::<Result<Vec<Vec<_>>, _>>()?;
+            assert!(cs.is_satisfied()?);
+            assert_eq!(Some(det), determinant(&a));
+            assert_eq!(Some(inverse), cofactor_inverse(&a));
+        }
+
+        // The closed-form solution of the normal equations is close to the fitted model.
+        let inverse = cofactor_inverse(&g).unwrap();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        for (row, c) in inverse.iter().zip(model.coefficients()) {
+            let v = row.iter().zip(&h).map(|(a, b)| a * b).sum::<f64>();
+            assert!((v - c).abs() < 1e-9);
+        }
+
+        // Singular matrices are rejected.
+        let singular = [vec![1., 2.], vec![2., 4.]];
+        assert_eq!(cofactor_inverse(&singular), None);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let a_var = singular
+            .iter()
+            .map(|row| F64Var::new_witness_vec(cs.clone(), row))
+            .collect::<Result<Vec<_>, _>>()?;
+        cofactor_inverse_gadget(&a_var)?;
+        assert!(!cs.is_satisfied()?);
+
+        // Other shapes are not supported.
+        assert_eq!(determinant(&[vec![1.]]), None);
+        assert!(determinant_gadget::<Fr>(&[vec![], vec![]]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn huber() -> Result<(), Box<dyn Error>> {
+        let (x, mut y) = dataset();
+        // An outlier that pulls the least squares fit away from the others.
+        y[3] += 10.;
+        let delta = 0.5;
+        let model = LinearModel::fit_huber(&x, &y, delta).unwrap();
+        let tolerance = huber_optimality_gap(&model, &x, &y, delta);
+        assert!(tolerance < 1e-9);
+        assert!((model.weights[0] - 2.).abs() < 0.1);
+        assert!((model.weights[1] + 3.).abs() < 0.1);
+
+        let circuit = |model: &LinearModel, delta, tolerance| HuberCircuit {
+            x: x.clone(),
+            y: y.clone(),
+            model: model.clone(),
+            delta,
+            tolerance,
+        };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(&model, delta, tolerance).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        let inputs = HuberCircuit::public_inputs::<Fr>(&model, delta, tolerance);
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], inputs);
+
+        // The least squares fit is not optimal for the Huber loss.
+        let ols = LinearModel::fit(&x, &y).unwrap();
+        assert!(!is_satisfied::<Fr>(circuit(&ols, delta, 1e-3))?);
+        let mut perturbed = model.clone();
+        perturbed.bias += 1e-3;
+        assert!(!is_satisfied::<Fr>(circuit(&perturbed, delta, tolerance))?);
+        assert!(!is_satisfied::<Fr>(circuit(&model, 0., 1.))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn quantile() -> Result<(), Box<dyn Error>> {
+        // Three samples on the line `y = 2x + 1`, and one above and one below it, so the line is
+        // the median regression.
+        let x = [0., 1., 2., 3., 4.].map(|x| vec![x]).to_vec();
+        let y = vec![1., 4., 5., 6., 9.];
+        let model = LinearModel {
+            weights: vec![2.],
+            bias: 1.,
+        };
+        let (tau, epsilon) = (0.5, 1e-12);
+        let subgradients = quantile_subgradients(&model, &x, &y, tau, epsilon).unwrap();
+        let tolerance = quantile_optimality_gap(&x, &subgradients);
+        assert!(tolerance < 1e-12);
+
+        let circuit = |model: &LinearModel, tau, subgradients: &[f64], tolerance| QuantileCircuit {
+            x: x.clone(),
+            y: y.clone(),
+            model: model.clone(),
+            tau,
+            epsilon,
+            tolerance,
+            subgradients: subgradients.to_vec(),
+        };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit(&model, tau, &subgradients, tolerance).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        let inputs = QuantileCircuit::public_inputs::<Fr>(&model, tau, epsilon, tolerance);
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], inputs);
+
+        // The subgradient of a positive residual must be `tau`.
+        let mut invalid = subgradients.clone();
+        invalid[1] = 0.;
+        assert!(!is_satisfied::<Fr>(circuit(&model, tau, &invalid, 1.))?);
+
+        // The line is not the 0.9 quantile regression, even with the best subgradients, and the
+        // least squares fit has no zero residuals to balance the subgradient.
+        let tau = 0.9;
+        let subgradients = quantile_subgradients(&model, &x, &y, tau, epsilon).unwrap();
+        let gap = quantile_optimality_gap(&x, &subgradients);
+        assert!(gap > 0.1);
+        assert!(!is_satisfied::<Fr>(circuit(
+            &model,
+            tau,
+            &subgradients,
+            0.1
+        ))?);
+        assert!(is_satisfied::<Fr>(circuit(
+            &model,
+            tau,
+            &subgradients,
+            gap
+        ))?);
+        let ols = LinearModel::fit(&x, &y).unwrap();
+        assert!(quantile_subgradients(&ols, &x, &y, 0.5, epsilon).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn prove_inference_bn254() -> Result<(), Box<dyn Error>> {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let (x, _) = dataset();
+        let model = LinearModel {
+            weights: vec![0.1, -0.3],
+            bias: 0.7,
+        };
+
+        let (pk, vk) = Groth16::<Bn254>::setup(
+            InferenceCircuit::new(
+                LinearModel {
+                    weights: vec![0.; 2],
+                    bias: 0.,
+                },
+                vec![vec![0.; 2]; x.len()],
+            ),
+            rng,
+        )?;
+        let circuit = InferenceCircuit::new(model, x.clone());
+        let mut y = circuit.y.clone();
+        let proof = Groth16::<Bn254>::prove(&pk, circuit, rng)?;
+        let inputs = InferenceCircuit::public_inputs(&x, &y);
+        assert!(Groth16::<Bn254>::verify(&vk, &inputs, &proof)?);
+
+        y[0] += 1.;
+        let inputs = InferenceCircuit::public_inputs(&x, &y);
+        assert!(!Groth16::<Bn254>::verify(&vk, &inputs, &proof)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn prove_inference_prepared() -> Result<(), Box<dyn Error>> {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let (x, _) = dataset();
+        let shape = || InferenceCircuit::shape(x.len(), 2);
+
+        let (pk, vk) = Groth16::<Bn254>::setup(shape(), rng)?;
+        let ppk = Groth16::<Bn254>::prepare_proving_key(pk, shape())?;
+        for bias in [0.7, -1.5] {
+            let model = LinearModel {
+                weights: vec![0.1, -0.3],
+                bias,
+            };
+            let circuit = InferenceCircuit::new(model, x.clone());
+            let inputs = InferenceCircuit::public_inputs(&x, &circuit.y);
+            let proof = Groth16::<Bn254>::prove_prepared(&ppk, circuit, rng)?;
+            assert!(Groth16::<Bn254>::verify(&vk, &inputs, &proof)?);
+        }
+
+        Ok(())
+    }
+
+    fn is_satisfied<F: PrimeField>(
+        circuit: impl ConstraintSynthesizer<F>,
+    ) -> Result<bool, Box<dyn Error>> {
+        let cs = ConstraintSystem::<F>::new_ref();
+        circuit.generate_constraints(cs.clone())?;
+        Ok(cs.is_satisfied()?)
+    }
+
+    fn check_field<F: PrimeField>() -> Result<(), Box<dyn Error>> {
+        let (x, y) = dataset();
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+
+        assert!(is_satisfied::<F>(InferenceCircuit::new(
+            model.clone(),
+            x.clone()
+        ))?);
+        assert!(is_satisfied::<F>(TrainingCircuit::new(
+            x, y, model, tolerance
+        ))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn pasta() -> Result<(), Box<dyn Error>> {
+        check_field::<ark_pallas::Fr>()?;
+        check_field::<ark_vesta::Fr>()
+    }
+
+    #[test]
+    fn report() -> Result<(), Box<dyn Error>> {
+        let inference = InferenceCircuit::constraint_report::<Fr>(4, 2)?;
+        assert_eq!(inference.num_instance_variables, 4 * 3 * 3);
+        let larger = InferenceCircuit::constraint_report::<Fr>(8, 2)?;
+        assert!(larger.num_constraints > inference.num_constraints);
+
+        let training = TrainingCircuit::constraint_report::<Fr>(4, 2)?;
+        assert_eq!(training.num_instance_variables, 4 * 3);
+        assert!(training.num_lookups > 0);
+
+        let huber = HuberCircuit::constraint_report::<Fr>(4, 2)?;
+        assert_eq!(huber.num_instance_variables, 5 * 3);
+        let noisy = NoisyTrainingCircuit::constraint_report::<Fr>(4, 2)?;
+        assert_eq!(noisy.num_instance_variables, 1 + 5 * 3);
+        assert!(noisy.num_constraints > training.num_constraints);
+        let federated = FederatedAveragingCircuit::constraint_report::<Fr>(4, 2)?;
+        assert_eq!(federated.num_instance_variables, 4 + 4 * 3 + 3 * 3);
+        let update = StatisticsUpdateCircuit::constraint_report::<Fr>(4, 2)?;
+        assert_eq!(update.num_instance_variables, 2);
+        let fairness = FairnessCircuit::constraint_report::<Fr>(4, 2)?;
+        assert_eq!(fairness.num_instance_variables, 4 * 3);
+        let quantile = QuantileCircuit::constraint_report::<Fr>(4, 2)?;
+        assert_eq!(quantile.num_instance_variables, 6 * 3);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    fmt::{Display, Formatter},
+    ops::{Add, Sub},
+};
+
+use ark_ff::PrimeField;
+
+use crate::{
+    float::FloatVar,
+    groth16::{generate_commitment, LOOKUP_TABLE_BITS},
+    r1cs::{
+        ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal,
+        SynthesisError, SynthesisMode,
+    },
+    r1cs_std::prelude::AllocVar,
+};
+
+/// The size of (a part of) a constraint system, which determines the cost of proving.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConstraintReport {
+    /// The number of R1CS constraints.
+    pub num_constraints: usize,
+    /// The number of public inputs, excluding the constant 1.
+    pub num_instance_variables: usize,
+    /// The number of private witnesses, including hints.
+    pub num_witness_variables: usize,
+    /// The number of queries to the lookup table, each of which covers `LOOKUP_TABLE_BITS` bits.
+    pub num_lookups: usize,
+}
+
+impl ConstraintReport {
+    /// Report the current size of the constraint system `cs`.
+    pub fn of<F: PrimeField>(cs: &ConstraintSystemRef<F>) -> Self {
+        cs.borrow().map_or_else(Self::default, |cs| Self {
+            num_constraints: cs.num_constraints,
+            num_instance_variables: cs.num_instance_variables - 1,
+            num_witness_variables: cs.num_witness_variables,
+            num_lookups: cs.num_committed_variables,
+        })
+    }
+
+    /// Run `f` and report the size of the constraints and variables it adds to `cs`.
+    pub fn measure<F: PrimeField, R>(
+        cs: &ConstraintSystemRef<F>,
+        f: impl FnOnce() -> R,
+    ) -> (R, Self) {
+        let before = Self::of(cs);
+        let result = f();
+        (result, Self::of(cs) - before)
+    }
+
+    /// Synthesize `circuit` in setup mode and report its size as seen by the Groth16 prover, i.e.,
+    /// the constraints and witnesses of the lookup argument are included, while `num_lookups` only
+    /// counts the queries made by the circuit itself.
+    pub fn circuit<F: PrimeField>(
+        circuit: impl ConstraintSynthesizer<F>,
+    ) -> Result<Self, SynthesisError> {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_mode(SynthesisMode::Setup);
+
+        circuit.generate_constraints(cs.clone())?;
+        let num_lookups = cs.num_committed_variables();
+        generate_commitment(cs.clone(), |_| None)?;
+
+        Ok(Self {
+            num_lookups,
+            ..Self::of(&cs)
+        })
+    }
+
+    /// The number of bits queried to the lookup table.
+    pub fn num_lookup_bits(&self) -> usize {
+        self.num_lookups * LOOKUP_TABLE_BITS
+    }
+}
+
+impl Add for ConstraintReport {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            num_constraints: self.num_constraints + other.num_constraints,
+            num_instance_variables: self.num_instance_variables + other.num_instance_variables,
+            num_witness_variables: self.num_witness_variables + other.num_witness_variables,
+            num_lookups: self.num_lookups + other.num_lookups,
+        }
+    }
+}
+
+impl Sub for ConstraintReport {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            num_constraints: self.num_constraints - other.num_constraints,
+            num_instance_variables: self.num_instance_variables - other.num_instance_variables,
+            num_witness_variables: self.num_witness_variables - other.num_witness_variables,
+            num_lookups: self.num_lookups - other.num_lookups,
+        }
+    }
+}
+
+impl Display for ConstraintReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} constraints, {} public inputs, {} witnesses, {} lookups ({} bits)",
+            self.num_constraints,
+            self.num_instance_variables,
+            self.num_witness_variables,
+            self.num_lookups,
+            self.num_lookup_bits()
+        )
+    }
+}
+
+/// Report the size of each operation on `FloatVar<F, E, M>`, excluding the allocation of its
+/// operands, except for `new`, which is the allocation of a witness.
+pub fn float_gadgets<F: PrimeField, const E: usize, const M: usize>(
+) -> Result<Vec<(&'static str, ConstraintReport)>, SynthesisError> {
+    type Unary<T> = fn(&T) -> Result<T, SynthesisError>;
+    type Binary<T> = fn(&T, &T) -> Result<T, SynthesisError>;
+
+    let cs = ConstraintSystem::<F>::new_ref();
+    cs.set_mode(SynthesisMode::Setup);
+
+    let (x, new) = ConstraintReport::measure(&cs, || {
+        FloatVar::<F, E, M>::new_witness(cs.clone(), || Ok(1f64))
+    });
+    let (x, y) = (x?, FloatVar::new_witness(cs.clone(), || Ok(2f64))?);
+
+    let mut reports = vec![("new", new)];
+    for (name, op) in [
+        ("add", (|x, y| Ok(x + y)) as Binary<FloatVar<F, E, M>>),
+        ("sub", |x, y| Ok(x - y)),
+        ("mul", |x, y| Ok(x * y)),
+        ("div", |x, y| Ok(x / y)),
+        ("hypot", FloatVar::hypot),
+    ] {
+        reports.push((name, ConstraintReport::measure(&cs, || op(&x, &y)).1));
+    }
+    for (name, op) in [
+        ("lt", FloatVar::is_lt as fn(&_, &_) -> _),
+        ("le", FloatVar::is_le),
+        ("gt", FloatVar::is_gt),
+        ("ge", FloatVar::is_ge),
+    ] {
+        let (result, report) = ConstraintReport::measure(&cs, || op(&x, &y));
+        result.map(|_| reports.push((name, report)))?;
+    }
+    for (name, op) in [
+        ("sqrt", FloatVar::sqrt as Unary<FloatVar<F, E, M>>),
+        ("exp2", FloatVar::exp2),
+        ("log2", FloatVar::log2),
+        ("tanh", FloatVar::tanh),
+        ("trunc", FloatVar::trunc),
+        ("floor", FloatVar::floor),
+        ("ceil", FloatVar::ceil),
+        ("round", |x| Ok(FloatVar::round(x, 32)?.0)),
+    ] {
+        let (result, report) = ConstraintReport::measure(&cs, || op(&x));
+        result.map(|_| reports.push((name, report)))?;
+    }
+
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{f32::F32Var, f64::F64Var, r1cs_std::prelude::EqGadget};
+    use ark_bls12_381::Fr;
+
+    struct AddCircuit;
+
+    impl ConstraintSynthesizer<Fr> for AddCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let a = F64Var::new_input(cs.clone(), || Ok(0.1))?;
+            let b = F64Var::new_witness(cs.clone(), || Ok(0.2))?;
+            let c = F64Var::new_input(cs.clone(), || Ok(0.1 + 0.2))?;
+            (a + b).enforce_equal(&c)
+        }
+    }
+
+    #[test]
+    fn gadgets() -> Result<(), SynthesisError> {
+        let f32_reports = float_gadgets::<Fr, 8, 23>()?;
+        let reports = float_gadgets::<Fr, 11, 52>()?;
+        assert_eq!(reports[0].0, "new");
+        for reports in [&f32_reports, &reports] {
+            assert!(reports.iter().all(|(_, r)| r.num_instance_variables == 0));
+            assert!(reports.iter().all(|(_, r)| r.num_constraints > 0));
+        }
+        assert!(f32_reports
+            .iter()
+            .map(|(name, _)| name)
+            .eq(reports.iter().map(|(name, _)| name)));
+
+        // Measuring the same gadget in prove mode gives the same report.
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = F32Var::new_witness(cs.clone(), || Ok(0.1f32))?;
+        let y = F32Var::new_witness(cs.clone(), || Ok(0.2f32))?;
+        let (_, report) = ConstraintReport::measure(&cs, || x * y);
+        assert_eq!(report, float_gadgets::<Fr, 8, 23>()?[3].1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn circuit() -> Result<(), SynthesisError> {
+        let report = ConstraintReport::circuit(AddCircuit)?;
+        assert_eq!(report.num_instance_variables, 6);
+        assert!(report.num_lookups > 0);
+        assert!(report.num_constraints > float_gadgets::<Fr, 11, 52>()?[1].1.num_constraints);
+
+        Ok(())
+    }
+}
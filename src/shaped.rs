@@ -0,0 +1,360 @@
+//! Regression circuits whose numbers of samples `N` and features `D` are part of their types, so
+//! that the keys generated for one shape cannot be used with a circuit of another shape.
+//!
+//! The keys of a circuit only fit the constraint system of the same shape, and using them with
+//! another dataset fails late (and obscurely) when proving. With `Shaped`, the shape is fixed once
+//! by the type, e.g., `Shaped<TrainingCircuit, 100, 3>`, which is checked against the data when
+//! the circuit is created by `Shaped::new`, and the keys returned by `Shaped::setup` carry the
+//! same type, so `Shaped::prove` only accepts the keys of the same circuit and shape:
+//!
+//! ```ignore
+//! type Circuit = Shaped<TrainingCircuit, 100, 3>;
+//! let (pk, vk) = Circuit::setup::<Bls12_381, _>(rng)?;
+//! let proof = Circuit::new(TrainingCircuit::new(x, y, model, tolerance))?.prove(&pk, rng)?;
+//! ```
+//!
+//! Only the shape created by `Shape::shape` is supported, e.g., a `TrainingCircuit` without
+//! standard errors, feature bounds, or a mask, since the optional parts change the circuit.
+
+use std::marker::PhantomData;
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use rand::{CryptoRng, RngCore};
+
+use crate::{
+    groth16::{CircuitSpecificSetupSNARK, Groth16, Proof, ProvingKey, VerifyingKey, SNARK},
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+    regression::{
+        CoefficientBoundsCircuit, CoefficientSignCircuit, DatasetSizeCircuit, FairnessCircuit,
+        HuberCircuit, InferenceCircuit, LinearModel, NoisyTrainingCircuit, OutlierCountCircuit,
+        QuantileCircuit, RSquaredCircuit, SelectiveDisclosureCircuit, StatisticsUpdateCircuit,
+        TrainingCircuit,
+    },
+    report::ConstraintReport,
+    Error,
+};
+
+/// A circuit over a dataset of samples with the same number of features.
+pub trait Shape: Sized {
+    /// Create a circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys.
+    fn shape(num_samples: usize, num_features: usize) -> Self;
+
+    /// Check whether the circuit has the same constraint system as
+    /// `Self::shape(num_samples, num_features)`.
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool;
+
+    /// Report the size of the circuit with `num_samples` samples of `num_features` features.
+    fn constraint_report<F: PrimeField>(
+        num_samples: usize,
+        num_features: usize,
+    ) -> Result<ConstraintReport, SynthesisError>
+    where
+        Self: ConstraintSynthesizer<F>,
+    {
+        ConstraintReport::circuit::<F>(Self::shape(num_samples, num_features))
+    }
+}
+
+/// Check whether `x` consists of `num_samples` rows of `num_features` features, and `model` has
+/// `num_features` weights.
+fn is_dataset(
+    x: &[Vec<f64>],
+    model: &LinearModel,
+    num_samples: usize,
+    num_features: usize,
+) -> bool {
+    x.len() == num_samples
+        && x.iter().all(|x| x.len() == num_features)
+        && model.weights.len() == num_features
+}
+
+impl Shape for InferenceCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        is_dataset(&self.x, &self.model, num_samples, num_features)
+            && self.y.len() == num_samples
+            && self.tolerance.is_none()
+    }
+}
+
+impl Shape for TrainingCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        is_dataset(&self.x, &self.model, num_samples, num_features)
+            && self.y.len() == num_samples
+            && self.standard_errors.is_none()
+            && self.feature_bounds.is_none()
+            && self.mask.is_none()
+    }
+}
+
+impl Shape for NoisyTrainingCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        self.training.has_shape(num_samples, num_features)
+    }
+}
+
+impl Shape for CoefficientBoundsCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        self.training.has_shape(num_samples, num_features) && self.bounds.len() == num_features + 1
+    }
+}
+
+impl Shape for CoefficientSignCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        self.training.has_shape(num_samples, num_features)
+    }
+}
+
+impl Shape for SelectiveDisclosureCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        self.training.has_shape(num_samples, num_features)
+            && self.revealed.len() == num_features + 1
+    }
+}
+
+impl Shape for DatasetSizeCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        self.training.has_shape(num_samples, num_features)
+    }
+}
+
+impl Shape for HuberCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        is_dataset(&self.x, &self.model, num_samples, num_features) && self.y.len() == num_samples
+    }
+}
+
+impl Shape for RSquaredCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        is_dataset(&self.x, &self.model, num_samples, num_features) && self.y.len() == num_samples
+    }
+}
+
+impl Shape for OutlierCountCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        is_dataset(&self.x, &self.model, num_samples, num_features) && self.y.len() == num_samples
+    }
+}
+
+impl Shape for QuantileCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        is_dataset(&self.x, &self.model, num_samples, num_features)
+            && self.y.len() == num_samples
+            && self.subgradients.len() == num_samples
+    }
+}
+
+impl Shape for FairnessCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        is_dataset(&self.x, &self.model, num_samples, num_features)
+            && self.group.len() == num_samples
+    }
+}
+
+impl Shape for StatisticsUpdateCircuit {
+    fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self::shape(num_samples, num_features)
+    }
+
+    fn has_shape(&self, num_samples: usize, num_features: usize) -> bool {
+        let n = num_features + 1;
+        self.x.len() == num_samples
+            && self.x.iter().all(|x| x.len() == num_features)
+            && self.y.len() == num_samples
+            && self.previous.h.len() == n
+            && self.previous.g.len() == n
+            && self.previous.g.iter().all(|g| g.len() == n)
+    }
+}
+
+/// The circuit `C` with `N` samples of `D` features.
+pub struct Shaped<C, const N: usize, const D: usize>(C);
+
+/// The key `K` (e.g., `ProvingKey`) of the circuit `C` with `N` samples of `D` features.
+pub struct ShapedKey<K, C, const N: usize, const D: usize> {
+    pub key: K,
+    _circuit: PhantomData<fn() -> C>,
+}
+
+impl<K, C, const N: usize, const D: usize> ShapedKey<K, C, N, D> {
+    /// Wrap `key`, e.g., loaded by `groth16::io::load`, which should be generated for the same
+    /// circuit and shape.
+    pub fn new(key: K) -> Self {
+        Self {
+            key,
+            _circuit: PhantomData,
+        }
+    }
+}
+
+impl<C: Shape, const N: usize, const D: usize> Shaped<C, N, D> {
+    /// Wrap `circuit`, or return an error if it does not have `N` samples of `D` features.
+    pub fn new(circuit: C) -> Result<Self, Error> {
+        if !circuit.has_shape(N, D) {
+            return Err(Error::Data(format!(
+                "the circuit does not have {N} samples of {D} features"
+            )));
+        }
+        Ok(Self(circuit))
+    }
+
+    /// Create the circuit whose values are all 0, e.g., for generating the keys.
+    pub fn shape() -> Self {
+        const {
+            assert!(
+                N > 0 && D > 0,
+                "a dataset has at least one sample and one feature"
+            )
+        };
+        Self(C::shape(N, D))
+    }
+
+    /// Unwrap the circuit.
+    pub fn into_inner(self) -> C {
+        self.0
+    }
+
+    /// Report the size of the circuit.
+    pub fn constraint_report<F: PrimeField>() -> Result<ConstraintReport, SynthesisError>
+    where
+        C: ConstraintSynthesizer<F>,
+    {
+        ConstraintReport::circuit::<F>(Self::shape())
+    }
+
+    /// Generate the proving and verifying keys of the circuit.
+    #[allow(clippy::type_complexity)]
+    pub fn setup<E: Pairing, R: RngCore + CryptoRng>(
+        rng: &mut R,
+    ) -> Result<
+        (
+            ShapedKey<ProvingKey<E>, C, N, D>,
+            ShapedKey<VerifyingKey<E>, C, N, D>,
+        ),
+        SynthesisError,
+    >
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let (pk, vk) = Groth16::<E>::setup(Self::shape(), rng)?;
+        Ok((ShapedKey::new(pk), ShapedKey::new(vk)))
+    }
+
+    /// Prove the circuit with the proving key `pk` of the same circuit and shape.
+    pub fn prove<E: Pairing, R: RngCore + CryptoRng>(
+        self,
+        pk: &ShapedKey<ProvingKey<E>, C, N, D>,
+        rng: &mut R,
+    ) -> Result<Proof<E>, SynthesisError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Groth16::<E>::prove(&pk.key, self, rng)
+    }
+
+    /// Verify `proof` of the circuit with the verifying key `vk` of the same circuit and shape,
+    /// where `inputs` are computed by the `public_inputs` of the circuit.
+    pub fn verify<E: Pairing>(
+        vk: &ShapedKey<VerifyingKey<E>, C, N, D>,
+        inputs: &[E::ScalarField],
+        proof: &Proof<E>,
+    ) -> Result<bool, SynthesisError>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Groth16::<E>::verify(&vk.key, inputs, proof)
+    }
+}
+
+impl<F: PrimeField, C: ConstraintSynthesizer<F>, const N: usize, const D: usize>
+    ConstraintSynthesizer<F> for Shaped<C, N, D>
+{
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        self.0.generate_constraints(cs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regression::optimality_gap;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+    use rand::{RngCore, SeedableRng};
+
+    #[test]
+    fn training() -> Result<(), Box<dyn std::error::Error>> {
+        type Circuit = Shaped<TrainingCircuit, 4, 1>;
+        let x = vec![vec![0.5], vec![-1.5], vec![3.75], vec![2.25]];
+        let y = vec![1.5, -2., 8.5, 5.75];
+        let model = LinearModel::fit(&x, &y).unwrap();
+        let tolerance = optimality_gap(&model, &x, &y);
+        let circuit =
+            |x: Vec<Vec<f64>>, y: Vec<f64>| TrainingCircuit::new(x, y, model.clone(), tolerance);
+
+        assert!(Circuit::new(circuit(x[..3].to_vec(), y[..3].to_vec())).is_err());
+        assert!(Circuit::new(circuit(vec![vec![0., 1.]; 4], y.clone())).is_err());
+        assert!(Shaped::<TrainingCircuit, 4, 2>::new(circuit(x.clone(), y.clone())).is_err());
+
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let (pk, vk) = Circuit::setup::<Bls12_381, _>(rng)?;
+        let proof = Circuit::new(circuit(x, y))?.prove(&pk, rng)?;
+        let inputs = TrainingCircuit::public_inputs::<Fr>(&model, tolerance);
+        assert!(Circuit::verify(&vk, &inputs, &proof)?);
+        assert_eq!(
+            Circuit::constraint_report::<Fr>()?,
+            TrainingCircuit::constraint_report::<Fr>(4, 1)?
+        );
+
+        Ok(())
+    }
+}
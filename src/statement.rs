@@ -0,0 +1,221 @@
+//! Regression circuits whose public inputs start with the statement of the proof, i.e., the
+//! fingerprint of the verifying key and the numbers of samples `N` and features `D`.
+//!
+//! A proof checked against the verifying key of another circuit or shape is simply rejected, which
+//! cannot be told apart from an invalid proof. With `Bound`, the prover sends the `Statement` with
+//! the proof, and `Bound::verify` first compares it with the verifying key and the expected shape,
+//! reporting a mismatch as an error, and then verifies the proof with the statement prepended to
+//! the public inputs, so that the prover cannot lie about the statement:
+//!
+//! ```ignore
+//! let pk = Groth16::<Bls12_381>::generate_random_parameters_with_reduction(
+//!     Bound::<InferenceCircuit>::shape(100, 3),
+//!     rng,
+//! )?;
+//! let statement = Statement::new(&pk.vk, 100, 3);
+//! let proof = Groth16::<Bls12_381>::prove(&pk, Bound::new(circuit, statement)?, rng)?;
+//! Bound::<InferenceCircuit>::verify(&vk, &statement, 100, 3, &inputs, &proof)?;
+//! ```
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::{
+    groth16::{Groth16, Proof, VerifyingKey, SNARK},
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+    r1cs_std::{
+        alloc::AllocVar,
+        fields::fp::FpVar,
+        prelude::{EqGadget, FieldVar},
+    },
+    shaped::Shape,
+    Error,
+};
+
+/// The statement of a proof, which is bound into its public inputs by `Bound`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Statement {
+    /// The fingerprint of the verifying key, see `VerifyingKey::fingerprint`.
+    pub fingerprint: [u8; 32],
+    /// The number of samples of the dataset.
+    pub num_samples: usize,
+    /// The number of features of each sample.
+    pub num_features: usize,
+}
+
+impl Statement {
+    /// Create the statement of proofs for the verifying key `vk` of a circuit with `num_samples`
+    /// samples of `num_features` features.
+    pub fn new<E: Pairing>(vk: &VerifyingKey<E>, num_samples: usize, num_features: usize) -> Self {
+        Self {
+            fingerprint: vk.fingerprint(),
+            num_samples,
+            num_features,
+        }
+    }
+
+    /// Compute the public inputs allocated by `Bound`, i.e., the fingerprint reduced modulo the
+    /// order of `F`, followed by the numbers of samples and features.
+    pub fn public_inputs<F: PrimeField>(&self) -> [F; 3] {
+        [
+            F::from_le_bytes_mod_order(&self.fingerprint),
+            F::from(self.num_samples as u64),
+            F::from(self.num_features as u64),
+        ]
+    }
+}
+
+/// The circuit `C` whose public inputs are preceded by a `Statement`, and which enforces that the
+/// numbers of samples and features in the statement are those of the circuit.
+pub struct Bound<C> {
+    circuit: C,
+    fingerprint: [u8; 32],
+    num_samples: usize,
+    num_features: usize,
+}
+
+impl<C: Shape> Bound<C> {
+    /// Bind `statement` to `circuit`, or return an error if the circuit does not have the numbers
+    /// of samples and features in the statement.
+    pub fn new(circuit: C, statement: Statement) -> Result<Self, Error> {
+        let Statement {
+            fingerprint,
+            num_samples,
+            num_features,
+        } = statement;
+        if !circuit.has_shape(num_samples, num_features) {
+            return Err(Error::Data(format!(
+                "the circuit does not have {num_samples} samples of {num_features} features"
+            )));
+        }
+        Ok(Self {
+            circuit,
+            fingerprint,
+            num_samples,
+            num_features,
+        })
+    }
+
+    /// Create the circuit with `num_samples` samples of `num_features` features, whose values are
+    /// all 0, e.g., for generating the keys, whose fingerprint is not known yet.
+    pub fn shape(num_samples: usize, num_features: usize) -> Self {
+        Self {
+            circuit: C::shape(num_samples, num_features),
+            fingerprint: [0; 32],
+            num_samples,
+            num_features,
+        }
+    }
+
+    /// Verify `proof` of the circuit with `num_samples` samples of `num_features` features with
+    /// the verifying key `vk`, where `statement` is sent by the prover, and `inputs` are computed
+    /// by the `public_inputs` of the circuit.
+    /// Return an error if the statement is for another verifying key or shape, instead of
+    /// rejecting the proof.
+    pub fn verify<E: Pairing>(
+        vk: &VerifyingKey<E>,
+        statement: &Statement,
+        num_samples: usize,
+        num_features: usize,
+        inputs: &[E::ScalarField],
+        proof: &Proof<E>,
+    ) -> Result<bool, Error>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        if statement.fingerprint != vk.fingerprint() {
+            return Err(SynthesisError::MismatchedVerifyingKey.into());
+        }
+        if (statement.num_samples, statement.num_features) != (num_samples, num_features) {
+            return Err(Error::Data(format!(
+                "the proof is for {} samples of {} features instead of {num_samples} samples of \
+                 {num_features} features",
+                statement.num_samples, statement.num_features
+            )));
+        }
+        let inputs = [&statement.public_inputs()[..], inputs].concat();
+        Ok(Groth16::<E>::verify(vk, &inputs, proof)?)
+    }
+}
+
+impl<F: PrimeField, C: ConstraintSynthesizer<F>> ConstraintSynthesizer<F> for Bound<C> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let statement = Statement {
+            fingerprint: self.fingerprint,
+            num_samples: self.num_samples,
+            num_features: self.num_features,
+        };
+        let [fingerprint, num_samples, num_features] = statement.public_inputs::<F>();
+        // The fingerprint depends on the keys of the circuit, so it cannot be constrained, and is
+        // only bound to the proof as a public input.
+        let _fingerprint = FpVar::new_input(cs.clone(), || Ok(fingerprint))?;
+        FpVar::new_input(cs.clone(), || Ok(num_samples))?
+            .enforce_equal(&FpVar::constant(num_samples))?;
+        FpVar::new_input(cs.clone(), || Ok(num_features))?
+            .enforce_equal(&FpVar::constant(num_features))?;
+        self.circuit.generate_constraints(cs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regression::{InferenceCircuit, LinearModel};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+    use rand::{RngCore, SeedableRng};
+
+    #[test]
+    fn inference() -> Result<(), Box<dyn std::error::Error>> {
+        type Circuit = Bound<InferenceCircuit>;
+        let model = LinearModel {
+            weights: vec![1.5],
+            bias: -0.25,
+        };
+        let x = vec![vec![0.5], vec![-1.5]];
+        let circuit = InferenceCircuit::new(model, x.clone());
+        let inputs = InferenceCircuit::public_inputs::<Fr>(&x, &circuit.y);
+
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let pk = Groth16::<Bls12_381>::generate_random_parameters_with_reduction(
+            Circuit::shape(2, 1),
+            rng,
+        )?;
+        let statement = Statement::new(&pk.vk, 2, 1);
+        assert!(Circuit::new(Circuit::shape(2, 1).circuit, Statement::new(&pk.vk, 3, 1)).is_err());
+        let proof = Groth16::<Bls12_381>::prove(&pk, Circuit::new(circuit, statement)?, rng)?;
+        assert!(Circuit::verify(&pk.vk, &statement, 2, 1, &inputs, &proof)?);
+
+        // A mismatched key or shape is reported as an error instead of a rejected proof.
+        let other = Groth16::<Bls12_381>::generate_random_parameters_with_reduction(
+            Circuit::shape(2, 1),
+            rng,
+        )?;
+        assert!(matches!(
+            Circuit::verify(&other.vk, &statement, 2, 1, &inputs, &proof),
+            Err(Error::Synthesis(SynthesisError::MismatchedVerifyingKey))
+        ));
+        assert!(matches!(
+            Circuit::verify(&pk.vk, &statement, 2, 2, &inputs, &proof),
+            Err(Error::Data(_))
+        ));
+
+        // The statement is bound to the proof.
+        for forged in [
+            Statement {
+                num_samples: 3,
+                ..statement
+            },
+            Statement {
+                fingerprint: [1; 32],
+                ..statement
+            },
+        ] {
+            let inputs = [&forged.public_inputs()[..], &inputs].concat();
+            assert!(!Groth16::<Bls12_381>::verify(&pk.vk, &inputs, &proof)?);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//! Stochastic rounding to fixed-point numbers, where each number is rounded up with probability
+//! equal to the fraction it loses, so that the rounding is unbiased in expectation, as used by
+//! low-precision training schemes. The random directions are derived from a committed seed, so
+//! that the prover cannot choose them after seeing the numbers.
+//!
+//! The `i`-th hash of the seed is `hash(&[seed, i, 0])`, whose lowest `MODULUS_BIT_SIZE - 1` bits
+//! are split into chunks of `STOCHASTIC_ROUNDING_BITS` bits, each of which is used for one
+//! rounding. The hashes have 3 inputs, so they are different from the noises derived from the
+//! same seed by `regression::noise`.
+
+use ark_ff::{BigInteger, PrimeField};
+use num::{BigInt, BigRational, FromPrimitive, One, ToPrimitive};
+
+use crate::{
+    float::FloatVar,
+    hash::{hash, hash_gadget},
+    powers::pow2,
+    r1cs::SynthesisError,
+    r1cs_std::{
+        alloc::AllocVar,
+        boolean::Boolean,
+        fields::{fp::FpVar, FieldVar},
+        prelude::EqGadget,
+        R1CSVar, ToBitsGadget,
+    },
+    traits::{BitDecompose, ToBigUint},
+};
+
+/// The number of random bits of each rounding, whose probability of rounding up is therefore a
+/// multiple of `2^-16` that is at most `2^-16` less than the lost fraction.
+pub const STOCHASTIC_ROUNDING_BITS: usize = 16;
+
+/// The number of roundings whose random bits are taken from each hash of the seed.
+fn chunks_per_hash<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize - 1) / STOCHASTIC_ROUNDING_BITS
+}
+
+/// Stochastic rounding outside the circuit, which makes the same random choices as
+/// `StochasticRoundingVar` with the same seed, e.g., for computing the public results.
+pub struct StochasticRounding<F: PrimeField> {
+    seed: F,
+    index: u64,
+    chunks: Vec<u64>,
+}
+
+impl<F: PrimeField> StochasticRounding<F> {
+    pub fn new(seed: u128) -> Self {
+        Self {
+            seed: F::from(seed),
+            index: 0,
+            chunks: vec![],
+        }
+    }
+
+    /// The random integer in `[0, 2^STOCHASTIC_ROUNDING_BITS)` of the next rounding.
+    fn next_random(&mut self) -> u64 {
+        if self.chunks.is_empty() {
+            let bits = hash(&[self.seed, F::from(self.index), F::zero()])
+                .into_bigint()
+                .to_bits_le();
+            self.index += 1;
+            // The chunks are consumed from the end, so they are stored in reverse order.
+            self.chunks = bits
+                .chunks(STOCHASTIC_ROUNDING_BITS)
+                .take(chunks_per_hash::<F>())
+                .map(|chunk| chunk.iter().rev().fold(0, |t, &b| t << 1 | b as u64))
+                .rev()
+                .collect();
+        }
+        self.chunks.pop().unwrap()
+    }
+
+    /// Round `x * 2^scale_bits` to an integer stochastically, see `StochasticRoundingVar::to_fixed`.
+    /// Return `None` if `x` is NaN or infinity.
+    pub fn to_fixed(&mut self, x: f64, scale_bits: usize) -> Option<i128> {
+        let r = self.next_random();
+        // `t` is `|x| * 2^(scale_bits + STOCHASTIC_ROUNDING_BITS)` truncated to an integer.
+        let t = (BigRational::from_f64(x.abs())?
+            * (BigInt::one() << (scale_bits + STOCHASTIC_ROUNDING_BITS)))
+            .to_integer();
+        let fraction = (&t % (1u64 << STOCHASTIC_ROUNDING_BITS)).to_u64()?;
+        let n = (t >> STOCHASTIC_ROUNDING_BITS) + BigInt::from((r < fraction) as u8);
+        let n = if x.is_sign_negative() { -n } else { n };
+        n.to_i128()
+    }
+}
+
+/// Stochastic rounding in the circuit, whose random choices are derived from a private seed that
+/// is bound to a public commitment.
+pub struct StochasticRoundingVar<F: PrimeField> {
+    seed: FpVar<F>,
+    index: u64,
+    chunks: Vec<FpVar<F>>,
+}
+
+impl<F: PrimeField> StochasticRoundingVar<F> {
+    /// Use the private `seed`, and enforce that its commitment is `commitment`, i.e., the hash of
+    /// `seed` computed by `regression::seed_commitment`.
+    pub fn new(seed: FpVar<F>, commitment: &FpVar<F>) -> Result<Self, SynthesisError> {
+        hash_gadget(std::slice::from_ref(&seed))?.enforce_equal(commitment)?;
+        Ok(Self {
+            seed,
+            index: 0,
+            chunks: vec![],
+        })
+    }
+
+    /// The random integer in `[0, 2^STOCHASTIC_ROUNDING_BITS)` of the next rounding.
+    fn next_random(&mut self) -> Result<FpVar<F>, SynthesisError> {
+        if self.chunks.is_empty() {
+            let index = FpVar::constant(F::from(self.index));
+            let h = hash_gadget(&[self.seed.clone(), index, FpVar::zero()])?;
+            self.index += 1;
+            // The decomposition is unique, so that the prover cannot choose between the bits of `h`
+            // and those of `h + p`.
+            let bits = h.to_bits_le()?;
+            self.chunks = bits
+                .chunks(STOCHASTIC_ROUNDING_BITS)
+                .take(chunks_per_hash::<F>())
+                .map(Boolean::le_bits_to_fp_var)
+                .rev()
+                .collect::<Result<_, _>>()?;
+        }
+        Ok(self.chunks.pop().unwrap())
+    }
+
+    /// Round `x * 2^scale_bits` to an integer stochastically, i.e., round its absolute value up
+    /// with probability equal to its fractional part (with `STOCHASTIC_ROUNDING_BITS` bits of
+    /// precision) and down otherwise, in the same representation as `FloatVar::to_fixed`.
+    /// The integer is enforced to be in the range `(-2^bits, 2^bits)`, and the circuit is
+    /// unsatisfiable if the number is NaN, infinity, or out of the range.
+    pub fn to_fixed<const E: usize, const M: usize>(
+        &mut self,
+        x: &FloatVar<F, E, M>,
+        scale_bits: usize,
+        bits: usize,
+    ) -> Result<FpVar<F>, SynthesisError> {
+        let r = self.next_random()?;
+        // `t = trunc(|x| * 2^(scale_bits + STOCHASTIC_ROUNDING_BITS))` is `n * 2^B + fraction`,
+        // where `B = STOCHASTIC_ROUNDING_BITS`, and `n` is `|x| * 2^scale_bits` rounded down.
+        let t = x.abs().to_fixed(
+            scale_bits + STOCHASTIC_ROUNDING_BITS,
+            bits + STOCHASTIC_ROUNDING_BITS,
+        )?;
+        let fraction = FpVar::new_hint(t.cs(), || {
+            Ok(F::from(
+                t.to_biguint()? % (1u64 << STOCHASTIC_ROUNDING_BITS),
+            ))
+        })?;
+        fraction.enforce_bit_length(STOCHASTIC_ROUNDING_BITS)?;
+        // `n` is the integer `(t - fraction) / 2^B` because the range of `n + round_up` is checked
+        // below, which leaves no other choice of `fraction` in `[0, 2^B)`.
+        let n = (&t - &fraction) * pow2::<F>(STOCHASTIC_ROUNDING_BITS).inverse().unwrap();
+        // Round up if `r < fraction`, i.e., with probability `fraction / 2^B`.
+        let round_up = (&fraction - &r - FpVar::one()).is_positive(STOCHASTIC_ROUNDING_BITS)?;
+        let n = n + FpVar::from(round_up);
+        n.enforce_bit_length(bits)?;
+
+        x.sign.select(&n.negate()?, &n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::{
+        f64::F64Var, r1cs::ConstraintSystem, regression::seed_commitment, utils::field_to_signed,
+    };
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn to_fixed() -> Result<(), Box<dyn Error>> {
+        let seed = thread_rng().gen();
+        let rng = &mut thread_rng();
+        // More values than the chunks of a hash, so that several hashes are used.
+        let values = (0..40)
+            .map(|_| rng.gen_range(-100f64..100.))
+            .chain([0., -0., 2.5, -2.5, 1e-300])
+            .collect::<Vec<_>>();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let commitment = FpVar::new_input(cs.clone(), || Ok(seed_commitment::<Fr>(seed)))?;
+        let seed_var = FpVar::new_witness(cs.clone(), || Ok(Fr::from(seed)))?;
+        let mut rounding = StochasticRoundingVar::new(seed_var, &commitment)?;
+        let mut native = StochasticRounding::<Fr>::new(seed);
+        for &v in &values {
+            let x = F64Var::new_witness(cs.clone(), || Ok(v))?;
+            let n = field_to_signed(rounding.to_fixed(&x, 4, 16)?.value()?).unwrap();
+            assert_eq!(Some(n), native.to_fixed(v, 4), "{v}");
+            // `n` is `v * 16` rounded either down or up.
+            assert!((n as f64 - v * 16.).abs() < 1., "{v} {n}");
+        }
+        assert!(cs.is_satisfied()?);
+
+        // The seed is bound to the commitment.
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let commitment = FpVar::new_input(cs.clone(), || Ok(seed_commitment::<Fr>(seed)))?;
+        let other = FpVar::new_witness(cs.clone(), || Ok(Fr::from(seed ^ 1)))?;
+        StochasticRoundingVar::new(other, &commitment)?;
+        assert!(!cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn unbiased() {
+        // `0.3` is rounded up to 1 with probability about 0.3.
+        let mut rounding = StochasticRounding::<Fr>::new(42);
+        let count = 10000;
+        let sum = (0..count)
+            .map(|_| rounding.to_fixed(0.3, 0).unwrap())
+            .sum::<i128>();
+        assert!((sum as f64 / count as f64 - 0.3).abs() < 0.02, "{sum}");
+    }
+}
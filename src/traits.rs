@@ -1,9 +1,9 @@
 use ark_ff::{BigInteger, PrimeField};
-use ark_std::One;
-use num::{BigUint, Integer};
+use num::BigUint;
 
 use crate::{
     groth16::LOOKUP_TABLE_BITS,
+    powers::pow2,
     r1cs::SynthesisError,
     r1cs_std::{
         fields::fp::FpVar,
@@ -12,6 +12,19 @@ use crate::{
     },
 };
 
+pub trait ToBigUint {
+    /// Convert the value of `self` to `BigUint`.
+    /// Return `SynthesisError::AssignmentMissing` if the value is unavailable, rather than falling
+    /// back to 0, so that hints computed from it are never silently wrong.
+    fn to_biguint(&self) -> Result<BigUint, SynthesisError>;
+}
+
+impl<F: PrimeField> ToBigUint for FpVar<F> {
+    fn to_biguint(&self) -> Result<BigUint, SynthesisError> {
+        Ok(self.value()?.into())
+    }
+}
+
 pub trait BitDecompose<F: PrimeField>
 where
     Self: Sized,
@@ -23,10 +36,9 @@ where
     /// Return the result and the original sign.
     fn abs(&self, length: usize) -> Result<(Self, Boolean<F>), SynthesisError>;
 
-    /// Determine whether `self` is positive.
-    fn is_positive(&self, length: usize) -> Result<Boolean<F>, SynthesisError> {
-        Ok(self.abs(length)?.1)
-    }
+    /// Determine whether `self` is non-negative, where `-2^length <= self < 2^length`.
+    /// This is cheaper than `abs` when only the sign is needed.
+    fn is_positive(&self, length: usize) -> Result<Boolean<F>, SynthesisError>;
 
     /// Compute the maximum of `self` and `other`,
     /// where the difference between `self` and `other` has at most `diff_length` bits.
@@ -35,6 +47,9 @@ where
     /// Compute the minimum of `self` and `other`,
     /// where the difference between `self` and `other` has at most `diff_length` bits.
     fn min(&self, other: &Self, diff_length: usize) -> Result<Self, SynthesisError>;
+
+    /// Compute `2^self` and enforce that `self` has at most `length` bits.
+    fn pow2(&self, length: usize) -> Result<Self, SynthesisError>;
 }
 
 impl<F: PrimeField> BitDecompose<F> for FpVar<F> {
@@ -47,27 +62,42 @@ impl<F: PrimeField> BitDecompose<F> for FpVar<F> {
         //     assert!(x < BigUint::one() << length);
         // }
 
-        let extended_length = length.next_multiple_of(&LOOKUP_TABLE_BITS);
+        let extended_length = length.next_multiple_of(LOOKUP_TABLE_BITS);
         let num_chunks = extended_length / LOOKUP_TABLE_BITS;
+        // The chunks are recomposed (and shifted) in `F`, which is only sound if the result cannot
+        // wrap around the modulus.
+        assert!(
+            extended_length < F::MODULUS_BIT_SIZE as usize,
+            "cannot enforce bit length {length} in a field of {} bits",
+            F::MODULUS_BIT_SIZE
+        );
 
-        let mut chunks = self
-            .value()
-            .unwrap_or_default()
-            .into_bigint()
-            .to_bits_le()
-            .chunks(LOOKUP_TABLE_BITS)
-            .take(num_chunks)
-            .map(|chunk| F::from_bigint(F::BigInt::from_bits_le(chunk)).unwrap())
-            .collect::<Vec<_>>();
-
-        if extended_length != length {
-            chunks[num_chunks - 1] *= F::from(BigUint::one() << (extended_length - length));
-        }
+        let chunks = self.value().map(|v| {
+            let mut chunks = v
+                .into_bigint()
+                .to_bits_le()
+                .chunks(LOOKUP_TABLE_BITS)
+                .take(num_chunks)
+                .map(|chunk| F::from_bigint(F::BigInt::from_bits_le(chunk)).unwrap())
+                .collect::<Vec<_>>();
+            if extended_length != length {
+                chunks[num_chunks - 1] *= pow2::<F>(extended_length - length);
+            }
+            chunks
+        });
 
+        // The chunks of a missing value are allocated without assignments, which fails unless
+        // their values are not needed (e.g., in the setup mode).
         let chunks = if cs.is_none() {
-            Vec::<FpVar<_>>::new_constant(cs, chunks)?
+            Vec::<FpVar<_>>::new_constant(cs, chunks?)?
         } else {
-            Vec::new_committed(cs, || Ok(chunks))?
+            (0..num_chunks)
+                .map(|i| {
+                    FpVar::new_committed(cs.clone(), || {
+                        chunks.as_ref().map(|c| c[i]).map_err(|&e| e)
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?
         };
 
         if num_chunks > 1 {
@@ -75,17 +105,14 @@ impl<F: PrimeField> BitDecompose<F> for FpVar<F> {
             for (i, v) in chunks.iter().enumerate() {
                 accumulated = &accumulated
                     + v * if i == num_chunks - 1 {
-                        F::from(
-                            BigUint::one() << (i * LOOKUP_TABLE_BITS - (extended_length - length)),
-                        )
+                        pow2::<F>(i * LOOKUP_TABLE_BITS - (extended_length - length))
                     } else {
-                        F::from(BigUint::one() << (i * LOOKUP_TABLE_BITS))
+                        pow2::<F>(i * LOOKUP_TABLE_BITS)
                     };
             }
             accumulated.enforce_equal(self)?;
         } else {
-            chunks[0]
-                .enforce_equal(&(self * F::from(BigUint::one() << (extended_length - length))))?;
+            chunks[0].enforce_equal(&(self * pow2::<F>(extended_length - length)))?;
         }
 
         Ok(())
@@ -105,6 +132,21 @@ impl<F: PrimeField> BitDecompose<F> for FpVar<F> {
         Ok((abs, is_positive))
     }
 
+    fn is_positive(&self, length: usize) -> Result<Boolean<F>, SynthesisError> {
+        let is_positive = Boolean::new_hint(self.cs(), || {
+            self.value()
+                .map(|v| v.into_bigint() < F::MODULUS_MINUS_ONE_DIV_TWO)
+        })?;
+
+        // `self + 2^length` is in `[0, 2^(length + 1))`, whose MSB is 1 if and only if `self` is non-negative.
+        // Instead of decomposing all the bits, we enforce that the remaining `length` bits are indeed
+        // `self + (1 - is_positive) * 2^length`, which is linear in `is_positive` and hence needs no select.
+        // Unlike `abs`, this also determines `is_positive` uniquely when `self` is 0.
+        (self + FpVar::from(is_positive.not()) * pow2::<F>(length)).enforce_bit_length(length)?;
+
+        Ok(is_positive)
+    }
+
     fn max(&self, other: &Self, diff_length: usize) -> Result<Self, SynthesisError> {
         (self - other).is_positive(diff_length)?.select(self, other)
     }
@@ -112,4 +154,103 @@ impl<F: PrimeField> BitDecompose<F> for FpVar<F> {
     fn min(&self, other: &Self, diff_length: usize) -> Result<Self, SynthesisError> {
         (self - other).is_positive(diff_length)?.select(other, self)
     }
+
+    fn pow2(&self, length: usize) -> Result<FpVar<F>, SynthesisError> {
+        let cs = self.cs();
+
+        // The bits are allocated one by one, since allocating a `Vec` evaluates the hint to learn the
+        // length, which fails in setup mode.
+        let bits = self.value().map(|v| v.into_bigint().to_bits_le());
+        let bits = (0..length)
+            .map(|i| Boolean::new_hint(cs.clone(), || Ok(bits.as_ref().map_err(|e| *e)?[i])))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Write `self` as `sum_i b_i * 2^i`, so that `2^self = prod_i 2^(b_i * 2^i)`, where each factor is
+        // selected by `b_i` from the precomputed powers `1` and `2^(2^i)`.
+        // This costs one constraint for each bit, one multiplication for each bit except the first one,
+        // and one constraint for the recomposition, all of which are independent of the size of `2^self`.
+        let mut recomposed = FpVar::zero();
+        let mut power = FpVar::one();
+        for (i, b) in bits.iter().enumerate() {
+            let b = FpVar::from(b.clone());
+            recomposed += &b * pow2::<F>(i);
+            power *= b * (pow2::<F>(1usize << i) - F::one()) + F::one();
+        }
+        // The recomposition also guarantees that `self` has at most `length` bits.
+        recomposed.enforce_equal(self)?;
+
+        Ok(power)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::ConstraintSystem;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn is_positive() -> Result<(), SynthesisError> {
+        for (x, expected) in [
+            (0i64, Some(true)),
+            (1, Some(true)),
+            (4095, Some(true)),
+            (-1, Some(false)),
+            (-4096, Some(false)),
+            (4096, None),
+            (-4097, None),
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let v = FpVar::new_witness(cs.clone(), || Ok(Fr::from(x)))?.is_positive(12)?;
+            // The range checks are only enforced by the lookup argument when proving, so we check the
+            // committed chunks against the table here.
+            let in_table = cs
+                .borrow()
+                .unwrap()
+                .committed_assignment
+                .iter()
+                .all(|&c| c < Fr::from(1u64 << LOOKUP_TABLE_BITS));
+            assert_eq!(cs.is_satisfied()? && in_table, expected.is_some());
+            if let Some(expected) = expected {
+                assert_eq!(v.value()?, expected);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn missing_value() -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_mode(crate::r1cs::SynthesisMode::Setup);
+        let x = FpVar::new_witness(cs.clone(), || Ok(Fr::from(42u8)))?;
+        assert_eq!(x.to_biguint(), Err(SynthesisError::AssignmentMissing));
+        // Hints are only computed when proving, so the range check is still synthesized.
+        x.enforce_bit_length(12)?;
+        assert!(cs.num_constraints() > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn pow2() -> Result<(), SynthesisError> {
+        for (x, length, satisfied) in [
+            (0u64, 6, true),
+            (55, 6, true),
+            (109, 7, true),
+            (64, 6, false),
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let v = FpVar::new_witness(cs.clone(), || Ok(Fr::from(x)))?.pow2(length)?;
+            if satisfied {
+                assert_eq!(v.value()?, ark_ff::Field::pow(&Fr::from(2u8), [x]));
+            }
+            assert_eq!(cs.is_satisfied()?, satisfied);
+        }
+
+        // The keys are generated without the value of the exponent.
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_mode(crate::r1cs::SynthesisMode::Setup);
+        let _ = FpVar::new_witness(cs.clone(), || Ok(Fr::from(55u8)))?.pow2(6)?;
+        assert!(cs.num_constraints() > 0);
+        Ok(())
+    }
 }
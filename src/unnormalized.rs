@@ -0,0 +1,360 @@
+use std::ops::{AddAssign, SubAssign};
+
+use ark_ff::PrimeField;
+
+use crate::{
+    float::FloatVar,
+    r1cs::{ConstraintSystemRef, SynthesisError},
+    r1cs_std::alloc::AllocVar,
+};
+
+/// `UnnormalizedFloatVar` accumulates a sum of `FloatVar`s, where the intermediate sums are neither
+/// normalized nor rounded.
+///
+/// Adding a number to the accumulator is free, and `finalize` aligns all the numbers to the largest
+/// one in an extended-precision window of `2M + 3` bits, adds them up, and then normalizes and rounds
+/// the sum only once. Therefore, the sum of `n` numbers costs `O(n)` alignments but only one
+/// normalization, which is much cheaper than `n - 1` additions of `FloatVar`s, and is also more
+/// accurate, as the numbers in the window are summed exactly.
+///
+/// Note that the result may differ from that of adding the numbers one by one, which rounds after
+/// each addition. Numbers smaller than the largest one by more than `2M + 3` bits (in terms of the
+/// exponent) are ignored, whose effect on the result is at most `n * 2^-(2M + 3)` relative to the
+/// largest number. Abnormal numbers are handled in the same way as consecutive additions, e.g., the
+/// sum is NaN if there are both `+Inf` and `-Inf`.
+#[derive(Clone)]
+pub struct UnnormalizedFloatVar<F: PrimeField, const E: usize, const M: usize> {
+    terms: Vec<FloatVar<F, E, M>>,
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> UnnormalizedFloatVar<F, E, M> {
+    /// Create an empty accumulator, whose sum is `+0`.
+    pub fn new() -> Self {
+        Self { terms: vec![] }
+    }
+
+    /// The number of numbers added so far.
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Check whether no number has been added.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Normalize and round the sum.
+    pub fn finalize(&self) -> Result<FloatVar<F, E, M>, SynthesisError> {
+        FloatVar::sum(&self.terms)
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> Default for UnnormalizedFloatVar<F, E, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> From<FloatVar<F, E, M>>
+    for UnnormalizedFloatVar<F, E, M>
+{
+    fn from(x: FloatVar<F, E, M>) -> Self {
+        Self { terms: vec![x] }
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> FromIterator<FloatVar<F, E, M>>
+    for UnnormalizedFloatVar<F, E, M>
+{
+    fn from_iter<I: IntoIterator<Item = FloatVar<F, E, M>>>(iter: I) -> Self {
+        Self {
+            terms: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> Extend<FloatVar<F, E, M>>
+    for UnnormalizedFloatVar<F, E, M>
+{
+    fn extend<I: IntoIterator<Item = FloatVar<F, E, M>>>(&mut self, iter: I) {
+        self.terms.extend(iter)
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> AddAssign<FloatVar<F, E, M>>
+    for UnnormalizedFloatVar<F, E, M>
+{
+    fn add_assign(&mut self, other: FloatVar<F, E, M>) {
+        self.terms.push(other)
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> AddAssign<&FloatVar<F, E, M>>
+    for UnnormalizedFloatVar<F, E, M>
+{
+    fn add_assign(&mut self, other: &FloatVar<F, E, M>) {
+        self.terms.push(other.clone())
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> SubAssign<FloatVar<F, E, M>>
+    for UnnormalizedFloatVar<F, E, M>
+{
+    fn sub_assign(&mut self, other: FloatVar<F, E, M>) {
+        self.terms.push(-other)
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> SubAssign<&FloatVar<F, E, M>>
+    for UnnormalizedFloatVar<F, E, M>
+{
+    fn sub_assign(&mut self, other: &FloatVar<F, E, M>) {
+        self.terms.push(-other)
+    }
+}
+
+impl<F: PrimeField, const E: usize, const M: usize> AddAssign<&UnnormalizedFloatVar<F, E, M>>
+    for UnnormalizedFloatVar<F, E, M>
+{
+    fn add_assign(&mut self, other: &UnnormalizedFloatVar<F, E, M>) {
+        self.terms.extend_from_slice(&other.terms)
+    }
+}
+
+/// Sum `values` natively by Kahan's compensated summation, which is mirrored by `kahan_sum_gadget`
+/// operation by operation, so that the results are bit-for-bit identical.
+///
+/// The rounding error of each addition is tracked in a compensation term and subtracted from the
+/// next number, so the error of the sum is `O(eps)` rather than `O(n eps)`, and the result hardly
+/// depends on the order of `values`. However, it is not exactly order-independent, so the prover
+/// should sum the numbers in the same order as the circuit.
+pub fn kahan_sum<U: num::Float>(values: &[U]) -> U {
+    let (mut sum, mut compensation) = (U::zero(), U::zero());
+    for &v in values {
+        let y = v - compensation;
+        let t = sum + y;
+        compensation = (t - sum) - y;
+        sum = t;
+    }
+    sum
+}
+
+/// Sum `terms` by Kahan's compensated summation in the circuit. See `kahan_sum`.
+///
+/// Unlike `UnnormalizedFloatVar`, every step is a correctly rounded `FloatVar` operation, so the
+/// result is the same as that of `kahan_sum`, at the cost of 4 additions per term.
+pub fn kahan_sum_gadget<F: PrimeField, const E: usize, const M: usize>(
+    terms: &[FloatVar<F, E, M>],
+) -> Result<FloatVar<F, E, M>, SynthesisError> {
+    let zero = FloatVar::new_constant(ConstraintSystemRef::None, 0.)?;
+    let (mut sum, mut compensation) = (zero.clone(), zero);
+    for v in terms {
+        let y = v - &compensation;
+        let t = &sum + &y;
+        compensation = (&t - &sum) - &y;
+        sum = t;
+    }
+    Ok(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        f64::{F64Var, UnnormalizedF64Var},
+        r1cs::ConstraintSystem,
+        r1cs_std::{alloc::AllocVar, R1CSVar},
+        report::ConstraintReport,
+    };
+    use ark_bls12_381::Fr;
+    use num::{traits::float::FloatCore, BigInt, Integer, One, Signed, ToPrimitive, Zero};
+    use rand::{thread_rng, Rng};
+
+    /// Sum `values` natively in the same way as `UnnormalizedF64Var`, i.e., round the exact sum of the
+    /// finite numbers in the window below the largest one.
+    fn sum(values: &[f64]) -> f64 {
+        let has_inf = values.contains(&f64::INFINITY);
+        let has_neg_inf = values.contains(&f64::NEG_INFINITY);
+        if values.iter().any(|v| v.is_nan()) || (has_inf && has_neg_inf) {
+            return f64::NAN;
+        }
+        if has_inf || has_neg_inf {
+            return if has_inf {
+                f64::INFINITY
+            } else {
+                f64::NEG_INFINITY
+            };
+        }
+
+        let exponent = |v: f64| {
+            let (m, e, _) = v.integer_decode();
+            m.ilog2() as i32 + e as i32
+        };
+        let max = values
+            .iter()
+            .filter(|v| !v.is_zero())
+            .map(|&v| exponent(v))
+            .max();
+        // The exact sum in units of `2^-1075`, as `integer_decode` returns an exponent of -1075 for subnormal numbers.
+        let mut exact = BigInt::zero();
+        for &v in values.iter().filter(|v| !v.is_zero()) {
+            if max.unwrap() - exponent(v) < F64Var::<Fr>::SUM_WINDOW as i32 {
+                let (m, e, s) = v.integer_decode();
+                exact += (BigInt::from(m) << (e + 1075) as usize) * s;
+            }
+        }
+        if exact.is_zero() {
+            return if !values.is_empty() && values.iter().all(|v| v.is_sign_negative()) {
+                -0.
+            } else {
+                0.
+            };
+        }
+
+        let mut q = exact.abs();
+        let shift = (q.bits() as usize).saturating_sub(53);
+        if shift > 0 {
+            let r = &q - ((&q >> shift) << shift);
+            let half = BigInt::one() << (shift - 1);
+            q >>= shift;
+            if r > half || (r == half && q.is_odd()) {
+                q += 1;
+            }
+        }
+        // Compute `q * 2^(shift - 1075)` step by step, which is exact unless it overflows.
+        let mut v = q.to_f64().unwrap() * exact.signum().to_f64().unwrap();
+        let mut e = shift as i32 - 1075;
+        while e != 0 {
+            let step = e.clamp(-1000, 1000);
+            v *= 2f64.powi(step);
+            e -= step;
+        }
+        v
+    }
+
+    fn check(values: &[f64]) -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let mut acc = UnnormalizedF64Var::new();
+        for &v in values {
+            acc += F64Var::new_witness(cs.clone(), || Ok(v))?;
+        }
+        let result = f64::from_bits(acc.finalize()?.value()?.to_u64().unwrap());
+        let expected = sum(values);
+        assert!(cs.is_satisfied()?, "{values:?}");
+        if expected.is_nan() {
+            assert!(result.is_nan(), "{values:?}: {result}");
+        } else {
+            assert_eq!(
+                result.to_bits(),
+                expected.to_bits(),
+                "{values:?}: {result} != {expected}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn special() -> Result<(), SynthesisError> {
+        let tiny = f64::from_bits(1);
+        for values in [
+            vec![],
+            vec![0.1],
+            vec![0.1, 0.2, 0.3],
+            vec![1., -1., 2f64.powi(-60)],
+            vec![1., 2f64.powi(-200)],
+            vec![1., 2f64.powi(-53)],
+            vec![1., 2f64.powi(-53), 2f64.powi(-100)],
+            vec![f64::MAX, f64::MAX, -f64::MAX],
+            vec![f64::MAX, f64::MAX],
+            vec![-f64::MAX, -f64::MAX],
+            vec![tiny, tiny, -tiny, f64::MIN_POSITIVE],
+            vec![f64::MIN_POSITIVE, -tiny],
+            vec![-0., -0.],
+            vec![0., -0.],
+            vec![1., -1.],
+            vec![f64::INFINITY, 1.],
+            vec![f64::NEG_INFINITY, f64::MAX, f64::MAX],
+            vec![f64::INFINITY, f64::NEG_INFINITY],
+            vec![f64::INFINITY, f64::INFINITY],
+            vec![f64::NAN, 1.],
+        ] {
+            check(&values)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn random() -> Result<(), SynthesisError> {
+        let rng = &mut thread_rng();
+        for _ in 0..20 {
+            // Numbers of similar magnitudes, where most of them are in the window.
+            let scale = 2f64.powi(rng.gen_range(-1000..1000));
+            let values = (0..rng.gen_range(1..50))
+                .map(|_| rng.gen_range(-1. ..1.) * scale * 2f64.powi(rng.gen_range(-60..60)))
+                .collect::<Vec<_>>();
+            check(&values)?;
+            // Arbitrary numbers.
+            let values = (0..rng.gen_range(1..10))
+                .map(|_| f64::from_bits(rng.gen()))
+                .collect::<Vec<_>>();
+            check(&values)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn kahan() -> Result<(), SynthesisError> {
+        let rng = &mut thread_rng();
+        let mut inputs = (0..10)
+            .map(|_| {
+                (0..rng.gen_range(0..20))
+                    .map(|_| rng.gen_range(-1. ..1.) * 2f64.powi(rng.gen_range(-60..60)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        inputs.push([vec![1.], vec![1e-16; 10]].concat());
+        inputs.push(vec![f64::INFINITY, 1.]);
+        inputs.push(vec![-0., -0.]);
+        for values in inputs {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let terms = F64Var::new_witness_vec(cs.clone(), &values)?;
+            let result = kahan_sum_gadget(&terms)?.to_f64().unwrap();
+            assert!(cs.is_satisfied()?);
+            let expected = kahan_sum(&values);
+            assert!(
+                result.to_bits() == expected.to_bits() || (result.is_nan() && expected.is_nan()),
+                "{values:?}: {result} != {expected}"
+            );
+        }
+        // The compensation recovers the small numbers that consecutive additions lose.
+        let values = [vec![1.], vec![1e-16; 10]].concat();
+        assert_eq!(kahan_sum(&values), 1. + 1e-15);
+        assert_eq!(values.iter().sum::<f64>(), 1.);
+        Ok(())
+    }
+
+    #[test]
+    fn constraints() -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let values = (0..16)
+            .map(|i| F64Var::new_witness(cs.clone(), || Ok(i as f64 * 0.1)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (result, deferred) = ConstraintReport::measure(&cs, || {
+            values
+                .iter()
+                .cloned()
+                .collect::<UnnormalizedF64Var<_>>()
+                .finalize()
+        });
+        result?;
+        let (_, consecutive) = ConstraintReport::measure(&cs, || {
+            values[1..].iter().fold(values[0].clone(), |acc, v| acc + v)
+        });
+
+        assert!(cs.is_satisfied()?);
+        assert!(deferred.num_constraints * 2 < consecutive.num_constraints);
+        assert!(deferred.num_lookups * 2 < consecutive.num_lookups);
+        Ok(())
+    }
+}
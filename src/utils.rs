@@ -0,0 +1,46 @@
+//! Conversions between field elements and signed integers outside the circuit, where a negative
+//! integer `-n` is represented by the field element `-n`, e.g., the unbiased exponent of a
+//! `FloatVar` or the result of `FloatVar::to_fixed`.
+
+use ark_ff::PrimeField;
+use num::{bigint::Sign, BigInt, BigUint, ToPrimitive};
+
+/// Map a signed integer to `F`.
+pub fn signed_to_field<F: PrimeField>(value: i128) -> F {
+    let magnitude = F::from(value.unsigned_abs());
+    if value < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Map a field element back to a signed integer, which is the inverse of `signed_to_field`.
+/// Same as the gadgets, elements less than `(p - 1) / 2` are non-negative, and the others are
+/// negative. Return `None` if the integer does not fit in `i128`.
+pub fn field_to_signed<F: PrimeField>(value: F) -> Option<i128> {
+    let (sign, magnitude) = if value.into_bigint() < F::MODULUS_MINUS_ONE_DIV_TWO {
+        (Sign::Plus, value)
+    } else {
+        (Sign::Minus, -value)
+    };
+    let magnitude: BigUint = magnitude.into();
+    BigInt::from_biguint(sign, magnitude).to_i128()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn signed() {
+        for v in [0, 1, -1, 1 << 100, -(1 << 100), i128::MAX, i128::MIN] {
+            let f = signed_to_field::<Fr>(v);
+            assert_eq!(f, Fr::from(v));
+            assert_eq!(field_to_signed(f), Some(v));
+        }
+        assert_eq!(field_to_signed(Fr::from(u128::MAX)), None);
+        assert_eq!(field_to_signed(-Fr::from(u128::MAX)), None);
+    }
+}
@@ -0,0 +1,160 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalDeserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    f32::F32Var,
+    f64::F64Var,
+    groth16::{io, prepare_verifying_key, Groth16, Proof, VerifyingKey},
+};
+
+/// The size of a compressed scalar field element in bytes, which is the same for BLS12-381 and
+/// BN254.
+const FIELD_SIZE: usize = 32;
+
+fn encode<F: PrimeField>(inputs: &[F]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(inputs.len() * FIELD_SIZE);
+    for x in inputs {
+        x.serialize_compressed(&mut bytes).unwrap();
+    }
+    bytes
+}
+
+/// Encode the public inputs of a `binary64` number allocated by `F64Var::new_input` as the
+/// concatenation of the compressed field elements, which can be passed to [`verify`] and
+/// [`verify_bn254`].
+#[wasm_bindgen(js_name = encodeF64Input)]
+pub fn encode_f64_input(value: f64) -> Vec<u8> {
+    // The encoding of small integers does not depend on the scalar field.
+    encode(&F64Var::<ark_bls12_381::Fr>::verifier_input(value))
+}
+
+/// Encode the public inputs of a `binary32` number allocated by `F32Var::new_input` as the
+/// concatenation of the compressed field elements, which can be passed to [`verify`] and
+/// [`verify_bn254`].
+#[wasm_bindgen(js_name = encodeF32Input)]
+pub fn encode_f32_input(value: f32) -> Vec<u8> {
+    encode(&F32Var::<ark_bls12_381::Fr>::verifier_input(value))
+}
+
+fn verify_with<E: Pairing>(vk: &[u8], proof: &[u8], inputs: &[u8]) -> Result<bool, JsError> {
+    if !inputs.len().is_multiple_of(FIELD_SIZE) {
+        return Err(JsError::new("Malformed public inputs"));
+    }
+    let vk: VerifyingKey<E> = io::read(vk)?;
+    let proof: Proof<E> = io::read(proof)?;
+    let inputs = inputs
+        .chunks(FIELD_SIZE)
+        .map(E::ScalarField::deserialize_compressed)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Groth16::<E>::verify_proof(
+        &prepare_verifying_key(&vk),
+        &proof,
+        &inputs,
+    )?)
+}
+
+fn encode_f64_inputs(inputs: &[f64]) -> Vec<u8> {
+    inputs.iter().flat_map(|&x| encode_f64_input(x)).collect()
+}
+
+/// Verify `proof` over BLS12-381 against the verifying key `vk`, both in the format written by
+/// [`crate::groth16::io::write`], with respect to `inputs`, the concatenation of compressed
+/// field elements, e.g., the outputs of [`encode_f64_input`] and [`encode_f32_input`].
+#[wasm_bindgen]
+pub fn verify(vk: &[u8], proof: &[u8], inputs: &[u8]) -> Result<bool, JsError> {
+    verify_with::<Bls12_381>(vk, proof, inputs)
+}
+
+/// Verify `proof` over BLS12-381 against the verifying key `vk`, where the public inputs of the
+/// circuit are exactly the `binary64` numbers in `inputs`, allocated by `F64Var::new_input` in order.
+#[wasm_bindgen(js_name = verifyF64)]
+pub fn verify_f64(vk: &[u8], proof: &[u8], inputs: &[f64]) -> Result<bool, JsError> {
+    verify(vk, proof, &encode_f64_inputs(inputs))
+}
+
+/// The same as [`verify`], but for proofs over BN254.
+#[wasm_bindgen(js_name = verifyBn254)]
+pub fn verify_bn254(vk: &[u8], proof: &[u8], inputs: &[u8]) -> Result<bool, JsError> {
+    verify_with::<Bn254>(vk, proof, inputs)
+}
+
+/// The same as [`verify_f64`], but for proofs over BN254.
+#[wasm_bindgen(js_name = verifyF64Bn254)]
+pub fn verify_f64_bn254(vk: &[u8], proof: &[u8], inputs: &[f64]) -> Result<bool, JsError> {
+    verify_bn254(vk, proof, &encode_f64_inputs(inputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+        r1cs_std::prelude::{AllocVar, EqGadget},
+    };
+    use ark_std::test_rng;
+
+    struct SubCircuit {
+        a: f64,
+        b: f64,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for SubCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let a = F64Var::new_input(cs.clone(), || Ok(self.a))?;
+            let b = F64Var::new_witness(cs.clone(), || Ok(self.b))?;
+            let c = F64Var::new_input(cs.clone(), || Ok(self.a - self.b))?;
+            (a - b).enforce_equal(&c)
+        }
+    }
+
+    /// Generate the serialized verifying key and proof of `SubCircuit { a: 1e10, b: 3.25 }`.
+    fn serialized<E: Pairing>() -> Result<(Vec<u8>, Vec<u8>), SynthesisError> {
+        let rng = &mut test_rng();
+
+        let pk = Groth16::<E>::generate_random_parameters_with_reduction(
+            SubCircuit { a: 0., b: 0. },
+            rng,
+        )?;
+        let proof = Groth16::<E>::create_random_proof_with_reduction(
+            SubCircuit { a: 1e10, b: 3.25 },
+            &pk,
+            rng,
+        )?;
+
+        let mut vk = vec![];
+        io::write(&pk.vk, &mut vk).unwrap();
+        let mut serialized_proof = vec![];
+        io::write(&proof, &mut serialized_proof).unwrap();
+
+        Ok((vk, serialized_proof))
+    }
+
+    #[test]
+    fn verify_serialized() -> Result<(), SynthesisError> {
+        let (vk, proof) = serialized::<Bls12_381>()?;
+
+        let inputs = [encode_f64_input(1e10), encode_f64_input(1e10 - 3.25)].concat();
+        assert!(verify(&vk, &proof, &inputs).unwrap());
+        assert!(verify_f64(&vk, &proof, &[1e10, 1e10 - 3.25]).unwrap());
+        assert!(!verify_f64(&vk, &proof, &[1e10, 1e10 - 3.5]).unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_serialized_bn254() -> Result<(), SynthesisError> {
+        let (vk, proof) = serialized::<Bn254>()?;
+
+        let inputs = [encode_f64_input(1e10), encode_f64_input(1e10 - 3.25)].concat();
+        assert!(verify_bn254(&vk, &proof, &inputs).unwrap());
+        assert!(verify_f64_bn254(&vk, &proof, &[1e10, 1e10 - 3.25]).unwrap());
+        assert!(!verify_f64_bn254(&vk, &proof, &[1e10, 1e10 - 3.5]).unwrap());
+
+        Ok(())
+    }
+}